@@ -0,0 +1,9 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let protoc = protoc_bin_vendored::protoc_bin_path()?;
+
+    std::env::set_var("PROTOC", protoc);
+
+    tonic_build::compile_protos("proto/archeon.proto")?;
+
+    Ok(())
+}
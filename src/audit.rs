@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+#[cfg(not(unix))]
+use std::env::var;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+use tokio::fs::{create_dir_all, read_to_string, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Download,
+    Install,
+    Uninstall,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub user: String,
+    pub action: AuditAction,
+    pub source: String,
+    pub checksum: Option<String>,
+    pub result: Result<(), String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl AuditEntry {
+    pub fn init(
+        action: AuditAction,
+        source: &str,
+        checksum: Option<String>,
+        result: Result<(), String>,
+    ) -> AuditEntry {
+        AuditEntry {
+            timestamp: crate::unix_timestamp(),
+            user: Self::init_user(),
+            action,
+            source: source.to_owned(),
+            checksum,
+            result,
+            tags: BTreeMap::new(),
+        }
+    }
+
+    #[cfg(unix)]
+    fn init_user() -> String {
+        let uid = unsafe { libc::getuid() };
+
+        passwd_name(uid).unwrap_or_else(|| uid.to_string())
+    }
+
+    #[cfg(not(unix))]
+    fn init_user() -> String {
+        var("USERNAME").unwrap_or_else(|_| String::from("unknown"))
+    }
+}
+
+#[cfg(unix)]
+fn passwd_name(uid: libc::uid_t) -> Option<String> {
+    let mut passwd = std::mem::MaybeUninit::<libc::passwd>::uninit();
+    let mut buffer: Vec<libc::c_char> = vec![0; 1024];
+    let mut result = std::ptr::null_mut();
+
+    loop {
+        let status = unsafe {
+            libc::getpwuid_r(
+                uid,
+                passwd.as_mut_ptr(),
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut result,
+            )
+        };
+
+        match status == libc::ERANGE && buffer.len() < 1024 * 1024 {
+            true => buffer.resize(buffer.len() * 2, 0),
+            false => break,
+        }
+    }
+
+    if result.is_null() {
+        return None;
+    }
+
+    let name = unsafe { std::ffi::CStr::from_ptr((*result).pw_name) };
+
+    Some(name.to_string_lossy().into_owned())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditLog {
+    pub path: PathBuf,
+}
+
+impl AuditLog {
+    pub async fn init(path: &Path) -> Result<AuditLog, Error> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).await?;
+        }
+
+        Ok(AuditLog {
+            path: path.to_owned(),
+        })
+    }
+
+    pub async fn append(&self, entry: &AuditEntry) -> Result<(), Error> {
+        let mut line = serde_json::to_vec(entry)?;
+        let mut file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)
+            .await?;
+
+        line.push(b'\n');
+
+        file.write_all(&line).await?;
+        file.sync_data().await?;
+
+        Ok(())
+    }
+
+    pub async fn entries(&self) -> Result<Vec<AuditEntry>, Error> {
+        let contents = read_to_string(&self.path).await?;
+        let mut entries = Vec::with_capacity(contents.lines().count());
+
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            entries.push(serde_json::from_str(line)?);
+        }
+
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn entry_init() {
+        let test_entry = AuditEntry::init(
+            AuditAction::Download,
+            "http://test-audit/test.deb",
+            Some(String::from("test_checksum")),
+            Ok(()),
+        );
+        assert!(test_entry.timestamp > 0);
+        assert!(!test_entry.user.is_empty());
+        assert_eq!(test_entry.action, AuditAction::Download);
+        assert_eq!(test_entry.source, "http://test-audit/test.deb");
+        assert_eq!(test_entry.checksum.as_deref(), Some("test_checksum"));
+        assert!(test_entry.result.is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn entry_user() {
+        let test_uid = unsafe { libc::getuid() };
+        let test_entry = AuditEntry::init(AuditAction::Download, "http://test-audit", None, Ok(()));
+        assert_eq!(passwd_name(0).as_deref(), Some("root"));
+        assert_eq!(
+            Some(test_entry.user),
+            passwd_name(test_uid).or_else(|| Some(test_uid.to_string())),
+        );
+        assert_eq!(passwd_name(libc::uid_t::MAX - 1), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir()
+            .join("archeon_test_audit_init")
+            .join("audit.log");
+        let test_audit_log = AuditLog::init(&test_path).await?;
+        assert_eq!(test_audit_log.path, test_path);
+        assert!(tokio::fs::metadata(test_path.parent().unwrap())
+            .await?
+            .is_dir());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn append() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_audit_append.log");
+        let test_audit_log = AuditLog::init(&test_path).await?;
+        let test_success = AuditEntry::init(
+            AuditAction::Install,
+            "http://test-audit/test.deb",
+            None,
+            Ok(()),
+        );
+        let test_failure = AuditEntry::init(
+            AuditAction::Uninstall,
+            "http://test-audit/test.deb",
+            None,
+            Err(String::from("test_error")),
+        );
+        test_audit_log.append(&test_success).await?;
+        test_audit_log.append(&test_failure).await?;
+        let test_entries = test_audit_log.entries().await?;
+        assert_eq!(test_entries, [test_success, test_failure]);
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,657 @@
+use hyper::body::to_bytes;
+use hyper::header::{HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use hyper::{Body, Method, Request, StatusCode, Uri};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use serde::{Deserialize, Serialize};
+
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use tokio::time::sleep;
+
+use crate::transfer::HttpClient;
+
+pub type AuthFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>> + Send + 'a>>;
+
+pub trait Authenticate: Send + Sync {
+    fn authenticate<'a>(&'a self, request: &'a mut Request<Body>) -> AuthFuture<'a>;
+
+    fn invalidate(&self) {}
+}
+
+const EXPIRY_SKEW: u64 = 60;
+
+pub(crate) const UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuth2Flow {
+    #[default]
+    ClientCredentials,
+    DeviceCode,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OAuth2Config {
+    pub flow: OAuth2Flow,
+    pub token_url: String,
+    pub device_authorization_url: Option<String>,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub scope: Option<String>,
+    pub hosts: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    pub access_token: String,
+    #[serde(default = "default_token_type")]
+    pub token_type: String,
+    pub expires_in: Option<u64>,
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct TokenError {
+    error: String,
+    error_description: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+struct CachedToken {
+    token: Token,
+    expires_at: Option<u64>,
+}
+
+type Prompt = Arc<dyn Fn(&DeviceAuthorization) + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct AuthChain {
+    pub authenticators: Vec<Arc<dyn Authenticate>>,
+}
+
+#[derive(Clone)]
+pub struct OAuth2 {
+    pub config: OAuth2Config,
+    client: HttpClient,
+    token: Arc<Mutex<Option<CachedToken>>>,
+    acquire: Arc<tokio::sync::Mutex<()>>,
+    prompt: Prompt,
+}
+
+impl OAuth2Config {
+    pub fn in_scope(&self, uri: &Uri) -> bool {
+        let host = match uri.host() {
+            Some(host) => host,
+            None => return false,
+        };
+
+        match self.hosts.is_empty() {
+            true => self
+                .token_url
+                .parse::<Uri>()
+                .ok()
+                .and_then(|token_url| token_url.host().map(|token_host| token_host.to_owned()))
+                .is_some_and(|token_host| token_host.eq_ignore_ascii_case(host)),
+            false => host_matches(&self.hosts, host),
+        }
+    }
+}
+
+impl OAuth2 {
+    pub fn init(config: OAuth2Config, client: HttpClient) -> OAuth2 {
+        OAuth2 {
+            config,
+            client,
+            token: Arc::default(),
+            acquire: Arc::default(),
+            prompt: Arc::new(|authorization: &DeviceAuthorization| {
+                let uri = authorization
+                    .verification_uri_complete
+                    .as_deref()
+                    .unwrap_or(&authorization.verification_uri);
+
+                eprintln!(
+                    "To authorize archeon, visit {} and enter the code {}",
+                    uri, authorization.user_code,
+                );
+            }),
+        }
+    }
+
+    pub fn with_prompt(
+        mut self,
+        prompt: impl Fn(&DeviceAuthorization) + Send + Sync + 'static,
+    ) -> OAuth2 {
+        self.prompt = Arc::new(prompt);
+        self
+    }
+
+    pub async fn access_token(&self) -> Result<Token, Error> {
+        let _acquire = self.acquire.lock().await;
+        let cached = self.lock().to_owned();
+        let now = crate::unix_timestamp();
+
+        if let Some(cached) = &cached {
+            if cached
+                .expires_at
+                .is_none_or(|expires_at| expires_at > now + EXPIRY_SKEW)
+            {
+                return Ok(cached.token.to_owned());
+            }
+        }
+
+        let refresh_token = cached.and_then(|cached| cached.token.refresh_token);
+        let refreshed = match &refresh_token {
+            Some(refresh_token) => self.launch_refresh(refresh_token).await.ok(),
+            None => None,
+        };
+        let mut token = match refreshed {
+            Some(token) => token,
+            None => match self.config.flow {
+                OAuth2Flow::ClientCredentials => self.launch_client_credentials().await?,
+                OAuth2Flow::DeviceCode => self.launch_device_code().await?,
+            },
+        };
+
+        if token.refresh_token.is_none() {
+            token.refresh_token = refresh_token;
+        }
+
+        *self.lock() = Some(CachedToken {
+            expires_at: token.expires_in.map(|expires_in| now + expires_in),
+            token: token.to_owned(),
+        });
+
+        Ok(token)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<CachedToken>> {
+        self.token
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    async fn launch_client_credentials(&self) -> Result<Token, Error> {
+        let mut form = vec![("grant_type", "client_credentials")];
+
+        self.launch_client_form(&mut form);
+
+        let (status, body) = self.launch_form(&self.config.token_url, &form).await?;
+
+        parse_token(&self.config.token_url, status, &body)
+    }
+
+    async fn launch_refresh(&self, refresh_token: &str) -> Result<Token, Error> {
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ];
+
+        self.launch_client_form(&mut form);
+
+        let (status, body) = self.launch_form(&self.config.token_url, &form).await?;
+
+        parse_token(&self.config.token_url, status, &body)
+    }
+
+    async fn launch_device_code(&self) -> Result<Token, Error> {
+        let device_authorization_url = match &self.config.device_authorization_url {
+            Some(device_authorization_url) => device_authorization_url,
+            None => {
+                let error = "The OAuth2 device code flow requires a device_authorization_url!";
+
+                return Err(Error::new(ErrorKind::InvalidInput, error));
+            }
+        };
+        let mut form = Vec::with_capacity(3);
+
+        self.launch_client_form(&mut form);
+
+        let (status, body) = self.launch_form(device_authorization_url, &form).await?;
+
+        if !status.is_success() {
+            return Err(token_error(device_authorization_url, status, &body));
+        }
+
+        let authorization: DeviceAuthorization = serde_json::from_slice(&body)
+            .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+        (self.prompt)(&authorization);
+
+        let deadline = crate::unix_timestamp() + authorization.expires_in;
+        let mut interval = authorization.interval;
+
+        loop {
+            let mut form = vec![
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("device_code", authorization.device_code.as_str()),
+            ];
+
+            self.launch_client_form(&mut form);
+
+            let (status, body) = self.launch_form(&self.config.token_url, &form).await?;
+
+            if status.is_success() {
+                return parse_token(&self.config.token_url, status, &body);
+            }
+
+            match serde_json::from_slice::<TokenError>(&body) {
+                Ok(error) if error.error == "authorization_pending" => {}
+                Ok(error) if error.error == "slow_down" => interval += 5,
+                _ => return Err(token_error(&self.config.token_url, status, &body)),
+            }
+
+            if crate::unix_timestamp() + interval > deadline {
+                let error = format!(
+                    "The OAuth2 device code {} expired before it was authorized!",
+                    authorization.user_code,
+                );
+
+                return Err(Error::new(ErrorKind::TimedOut, error));
+            }
+
+            sleep(Duration::from_secs(interval)).await;
+        }
+    }
+
+    fn launch_client_form<'a>(&'a self, form: &mut Vec<(&'a str, &'a str)>) {
+        form.push(("client_id", &self.config.client_id));
+
+        if let Some(client_secret) = &self.config.client_secret {
+            form.push(("client_secret", client_secret));
+        }
+
+        if let Some(scope) = &self.config.scope {
+            form.push(("scope", scope));
+        }
+    }
+
+    async fn launch_form(
+        &self,
+        url: &str,
+        form: &[(&str, &str)],
+    ) -> Result<(StatusCode, hyper::body::Bytes), Error> {
+        let body = encode_form(form);
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(url)
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(ACCEPT, "application/json")
+            .body(Body::from(body))
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+        let response = self.client.send(request).await.map_err(Error::other)?;
+        let status = response.status();
+        let body = to_bytes(response.into_body()).await.map_err(Error::other)?;
+
+        Ok((status, body))
+    }
+}
+
+impl Authenticate for OAuth2 {
+    fn authenticate<'a>(&'a self, request: &'a mut Request<Body>) -> AuthFuture<'a> {
+        Box::pin(async move {
+            if request.headers().contains_key(AUTHORIZATION) || !self.config.in_scope(request.uri())
+            {
+                return Ok(());
+            }
+
+            let token = self.access_token().await?;
+            let token_type = match token.token_type.eq_ignore_ascii_case("bearer") {
+                true => "Bearer",
+                false => token.token_type.as_str(),
+            };
+            let value = HeaderValue::from_str(&format!("{} {}", token_type, token.access_token))?;
+
+            request.headers_mut().insert(AUTHORIZATION, value);
+
+            Ok(())
+        })
+    }
+
+    fn invalidate(&self) {
+        if let Some(cached) = self.lock().as_mut() {
+            cached.expires_at = Some(0);
+        }
+    }
+}
+
+impl Authenticate for AuthChain {
+    fn authenticate<'a>(&'a self, request: &'a mut Request<Body>) -> AuthFuture<'a> {
+        Box::pin(async move {
+            for authenticator in &self.authenticators {
+                if request.headers().contains_key(AUTHORIZATION) {
+                    break;
+                }
+
+                authenticator.authenticate(request).await?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn invalidate(&self) {
+        for authenticator in &self.authenticators {
+            authenticator.invalidate();
+        }
+    }
+}
+
+pub fn host_matches(patterns: &[String], host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim_end_matches('.').to_ascii_lowercase();
+
+        match pattern.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .is_some_and(|subdomain| subdomain.ends_with('.')),
+            None => host == pattern,
+        }
+    })
+}
+
+fn default_token_type() -> String {
+    String::from("Bearer")
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+fn encode_form(form: &[(&str, &str)]) -> String {
+    form.iter()
+        .map(|(name, value)| {
+            format!(
+                "{}={}",
+                utf8_percent_encode(name, UNRESERVED),
+                utf8_percent_encode(value, UNRESERVED),
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+fn parse_token(url: &str, status: StatusCode, body: &[u8]) -> Result<Token, Error> {
+    if !status.is_success() {
+        return Err(token_error(url, status, body));
+    }
+
+    serde_json::from_slice(body).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+}
+
+fn token_error(url: &str, status: StatusCode, body: &[u8]) -> Error {
+    let reason = match serde_json::from_slice::<TokenError>(body) {
+        Ok(TokenError {
+            error_description: Some(description),
+            ..
+        }) => description,
+        Ok(TokenError { error, .. }) => error,
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    };
+    let error = format!(
+        "OAuth2 request to {} failed with {}: {}!",
+        url, status, reason
+    );
+
+    Error::new(ErrorKind::PermissionDenied, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer::Transfer;
+    use mockito::Matcher;
+
+    fn test_oauth2(test_server: &mockito::Server, flow: OAuth2Flow) -> OAuth2 {
+        OAuth2::init(
+            OAuth2Config {
+                flow,
+                token_url: format!("{}/token", test_server.url()),
+                device_authorization_url: Some(format!("{}/device", test_server.url())),
+                client_id: String::from("test_client"),
+                client_secret: Some(String::from("test secret")),
+                scope: Some(String::from("artifacts.read")),
+                hosts: Vec::new(),
+            },
+            Transfer::init_client(),
+        )
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn encode_form() {
+        assert_eq!(
+            super::encode_form(&[("grant_type", "client_credentials"), ("scope", "a b/c")]),
+            "grant_type=client_credentials&scope=a%20b%2Fc",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn client_credentials() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mock_token = test_server
+            .mock("POST", "/token")
+            .match_header("content-type", "application/x-www-form-urlencoded")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("grant_type".into(), "client_credentials".into()),
+                Matcher::UrlEncoded("client_secret".into(), "test secret".into()),
+            ]))
+            .with_status(200)
+            .with_body(r#"{"access_token":"test_access","token_type":"bearer","expires_in":3600}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let test_oauth2 = test_oauth2(&test_server, OAuth2Flow::ClientCredentials);
+        let mut test_request =
+            Request::get(format!("{}/test.deb", test_server.url())).body(Body::empty())?;
+        test_oauth2.authenticate(&mut test_request).await?;
+        assert_eq!(test_request.headers()[AUTHORIZATION], "Bearer test_access");
+        let mut test_request =
+            Request::get(format!("{}/test.deb", test_server.url())).body(Body::empty())?;
+        test_oauth2.authenticate(&mut test_request).await?;
+        assert_eq!(test_request.headers()[AUTHORIZATION], "Bearer test_access");
+        let mut test_request =
+            Request::get("http://test-oauth2-elsewhere.invalid/test.deb").body(Body::empty())?;
+        test_oauth2.authenticate(&mut test_request).await?;
+        assert!(!test_request.headers().contains_key(AUTHORIZATION));
+        mock_token.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn refresh() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mock_token = test_server
+            .mock("POST", "/token")
+            .match_body(Matcher::UrlEncoded(
+                "grant_type".into(),
+                "client_credentials".into(),
+            ))
+            .with_status(200)
+            .with_body(
+                r#"{"access_token":"test_first","expires_in":10,"refresh_token":"test_refresh"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_refresh = test_server
+            .mock("POST", "/token")
+            .match_body(Matcher::UrlEncoded(
+                "refresh_token".into(),
+                "test_refresh".into(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"access_token":"test_second","expires_in":3600}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let test_oauth2 = test_oauth2(&test_server, OAuth2Flow::ClientCredentials);
+        assert_eq!(test_oauth2.access_token().await?.access_token, "test_first");
+        let test_token = test_oauth2.access_token().await?;
+        assert_eq!(test_token.access_token, "test_second");
+        assert_eq!(test_token.refresh_token.as_deref(), Some("test_refresh"));
+        assert_eq!(
+            test_oauth2.access_token().await?.access_token,
+            "test_second"
+        );
+        mock_token.assert_async().await;
+        mock_refresh.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn device_code() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        test_server
+            .mock("POST", "/device")
+            .with_status(200)
+            .with_body(
+                r#"{"device_code":"test_device","user_code":"TEST-CODE","verification_uri":"https://test-idp/device","expires_in":60,"interval":0}"#,
+            )
+            .create_async()
+            .await;
+        let mock_pending = test_server
+            .mock("POST", "/token")
+            .with_status(400)
+            .with_body(r#"{"error":"authorization_pending"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let mock_token = test_server
+            .mock("POST", "/token")
+            .match_body(Matcher::UrlEncoded(
+                "device_code".into(),
+                "test_device".into(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"access_token":"test_device_access"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let test_prompted = Arc::new(Mutex::new(None));
+        let test_shared = test_prompted.to_owned();
+        let test_oauth2 = test_oauth2(&test_server, OAuth2Flow::DeviceCode).with_prompt(
+            move |test_authorization: &DeviceAuthorization| {
+                *test_shared.lock().unwrap() = Some(test_authorization.user_code.to_owned());
+            },
+        );
+        let test_token = test_oauth2.access_token().await?;
+        assert_eq!(test_token.access_token, "test_device_access");
+        assert_eq!(test_prompted.lock().unwrap().as_deref(), Some("TEST-CODE"));
+        mock_pending.assert_async().await;
+        mock_token.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn token_error() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        test_server
+            .mock("POST", "/token")
+            .with_status(401)
+            .with_body(r#"{"error":"invalid_client","error_description":"Unknown client"}"#)
+            .create_async()
+            .await;
+        let test_oauth2 = test_oauth2(&test_server, OAuth2Flow::ClientCredentials);
+        let test_error = test_oauth2.access_token().await.unwrap_err();
+        assert_eq!(test_error.kind(), ErrorKind::PermissionDenied);
+        assert!(test_error.to_string().contains("Unknown client"));
+        let test_oauth2 = OAuth2::init(OAuth2Config::default(), Transfer::init_client());
+        let test_oauth2 = OAuth2 {
+            config: OAuth2Config {
+                flow: OAuth2Flow::DeviceCode,
+                ..test_oauth2.config.to_owned()
+            },
+            ..test_oauth2
+        };
+        assert_eq!(
+            test_oauth2.access_token().await.unwrap_err().kind(),
+            ErrorKind::InvalidInput,
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn in_scope() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_config = OAuth2Config {
+            token_url: String::from("https://auth.test-oauth2.invalid/token"),
+            ..OAuth2Config::default()
+        };
+        assert!(test_config.in_scope(&"https://auth.test-oauth2.invalid/a.deb".parse()?));
+        assert!(!test_config.in_scope(&"https://cdn.test-oauth2.invalid/a.deb".parse()?));
+        assert!(!test_config.in_scope(&"/a.deb".parse()?));
+        let test_config = OAuth2Config {
+            hosts: vec![
+                String::from("artifacts.test-oauth2.invalid"),
+                String::from("*.cdn.test-oauth2.invalid"),
+            ],
+            ..test_config
+        };
+        assert!(!test_config.in_scope(&"https://auth.test-oauth2.invalid/a.deb".parse()?));
+        assert!(test_config.in_scope(&"https://Artifacts.test-oauth2.invalid/a.deb".parse()?));
+        assert!(test_config.in_scope(&"https://eu.cdn.test-oauth2.invalid/a.deb".parse()?));
+        assert!(!test_config.in_scope(&"https://cdn.test-oauth2.invalid/a.deb".parse()?));
+        assert!(!test_config.in_scope(&"https://evilcdn.test-oauth2.invalid/a.deb".parse()?));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn chain() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        struct TestBearer(&'static str);
+
+        impl Authenticate for TestBearer {
+            fn authenticate<'a>(&'a self, request: &'a mut Request<Body>) -> AuthFuture<'a> {
+                Box::pin(async move {
+                    let value = HeaderValue::from_static(self.0);
+
+                    request.headers_mut().insert(AUTHORIZATION, value);
+
+                    Ok(())
+                })
+            }
+        }
+
+        let test_chain = AuthChain {
+            authenticators: vec![
+                Arc::new(TestBearer("Bearer test_first")),
+                Arc::new(TestBearer("Bearer test_second")),
+            ],
+        };
+        let mut test_request = Request::new(Body::empty());
+        test_chain.authenticate(&mut test_request).await?;
+        assert_eq!(test_request.headers()[AUTHORIZATION], "Bearer test_first");
+        let mut test_request = Request::builder()
+            .header(AUTHORIZATION, "Bearer test_existing")
+            .body(Body::empty())?;
+        test_chain.authenticate(&mut test_request).await?;
+        assert_eq!(
+            test_request.headers()[AUTHORIZATION],
+            "Bearer test_existing"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,418 @@
+use serde::{Deserialize, Serialize};
+
+use hyper::header::{HeaderName, HeaderValue};
+
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use tokio::fs::{create_dir_all, read_to_string};
+
+use crate::cache::Retention;
+use crate::checksum::Algorithm;
+use crate::error;
+use crate::hook::HookOutput;
+use crate::installer::Installer;
+use crate::overwrite::OverwritePolicy;
+use crate::redirect::RedirectPolicy;
+use crate::template;
+use crate::transfer::{HttpClient, Transfer};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Batch {
+    #[serde(default, rename = "artifact")]
+    pub artifacts: Vec<BatchArtifact>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatchArtifact {
+    pub url: String,
+    pub checksum: Option<String>,
+    pub algorithm: Option<Algorithm>,
+    pub destination: Option<PathBuf>,
+    pub install: bool,
+    pub installer: Installer,
+    pub delete: bool,
+    pub retention: Option<Retention>,
+    pub post_install: Vec<String>,
+    pub pin: bool,
+    pub refresh: bool,
+    pub timestamping: bool,
+    pub overwrite: Option<OverwritePolicy>,
+    pub priority: Priority,
+    pub user_agent: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    pub variables: BTreeMap<String, String>,
+    pub tags: BTreeMap<String, String>,
+    pub redirect: RedirectPolicy,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub results: Vec<BatchResult>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub url: String,
+    pub file_path: Option<PathBuf>,
+    pub installation: Option<u64>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub error_kind: Option<error::ErrorKind>,
+    #[serde(default)]
+    pub hooks: Vec<HookOutput>,
+}
+
+impl Batch {
+    pub async fn init(path: &Path) -> Result<Batch, Error> {
+        let contents = read_to_string(path).await?;
+
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Batch, Error> {
+        toml::from_str(contents).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn parse_urls(contents: &str) -> Batch {
+        let artifacts = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|url| BatchArtifact {
+                url: url.to_owned(),
+                ..BatchArtifact::default()
+            })
+            .collect();
+
+        Batch { artifacts }
+    }
+}
+
+impl BatchArtifact {
+    pub fn retention(&self) -> Retention {
+        match (self.retention, self.delete) {
+            (Some(retention), _) => retention,
+            (None, true) => Retention::Delete,
+            (None, false) => Retention::Keep,
+        }
+    }
+
+    pub async fn transfer(&self) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        self.transfer_with_client(Transfer::init_client()).await
+    }
+
+    pub async fn transfer_with_client(
+        &self,
+        client: HttpClient,
+    ) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        let url = template::expand(&self.url, &self.variables)?;
+        let mut transfer = Transfer::init_with_client(&url, client).await?;
+
+        if let Some(destination) = &self.destination {
+            create_dir_all(destination).await?;
+
+            transfer.temp_dir = destination.to_owned();
+            transfer.file_path = destination.join(&transfer.filename);
+        }
+
+        transfer.checksum = self.checksum.to_owned();
+        transfer.algorithm = self.algorithm;
+        transfer.installer = self.installer.to_owned();
+        transfer.refresh = self.refresh;
+        transfer.timestamping = self.timestamping;
+        transfer.overwrite = self.overwrite.unwrap_or_default();
+        transfer.redirect = self.redirect;
+        transfer.retention = self.retention();
+        transfer.post_install = self.post_install.to_owned();
+        transfer.tags = self.tags.to_owned();
+
+        if let Some(user_agent) = &self.user_agent {
+            transfer.user_agent = user_agent.to_owned();
+        }
+
+        for (name, value) in &self.headers {
+            transfer.headers.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+
+        Ok(transfer)
+    }
+}
+
+pub fn parse_tag(input: &str) -> Result<(String, String), Error> {
+    match input.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_owned(), value.to_owned()))
+        }
+        _ => {
+            let error = format!("Invalid tag '{}', expected key=value!", input);
+
+            Err(Error::new(ErrorKind::InvalidInput, error))
+        }
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let priority = match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+        };
+
+        f.write_str(priority)
+    }
+}
+
+impl std::str::FromStr for Priority {
+    type Err = Error;
+
+    fn from_str(priority: &str) -> Result<Priority, Error> {
+        match priority {
+            "low" => Ok(Priority::Low),
+            "normal" => Ok(Priority::Normal),
+            "high" => Ok(Priority::High),
+            _ => {
+                let error = format!("Invalid priority '{}'!", priority);
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+}
+
+impl BatchReport {
+    pub fn succeeded(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.error.is_none())
+            .count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.succeeded()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::installer::Escalation;
+
+    const TEST_BATCH: &str = r#"
+        [[artifact]]
+        url = "http://test-batch/first.deb"
+        checksum = "test_checksum"
+        install = true
+
+        [artifact.installer]
+        escalation = "sudo"
+
+        [[artifact]]
+        url = "http://test-batch/second.tar.gz"
+        destination = "/tmp/archeon_test_batch_destination"
+        priority = "high"
+        algorithm = "blake3"
+        overwrite = "rename-unique"
+        user_agent = "test-agent/1.0"
+
+        [artifact.headers]
+        x-test-header = "test_value"
+
+        [artifact.tags]
+        ticket = "OPS-42"
+        owner = "platform"
+
+        [artifact.redirect]
+        max_redirects = 3
+        cross_origin = false
+    "#;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_batch_init.toml");
+        tokio::fs::write(&test_path, TEST_BATCH).await?;
+        let test_batch = Batch::init(&test_path).await?;
+        assert_eq!(test_batch.artifacts.len(), 2);
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_batch = Batch::parse(TEST_BATCH)?;
+        assert_eq!(test_batch.artifacts[0].url, "http://test-batch/first.deb");
+        assert_eq!(
+            test_batch.artifacts[0].checksum.as_deref(),
+            Some("test_checksum"),
+        );
+        assert!(test_batch.artifacts[0].install);
+        assert_eq!(
+            test_batch.artifacts[0].installer.escalation,
+            Escalation::Sudo,
+        );
+        assert!(test_batch.artifacts[0].installer.noninteractive);
+        assert!(!test_batch.artifacts[1].install);
+        assert_eq!(
+            test_batch.artifacts[1].destination.as_deref(),
+            Some(Path::new("/tmp/archeon_test_batch_destination")),
+        );
+        assert_eq!(test_batch.artifacts[0].priority, Priority::Normal);
+        assert_eq!(test_batch.artifacts[1].priority, Priority::High);
+        assert_eq!(test_batch.artifacts[1].algorithm, Some(Algorithm::Blake3));
+        assert!(Batch::parse("[[artifact]]\nurl = 1").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_tag() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!(
+            super::parse_tag("ticket=OPS-42")?,
+            (String::from("ticket"), String::from("OPS-42")),
+        );
+        assert_eq!(
+            super::parse_tag("note=a=b")?,
+            (String::from("note"), String::from("a=b")),
+        );
+        assert!(super::parse_tag("ticket").is_err());
+        assert!(super::parse_tag("=OPS-42").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_urls() {
+        let test_batch = Batch::parse_urls(
+            "# test mirrors\nhttp://test-batch/first.deb\n\n   \n  http://test-batch/second.iso  \n#http://test-batch/skipped.deb\n",
+        );
+        let test_urls: Vec<&str> = test_batch
+            .artifacts
+            .iter()
+            .map(|artifact| artifact.url.as_str())
+            .collect();
+        assert_eq!(
+            test_urls,
+            [
+                "http://test-batch/first.deb",
+                "http://test-batch/second.iso"
+            ],
+        );
+        assert!(Batch::parse_urls("").artifacts.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn transfer() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_batch = Batch::parse(TEST_BATCH)?;
+        let test_first_transfer = test_batch.artifacts[0].transfer().await?;
+        let test_second_transfer = test_batch.artifacts[1].transfer().await?;
+        assert_eq!(
+            test_first_transfer.checksum.as_deref(),
+            Some("test_checksum")
+        );
+        assert_eq!(test_first_transfer.installer.escalation, Escalation::Sudo);
+        assert_eq!(test_second_transfer.algorithm, Some(Algorithm::Blake3));
+        assert_eq!(
+            test_second_transfer.overwrite,
+            OverwritePolicy::RenameUnique
+        );
+        assert_eq!(test_first_transfer.overwrite, OverwritePolicy::Overwrite);
+        assert_eq!(
+            test_first_transfer.user_agent,
+            crate::transfer::DEFAULT_USER_AGENT,
+        );
+        assert_eq!(test_second_transfer.user_agent, "test-agent/1.0");
+        assert_eq!(test_second_transfer.headers["x-test-header"], "test_value");
+        assert!(test_first_transfer.headers.is_empty());
+        assert_eq!(test_second_transfer.tags["ticket"], "OPS-42");
+        assert_eq!(test_second_transfer.tags["owner"], "platform");
+        assert!(test_first_transfer.tags.is_empty());
+        assert_eq!(test_first_transfer.redirect, RedirectPolicy::default());
+        assert_eq!(test_second_transfer.redirect.max_redirects, 3);
+        assert!(!test_second_transfer.redirect.cross_origin);
+        assert!(test_second_transfer.redirect.strip_authorization);
+        assert_eq!(
+            test_second_transfer.file_path,
+            Path::new("/tmp/archeon_test_batch_destination/second.tar.gz"),
+        );
+        assert_eq!(
+            test_second_transfer.temp_dir,
+            Path::new("/tmp/archeon_test_batch_destination"),
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn transfer_template() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_batch = Batch::parse(
+            r#"
+            [[artifact]]
+            url = "http://test-template/{version}/{target}/app-{version}.tar.gz"
+
+            [artifact.variables]
+            version = "1.2.3"
+            target = "x86_64-linux"
+        "#,
+        )?;
+        let test_transfer = test_batch.artifacts[0].transfer().await?;
+        assert_eq!(
+            test_transfer.uri.to_string(),
+            "http://test-template/1.2.3/x86_64-linux/app-1.2.3.tar.gz",
+        );
+        assert_eq!(test_transfer.filename, Path::new("app-1.2.3.tar.gz"));
+        let test_artifact = BatchArtifact {
+            url: String::from("http://test-template/{missing}.tar.gz"),
+            ..BatchArtifact::default()
+        };
+        assert!(test_artifact.transfer().await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn priority() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert!(Priority::High > Priority::Normal);
+        assert!(Priority::Normal > Priority::Low);
+        assert_eq!("high".parse::<Priority>()?, Priority::High);
+        assert_eq!(Priority::Low.to_string(), "low");
+        assert!("urgent".parse::<Priority>().is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn report() {
+        let test_report = BatchReport {
+            results: vec![
+                BatchResult {
+                    url: String::from("http://test-batch/first.deb"),
+                    file_path: None,
+                    installation: None,
+                    error: Some(String::from("test_error")),
+                    error_kind: Some(error::ErrorKind::Server),
+                    hooks: Vec::new(),
+                },
+                BatchResult {
+                    url: String::from("http://test-batch/second.tar.gz"),
+                    file_path: Some(PathBuf::from("/tmp/archeon/second.tar.gz")),
+                    installation: None,
+                    error: None,
+                    error_kind: None,
+                    hooks: Vec::new(),
+                },
+            ],
+        };
+        assert_eq!(test_report.succeeded(), 1);
+        assert_eq!(test_report.failed(), 1);
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+use std::time::Duration;
+
+use tokio::fs::{create_dir_all, remove_file};
+
+use crate::error::ArcheonError;
+use crate::http_cache::HttpCacheEntry;
+use crate::transfer::Transfer;
+use crate::Archeon;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchStrategy {
+    pub segments: usize,
+    pub write_buffer: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub strategy: BenchStrategy,
+    pub bytes: u64,
+    pub duration: Duration,
+    pub bytes_per_second: u64,
+}
+
+pub fn strategies(segments: &[usize], write_buffers: &[usize]) -> Vec<BenchStrategy> {
+    let mut strategies = Vec::with_capacity(segments.len() * write_buffers.len());
+
+    for segments in segments {
+        for write_buffer in write_buffers {
+            strategies.push(BenchStrategy {
+                segments: (*segments).max(1),
+                write_buffer: *write_buffer,
+            });
+        }
+    }
+
+    strategies
+}
+
+pub async fn run(
+    archeon: &Archeon,
+    url: &str,
+    strategies: &[BenchStrategy],
+    directory: &Path,
+) -> Result<Vec<BenchResult>, ArcheonError> {
+    let mut results = Vec::with_capacity(strategies.len());
+
+    create_dir_all(directory).await?;
+
+    for (index, strategy) in strategies.iter().enumerate() {
+        let mut transfer = Transfer::init_with_client(url, archeon.client.to_owned()).await?;
+
+        transfer.file_path = directory.join(format!("archeon_bench_{}", index));
+        transfer.limiter = archeon.limiter.to_owned();
+        transfer.buffer = archeon.buffer.to_owned();
+        transfer.cookies = archeon.cookies.to_owned();
+        transfer.auth = archeon.auth.to_owned();
+        transfer.pre_request = archeon.pre_request.to_owned();
+        transfer.shutdown = archeon.shutdown.to_owned();
+        transfer.segments = strategy.segments;
+        transfer.write_buffer = strategy.write_buffer;
+
+        let result = transfer.launch().await;
+
+        for path in [
+            HttpCacheEntry::path(&transfer.file_path),
+            transfer.file_path,
+        ] {
+            let _ = remove_file(path).await;
+        }
+
+        let summary = result?;
+
+        results.push(BenchResult {
+            strategy: *strategy,
+            bytes: summary.bytes,
+            duration: summary.duration,
+            bytes_per_second: summary.avg_speed,
+        });
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockArtifact, MockServer};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn strategies() {
+        let test_strategies = super::strategies(&[1, 0, 4], &[8192]);
+        assert_eq!(test_strategies.len(), 3);
+        assert_eq!(test_strategies[1].segments, 1);
+        assert_eq!(test_strategies[2].segments, 4);
+        assert!(test_strategies
+            .iter()
+            .all(|strategy| strategy.write_buffer == 8192));
+        assert!(super::strategies(&[1], &[]).is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_server = MockServer::start().await?;
+        let test_url = test_server.add("test_bench.bin", MockArtifact::sized(256 * 1024));
+        let test_directory = std::env::temp_dir().join("archeon_test_bench");
+        let test_archeon = Archeon::ignite().await;
+        let test_strategies = super::strategies(&[1, 4], &[0, 65536]);
+        let test_results =
+            super::run(&test_archeon, &test_url, &test_strategies, &test_directory).await?;
+        assert_eq!(test_results.len(), 4);
+        assert!(test_results.iter().all(|result| result.bytes == 256 * 1024));
+        assert_eq!(test_results[2].strategy.segments, 4);
+        assert_eq!(test_server.hits("test_bench.bin"), 2 + 5 * 2);
+        assert!(std::fs::read_dir(&test_directory)?.next().is_none());
+        tokio::fs::remove_dir(&test_directory).await?;
+        Ok(())
+    }
+}
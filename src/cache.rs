@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::env::var_os;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use tokio::fs::{copy, create_dir_all, metadata, read, remove_file, rename, write};
+
+use crate::checksum;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cache {
+    pub root: PathBuf,
+    pub index: HashMap<String, String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Retention {
+    Delete,
+    #[default]
+    Keep,
+    Cache,
+}
+
+pub fn default_cache_path() -> PathBuf {
+    match (var_os("XDG_CACHE_HOME"), var_os("HOME")) {
+        (Some(cache_dir), _) => PathBuf::from(cache_dir).join("archeon"),
+        (None, Some(home)) => PathBuf::from(home).join(".cache/archeon"),
+        (None, None) => PathBuf::from("archeon-cache"),
+    }
+}
+
+impl Cache {
+    pub async fn init(root: &Path) -> Result<Cache, Error> {
+        create_dir_all(root.join("blobs").join("sha256")).await?;
+
+        let index = match read(root.join("index.json")).await {
+            Ok(contents) => serde_json::from_slice(&contents)?,
+            Err(error) if error.kind() == ErrorKind::NotFound => HashMap::with_capacity(10),
+            Err(error) => return Err(error),
+        };
+
+        Ok(Cache {
+            root: root.to_owned(),
+            index,
+        })
+    }
+
+    pub fn blob_path(&self, checksum: &str) -> PathBuf {
+        let prefix = checksum.get(..2).unwrap_or(checksum);
+
+        self.root
+            .join("blobs")
+            .join("sha256")
+            .join(prefix)
+            .join(checksum)
+    }
+
+    pub async fn contains(&self, checksum: &str) -> bool {
+        metadata(self.blob_path(checksum)).await.is_ok()
+    }
+
+    pub fn lookup(&self, source: &str) -> Option<&str> {
+        self.index.get(source).map(String::as_str)
+    }
+
+    pub async fn insert(&mut self, source: &str, path: &Path) -> Result<String, Error> {
+        let checksum = checksum::sha256(path).await?;
+        let blob_path = self.blob_path(&checksum);
+
+        if !self.contains(&checksum).await {
+            if let Some(parent) = blob_path.parent() {
+                create_dir_all(parent).await?;
+            }
+
+            let staging_path = blob_path.with_extension("part");
+
+            copy(path, &staging_path).await?;
+
+            rename(&staging_path, &blob_path).await?;
+        }
+
+        self.index.insert(source.to_owned(), checksum.to_owned());
+        self.save().await?;
+
+        Ok(checksum)
+    }
+
+    pub async fn link(&self, checksum: &str, destination: &Path) -> Result<bool, Error> {
+        if !self.contains(checksum).await {
+            return Ok(false);
+        }
+
+        if let Some(parent) = destination.parent() {
+            create_dir_all(parent).await?;
+        }
+
+        match remove_file(destination).await {
+            Ok(()) => {}
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => return Err(error),
+        }
+
+        copy(self.blob_path(checksum), destination).await?;
+
+        Ok(true)
+    }
+
+    pub async fn save(&self) -> Result<(), Error> {
+        let contents = serde_json::to_vec_pretty(&self.index)?;
+
+        write(self.root.join("index.json"), contents).await
+    }
+}
+
+impl std::str::FromStr for Retention {
+    type Err = Error;
+
+    fn from_str(retention: &str) -> Result<Retention, Error> {
+        match retention {
+            "delete" => Ok(Retention::Delete),
+            "keep" => Ok(Retention::Keep),
+            "cache" => Ok(Retention::Cache),
+            _ => {
+                let error = format!("Invalid retention '{}'!", retention);
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+}
+
+impl Retention {
+    pub async fn apply(
+        &self,
+        source: &str,
+        path: &Path,
+        cache: Option<&mut Cache>,
+    ) -> Result<Option<PathBuf>, Error> {
+        match self {
+            Retention::Keep => Ok(Some(path.to_owned())),
+            Retention::Delete => {
+                remove_file(path).await?;
+
+                Ok(None)
+            }
+            Retention::Cache => {
+                let mut default = None;
+                let cache = match cache {
+                    Some(cache) => cache,
+                    None => default.insert(Cache::init(&default_cache_path()).await?),
+                };
+                let checksum = cache.insert(source, path).await?;
+
+                remove_file(path).await?;
+
+                Ok(Some(cache.blob_path(&checksum)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_CHECKSUM: &str = "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_root = std::env::temp_dir().join("archeon_test_cache_init");
+        let test_cache = Cache::init(&test_root).await?;
+        assert_eq!(test_cache.root, test_root);
+        assert!(test_cache.index.is_empty());
+        assert!(tokio::fs::metadata(test_root.join("blobs/sha256"))
+            .await?
+            .is_dir());
+        tokio::fs::remove_dir_all(&test_root).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn blob_path() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_root = std::env::temp_dir().join("archeon_test_cache_blob_path");
+        let test_cache = Cache::init(&test_root).await?;
+        assert_eq!(
+            test_cache.blob_path(TEST_CHECKSUM),
+            test_root.join("blobs/sha256/44").join(TEST_CHECKSUM),
+        );
+        tokio::fs::remove_dir_all(&test_root).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn insert() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_root = std::env::temp_dir().join("archeon_test_cache_insert");
+        let test_file = std::env::temp_dir().join("archeon_test_cache_insert.txt");
+        tokio::fs::write(&test_file, "test_body").await?;
+        let mut test_cache = Cache::init(&test_root).await?;
+        let test_checksum = test_cache
+            .insert("http://test-cache/a.txt", &test_file)
+            .await?;
+        test_cache
+            .insert("http://test-cache/b.txt", &test_file)
+            .await?;
+        assert_eq!(test_checksum, TEST_CHECKSUM);
+        assert!(test_cache.contains(TEST_CHECKSUM).await);
+        assert_eq!(
+            test_cache.lookup("http://test-cache/a.txt"),
+            Some(TEST_CHECKSUM)
+        );
+        assert_eq!(
+            test_cache.lookup("http://test-cache/b.txt"),
+            Some(TEST_CHECKSUM)
+        );
+        assert!(test_cache.lookup("http://test-cache/c.txt").is_none());
+        let test_reloaded_cache = Cache::init(&test_root).await?;
+        assert_eq!(test_reloaded_cache.index, test_cache.index);
+        tokio::fs::remove_dir_all(&test_root).await?;
+        tokio::fs::remove_file(&test_file).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn link() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_root = std::env::temp_dir().join("archeon_test_cache_link");
+        let test_file = std::env::temp_dir().join("archeon_test_cache_link.txt");
+        let test_destination = std::env::temp_dir().join("archeon_test_cache_link_destination.txt");
+        tokio::fs::write(&test_file, "test_body").await?;
+        let mut test_cache = Cache::init(&test_root).await?;
+        assert!(!test_cache.link(TEST_CHECKSUM, &test_destination).await?);
+        test_cache
+            .insert("http://test-cache/link.txt", &test_file)
+            .await?;
+        assert!(test_cache.link(TEST_CHECKSUM, &test_destination).await?);
+        assert!(test_cache.link(TEST_CHECKSUM, &test_destination).await?);
+        assert_eq!(tokio::fs::read(&test_destination).await?, b"test_body");
+        tokio::fs::remove_dir_all(&test_root).await?;
+        tokio::fs::remove_file(&test_file).await?;
+        tokio::fs::remove_file(&test_destination).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn retention() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_root = std::env::temp_dir().join("archeon_test_cache_retention");
+        let test_file = std::env::temp_dir().join("archeon_test_cache_retention.deb");
+        let test_source = "http://test-cache/retention.deb";
+        let mut test_cache = Cache::init(&test_root).await?;
+        assert_eq!("delete".parse::<Retention>()?, Retention::Delete);
+        assert_eq!("keep".parse::<Retention>()?, Retention::Keep);
+        assert_eq!("cache".parse::<Retention>()?, Retention::Cache);
+        assert!("archive".parse::<Retention>().is_err());
+        tokio::fs::write(&test_file, "test_body").await?;
+        assert_eq!(
+            Retention::Keep
+                .apply(test_source, &test_file, Some(&mut test_cache))
+                .await?,
+            Some(test_file.to_owned()),
+        );
+        assert!(test_file.exists());
+        assert_eq!(
+            Retention::Cache
+                .apply(test_source, &test_file, Some(&mut test_cache))
+                .await?,
+            Some(test_cache.blob_path(TEST_CHECKSUM)),
+        );
+        assert!(!test_file.exists());
+        assert_eq!(test_cache.lookup(test_source), Some(TEST_CHECKSUM));
+        tokio::fs::write(&test_file, "test_body").await?;
+        assert!(Retention::Delete
+            .apply(test_source, &test_file, None)
+            .await?
+            .is_none());
+        assert!(!test_file.exists());
+        tokio::fs::remove_dir_all(&test_root).await?;
+        Ok(())
+    }
+}
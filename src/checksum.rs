@@ -0,0 +1,393 @@
+use md5::Md5;
+
+use serde::{Deserialize, Serialize};
+
+use sha2::{Digest as _, Sha256, Sha512};
+
+use std::fmt::Write;
+use std::io::{Error, ErrorKind};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::fs::{metadata, File};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::io::AsyncReadExt;
+
+pub const PARALLEL_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Algorithm {
+    #[default]
+    Sha256,
+    Sha512,
+    Blake3,
+    Md5,
+    Crc32,
+}
+
+#[derive(Clone, Debug)]
+pub enum Digest {
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<blake3::Hasher>),
+    Md5(Md5),
+    Crc32(crc32fast::Hasher),
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(algorithm: &str) -> Result<Algorithm, Error> {
+        match algorithm.to_ascii_lowercase().as_str() {
+            "sha256" | "sha-256" => Ok(Algorithm::Sha256),
+            "sha512" | "sha-512" => Ok(Algorithm::Sha512),
+            "blake3" => Ok(Algorithm::Blake3),
+            "md5" => Ok(Algorithm::Md5),
+            "crc32" => Ok(Algorithm::Crc32),
+            _ => {
+                let error = format!("Invalid hash algorithm '{}'!", algorithm);
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let algorithm = match self {
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha512 => "sha512",
+            Algorithm::Blake3 => "blake3",
+            Algorithm::Md5 => "md5",
+            Algorithm::Crc32 => "crc32",
+        };
+
+        f.write_str(algorithm)
+    }
+}
+
+impl Algorithm {
+    pub fn hex_len(&self) -> usize {
+        match self {
+            Algorithm::Sha256 | Algorithm::Blake3 => 64,
+            Algorithm::Sha512 => 128,
+            Algorithm::Md5 => 32,
+            Algorithm::Crc32 => 8,
+        }
+    }
+
+    pub fn is_weak(&self) -> bool {
+        matches!(self, Algorithm::Md5 | Algorithm::Crc32)
+    }
+
+    pub fn detect(expected: &str) -> Option<Algorithm> {
+        let expected = expected.trim();
+
+        if let Some((prefix, _)) = expected.split_once(':') {
+            return prefix.parse().ok();
+        }
+
+        match expected.len() {
+            64 => Some(Algorithm::Sha256),
+            128 => Some(Algorithm::Sha512),
+            32 => Some(Algorithm::Md5),
+            8 => Some(Algorithm::Crc32),
+            _ => None,
+        }
+    }
+
+    pub fn resolve(expected: &str, configured: Option<Algorithm>) -> Algorithm {
+        match expected.trim().split_once(':') {
+            Some((prefix, _)) => prefix.parse().ok(),
+            None => configured.or_else(|| Algorithm::detect(expected)),
+        }
+        .unwrap_or_default()
+    }
+}
+
+impl Digest {
+    pub fn new(algorithm: Algorithm) -> Digest {
+        match algorithm {
+            Algorithm::Sha256 => Digest::Sha256(Sha256::new()),
+            Algorithm::Sha512 => Digest::Sha512(Sha512::new()),
+            Algorithm::Blake3 => Digest::Blake3(Box::default()),
+            Algorithm::Md5 => Digest::Md5(Md5::new()),
+            Algorithm::Crc32 => Digest::Crc32(crc32fast::Hasher::new()),
+        }
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            Digest::Sha256(_) => Algorithm::Sha256,
+            Digest::Sha512(_) => Algorithm::Sha512,
+            Digest::Blake3(_) => Algorithm::Blake3,
+            Digest::Md5(_) => Algorithm::Md5,
+            Digest::Crc32(_) => Algorithm::Crc32,
+        }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Digest::Sha256(hasher) => hasher.update(bytes),
+            Digest::Sha512(hasher) => hasher.update(bytes),
+            Digest::Blake3(hasher) => {
+                hasher.update(bytes);
+            }
+            Digest::Md5(hasher) => hasher.update(bytes),
+            Digest::Crc32(hasher) => hasher.update(bytes),
+        }
+    }
+
+    pub fn finalize(self) -> String {
+        match self {
+            Digest::Sha256(hasher) => to_hex(&hasher.finalize()),
+            Digest::Sha512(hasher) => to_hex(&hasher.finalize()),
+            Digest::Blake3(hasher) => to_hex(hasher.finalize().as_bytes()),
+            Digest::Md5(hasher) => to_hex(&hasher.finalize()),
+            Digest::Crc32(hasher) => to_hex(&hasher.finalize().to_be_bytes()),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sha256(path: &Path) -> Result<String, Error> {
+    digest(path, Algorithm::Sha256).await
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn digest(path: &Path, algorithm: Algorithm) -> Result<String, Error> {
+    if algorithm == Algorithm::Blake3 && metadata(path).await?.len() >= PARALLEL_THRESHOLD {
+        return blake3_parallel(path).await;
+    }
+
+    let mut hasher = Digest::new(algorithm);
+
+    digest_update(path, &mut hasher).await?;
+
+    Ok(hasher.finalize())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn digest_update(path: &Path, hasher: &mut Digest) -> Result<(), Error> {
+    let mut file = File::open(path).await?;
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn blake3_parallel(path: &Path) -> Result<String, Error> {
+    let path = path.to_owned();
+
+    tokio::task::spawn_blocking(move || {
+        let mut hasher = blake3::Hasher::new();
+
+        hasher.update_mmap_rayon(&path)?;
+
+        Ok(to_hex(hasher.finalize().as_bytes()))
+    })
+    .await
+    .map_err(Error::other)?
+}
+
+pub fn sha256_bytes(bytes: &[u8]) -> String {
+    digest_bytes(bytes, Algorithm::Sha256)
+}
+
+pub fn digest_bytes(bytes: &[u8], algorithm: Algorithm) -> String {
+    let mut hasher = Digest::new(algorithm);
+
+    hasher.update(bytes);
+    hasher.finalize()
+}
+
+pub fn verify_bytes(bytes: &[u8], expected: &str) -> bool {
+    matches(
+        expected,
+        &digest_bytes(bytes, Algorithm::resolve(expected, None)),
+    )
+}
+
+pub fn is_valid(checksum: &str) -> bool {
+    let hex = strip_prefix(checksum);
+
+    Algorithm::detect(checksum).is_some_and(|algorithm| hex.len() == algorithm.hex_len())
+        && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub fn strip_prefix(checksum: &str) -> &str {
+    let checksum = checksum.trim();
+
+    match checksum.split_once(':') {
+        Some((prefix, hex)) if prefix.parse::<Algorithm>().is_ok() => hex,
+        _ => checksum,
+    }
+}
+
+pub fn matches(expected: &str, actual: &str) -> bool {
+    strip_prefix(expected).eq_ignore_ascii_case(strip_prefix(actual))
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+
+    for byte in bytes {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+
+    hex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_SHA512: &str = "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f";
+    const TEST_BLAKE3: &str = "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sha256() -> Result<(), std::io::Error> {
+        let test_path = std::env::temp_dir().join("archeon_test_checksum_sha256.txt");
+        tokio::fs::write(&test_path, "test_body").await?;
+        let test_checksum = super::sha256(&test_path).await?;
+        assert_eq!(
+            test_checksum,
+            "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6",
+        );
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sha256_bytes() {
+        let test_checksum = "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6";
+        assert_eq!(super::sha256_bytes(b"test_body"), test_checksum);
+        assert!(super::verify_bytes(
+            b"test_body",
+            &test_checksum.to_ascii_uppercase()
+        ));
+        assert!(!super::verify_bytes(b"test_tags", test_checksum));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn digest() -> Result<(), std::io::Error> {
+        let test_path = std::env::temp_dir().join("archeon_test_checksum_digest.txt");
+        tokio::fs::write(&test_path, "abc").await?;
+        assert_eq!(
+            super::digest(&test_path, Algorithm::Sha512).await?,
+            TEST_SHA512,
+        );
+        assert_eq!(
+            super::digest(&test_path, Algorithm::Blake3).await?,
+            TEST_BLAKE3,
+        );
+        assert_eq!(
+            super::digest_bytes(b"abc", Algorithm::Sha256),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        );
+        assert!(super::verify_bytes(b"abc", TEST_SHA512));
+        assert!(super::verify_bytes(
+            b"abc",
+            &format!("blake3:{}", TEST_BLAKE3.to_ascii_uppercase()),
+        ));
+        assert!(!super::verify_bytes(b"abc", TEST_BLAKE3));
+        assert_eq!(
+            super::digest(&test_path, Algorithm::Md5).await?,
+            "900150983cd24fb0d6963f7d28e17f72",
+        );
+        assert_eq!(
+            super::digest(&test_path, Algorithm::Crc32).await?,
+            "352441c2",
+        );
+        assert!(super::verify_bytes(b"abc", "CRC32:352441C2"));
+        assert!(!super::verify_bytes(
+            b"abd",
+            "900150983cd24fb0d6963f7d28e17f72"
+        ));
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn blake3_parallel() -> Result<(), std::io::Error> {
+        let test_path = std::env::temp_dir().join("archeon_test_checksum_blake3_parallel.bin");
+        let test_body: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        tokio::fs::write(&test_path, &test_body).await?;
+        assert_eq!(
+            super::blake3_parallel(&test_path).await?,
+            digest_bytes(&test_body, Algorithm::Blake3),
+        );
+        assert_eq!(
+            super::digest(&test_path, Algorithm::Blake3).await?,
+            digest_bytes(&test_body, Algorithm::Blake3),
+        );
+        tokio::fs::remove_file(&test_path).await?;
+        assert_eq!(
+            super::blake3_parallel(&test_path).await.unwrap_err().kind(),
+            ErrorKind::NotFound,
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn detect() -> Result<(), std::io::Error> {
+        assert_eq!("SHA-512".parse::<Algorithm>()?, Algorithm::Sha512);
+        assert_eq!("blake3".parse::<Algorithm>()?, Algorithm::Blake3);
+        assert!("md4".parse::<Algorithm>().is_err());
+        assert_eq!(Algorithm::Blake3.to_string(), "blake3");
+        assert_eq!(Algorithm::detect(TEST_SHA512), Some(Algorithm::Sha512));
+        assert_eq!(Algorithm::detect(TEST_BLAKE3), Some(Algorithm::Sha256));
+        assert_eq!(
+            Algorithm::detect(&format!("blake3:{}", TEST_BLAKE3)),
+            Some(Algorithm::Blake3),
+        );
+        assert_eq!(Algorithm::detect("md4:00"), None);
+        assert_eq!(Algorithm::detect("00"), None);
+        assert_eq!(Algorithm::detect(&"0".repeat(32)), Some(Algorithm::Md5));
+        assert_eq!(Algorithm::detect("352441c2"), Some(Algorithm::Crc32));
+        assert!(Algorithm::Md5.is_weak());
+        assert!(Algorithm::Crc32.is_weak());
+        assert!(!Algorithm::Blake3.is_weak());
+        assert_eq!(
+            Algorithm::resolve(TEST_BLAKE3, Some(Algorithm::Blake3)),
+            Algorithm::Blake3,
+        );
+        assert_eq!(
+            Algorithm::resolve(&format!("sha512:{}", TEST_SHA512), Some(Algorithm::Blake3)),
+            Algorithm::Sha512,
+        );
+        assert_eq!(Algorithm::resolve("00", None), Algorithm::Sha256);
+        assert_eq!(Algorithm::Sha512.hex_len(), 128);
+        assert!(super::matches(
+            &format!("blake3:{}", TEST_BLAKE3),
+            TEST_BLAKE3
+        ));
+        assert!(!super::matches(TEST_SHA512, TEST_BLAKE3));
+        assert!(super::is_valid(TEST_SHA512));
+        assert!(super::is_valid(&format!("blake3:{}", TEST_BLAKE3)));
+        assert!(!super::is_valid(&format!("sha512:{}", TEST_BLAKE3)));
+        assert!(!super::is_valid(&"z".repeat(64)));
+        assert_eq!(super::strip_prefix(" sha256:00 "), "00");
+        assert_eq!(super::strip_prefix("md4:00"), "md4:00");
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn to_hex() {
+        assert_eq!(super::to_hex(&[0, 15, 16, 255]), "000f10ff");
+        assert_eq!(super::to_hex(&[]), "");
+    }
+}
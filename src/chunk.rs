@@ -0,0 +1,462 @@
+use hyper::{Body, Method, Request, Uri};
+
+use serde::{Deserialize, Serialize};
+
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use tokio::fs::{create_dir_all, metadata, read, rename, write, File};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::checksum;
+use crate::source::launch_request;
+use crate::transfer::{resolve_location, HttpClient};
+
+pub const MIN_CHUNK_SIZE: usize = 16 * 1024;
+pub const AVG_CHUNK_SIZE: usize = 64 * 1024;
+pub const MAX_CHUNK_SIZE: usize = 256 * 1024;
+
+const GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0; 256];
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut index = 0;
+
+    while index < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut value = state;
+
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[index] = value ^ (value >> 31);
+        index += 1;
+    }
+
+    table
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunk {
+    pub digest: String,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkIndex {
+    pub digest: String,
+    pub size: u64,
+    pub chunks: Vec<Chunk>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chunker {
+    pub min_size: usize,
+    pub avg_size: usize,
+    pub max_size: usize,
+    hash: u64,
+    buffer: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkStore {
+    pub root: PathBuf,
+    pub min_size: usize,
+    pub avg_size: usize,
+    pub max_size: usize,
+}
+
+pub fn chunk_location(digest: &str) -> String {
+    let prefix = digest.get(..2).unwrap_or(digest);
+
+    format!("sha256/{}/{}", prefix, digest)
+}
+
+impl Chunker {
+    pub fn init(min_size: usize, avg_size: usize, max_size: usize) -> Chunker {
+        Chunker {
+            min_size,
+            avg_size: avg_size.next_power_of_two(),
+            max_size: max_size.max(min_size),
+            hash: 0,
+            buffer: Vec::with_capacity(max_size),
+        }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mask = self.avg_size as u64 - 1;
+        let mut chunks = Vec::new();
+
+        for byte in bytes {
+            self.buffer.push(*byte);
+            self.hash = (self.hash << 1).wrapping_add(GEAR[*byte as usize]);
+
+            let length = self.buffer.len();
+
+            if (length >= self.min_size && self.hash & mask == 0) || length >= self.max_size {
+                chunks.push(std::mem::replace(
+                    &mut self.buffer,
+                    Vec::with_capacity(self.max_size),
+                ));
+                self.hash = 0;
+            }
+        }
+
+        chunks
+    }
+
+    pub fn finish(self) -> Option<Vec<u8>> {
+        (!self.buffer.is_empty()).then_some(self.buffer)
+    }
+}
+
+impl Default for Chunker {
+    fn default() -> Chunker {
+        Chunker::init(MIN_CHUNK_SIZE, AVG_CHUNK_SIZE, MAX_CHUNK_SIZE)
+    }
+}
+
+impl ChunkIndex {
+    pub async fn load(path: &Path) -> Result<ChunkIndex, Error> {
+        let contents = read(path).await?;
+
+        serde_json::from_slice(&contents).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub async fn save(&self, path: &Path) -> Result<(), Error> {
+        let contents = serde_json::to_vec_pretty(self)?;
+
+        write(path, contents).await
+    }
+
+    pub async fn fetch(client: &HttpClient, url: &Uri) -> Result<ChunkIndex, Error> {
+        let body = launch_get(client, url).await?;
+
+        serde_json::from_slice(&body).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+}
+
+impl ChunkStore {
+    pub async fn init(root: &Path) -> Result<ChunkStore, Error> {
+        create_dir_all(root.join("sha256")).await?;
+
+        Ok(ChunkStore {
+            root: root.to_owned(),
+            min_size: MIN_CHUNK_SIZE,
+            avg_size: AVG_CHUNK_SIZE,
+            max_size: MAX_CHUNK_SIZE,
+        })
+    }
+
+    pub fn chunk_path(&self, digest: &str) -> PathBuf {
+        self.root.join(chunk_location(digest))
+    }
+
+    pub async fn contains(&self, digest: &str) -> bool {
+        metadata(self.chunk_path(digest)).await.is_ok()
+    }
+
+    pub async fn insert(&self, path: &Path) -> Result<ChunkIndex, Error> {
+        let mut file = File::open(path).await?;
+        let mut chunker = Chunker::init(self.min_size, self.avg_size, self.max_size);
+        let mut buffer = vec![0; 64 * 1024];
+        let mut index = ChunkIndex::default();
+
+        loop {
+            let read = file.read(&mut buffer).await?;
+
+            if read == 0 {
+                break;
+            }
+
+            for chunk in chunker.update(&buffer[..read]) {
+                index.chunks.push(self.insert_chunk(&chunk).await?);
+            }
+        }
+
+        if let Some(chunk) = chunker.finish() {
+            index.chunks.push(self.insert_chunk(&chunk).await?);
+        }
+
+        index.size = index.chunks.iter().map(|chunk| chunk.size).sum();
+        index.digest = checksum::sha256(path).await?;
+
+        Ok(index)
+    }
+
+    pub async fn missing(&self, index: &ChunkIndex) -> Vec<Chunk> {
+        let mut missing: Vec<Chunk> = Vec::new();
+
+        for chunk in &index.chunks {
+            if !self.contains(&chunk.digest).await
+                && !missing.iter().any(|known| known.digest == chunk.digest)
+            {
+                missing.push(chunk.to_owned());
+            }
+        }
+
+        missing
+    }
+
+    pub async fn fetch(
+        &self,
+        client: &HttpClient,
+        base: &Uri,
+        index: &ChunkIndex,
+    ) -> Result<u64, Error> {
+        let mut downloaded = 0;
+
+        for chunk in self.missing(index).await {
+            let url = resolve_location(base, &chunk_location(&chunk.digest)).ok_or_else(|| {
+                let error = format!("Invalid chunk store '{}'!", base);
+
+                Error::new(ErrorKind::InvalidInput, error)
+            })?;
+            let body = launch_get(client, &url).await?;
+
+            if !checksum::verify_bytes(&body, &chunk.digest) {
+                let error = format!(
+                    "Checksum mismatch for chunk {}: got {}!",
+                    chunk.digest,
+                    checksum::sha256_bytes(&body),
+                );
+
+                return Err(Error::new(ErrorKind::InvalidData, error));
+            }
+
+            self.insert_chunk(&body).await?;
+            downloaded += body.len() as u64;
+        }
+
+        Ok(downloaded)
+    }
+
+    pub async fn assemble(&self, index: &ChunkIndex, destination: &Path) -> Result<(), Error> {
+        if let Some(parent) = destination.parent() {
+            create_dir_all(parent).await?;
+        }
+
+        let staging_path = destination.with_extension("part");
+        let mut file = File::create(&staging_path).await?;
+
+        for chunk in &index.chunks {
+            let contents = read(self.chunk_path(&chunk.digest)).await?;
+
+            file.write_all(&contents).await?;
+        }
+
+        file.flush().await?;
+        drop(file);
+
+        let actual = checksum::sha256(&staging_path).await?;
+
+        if !actual.eq_ignore_ascii_case(&index.digest) {
+            tokio::fs::remove_file(&staging_path).await?;
+
+            let error = format!(
+                "Checksum mismatch for {:?}: expected {}, got {}!",
+                destination, index.digest, actual,
+            );
+
+            return Err(Error::new(ErrorKind::InvalidData, error));
+        }
+
+        rename(&staging_path, destination).await
+    }
+
+    async fn insert_chunk(&self, contents: &[u8]) -> Result<Chunk, Error> {
+        let digest = checksum::sha256_bytes(contents);
+        let chunk_path = self.chunk_path(&digest);
+
+        if !self.contains(&digest).await {
+            if let Some(parent) = chunk_path.parent() {
+                create_dir_all(parent).await?;
+            }
+
+            let staging_path = chunk_path.with_extension("part");
+
+            write(&staging_path, contents).await?;
+            rename(&staging_path, &chunk_path).await?;
+        }
+
+        Ok(Chunk {
+            digest,
+            size: contents.len() as u64,
+        })
+    }
+}
+
+async fn launch_get(client: &HttpClient, url: &Uri) -> Result<hyper::body::Bytes, Error> {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(url)
+        .body(Body::empty())
+        .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+    let (status, _, body) = launch_request(client, request).await?;
+
+    match status.is_success() {
+        true => Ok(body),
+        false => {
+            let error = format!("Request for {} failed with {}!", url, status);
+
+            Err(Error::other(error))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer::Transfer;
+
+    use std::str::FromStr;
+
+    fn test_bytes(length: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+
+        (0..length)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    async fn test_store(name: &str) -> Result<ChunkStore, Error> {
+        let test_root = std::env::temp_dir().join(name);
+        let _ = tokio::fs::remove_dir_all(&test_root).await;
+        let mut test_store = ChunkStore::init(&test_root).await?;
+        test_store.min_size = 256;
+        test_store.avg_size = 1024;
+        test_store.max_size = 4096;
+        Ok(test_store)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn chunker() {
+        let test_bytes = test_bytes(64 * 1024, 7);
+        let mut test_chunker = Chunker::init(256, 1024, 4096);
+        let mut test_chunks = test_chunker.update(&test_bytes[..1000]);
+        test_chunks.extend(test_chunker.update(&test_bytes[1000..]));
+        test_chunks.extend(test_chunker.finish());
+        assert!(test_chunks.len() > 16);
+        assert!(test_chunks.iter().all(|chunk| chunk.len() <= 4096));
+        assert!(test_chunks[..test_chunks.len() - 1]
+            .iter()
+            .all(|chunk| chunk.len() >= 256));
+        assert_eq!(test_chunks.concat(), test_bytes);
+        let mut test_shifted = b"test_prefix".to_vec();
+        test_shifted.extend_from_slice(&test_bytes);
+        let mut test_chunker = Chunker::init(256, 1024, 4096);
+        let mut test_shifted_chunks = test_chunker.update(&test_shifted);
+        test_shifted_chunks.extend(test_chunker.finish());
+        let test_shared = test_shifted_chunks
+            .iter()
+            .filter(|chunk| test_chunks.contains(chunk))
+            .count();
+        assert!(test_shared >= test_chunks.len() - 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn insert() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_store = test_store("archeon_test_chunk_insert").await?;
+        let test_file = std::env::temp_dir().join("archeon_test_chunk_insert.img");
+        let test_bytes = test_bytes(32 * 1024, 11);
+        tokio::fs::write(&test_file, &test_bytes).await?;
+        let test_index = test_store.insert(&test_file).await?;
+        assert_eq!(test_index.size, test_bytes.len() as u64);
+        assert_eq!(test_index.digest, checksum::sha256_bytes(&test_bytes));
+        assert!(test_store.missing(&test_index).await.is_empty());
+        let test_index_path = test_store.root.join("test.json");
+        test_index.save(&test_index_path).await?;
+        assert_eq!(ChunkIndex::load(&test_index_path).await?, test_index);
+        let test_destination = test_store.root.join("assembled.img");
+        test_store.assemble(&test_index, &test_destination).await?;
+        assert_eq!(tokio::fs::read(&test_destination).await?, test_bytes);
+        let mut test_corrupt = test_index.to_owned();
+        test_corrupt.digest = String::from("00");
+        assert_eq!(
+            test_store
+                .assemble(&test_corrupt, &test_destination)
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidData,
+        );
+        tokio::fs::remove_dir_all(&test_store.root).await?;
+        tokio::fs::remove_file(&test_file).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fetch() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_remote = test_store("archeon_test_chunk_fetch_remote").await?;
+        let test_local = test_store("archeon_test_chunk_fetch_local").await?;
+        let test_old = std::env::temp_dir().join("archeon_test_chunk_fetch_old.img");
+        let test_new = std::env::temp_dir().join("archeon_test_chunk_fetch_new.img");
+        let test_old_bytes = test_bytes(32 * 1024, 13);
+        let mut test_new_bytes = test_old_bytes.to_owned();
+        test_new_bytes[16 * 1024..16 * 1024 + 8].copy_from_slice(b"updated!");
+        tokio::fs::write(&test_old, &test_old_bytes).await?;
+        tokio::fs::write(&test_new, &test_new_bytes).await?;
+        test_local.insert(&test_old).await?;
+        let test_index = test_remote.insert(&test_new).await?;
+        let test_missing = test_local.missing(&test_index).await;
+        assert!(!test_missing.is_empty());
+        assert!(test_missing.len() < test_index.chunks.len() / 2);
+        let mut test_server = mockito::Server::new_async().await;
+        let mock_index = test_server
+            .mock("GET", "/images/test.json")
+            .with_status(200)
+            .with_body(serde_json::to_vec(&test_index)?)
+            .create_async()
+            .await;
+        let mut test_mocks = Vec::new();
+        for test_chunk in &test_missing {
+            test_mocks.push(
+                test_server
+                    .mock(
+                        "GET",
+                        format!("/images/chunks/{}", chunk_location(&test_chunk.digest)).as_str(),
+                    )
+                    .with_status(200)
+                    .with_body(tokio::fs::read(test_remote.chunk_path(&test_chunk.digest)).await?)
+                    .create_async()
+                    .await,
+            );
+        }
+        let test_client = Transfer::init_client();
+        let test_index = ChunkIndex::fetch(
+            &test_client,
+            &Uri::from_str(&format!("{}/images/test.json", test_server.url()))?,
+        )
+        .await?;
+        let test_downloaded = test_local
+            .fetch(
+                &test_client,
+                &Uri::from_str(&format!("{}/images/chunks/", test_server.url()))?,
+                &test_index,
+            )
+            .await?;
+        assert_eq!(
+            test_downloaded,
+            test_missing.iter().map(|chunk| chunk.size).sum::<u64>(),
+        );
+        assert!(test_local.missing(&test_index).await.is_empty());
+        let test_destination = test_local.root.join("assembled.img");
+        test_local.assemble(&test_index, &test_destination).await?;
+        assert_eq!(tokio::fs::read(&test_destination).await?, test_new_bytes);
+        mock_index.assert_async().await;
+        for test_mock in test_mocks {
+            test_mock.assert_async().await;
+        }
+        tokio::fs::remove_dir_all(&test_remote.root).await?;
+        tokio::fs::remove_dir_all(&test_local.root).await?;
+        tokio::fs::remove_file(&test_old).await?;
+        tokio::fs::remove_file(&test_new).await?;
+        Ok(())
+    }
+}
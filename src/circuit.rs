@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::ArcheonError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen,
+}
+
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+    hosts: Arc<Mutex<HashMap<String, CircuitState>>>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> CircuitBreaker {
+        CircuitBreaker::init(5, Duration::from_secs(60))
+    }
+}
+
+impl CircuitBreaker {
+    pub fn init(failure_threshold: u32, cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            hosts: Arc::new(Mutex::new(HashMap::with_capacity(8))),
+        }
+    }
+
+    pub fn state(&self, host: &str) -> CircuitState {
+        self.lock()
+            .get(&host.to_ascii_lowercase())
+            .copied()
+            .unwrap_or(CircuitState::Closed { failures: 0 })
+    }
+
+    pub fn check(&self, host: &str) -> Result<(), ArcheonError> {
+        let mut hosts = self.lock();
+        let state = match hosts.get_mut(&host.to_ascii_lowercase()) {
+            Some(state) => state,
+            None => return Ok(()),
+        };
+
+        match *state {
+            CircuitState::Open { until } if until > Instant::now() => {
+                let error = format!(
+                    "Circuit open for {}, retrying in {}s!",
+                    host,
+                    until.saturating_duration_since(Instant::now()).as_secs(),
+                );
+
+                Err(ArcheonError::Unavailable(error))
+            }
+            CircuitState::Open { .. } => {
+                *state = CircuitState::HalfOpen;
+
+                Ok(())
+            }
+            CircuitState::HalfOpen => {
+                let error = format!("Circuit half-open for {}, trial in progress!", host);
+
+                Err(ArcheonError::Unavailable(error))
+            }
+            CircuitState::Closed { .. } => Ok(()),
+        }
+    }
+
+    pub fn record_success(&self, host: &str) {
+        self.lock().remove(&host.to_ascii_lowercase());
+    }
+
+    pub fn record_failure(&self, host: &str) {
+        let mut hosts = self.lock();
+        let state = hosts
+            .entry(host.to_ascii_lowercase())
+            .or_insert(CircuitState::Closed { failures: 0 });
+        let open = CircuitState::Open {
+            until: Instant::now() + self.cooldown,
+        };
+
+        *state = match *state {
+            CircuitState::Closed { failures } if failures + 1 < self.failure_threshold => {
+                CircuitState::Closed {
+                    failures: failures + 1,
+                }
+            }
+            _ => open,
+        };
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CircuitState>> {
+        self.hosts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn record_failure() {
+        let test_breaker = CircuitBreaker::init(2, Duration::from_secs(60));
+        assert!(test_breaker.check("dead.test").is_ok());
+        test_breaker.record_failure("dead.test");
+        assert_eq!(
+            test_breaker.state("dead.test"),
+            CircuitState::Closed { failures: 1 },
+        );
+        assert!(test_breaker.check("dead.test").is_ok());
+        test_breaker.record_failure("DEAD.test");
+        assert!(matches!(
+            test_breaker.state("dead.test"),
+            CircuitState::Open { .. },
+        ));
+        let test_error = test_breaker.check("dead.test").unwrap_err();
+        assert_eq!(test_error.kind(), crate::error::ErrorKind::Network);
+        assert!(!test_error.is_retryable());
+        assert!(test_breaker.check("alive.test").is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn half_open() {
+        let test_breaker = CircuitBreaker::init(1, Duration::from_millis(20));
+        test_breaker.record_failure("flaky.test");
+        assert!(test_breaker.check("flaky.test").is_err());
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(test_breaker.check("flaky.test").is_ok());
+        assert_eq!(test_breaker.state("flaky.test"), CircuitState::HalfOpen);
+        assert!(test_breaker.check("flaky.test").is_err());
+        test_breaker.record_failure("flaky.test");
+        assert!(matches!(
+            test_breaker.state("flaky.test"),
+            CircuitState::Open { .. },
+        ));
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(test_breaker.check("flaky.test").is_ok());
+        test_breaker.record_success("flaky.test");
+        assert_eq!(
+            test_breaker.state("flaky.test"),
+            CircuitState::Closed { failures: 0 },
+        );
+    }
+}
@@ -0,0 +1,451 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap};
+use std::env::var_os;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::fs::read_to_string;
+
+use crate::auth::OAuth2Config;
+use crate::aws::AwsConfig;
+use crate::batch::BatchArtifact;
+use crate::cache::Retention;
+use crate::daemon::RemoteConfig;
+use crate::durability::Durability;
+use crate::overwrite::OverwritePolicy;
+use crate::partial::{default_quarantine_dir, PartialCleanup, PartialPolicy};
+use crate::proxy::parse_proxy;
+use crate::resume::ResumeCheck;
+use crate::transfer::ClientOptions;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub default_profile: Option<String>,
+    #[serde(rename = "profile")]
+    pub profiles: HashMap<String, Profile>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub destination: Option<PathBuf>,
+    pub user_agent: Option<String>,
+    pub proxy: Option<String>,
+    pub connect_timeout: Option<u64>,
+    pub concurrency: Option<usize>,
+    pub buffer_limit: Option<usize>,
+    pub write_buffer: Option<usize>,
+    pub durability: Option<Durability>,
+    pub segments: Option<usize>,
+    pub resume_check: Option<ResumeCheck>,
+    pub partial: Option<PartialPolicy>,
+    pub partial_ttl: Option<u64>,
+    pub quarantine_dir: Option<PathBuf>,
+    pub retention: Option<Retention>,
+    pub post_install: Vec<String>,
+    pub timestamping: Option<bool>,
+    pub overwrite: Option<OverwritePolicy>,
+    pub quiet: Option<bool>,
+    pub debug_http: Option<bool>,
+    pub har: Option<PathBuf>,
+    pub audit_log: Option<PathBuf>,
+    pub headers: BTreeMap<String, String>,
+    pub variables: BTreeMap<String, String>,
+    pub oauth2: Option<OAuth2Config>,
+    pub aws: Option<AwsConfig>,
+    pub netrc: Option<bool>,
+    pub keyring: Option<bool>,
+    pub credential_helper: Option<String>,
+    pub mirrors: Vec<String>,
+    pub remote: Option<RemoteConfig>,
+}
+
+pub fn default_config_path() -> PathBuf {
+    match (var_os("XDG_CONFIG_HOME"), var_os("HOME")) {
+        (Some(config_dir), _) => PathBuf::from(config_dir).join("archeon/config.toml"),
+        (None, Some(home)) => PathBuf::from(home).join(".config/archeon/config.toml"),
+        (None, None) => PathBuf::from("archeon.toml"),
+    }
+}
+
+impl Config {
+    pub async fn init(path: &Path) -> Result<Config, Error> {
+        match read_to_string(path).await {
+            Ok(contents) => Self::parse(&contents),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Config, Error> {
+        toml::from_str(contents).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, Error> {
+        match name.or(self.default_profile.as_deref()) {
+            Some(name) => match self.profiles.get(name) {
+                Some(profile) => Ok(profile.to_owned()),
+                None => {
+                    let error = format!("No profile named '{}' found!", name);
+
+                    Err(Error::new(ErrorKind::NotFound, error))
+                }
+            },
+            None => Ok(Profile::default()),
+        }
+    }
+}
+
+impl Profile {
+    pub fn apply_env(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<(), Error> {
+        for (name, value) in vars {
+            match name.as_str() {
+                "ARCHEON_DESTINATION" => self.destination = Some(PathBuf::from(value)),
+                "ARCHEON_USER_AGENT" => self.user_agent = Some(value),
+                "ARCHEON_PROXY" => self.proxy = Some(value),
+                "ARCHEON_CONNECT_TIMEOUT" => self.connect_timeout = Some(parse_env(&name, &value)?),
+                "ARCHEON_CONCURRENCY" => self.concurrency = Some(parse_env(&name, &value)?),
+                "ARCHEON_BUFFER_LIMIT" => self.buffer_limit = Some(parse_env(&name, &value)?),
+                "ARCHEON_WRITE_BUFFER" => self.write_buffer = Some(parse_env(&name, &value)?),
+                "ARCHEON_DURABILITY" => self.durability = Some(parse_env(&name, &value)?),
+                "ARCHEON_SEGMENTS" => self.segments = Some(parse_env(&name, &value)?),
+                "ARCHEON_RESUME_CHECK" => self.resume_check = Some(parse_env(&name, &value)?),
+                "ARCHEON_PARTIAL" => self.partial = Some(parse_env(&name, &value)?),
+                "ARCHEON_PARTIAL_TTL" => self.partial_ttl = Some(parse_env(&name, &value)?),
+                "ARCHEON_QUARANTINE_DIR" => self.quarantine_dir = Some(PathBuf::from(value)),
+                "ARCHEON_RETENTION" => self.retention = Some(parse_env(&name, &value)?),
+                "ARCHEON_OVERWRITE" => self.overwrite = Some(parse_env(&name, &value)?),
+                "ARCHEON_MIRRORS" => {
+                    self.mirrors = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|mirror| !mirror.is_empty())
+                        .map(str::to_owned)
+                        .collect();
+                }
+                "ARCHEON_TIMESTAMPING" => self.timestamping = Some(parse_env_bool(&name, &value)?),
+                "ARCHEON_QUIET" => self.quiet = Some(parse_env_bool(&name, &value)?),
+                "ARCHEON_HAR" => self.har = Some(PathBuf::from(value)),
+                "ARCHEON_AUDIT_LOG" => self.audit_log = Some(PathBuf::from(value)),
+                "ARCHEON_DEBUG_HTTP" => self.debug_http = Some(parse_env_bool(&name, &value)?),
+                "ARCHEON_NETRC" => self.netrc = Some(parse_env_bool(&name, &value)?),
+                "ARCHEON_KEYRING" => self.keyring = Some(parse_env_bool(&name, &value)?),
+                "ARCHEON_CREDENTIAL_HELPER" => self.credential_helper = Some(value),
+                "ARCHEON_REMOTE_TOKEN" => {
+                    self.remote.get_or_insert_with(RemoteConfig::default).token = Some(value)
+                }
+                _ => {
+                    if let Some(header) = name.strip_prefix("ARCHEON_HEADER_") {
+                        let header = header.to_ascii_lowercase().replace('_', "-");
+
+                        self.headers.insert(header, value);
+                    } else if let Some(variable) = name.strip_prefix("ARCHEON_VAR_") {
+                        self.variables.insert(variable.to_ascii_lowercase(), value);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn client_options(&self) -> Result<ClientOptions, Error> {
+        let mut options = ClientOptions::default();
+
+        if let Some(proxy) = &self.proxy {
+            options.proxy = Some(parse_proxy(proxy)?);
+        }
+
+        if let Some(connect_timeout) = self.connect_timeout {
+            options.connect_timeout = Some(Duration::from_secs(connect_timeout));
+        }
+
+        Ok(options)
+    }
+
+    pub fn partial_cleanup(&self) -> PartialCleanup {
+        PartialCleanup {
+            policy: self.partial.unwrap_or_default(),
+            ttl: self.partial_ttl.map(Duration::from_secs),
+            quarantine_dir: self
+                .quarantine_dir
+                .to_owned()
+                .unwrap_or_else(default_quarantine_dir),
+        }
+    }
+
+    pub fn apply(&self, artifact: &BatchArtifact) -> BatchArtifact {
+        let mut artifact = artifact.to_owned();
+
+        if artifact.destination.is_none() {
+            artifact.destination = self.destination.to_owned();
+        }
+
+        if artifact.user_agent.is_none() {
+            artifact.user_agent = self.user_agent.to_owned();
+        }
+
+        if artifact.retention.is_none() && !artifact.delete {
+            artifact.retention = self.retention;
+        }
+
+        if artifact.overwrite.is_none() {
+            artifact.overwrite = self.overwrite;
+        }
+
+        if artifact.post_install.is_empty() {
+            artifact.post_install = self.post_install.to_owned();
+        }
+
+        for (name, value) in &self.headers {
+            artifact
+                .headers
+                .entry(name.to_owned())
+                .or_insert_with(|| value.to_owned());
+        }
+
+        for (name, value) in &self.variables {
+            artifact
+                .variables
+                .entry(name.to_owned())
+                .or_insert_with(|| value.to_owned());
+        }
+
+        artifact
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, Error> {
+    value.trim().parse().map_err(|_| {
+        let error = format!("Invalid value '{}' for {}!", value, name);
+
+        Error::new(ErrorKind::InvalidInput, error)
+    })
+}
+
+fn parse_env_bool(name: &str, value: &str) -> Result<bool, Error> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" | "" => Ok(false),
+        _ => parse_env(name, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_CONFIG: &str = r#"
+        default_profile = "home"
+
+        [profile.home]
+
+        [profile.work]
+        destination = "/tmp/archeon_test_config_work"
+        user_agent = "test-work/1.0"
+        proxy = "http://test-proxy.invalid:3128"
+        connect_timeout = 5
+
+        [profile.work.headers]
+        authorization = "Bearer test_token"
+
+        [profile.work.variables]
+        version = "1.2.3"
+        target = "x86_64-linux"
+
+        [profile.work.oauth2]
+        flow = "device_code"
+        token_url = "https://test-idp.invalid/token"
+        device_authorization_url = "https://test-idp.invalid/device"
+        client_id = "test_client"
+
+        [profile.s3]
+        aws = { region = "eu-central-1" }
+    "#;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_config_init.toml");
+        assert_eq!(Config::init(&test_path).await?, Config::default());
+        tokio::fs::write(&test_path, TEST_CONFIG).await?;
+        assert_eq!(Config::init(&test_path).await?.profiles.len(), 3);
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn profile() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_config = Config::parse(TEST_CONFIG)?;
+        assert_eq!(test_config.profile(None)?, Profile::default());
+        let test_profile = test_config.profile(Some("work"))?;
+        assert_eq!(test_profile.connect_timeout, Some(5));
+        let test_oauth2 = test_profile.oauth2.unwrap();
+        assert_eq!(test_oauth2.flow, crate::auth::OAuth2Flow::DeviceCode);
+        assert_eq!(test_oauth2.client_id, "test_client");
+        assert!(test_oauth2.client_secret.is_none());
+        let test_aws = test_config.profile(Some("s3"))?.aws.unwrap();
+        assert_eq!(test_aws.region.as_deref(), Some("eu-central-1"));
+        assert!(test_aws.service.is_none());
+        assert!(test_config.profile(Some("missing")).is_err());
+        assert_eq!(Config::default().profile(None)?, Profile::default());
+        assert!(Config::parse("default_profile = 1").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply_env() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_profile = Config::parse(TEST_CONFIG)?.profile(Some("work"))?;
+        let test_vars = [
+            ("ARCHEON_DESTINATION", "/tmp/archeon_test_apply_env"),
+            ("ARCHEON_CONNECT_TIMEOUT", "9"),
+            ("ARCHEON_CONCURRENCY", "4"),
+            ("ARCHEON_BUFFER_LIMIT", "1048576"),
+            ("ARCHEON_WRITE_BUFFER", "65536"),
+            ("ARCHEON_DURABILITY", "periodic"),
+            ("ARCHEON_SEGMENTS", "4"),
+            ("ARCHEON_RESUME_CHECK", "trailing"),
+            ("ARCHEON_PARTIAL", "quarantine"),
+            ("ARCHEON_RETENTION", "cache"),
+            ("ARCHEON_PARTIAL_TTL", "86400"),
+            ("ARCHEON_QUARANTINE_DIR", "/tmp/archeon_test_quarantine"),
+            (
+                "ARCHEON_MIRRORS",
+                "http://first.test/debian, http://second.test/debian,",
+            ),
+            ("ARCHEON_TIMESTAMPING", "1"),
+            ("ARCHEON_OVERWRITE", "skip"),
+            ("ARCHEON_QUIET", "yes"),
+            ("ARCHEON_DEBUG_HTTP", "true"),
+            ("ARCHEON_HAR", "/tmp/archeon_test.har"),
+            ("ARCHEON_AUDIT_LOG", "/tmp/archeon_test_audit.log"),
+            ("ARCHEON_NETRC", "off"),
+            ("ARCHEON_CREDENTIAL_HELPER", "vault"),
+            ("ARCHEON_REMOTE_TOKEN", "test_remote_token"),
+            ("ARCHEON_HEADER_X_TEST_HEADER", "test_value"),
+            ("ARCHEON_VAR_VERSION", "2.0.0"),
+            ("HOME", "/tmp/archeon_test_home"),
+        ];
+        test_profile.apply_env(
+            test_vars
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        )?;
+        assert_eq!(
+            test_profile.destination,
+            Some(PathBuf::from("/tmp/archeon_test_apply_env")),
+        );
+        assert_eq!(test_profile.connect_timeout, Some(9));
+        assert_eq!(test_profile.concurrency, Some(4));
+        assert_eq!(test_profile.buffer_limit, Some(1048576));
+        assert_eq!(test_profile.write_buffer, Some(65536));
+        assert_eq!(test_profile.durability, Some(Durability::Periodic));
+        assert_eq!(test_profile.segments, Some(4));
+        assert_eq!(test_profile.resume_check, Some(ResumeCheck::Trailing));
+        assert_eq!(test_profile.retention, Some(Retention::Cache));
+        assert_eq!(test_profile.timestamping, Some(true));
+        let test_artifact = test_profile.apply(&BatchArtifact::default());
+        assert_eq!(test_artifact.retention(), Retention::Cache);
+        assert_eq!(test_artifact.overwrite, Some(OverwritePolicy::Skip));
+        let test_artifact = test_profile.apply(&BatchArtifact {
+            delete: true,
+            ..BatchArtifact::default()
+        });
+        assert_eq!(test_artifact.retention(), Retention::Delete);
+        let test_cleanup = test_profile.partial_cleanup();
+        assert_eq!(test_cleanup.policy, PartialPolicy::Quarantine);
+        assert_eq!(test_cleanup.ttl, Some(Duration::from_secs(86400)));
+        assert_eq!(
+            test_cleanup.quarantine_dir,
+            PathBuf::from("/tmp/archeon_test_quarantine"),
+        );
+        assert_eq!(
+            Profile::default().partial_cleanup(),
+            PartialCleanup::default(),
+        );
+        assert_eq!(
+            test_profile.mirrors,
+            vec!["http://first.test/debian", "http://second.test/debian"],
+        );
+        assert_eq!(test_profile.quiet, Some(true));
+        assert_eq!(test_profile.debug_http, Some(true));
+        assert_eq!(
+            test_profile.har,
+            Some(PathBuf::from("/tmp/archeon_test.har"))
+        );
+        assert_eq!(
+            test_profile.audit_log,
+            Some(PathBuf::from("/tmp/archeon_test_audit.log"))
+        );
+        assert_eq!(test_profile.netrc, Some(false));
+        assert_eq!(test_profile.credential_helper.as_deref(), Some("vault"));
+        assert_eq!(
+            test_profile
+                .remote
+                .as_ref()
+                .and_then(|remote| remote.token.as_deref()),
+            Some("test_remote_token"),
+        );
+        assert_eq!(test_profile.user_agent.as_deref(), Some("test-work/1.0"));
+        assert_eq!(test_profile.headers["x-test-header"], "test_value");
+        assert_eq!(test_profile.headers["authorization"], "Bearer test_token");
+        assert_eq!(test_profile.variables["version"], "2.0.0");
+        assert_eq!(test_profile.variables["target"], "x86_64-linux");
+        let test_invalid = [(String::from("ARCHEON_CONCURRENCY"), String::from("many"))];
+        assert!(test_profile.apply_env(test_invalid).is_err());
+        let test_invalid = [(String::from("ARCHEON_QUIET"), String::from("maybe"))];
+        assert!(test_profile.apply_env(test_invalid).is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn client_options() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_profile = Config::parse(TEST_CONFIG)?.profile(Some("work"))?;
+        let test_options = test_profile.client_options()?;
+        assert_eq!(
+            test_options.proxy,
+            Some(hyper::Uri::from_static("http://test-proxy.invalid:3128")),
+        );
+        assert_eq!(test_options.connect_timeout, Some(Duration::from_secs(5)));
+        let test_profile = Profile {
+            proxy: Some(String::from("socks5://test-proxy.invalid")),
+            ..Profile::default()
+        };
+        assert!(test_profile.client_options().is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_profile = Config::parse(TEST_CONFIG)?.profile(Some("work"))?;
+        let test_artifact = test_profile.apply(&BatchArtifact {
+            url: String::from("http://test-config/test.deb"),
+            user_agent: Some(String::from("test-override/2.0")),
+            variables: BTreeMap::from([(String::from("version"), String::from("9.9.9"))]),
+            ..BatchArtifact::default()
+        });
+        assert_eq!(
+            test_artifact.destination,
+            Some(PathBuf::from("/tmp/archeon_test_config_work")),
+        );
+        assert_eq!(
+            test_artifact.user_agent.as_deref(),
+            Some("test-override/2.0")
+        );
+        assert_eq!(
+            test_artifact
+                .headers
+                .get("authorization")
+                .map(String::as_str),
+            Some("Bearer test_token"),
+        );
+        assert_eq!(test_artifact.variables["version"], "9.9.9");
+        assert_eq!(test_artifact.variables["target"], "x86_64-linux");
+        Ok(())
+    }
+}
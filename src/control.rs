@@ -0,0 +1,398 @@
+use serde::{Deserialize, Serialize};
+
+use std::env::{temp_dir, var_os};
+use std::fs::Permissions;
+use std::io::{Error, ErrorKind};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use tokio::fs::{metadata, remove_file, set_permissions, DirBuilder};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::batch::{BatchArtifact, Priority};
+use crate::daemon::{Daemon, Job, QueueState};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum ControlRequest {
+    Add { artifact: Box<BatchArtifact> },
+    Status { id: Option<u64> },
+    Cancel { id: u64 },
+    Pause { id: u64 },
+    Resume { id: u64 },
+    Prioritize { id: u64, priority: Priority },
+    Export,
+    Import { queue: QueueState },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "response", rename_all = "lowercase")]
+pub enum ControlResponse {
+    Job { job: Box<Job> },
+    Jobs { jobs: Vec<Job> },
+    Queue { queue: QueueState },
+    Error { error: String },
+}
+
+pub fn default_socket_path() -> PathBuf {
+    if let Some(runtime_dir) = var_os("XDG_RUNTIME_DIR") {
+        return PathBuf::from(runtime_dir).join("archeon.sock");
+    }
+
+    let uid = unsafe { libc::getuid() };
+    let runtime_dir = PathBuf::from(format!("/run/user/{}", uid));
+
+    match std::fs::metadata(&runtime_dir) {
+        Ok(metadata) if metadata.is_dir() && metadata.uid() == uid => {
+            runtime_dir.join("archeon.sock")
+        }
+        _ => temp_dir()
+            .join(format!("archeon-{}", uid))
+            .join("archeon.sock"),
+    }
+}
+
+async fn launch_socket_dir(directory: &Path) -> Result<(), Error> {
+    DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(directory)
+        .await?;
+
+    let metadata = metadata(directory).await?;
+    let owned = metadata.uid() == unsafe { libc::getuid() } || metadata.uid() == 0;
+    let shared = metadata.mode() & 0o022 != 0 && metadata.mode() & 0o1000 == 0;
+
+    if !owned || shared {
+        let error = format!(
+            "Refusing to create a control socket in {:?}, other users can write to it!",
+            directory,
+        );
+
+        return Err(Error::new(ErrorKind::PermissionDenied, error));
+    }
+
+    Ok(())
+}
+
+impl Daemon {
+    pub async fn serve_unix(&self, path: &Path) -> Result<(), Error> {
+        let listener = Self::launch_unix_listener(path).await?;
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let daemon = self.to_owned();
+
+            tokio::spawn(async move { daemon.launch_unix_connection(stream).await });
+        }
+    }
+
+    async fn launch_unix_listener(path: &Path) -> Result<UnixListener, Error> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            launch_socket_dir(parent).await?;
+        }
+
+        if UnixStream::connect(path).await.is_ok() {
+            let error = format!("An archeon daemon is already listening on {:?}!", path);
+
+            return Err(Error::new(ErrorKind::AddrInUse, error));
+        }
+
+        match remove_file(path).await {
+            Err(error) if error.kind() != ErrorKind::NotFound => return Err(error),
+            _ => {}
+        }
+
+        let listener = UnixListener::bind(path)?;
+
+        set_permissions(path, Permissions::from_mode(0o600)).await?;
+
+        Ok(listener)
+    }
+
+    async fn launch_unix_connection(&self, stream: UnixStream) -> Result<(), Error> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            let response = match serde_json::from_str::<ControlRequest>(&line) {
+                Ok(request) => self.control(request).await,
+                Err(error) => ControlResponse::Error {
+                    error: error.to_string(),
+                },
+            };
+            let mut contents = serde_json::to_vec(&response)?;
+
+            contents.push(b'\n');
+
+            writer.write_all(&contents).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn control(&self, request: ControlRequest) -> ControlResponse {
+        match request {
+            ControlRequest::Add { artifact } => ControlResponse::Job {
+                job: Box::new(self.submit(*artifact).await),
+            },
+            ControlRequest::Status { id: None } => ControlResponse::Jobs {
+                jobs: self.jobs.lock().await.to_owned(),
+            },
+            ControlRequest::Status { id: Some(id) } => match self.job(id).await {
+                Some(job) => ControlResponse::Job { job: Box::new(job) },
+                None => Self::control_not_found(id),
+            },
+            ControlRequest::Cancel { id } => match self.cancel(id).await {
+                Some(job) => ControlResponse::Job { job: Box::new(job) },
+                None => Self::control_not_found(id),
+            },
+            ControlRequest::Pause { id } => match self.pause(id).await {
+                Some(job) => ControlResponse::Job { job: Box::new(job) },
+                None => Self::control_not_found(id),
+            },
+            ControlRequest::Resume { id } => match self.resume(id).await {
+                Some(job) => ControlResponse::Job { job: Box::new(job) },
+                None => Self::control_not_found(id),
+            },
+            ControlRequest::Prioritize { id, priority } => {
+                match self.prioritize(id, priority).await {
+                    Some(job) => ControlResponse::Job { job: Box::new(job) },
+                    None => Self::control_not_found(id),
+                }
+            }
+            ControlRequest::Export => ControlResponse::Queue {
+                queue: self.export().await,
+            },
+            ControlRequest::Import { queue } => ControlResponse::Jobs {
+                jobs: self.import(queue).await,
+            },
+        }
+    }
+
+    fn control_not_found(id: u64) -> ControlResponse {
+        ControlResponse::Error {
+            error: format!("job {} not found", id),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ControlClient {
+    pub path: PathBuf,
+}
+
+impl ControlClient {
+    pub fn init(path: &Path) -> ControlClient {
+        ControlClient {
+            path: path.to_owned(),
+        }
+    }
+
+    pub async fn send(&self, request: &ControlRequest) -> Result<ControlResponse, Error> {
+        let stream = UnixStream::connect(&self.path).await.map_err(|error| {
+            let message = format!(
+                "Could not connect to an archeon daemon at {:?}: {}",
+                self.path, error,
+            );
+
+            Error::new(error.kind(), message)
+        })?;
+        let (reader, mut writer) = stream.into_split();
+        let mut contents = serde_json::to_vec(request)?;
+
+        contents.push(b'\n');
+
+        writer.write_all(&contents).await?;
+        writer.shutdown().await?;
+
+        let mut lines = BufReader::new(reader).lines();
+
+        match lines.next_line().await? {
+            Some(line) => Ok(serde_json::from_str(&line)?),
+            None => Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "The archeon daemon closed the connection without a response!",
+            )),
+        }
+    }
+
+    pub async fn add(&self, artifact: BatchArtifact) -> Result<ControlResponse, Error> {
+        self.send(&ControlRequest::Add {
+            artifact: Box::new(artifact),
+        })
+        .await
+    }
+
+    pub async fn status(&self, id: Option<u64>) -> Result<ControlResponse, Error> {
+        self.send(&ControlRequest::Status { id }).await
+    }
+
+    pub async fn cancel(&self, id: u64) -> Result<ControlResponse, Error> {
+        self.send(&ControlRequest::Cancel { id }).await
+    }
+
+    pub async fn pause(&self, id: u64) -> Result<ControlResponse, Error> {
+        self.send(&ControlRequest::Pause { id }).await
+    }
+
+    pub async fn resume(&self, id: u64) -> Result<ControlResponse, Error> {
+        self.send(&ControlRequest::Resume { id }).await
+    }
+
+    pub async fn prioritize(&self, id: u64, priority: Priority) -> Result<ControlResponse, Error> {
+        self.send(&ControlRequest::Prioritize { id, priority })
+            .await
+    }
+
+    pub async fn export(&self) -> Result<ControlResponse, Error> {
+        self.send(&ControlRequest::Export).await
+    }
+
+    pub async fn import(&self, queue: QueueState) -> Result<ControlResponse, Error> {
+        self.send(&ControlRequest::Import { queue }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Archeon;
+
+    async fn test_client(name: &str) -> (ControlClient, PathBuf) {
+        let test_path = std::env::temp_dir().join(format!("archeon_test_{}.sock", name));
+        let test_daemon = Daemon::init(Archeon::ignite().await).await;
+        let test_listener = Daemon::launch_unix_listener(&test_path).await.unwrap();
+        tokio::spawn(async move {
+            while let Ok((test_stream, _)) = test_listener.accept().await {
+                let test_daemon = test_daemon.to_owned();
+                tokio::spawn(async move { test_daemon.launch_unix_connection(test_stream).await });
+            }
+        });
+        (ControlClient::init(&test_path), test_path)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn default_socket_path() {
+        assert!(super::default_socket_path().ends_with("archeon.sock"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_unix_listener() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_control_listener");
+        let test_path = test_dir.join("private").join("archeon.sock");
+        let _ = tokio::fs::remove_dir_all(&test_dir).await;
+        let test_listener = Daemon::launch_unix_listener(&test_path).await?;
+        let test_mode = |test_metadata: std::fs::Metadata| test_metadata.mode() & 0o777;
+        assert_eq!(
+            test_mode(std::fs::metadata(test_path.parent().unwrap())?),
+            0o700
+        );
+        assert_eq!(test_mode(std::fs::metadata(&test_path)?), 0o600);
+        drop(test_listener);
+        let test_shared = test_dir.join("shared");
+        tokio::fs::create_dir_all(&test_shared).await?;
+        set_permissions(&test_shared, Permissions::from_mode(0o777)).await?;
+        let test_error = Daemon::launch_unix_listener(&test_shared.join("archeon.sock"))
+            .await
+            .unwrap_err();
+        assert_eq!(test_error.kind(), ErrorKind::PermissionDenied);
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn add_and_status() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (test_client, test_path) = test_client("control_add_and_status").await;
+        let test_artifact = BatchArtifact {
+            url: String::from("http://test-control-add/test.deb"),
+            ..BatchArtifact::default()
+        };
+        match test_client.add(test_artifact).await? {
+            ControlResponse::Job { job } => assert_eq!(job.id, 0),
+            test_response => panic!("unexpected response {:?}", test_response),
+        }
+        match test_client.status(None).await? {
+            ControlResponse::Jobs { jobs } => assert_eq!(jobs.len(), 1),
+            test_response => panic!("unexpected response {:?}", test_response),
+        }
+        match test_client.status(Some(0)).await? {
+            ControlResponse::Job { job } => {
+                assert_eq!(job.artifact.url, "http://test-control-add/test.deb")
+            }
+            test_response => panic!("unexpected response {:?}", test_response),
+        }
+        match test_client.prioritize(0, Priority::High).await? {
+            ControlResponse::Job { job } => assert_eq!(job.id, 0),
+            test_response => panic!("unexpected response {:?}", test_response),
+        }
+        match test_client.cancel(0).await? {
+            ControlResponse::Job { job } => assert!(job.status.is_finished()),
+            test_response => panic!("unexpected response {:?}", test_response),
+        }
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn export_and_import() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (test_client, test_path) = test_client("control_export_and_import").await;
+        match test_client.export().await? {
+            ControlResponse::Queue { queue } => assert!(queue.artifacts.is_empty()),
+            test_response => panic!("unexpected response {:?}", test_response),
+        }
+        let test_queue = QueueState {
+            exported: 1_686_000_000,
+            artifacts: vec![BatchArtifact {
+                url: String::from("http://test-control-import/test.deb"),
+                priority: Priority::High,
+                ..BatchArtifact::default()
+            }],
+        };
+        match test_client.import(test_queue).await? {
+            ControlResponse::Jobs { jobs } => {
+                assert_eq!(jobs.len(), 1);
+                assert_eq!(jobs[0].artifact.priority, Priority::High);
+            }
+            test_response => panic!("unexpected response {:?}", test_response),
+        }
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn not_found() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (test_client, test_path) = test_client("control_not_found").await;
+        assert_eq!(
+            test_client.status(Some(42)).await?,
+            ControlResponse::Error {
+                error: String::from("job 42 not found"),
+            },
+        );
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn send_without_daemon() {
+        let test_path = std::env::temp_dir().join("archeon_test_control_missing.sock");
+        let test_client = ControlClient::init(&test_path);
+        let test_error = test_client.status(None).await.unwrap_err();
+        assert!(test_error
+            .to_string()
+            .contains("Could not connect to an archeon daemon"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_unix_listener_in_use() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (_, test_path) = test_client("control_in_use").await;
+        let test_error = Daemon::launch_unix_listener(&test_path).await.unwrap_err();
+        assert_eq!(test_error.kind(), ErrorKind::AddrInUse);
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,371 @@
+use hyper::header::{HeaderMap, HeaderValue, SET_COOKIE};
+use hyper::Uri;
+
+use publicsuffix::{List, Psl};
+
+use serde::{Deserialize, Serialize};
+
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+pub const PUBLIC_SUFFIX_LIST: &str = "/usr/share/publicsuffix/public_suffix_list.dat";
+
+static PUBLIC_SUFFIXES: OnceLock<Option<List>> = OnceLock::new();
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub host_only: bool,
+    pub path: String,
+    pub secure: bool,
+    pub expires: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CookieJar {
+    cookies: Arc<Mutex<Vec<Cookie>>>,
+}
+
+impl Cookie {
+    pub fn parse(uri: &Uri, set_cookie: &str, now: u64) -> Option<Cookie> {
+        let mut attributes = set_cookie.split(';');
+        let (name, value) = attributes.next()?.split_once('=')?;
+        let name = name.trim();
+
+        if name.is_empty() {
+            return None;
+        }
+
+        let host = uri.host()?.to_ascii_lowercase();
+        let mut cookie = Cookie {
+            name: name.to_owned(),
+            value: value.trim().trim_matches('"').to_owned(),
+            domain: host.to_owned(),
+            host_only: true,
+            path: Self::default_path(uri),
+            secure: false,
+            expires: None,
+        };
+        let mut max_age = None;
+
+        for attribute in attributes {
+            let (key, value) = match attribute.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (attribute.trim(), ""),
+            };
+
+            match key.to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+
+                    if is_ip(&host) || is_public_suffix(public_suffixes(), &domain) {
+                        match domain == host {
+                            true => continue,
+                            false => return None,
+                        }
+                    }
+
+                    if !Self::domain_matches(&host, &domain) {
+                        return None;
+                    }
+
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_owned(),
+                "secure" => cookie.secure = true,
+                "max-age" => {
+                    max_age = value
+                        .parse::<i64>()
+                        .ok()
+                        .map(|seconds| now.saturating_add_signed(seconds))
+                }
+                "expires" if cookie.expires.is_none() => cookie.expires = parse_http_date(value),
+                _ => {}
+            }
+        }
+
+        if max_age.is_some() {
+            cookie.expires = max_age;
+        }
+
+        Some(cookie)
+    }
+
+    fn default_path(uri: &Uri) -> String {
+        match uri.path().rfind('/') {
+            Some(0) | None => String::from("/"),
+            Some(index) => uri.path()[..index].to_owned(),
+        }
+    }
+
+    fn domain_matches(host: &str, domain: &str) -> bool {
+        host == domain || host.ends_with(&format!(".{}", domain))
+    }
+
+    pub fn matches(&self, uri: &Uri, now: u64) -> bool {
+        let host = match uri.host() {
+            Some(host) => host.to_ascii_lowercase(),
+            None => return false,
+        };
+        let domain = match self.host_only {
+            true => host == self.domain,
+            false => Self::domain_matches(&host, &self.domain),
+        };
+        let path = uri.path();
+        let path = path == self.path
+            || (path.starts_with(&self.path)
+                && (self.path.ends_with('/') || path[self.path.len()..].starts_with('/')));
+        let secure = !self.secure || uri.scheme_str() == Some("https");
+
+        domain && path && secure && !self.is_expired(now)
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        matches!(self.expires, Some(expires) if expires <= now)
+    }
+}
+
+impl CookieJar {
+    pub fn store(&self, uri: &Uri, headers: &HeaderMap) {
+        let now = crate::unix_timestamp();
+        let mut cookies = self.lock();
+
+        for set_cookie in headers.get_all(SET_COOKIE) {
+            let cookie = match set_cookie
+                .to_str()
+                .ok()
+                .and_then(|set_cookie| Cookie::parse(uri, set_cookie, now))
+            {
+                Some(cookie) => cookie,
+                None => continue,
+            };
+
+            cookies.retain(|existing| {
+                existing.name != cookie.name
+                    || existing.domain != cookie.domain
+                    || existing.path != cookie.path
+            });
+
+            if !cookie.is_expired(now) {
+                cookies.push(cookie);
+            }
+        }
+    }
+
+    pub fn header(&self, uri: &Uri) -> Option<HeaderValue> {
+        let now = crate::unix_timestamp();
+        let mut cookies = self.lock();
+
+        cookies.retain(|cookie| !cookie.is_expired(now));
+
+        let mut matching: Vec<&Cookie> = cookies
+            .iter()
+            .filter(|cookie| cookie.matches(uri, now))
+            .collect();
+
+        if matching.is_empty() {
+            return None;
+        }
+
+        matching.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+
+        let header = matching
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<String>>()
+            .join("; ");
+
+        HeaderValue::from_str(&header).ok()
+    }
+
+    pub fn cookies(&self) -> Vec<Cookie> {
+        self.lock().to_owned()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Cookie>> {
+        self.cookies
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+pub fn is_public_suffix(list: Option<&List>, domain: &str) -> bool {
+    !domain.contains('.')
+        || list
+            .and_then(|list| list.suffix(domain.as_bytes()))
+            .is_some_and(|suffix| suffix.as_bytes() == domain.as_bytes())
+}
+
+fn public_suffixes() -> Option<&'static List> {
+    PUBLIC_SUFFIXES
+        .get_or_init(|| {
+            std::fs::read(PUBLIC_SUFFIX_LIST)
+                .ok()
+                .and_then(|list| List::from_bytes(&list).ok())
+        })
+        .as_ref()
+}
+
+fn is_ip(host: &str) -> bool {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .is_ok()
+}
+
+pub fn parse_http_date(date: &str) -> Option<u64> {
+    let mut fields = date.split_whitespace().skip(1);
+    let day: u64 = fields.next()?.parse().ok()?;
+    let month = match fields.next()? {
+        "Jan" => 1,
+        "Feb" => 2,
+        "Mar" => 3,
+        "Apr" => 4,
+        "May" => 5,
+        "Jun" => 6,
+        "Jul" => 7,
+        "Aug" => 8,
+        "Sep" => 9,
+        "Oct" => 10,
+        "Nov" => 11,
+        "Dec" => 12,
+        _ => return None,
+    };
+    let year: i64 = fields.next()?.parse().ok()?;
+    let mut time = fields.next()?.split(':');
+    let hours: u64 = time.next()?.parse().ok()?;
+    let minutes: u64 = time.next()?.parse().ok()?;
+    let seconds: u64 = time.next()?.parse().ok()?;
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = u64::try_from(era * 146097 + day_of_era - 719468).ok()?;
+
+    Some(days * 86400 + hours * 3600 + minutes * 60 + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_headers(set_cookies: &[&'static str]) -> HeaderMap {
+        let mut test_headers = HeaderMap::new();
+        for set_cookie in set_cookies {
+            test_headers.append(SET_COOKIE, HeaderValue::from_static(set_cookie));
+        }
+        test_headers
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse() {
+        let test_uri = Uri::from_static("https://portal.test/auth/login");
+        let test_cookie = Cookie::parse(
+            &test_uri,
+            "session=abc123; Domain=.Portal.test; Path=/; Secure; HttpOnly; Max-Age=60",
+            1000,
+        )
+        .unwrap();
+        assert_eq!(test_cookie.name, "session");
+        assert_eq!(test_cookie.value, "abc123");
+        assert_eq!(test_cookie.domain, "portal.test");
+        assert!(!test_cookie.host_only);
+        assert_eq!(test_cookie.path, "/");
+        assert!(test_cookie.secure);
+        assert_eq!(test_cookie.expires, Some(1060));
+        let test_default = Cookie::parse(&test_uri, "token=xyz", 1000).unwrap();
+        assert!(test_default.host_only);
+        assert_eq!(test_default.path, "/auth");
+        assert!(test_default.expires.is_none());
+        assert!(Cookie::parse(&test_uri, "other=1; Domain=elsewhere.test", 1000).is_none());
+        assert!(Cookie::parse(&test_uri, "no_value", 1000).is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn domains() {
+        let test_uri = Uri::from_static("https://portal.com/login");
+        assert!(Cookie::parse(&test_uri, "session=abc; Domain=com", 0).is_none());
+        assert!(Cookie::parse(&test_uri, "session=abc; Domain=.com", 0).is_none());
+        let test_local = Cookie::parse(
+            &Uri::from_static("http://localhost/"),
+            "session=abc; Domain=localhost",
+            0,
+        )
+        .unwrap();
+        assert!(test_local.host_only);
+        let test_ip_uri = Uri::from_static("http://127.0.0.1/login");
+        let test_ip = Cookie::parse(&test_ip_uri, "session=abc; Domain=127.0.0.1", 0).unwrap();
+        assert!(test_ip.host_only);
+        assert!(!test_ip.matches(&Uri::from_static("http://0.127.0.0.1/"), 0));
+        assert!(Cookie::parse(&test_ip_uri, "session=abc; Domain=0.0.1", 0).is_none());
+        let test_list: List =
+            "// ===BEGIN ICANN DOMAINS===\ncom\nco.uk\n// ===END ICANN DOMAINS===\n"
+                .parse()
+                .unwrap();
+        assert!(is_public_suffix(Some(&test_list), "co.uk"));
+        assert!(!is_public_suffix(Some(&test_list), "portal.co.uk"));
+        assert!(is_public_suffix(None, "uk"));
+        assert!(!is_public_suffix(None, "co.uk"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn matches() {
+        let test_uri = Uri::from_static("https://portal.test/downloads/file.iso");
+        let test_cookie =
+            Cookie::parse(&test_uri, "session=abc; Path=/downloads; Secure", 0).unwrap();
+        assert!(test_cookie.matches(&test_uri, 0));
+        assert!(!test_cookie.matches(&Uri::from_static("http://portal.test/downloads/f"), 0));
+        assert!(!test_cookie.matches(&Uri::from_static("https://portal.test/downloadsx"), 0));
+        assert!(!test_cookie.matches(&Uri::from_static("https://cdn.portal.test/downloads"), 0));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn store_and_header() {
+        let test_jar = CookieJar::default();
+        let test_uri = Uri::from_static("http://portal.test/login");
+        test_jar.store(
+            &test_uri,
+            &test_headers(&["session=abc; Path=/", "theme=dark; Path=/files"]),
+        );
+        assert_eq!(
+            test_jar.header(&Uri::from_static("http://portal.test/files/test.deb")),
+            Some(HeaderValue::from_static("theme=dark; session=abc")),
+        );
+        assert_eq!(
+            test_jar.header(&Uri::from_static("http://portal.test/other")),
+            Some(HeaderValue::from_static("session=abc")),
+        );
+        assert!(test_jar
+            .header(&Uri::from_static("http://other.test/"))
+            .is_none());
+        test_jar.store(
+            &test_uri,
+            &test_headers(&["session=; Path=/; Expires=Thu, 01 Jan 1970 00:00:00 GMT"]),
+        );
+        assert_eq!(test_jar.cookies().len(), 1);
+        test_jar.clear();
+        assert!(test_jar.cookies().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_http_date() {
+        assert_eq!(
+            super::parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"),
+            Some(0)
+        );
+        assert_eq!(
+            super::parse_http_date("Thu, 15 Jun 2023 12:00:00 GMT"),
+            Some(1_686_830_400),
+        );
+        assert!(super::parse_http_date("not a date").is_none());
+    }
+}
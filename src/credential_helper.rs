@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::process::Stdio;
+
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::credentials::{Credential, CredentialFuture, CredentialStore};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CredentialHelper {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+pub fn helper_program(helper: &str) -> String {
+    match helper.contains('/') {
+        true => helper.to_owned(),
+        false => format!("archeon-credential-{}", helper),
+    }
+}
+
+impl CredentialHelper {
+    pub fn init(helper: &str) -> Result<CredentialHelper, Error> {
+        let mut words = helper.split_whitespace();
+
+        match words.next() {
+            Some(program) => Ok(CredentialHelper {
+                program: helper_program(program),
+                args: words.map(str::to_owned).collect(),
+            }),
+            None => {
+                let error = String::from("The credential helper is empty!");
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+
+    pub async fn fill(&self, host: &str) -> Result<Option<Credential>, Error> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .arg("get")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|error| {
+                let kind = error.kind();
+                let error = format!(
+                    "Unable to run credential helper '{}': {}!",
+                    self.program, error,
+                );
+
+                Error::new(kind, error)
+            })?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            let request = format!("protocol=https\nhost={}\n\n", host);
+
+            match stdin.write_all(request.as_bytes()).await {
+                Err(error) if error.kind() != ErrorKind::BrokenPipe => return Err(error),
+                _ => {}
+            }
+        }
+
+        let output = child.wait_with_output().await?;
+
+        if !output.status.success() {
+            let error = format!(
+                "Credential helper '{}' failed for {} with {}!",
+                self.program, host, output.status,
+            );
+
+            return Err(Error::new(ErrorKind::PermissionDenied, error));
+        }
+
+        Ok(Self::parse(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    pub fn parse(output: &str) -> Option<Credential> {
+        let mut fields: HashMap<&str, &str> = HashMap::new();
+
+        for line in output.lines() {
+            if line.is_empty() {
+                break;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                fields.entry(key).or_insert(value);
+            }
+        }
+
+        if let Some(token) = fields.get("token") {
+            return Some(Credential::Token(token.to_string()));
+        }
+
+        if let (Some(authtype), Some(credential)) =
+            (fields.get("authtype"), fields.get("credential"))
+        {
+            if authtype.eq_ignore_ascii_case("bearer") {
+                return Some(Credential::Token(credential.to_string()));
+            }
+        }
+
+        match (fields.get("username"), fields.get("password")) {
+            (Some(username), Some(password)) => Some(Credential::Basic {
+                username: username.to_string(),
+                password: password.to_string(),
+            }),
+            (None, Some(password)) => Some(Credential::Token(password.to_string())),
+            _ => None,
+        }
+    }
+}
+
+impl CredentialStore for CredentialHelper {
+    fn get<'a>(&'a self, host: &'a str) -> CredentialFuture<'a> {
+        Box::pin(self.fill(host))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_helper = CredentialHelper::init("vault --role ci")?;
+        assert_eq!(test_helper.program, "archeon-credential-vault");
+        assert_eq!(test_helper.args, ["--role", "ci"]);
+        let test_helper = CredentialHelper::init("/opt/helpers/internal")?;
+        assert_eq!(test_helper.program, "/opt/helpers/internal");
+        assert!(test_helper.args.is_empty());
+        assert!(CredentialHelper::init("  ").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse() {
+        assert_eq!(
+            CredentialHelper::parse("username=test_user\npassword=test=password\n"),
+            Some(Credential::Basic {
+                username: String::from("test_user"),
+                password: String::from("test=password"),
+            }),
+        );
+        assert_eq!(
+            CredentialHelper::parse("authtype=Bearer\ncredential=test_token\n"),
+            Some(Credential::Token(String::from("test_token"))),
+        );
+        assert_eq!(
+            CredentialHelper::parse("token=test_token\nusername=ignored\npassword=ignored\n"),
+            Some(Credential::Token(String::from("test_token"))),
+        );
+        assert_eq!(
+            CredentialHelper::parse("password=test_token\n"),
+            Some(Credential::Token(String::from("test_token"))),
+        );
+        assert_eq!(CredentialHelper::parse("\nusername=after_blank\n"), None);
+        assert_eq!(CredentialHelper::parse("username=test_user\n"), None);
+        assert_eq!(CredentialHelper::parse(""), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fill() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_credential_helper_fill");
+        tokio::fs::write(
+            &test_path,
+            "#!/bin/sh\n\
+             test \"$2\" = get || exit 2\n\
+             while read -r line && [ -n \"$line\" ]; do\n\
+               case \"$line\" in host=*) host=\"${line#host=}\" ;; esac\n\
+             done\n\
+             case \"$host\" in\n\
+               mirror.test) printf 'username=%s\\npassword=test_password\\n' \"$1\" ;;\n\
+               denied.test) exit 1 ;;\n\
+             esac\n",
+        )
+        .await?;
+        let test_helper =
+            CredentialHelper::init(&format!("/bin/sh {} test_user", test_path.display()))?;
+        assert_eq!(
+            test_helper.fill("mirror.test").await?,
+            Some(Credential::Basic {
+                username: String::from("test_user"),
+                password: String::from("test_password"),
+            }),
+        );
+        assert_eq!(test_helper.fill("other.test").await?, None);
+        let test_error = test_helper.fill("denied.test").await.unwrap_err();
+        assert_eq!(test_error.kind(), ErrorKind::PermissionDenied);
+        let test_missing = CredentialHelper::init("/nonexistent/archeon_test_helper")?;
+        assert_eq!(
+            test_missing.fill("mirror.test").await.unwrap_err().kind(),
+            ErrorKind::NotFound,
+        );
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use hyper::header::{HeaderValue, InvalidHeaderValue, AUTHORIZATION};
+use hyper::{Body, Request};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::Error;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::auth::{AuthFuture, Authenticate};
+
+pub type CredentialFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Option<Credential>, Error>> + Send + 'a>>;
+
+pub trait CredentialStore: Send + Sync {
+    fn get<'a>(&'a self, host: &'a str) -> CredentialFuture<'a>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Credential {
+    Token(String),
+    Basic { username: String, password: String },
+}
+
+#[derive(Clone)]
+pub struct StoreAuth {
+    pub store: Arc<dyn CredentialStore>,
+    cache: Arc<Mutex<HashMap<String, Option<Credential>>>>,
+}
+
+impl Credential {
+    pub fn header(&self) -> Result<HeaderValue, InvalidHeaderValue> {
+        let value = match self {
+            Credential::Token(token) => format!("Bearer {}", token),
+            Credential::Basic { username, password } => {
+                format!(
+                    "Basic {}",
+                    STANDARD.encode(format!("{}:{}", username, password))
+                )
+            }
+        };
+        let mut value = HeaderValue::from_str(&value)?;
+
+        value.set_sensitive(true);
+
+        Ok(value)
+    }
+}
+
+impl StoreAuth {
+    pub fn init(store: Arc<dyn CredentialStore>) -> StoreAuth {
+        StoreAuth {
+            store,
+            cache: Arc::default(),
+        }
+    }
+
+    pub async fn credential(&self, host: &str) -> Result<Option<Credential>, Error> {
+        let host = host.to_ascii_lowercase();
+
+        if let Some(credential) = self.lock().get(&host) {
+            return Ok(credential.to_owned());
+        }
+
+        let credential = self.store.get(&host).await?;
+
+        self.lock().insert(host, credential.to_owned());
+
+        Ok(credential)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Option<Credential>>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Authenticate for StoreAuth {
+    fn authenticate<'a>(&'a self, request: &'a mut Request<Body>) -> AuthFuture<'a> {
+        Box::pin(async move {
+            if request.headers().contains_key(AUTHORIZATION) {
+                return Ok(());
+            }
+
+            let host = match request.uri().host() {
+                Some(host) => host.to_owned(),
+                None => return Ok(()),
+            };
+
+            if let Some(credential) = self.credential(&host).await? {
+                request
+                    .headers_mut()
+                    .insert(AUTHORIZATION, credential.header()?);
+            }
+
+            Ok(())
+        })
+    }
+
+    fn invalidate(&self) {
+        self.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct TestStore {
+        lookups: AtomicUsize,
+    }
+
+    impl CredentialStore for TestStore {
+        fn get<'a>(&'a self, host: &'a str) -> CredentialFuture<'a> {
+            Box::pin(async move {
+                self.lookups.fetch_add(1, Ordering::SeqCst);
+
+                match host {
+                    "token.test" => Ok(Some(Credential::Token(String::from("test_token")))),
+                    "basic.test" => Ok(Some(Credential::Basic {
+                        username: String::from("test_user"),
+                        password: String::from("test_password"),
+                    })),
+                    _ => Ok(None),
+                }
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn header() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_header = Credential::Token(String::from("test_token")).header()?;
+        assert_eq!(test_header, "Bearer test_token");
+        assert!(test_header.is_sensitive());
+        let test_header = Credential::Basic {
+            username: String::from("test_user"),
+            password: String::from("test_password"),
+        }
+        .header()?;
+        assert_eq!(test_header, "Basic dGVzdF91c2VyOnRlc3RfcGFzc3dvcmQ=");
+        assert!(Credential::Token(String::from("bad\ntoken"))
+            .header()
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn authenticate() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_store = Arc::new(TestStore {
+            lookups: AtomicUsize::new(0),
+        });
+        let test_auth = StoreAuth::init(test_store.to_owned());
+        for _ in 0..2 {
+            let mut test_request = Request::builder()
+                .uri("https://TOKEN.test/test.deb")
+                .body(Body::empty())?;
+            test_auth.authenticate(&mut test_request).await?;
+            assert_eq!(test_request.headers()[AUTHORIZATION], "Bearer test_token");
+        }
+        assert_eq!(test_store.lookups.load(Ordering::SeqCst), 1);
+        let mut test_request = Request::builder()
+            .uri("https://basic.test/test.deb")
+            .header(AUTHORIZATION, "Bearer test_existing")
+            .body(Body::empty())?;
+        test_auth.authenticate(&mut test_request).await?;
+        assert_eq!(
+            test_request.headers()[AUTHORIZATION],
+            "Bearer test_existing"
+        );
+        let mut test_request = Request::builder()
+            .uri("https://other.test/test.deb")
+            .body(Body::empty())?;
+        test_auth.authenticate(&mut test_request).await?;
+        assert!(!test_request.headers().contains_key(AUTHORIZATION));
+        test_auth.invalidate();
+        let mut test_request = Request::builder()
+            .uri("https://token.test/test.deb")
+            .body(Body::empty())?;
+        test_auth.authenticate(&mut test_request).await?;
+        assert_eq!(test_store.lookups.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+}
@@ -0,0 +1,1190 @@
+use hyper::body::to_bytes;
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use hyper::server::conn::AddrIncoming;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::convert::Infallible;
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::sync::broadcast::{channel, Receiver, Sender};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+#[cfg(feature = "journal")]
+use tokio::sync::oneshot;
+use tokio::sync::{Mutex, Notify};
+
+use crate::batch::{Batch, BatchArtifact, BatchResult, Priority};
+use crate::error;
+use crate::installer::Escalation;
+#[cfg(feature = "journal")]
+use crate::journal::Journal;
+use crate::progress::AggregateProgress;
+use crate::Archeon;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Paused,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl std::fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Paused => "paused",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        };
+
+        f.write_str(status)
+    }
+}
+
+impl std::str::FromStr for JobStatus {
+    type Err = std::io::Error;
+
+    fn from_str(status: &str) -> Result<JobStatus, std::io::Error> {
+        match status {
+            "queued" => Ok(JobStatus::Queued),
+            "paused" => Ok(JobStatus::Paused),
+            "running" => Ok(JobStatus::Running),
+            "completed" => Ok(JobStatus::Completed),
+            "failed" => Ok(JobStatus::Failed),
+            "cancelled" => Ok(JobStatus::Cancelled),
+            _ => {
+                let error = format!("Invalid job status '{}'!", status);
+
+                Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled,
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    pub status: JobStatus,
+    pub artifact: BatchArtifact,
+    pub result: Option<BatchResult>,
+    #[serde(default)]
+    pub progress: Option<AggregateProgress>,
+    #[serde(default)]
+    pub coalesced: Option<u64>,
+}
+
+impl Job {
+    pub fn duplicates(&self, artifact: &BatchArtifact) -> bool {
+        let same_checksum = match (&self.artifact.checksum, &artifact.checksum) {
+            (Some(checksum), Some(other)) => checksum.eq_ignore_ascii_case(other),
+            _ => false,
+        };
+
+        matches!(self.status, JobStatus::Queued | JobStatus::Running)
+            && self.coalesced.is_none()
+            && self.artifact.install == artifact.install
+            && !artifact.delete
+            && (self.artifact.url == artifact.url || same_checksum)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueState {
+    pub exported: u64,
+    pub artifacts: Vec<BatchArtifact>,
+}
+
+impl QueueState {
+    pub fn parse(contents: &str) -> Result<QueueState, Error> {
+        serde_json::from_str(contents).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteConfig {
+    pub token: Option<String>,
+    pub destinations: Vec<PathBuf>,
+}
+
+impl RemoteConfig {
+    pub fn authenticated(&self, authorization: Option<&str>) -> bool {
+        let presented =
+            authorization.and_then(|authorization| authorization.strip_prefix("Bearer "));
+
+        match (self.token.as_deref(), presented) {
+            (Some(token), Some(presented)) if !token.is_empty() => {
+                token.len() == presented.len()
+                    && token
+                        .bytes()
+                        .zip(presented.bytes())
+                        .fold(0, |difference, (a, b)| difference | (a ^ b))
+                        == 0
+            }
+            _ => false,
+        }
+    }
+
+    pub fn allows(&self, destination: &Path) -> bool {
+        destination.is_absolute()
+            && !destination
+                .components()
+                .any(|component| component == Component::ParentDir)
+            && self
+                .destinations
+                .iter()
+                .any(|allowed| destination.starts_with(allowed))
+    }
+}
+
+#[derive(Clone)]
+pub struct Daemon {
+    pub jobs: Arc<Mutex<Vec<Job>>>,
+    pub remote: RemoteConfig,
+    queue: UnboundedSender<()>,
+    updates: Sender<Job>,
+    cancel: Arc<Notify>,
+    busy: Arc<Mutex<()>>,
+    #[cfg(feature = "journal")]
+    journal: Option<UnboundedSender<JournalWrite>>,
+}
+
+#[cfg(feature = "journal")]
+enum JournalWrite {
+    Record(Box<Job>),
+    Flush(oneshot::Sender<()>),
+}
+
+impl Daemon {
+    pub async fn init(archeon: Archeon) -> Daemon {
+        let (daemon, receiver) = Self::init_state(Vec::with_capacity(10));
+
+        tokio::spawn(daemon.to_owned().launch_worker(archeon, receiver));
+
+        daemon
+    }
+
+    #[cfg(feature = "journal")]
+    pub async fn init_with_journal(
+        archeon: Archeon,
+        journal: Journal,
+    ) -> Result<Daemon, std::io::Error> {
+        let restore = journal.to_owned();
+        let jobs = tokio::task::spawn_blocking(move || restore.restore())
+            .await
+            .map_err(std::io::Error::other)??;
+        let queued = jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Queued)
+            .count();
+        let (mut daemon, receiver) = Self::init_state(jobs);
+
+        daemon.journal = Some(Self::launch_journal(journal));
+
+        for _ in 0..queued {
+            let _ = daemon.queue.send(());
+        }
+
+        tokio::spawn(daemon.to_owned().launch_worker(archeon, receiver));
+
+        Ok(daemon)
+    }
+
+    fn init_state(jobs: Vec<Job>) -> (Daemon, UnboundedReceiver<()>) {
+        let (queue, receiver) = unbounded_channel();
+        let (updates, _) = channel(64);
+        let daemon = Daemon {
+            jobs: Arc::new(Mutex::new(jobs)),
+            remote: RemoteConfig::default(),
+            queue,
+            updates,
+            cancel: Arc::new(Notify::new()),
+            busy: Arc::new(Mutex::new(())),
+            #[cfg(feature = "journal")]
+            journal: None,
+        };
+
+        (daemon, receiver)
+    }
+
+    async fn launch_worker(self, mut archeon: Archeon, mut receiver: UnboundedReceiver<()>) {
+        let batch_progress = archeon.progress.to_owned();
+        let mut progress = batch_progress.subscribe();
+        let shutdown = archeon.shutdown.to_owned();
+
+        while receiver.recv().await.is_some() {
+            let _busy = self.busy.lock().await;
+
+            if shutdown.is_triggered() {
+                break;
+            }
+
+            let job = match self.launch_next().await {
+                Some(job) => job,
+                None => continue,
+            };
+            let id = job.id;
+            let batch = Batch {
+                artifacts: vec![job.artifact],
+            };
+
+            let apply = archeon.apply(&batch);
+
+            tokio::pin!(apply);
+
+            loop {
+                tokio::select! {
+                    mut report = &mut apply => {
+                        let result = report.results.pop();
+                        let status = match &result {
+                            Some(result) if result.error_kind == Some(error::ErrorKind::Cancelled) => break,
+                            Some(result) if result.error.is_none() => JobStatus::Completed,
+                            _ => JobStatus::Failed,
+                        };
+
+                        self.launch_progress(id, batch_progress.aggregate()).await;
+                        self.launch_update(id, status, result).await;
+
+                        break;
+                    }
+                    Ok(update) = progress.recv() => {
+                        self.launch_progress(id, update.aggregate).await;
+                    }
+                    _ = self.launch_cancelled(id) => break,
+                }
+            }
+        }
+    }
+
+    async fn launch_next(&self) -> Option<Job> {
+        let mut jobs = self.jobs.lock().await;
+        let index = next_queued(&jobs)?;
+        let job = &mut jobs[index];
+
+        job.status = JobStatus::Running;
+
+        self.launch_record(job);
+
+        let _ = self.updates.send(job.to_owned());
+
+        let job = job.to_owned();
+
+        self.launch_followers(&mut jobs, job.id, JobStatus::Running, None);
+
+        Some(job)
+    }
+
+    async fn launch_update(
+        &self,
+        id: u64,
+        status: JobStatus,
+        result: Option<BatchResult>,
+    ) -> Option<Job> {
+        let mut jobs = self.jobs.lock().await;
+        let job = jobs.iter_mut().find(|job| job.id == id)?;
+
+        if job.status.is_finished() {
+            return None;
+        }
+
+        job.status = status;
+        job.result = result.to_owned();
+
+        self.launch_record(job);
+
+        let _ = self.updates.send(job.to_owned());
+
+        let job = job.to_owned();
+
+        self.launch_followers(&mut jobs, id, status, result);
+
+        Some(job)
+    }
+
+    fn launch_followers(
+        &self,
+        jobs: &mut [Job],
+        id: u64,
+        status: JobStatus,
+        result: Option<BatchResult>,
+    ) {
+        let followers = jobs
+            .iter_mut()
+            .filter(|job| job.coalesced == Some(id) && !job.status.is_finished());
+
+        for follower in followers {
+            match status {
+                JobStatus::Cancelled => {
+                    follower.coalesced = None;
+                    follower.status = JobStatus::Queued;
+
+                    let _ = self.queue.send(());
+                }
+                _ => {
+                    follower.status = status;
+                    follower.result = result.to_owned().map(|result| BatchResult {
+                        url: follower.artifact.url.to_owned(),
+                        ..result
+                    });
+                }
+            }
+
+            self.launch_record(follower);
+
+            let _ = self.updates.send(follower.to_owned());
+        }
+    }
+
+    #[cfg(feature = "journal")]
+    fn launch_journal(journal: Journal) -> UnboundedSender<JournalWrite> {
+        let (writes, mut receiver) = unbounded_channel();
+
+        tokio::task::spawn_blocking(move || {
+            while let Some(write) = receiver.blocking_recv() {
+                match write {
+                    JournalWrite::Record(job) => {
+                        if let Err(error) = journal.record(&job) {
+                            eprintln!("Unable to journal job {}: {}", job.id, error);
+                        }
+                    }
+                    JournalWrite::Flush(flushed) => {
+                        let _ = flushed.send(());
+                    }
+                }
+            }
+        });
+
+        writes
+    }
+
+    fn launch_record(&self, job: &Job) {
+        #[cfg(feature = "journal")]
+        if let Some(journal) = &self.journal {
+            let _ = journal.send(JournalWrite::Record(Box::new(job.to_owned())));
+        }
+
+        #[cfg(not(feature = "journal"))]
+        let _ = job;
+    }
+
+    pub async fn flush_journal(&self) {
+        #[cfg(feature = "journal")]
+        if let Some(journal) = &self.journal {
+            let (flushed, done) = oneshot::channel();
+
+            if journal.send(JournalWrite::Flush(flushed)).is_ok() {
+                let _ = done.await;
+            }
+        }
+    }
+
+    async fn launch_progress(&self, id: u64, progress: AggregateProgress) {
+        let mut jobs = self.jobs.lock().await;
+
+        if let Some(job) = jobs.iter_mut().find(|job| job.id == id) {
+            job.progress = Some(progress);
+
+            let _ = self.updates.send(job.to_owned());
+        }
+    }
+
+    async fn launch_transition(
+        &self,
+        id: u64,
+        transition: impl FnOnce(&mut Job) -> bool,
+    ) -> Option<Job> {
+        let mut jobs = self.jobs.lock().await;
+        let job = jobs.iter_mut().find(|job| job.id == id)?;
+
+        if transition(job) {
+            self.launch_record(job);
+
+            let _ = self.updates.send(job.to_owned());
+        }
+
+        Some(job.to_owned())
+    }
+
+    pub async fn pause(&self, id: u64) -> Option<Job> {
+        self.launch_transition(id, |job| match job.status {
+            JobStatus::Queued => {
+                job.status = JobStatus::Paused;
+
+                true
+            }
+            _ => false,
+        })
+        .await
+    }
+
+    pub async fn resume(&self, id: u64) -> Option<Job> {
+        let job = self
+            .launch_transition(id, |job| match job.status {
+                JobStatus::Paused => {
+                    job.status = JobStatus::Queued;
+
+                    true
+                }
+                _ => false,
+            })
+            .await?;
+
+        if job.status == JobStatus::Queued {
+            let _ = self.queue.send(());
+        }
+
+        Some(job)
+    }
+
+    pub async fn prioritize(&self, id: u64, priority: Priority) -> Option<Job> {
+        self.launch_transition(id, |job| match job.status.is_finished() {
+            true => false,
+            false => {
+                job.artifact.priority = priority;
+
+                true
+            }
+        })
+        .await
+    }
+
+    async fn launch_cancelled(&self, id: u64) {
+        loop {
+            let notified = self.cancel.notified();
+
+            if let Some(job) = self.job(id).await {
+                if job.status == JobStatus::Cancelled {
+                    return;
+                }
+            }
+
+            notified.await;
+        }
+    }
+
+    pub async fn cancel(&self, id: u64) -> Option<Job> {
+        let job = match self.job(id).await {
+            Some(job) if job.status.is_finished() => Some(job),
+            Some(_) => self.launch_update(id, JobStatus::Cancelled, None).await,
+            None => None,
+        };
+
+        self.cancel.notify_waiters();
+
+        job
+    }
+
+    pub fn subscribe(&self) -> Receiver<Job> {
+        self.updates.subscribe()
+    }
+
+    pub async fn drain(&self) {
+        let _busy = self.busy.lock().await;
+
+        self.flush_journal().await;
+    }
+
+    pub async fn submit(&self, artifact: BatchArtifact) -> Job {
+        let mut jobs = self.jobs.lock().await;
+        let primary = jobs.iter().find(|job| job.duplicates(&artifact));
+        let job = Job {
+            id: jobs.len() as u64,
+            status: primary
+                .map(|primary| primary.status)
+                .unwrap_or(JobStatus::Queued),
+            artifact,
+            result: None,
+            progress: None,
+            coalesced: primary.map(|primary| primary.id),
+        };
+
+        jobs.push(job.to_owned());
+
+        self.launch_record(&job);
+
+        let _ = self.queue.send(());
+
+        job
+    }
+
+    pub async fn requeue(&self, artifacts: Vec<BatchArtifact>) -> Vec<Job> {
+        let mut requeued = Vec::with_capacity(artifacts.len());
+
+        for artifact in artifacts {
+            let pending = self
+                .jobs
+                .lock()
+                .await
+                .iter()
+                .any(|job| !job.status.is_finished() && job.artifact.url == artifact.url);
+
+            if !pending {
+                requeued.push(self.submit(artifact).await);
+            }
+        }
+
+        requeued
+    }
+
+    pub async fn export(&self) -> QueueState {
+        let artifacts = self
+            .jobs
+            .lock()
+            .await
+            .iter()
+            .filter(|job| !job.status.is_finished())
+            .map(|job| job.artifact.to_owned())
+            .collect();
+
+        QueueState {
+            exported: crate::unix_timestamp(),
+            artifacts,
+        }
+    }
+
+    pub async fn import(&self, queue: QueueState) -> Vec<Job> {
+        self.requeue(queue.artifacts).await
+    }
+
+    pub async fn job(&self, id: u64) -> Option<Job> {
+        self.jobs
+            .lock()
+            .await
+            .iter()
+            .find(|job| job.id == id)
+            .cloned()
+    }
+
+    pub async fn serve(&self, address: SocketAddr) -> Result<(), hyper::Error> {
+        let (_, server) = self.bind(address)?;
+
+        server.await
+    }
+
+    pub fn bind(
+        &self,
+        address: SocketAddr,
+    ) -> Result<(SocketAddr, impl Future<Output = Result<(), hyper::Error>>), hyper::Error> {
+        let daemon = self.to_owned();
+        let make_service = make_service_fn(move |_| {
+            let daemon = daemon.to_owned();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let daemon = daemon.to_owned();
+
+                    async move { Ok::<_, Infallible>(daemon.route(request).await) }
+                }))
+            }
+        });
+        let server: Server<AddrIncoming, _> = Server::try_bind(&address)?.serve(make_service);
+
+        Ok((server.local_addr(), server))
+    }
+
+    async fn route(&self, request: Request<Body>) -> Response<Body> {
+        let method = request.method().to_owned();
+        let path = request.uri().path().trim_end_matches('/').to_owned();
+        let segments: Vec<&str> = path.split('/').skip(1).collect();
+
+        match (method, segments.as_slice()) {
+            (Method::POST, ["jobs"]) => {
+                let authenticated = self.remote.authenticated(
+                    request
+                        .headers()
+                        .get(AUTHORIZATION)
+                        .and_then(|authorization| authorization.to_str().ok()),
+                );
+                let body = match to_bytes(request.into_body()).await {
+                    Ok(body) => body,
+                    Err(error) => return Self::route_error(StatusCode::BAD_REQUEST, error),
+                };
+
+                match serde_json::from_slice::<BatchArtifact>(&body) {
+                    Ok(artifact) => match check_remote(&artifact, &self.remote, authenticated) {
+                        Ok(()) => {
+                            let job = self.submit(artifact).await;
+
+                            Self::route_json(StatusCode::ACCEPTED, &job)
+                        }
+                        Err(error) => Self::route_error(StatusCode::FORBIDDEN, error),
+                    },
+                    Err(error) => Self::route_error(StatusCode::BAD_REQUEST, error),
+                }
+            }
+            (Method::GET, ["jobs"]) => {
+                let jobs = self.jobs.lock().await.to_owned();
+
+                Self::route_json(StatusCode::OK, &jobs)
+            }
+            (Method::GET, ["jobs", id]) => match id.parse().ok() {
+                Some(id) => match self.job(id).await {
+                    Some(job) => Self::route_json(StatusCode::OK, &job),
+                    None => Self::route_error(StatusCode::NOT_FOUND, "job not found"),
+                },
+                None => Self::route_error(StatusCode::BAD_REQUEST, "invalid job id"),
+            },
+            (Method::DELETE, ["jobs", id]) => match id.parse().ok() {
+                Some(id) => match self.cancel(id).await {
+                    Some(job) => Self::route_json(StatusCode::OK, &job),
+                    None => Self::route_error(StatusCode::NOT_FOUND, "job not found"),
+                },
+                None => Self::route_error(StatusCode::BAD_REQUEST, "invalid job id"),
+            },
+            _ => Self::route_error(StatusCode::NOT_FOUND, "not found"),
+        }
+    }
+
+    fn route_json<T: Serialize>(status: StatusCode, value: &T) -> Response<Body> {
+        match serde_json::to_vec(value) {
+            Ok(body) => {
+                let mut response = Response::new(Body::from(body));
+
+                *response.status_mut() = status;
+                response
+                    .headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+                response
+            }
+            Err(error) => {
+                let mut response = Response::new(Body::from(error.to_string()));
+
+                *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+
+                response
+            }
+        }
+    }
+
+    fn route_error<E: std::fmt::Display>(status: StatusCode, error: E) -> Response<Body> {
+        let body = serde_json::json!({ "error": error.to_string() });
+
+        Self::route_json(status, &body)
+    }
+}
+
+pub fn check_remote(
+    artifact: &BatchArtifact,
+    remote: &RemoteConfig,
+    authenticated: bool,
+) -> Result<(), Error> {
+    let field = if !artifact.post_install.is_empty() {
+        "post_install"
+    } else if artifact.installer.plugin.is_some() {
+        "installer.plugin"
+    } else if artifact.installer.escalation != Escalation::None {
+        "installer.escalation"
+    } else if artifact.install && !authenticated {
+        "install without a token"
+    } else if artifact
+        .destination
+        .as_deref()
+        .is_some_and(|destination| !authenticated && !remote.allows(destination))
+    {
+        "a destination outside remote.destinations without a token"
+    } else {
+        return Ok(());
+    };
+    let error = format!("Jobs submitted over the network cannot set {}!", field);
+
+    Err(Error::new(ErrorKind::PermissionDenied, error))
+}
+
+fn next_queued(jobs: &[Job]) -> Option<usize> {
+    jobs.iter()
+        .enumerate()
+        .filter(|(_, job)| job.status == JobStatus::Queued && job.coalesced.is_none())
+        .max_by_key(|(_, job)| (job.artifact.priority, Reverse(job.id)))
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::Client;
+    use std::path::PathBuf;
+
+    async fn test_daemon() -> (Daemon, SocketAddr) {
+        let mut test_daemon = Daemon::init(Archeon::ignite().await).await;
+        test_daemon.remote.token = Some(String::from("test_token"));
+        let (test_address, test_server) = test_daemon
+            .bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .unwrap();
+        tokio::spawn(test_server);
+        (test_daemon, test_address)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn submit() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (test_daemon, _) = test_daemon().await;
+        let test_artifact = BatchArtifact {
+            url: String::from("http://test-daemon-submit/test.deb"),
+            ..BatchArtifact::default()
+        };
+        let test_job = test_daemon.submit(test_artifact.to_owned()).await;
+        assert_eq!(test_job.id, 0);
+        assert_eq!(test_job.status, JobStatus::Queued);
+        assert_eq!(test_job.artifact, test_artifact);
+        assert!(test_daemon.job(0).await.is_some());
+        assert!(test_daemon.job(1).await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn requeue() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (test_daemon, _) = Daemon::init_state(Vec::new());
+        let test_artifacts: Vec<BatchArtifact> = ["first", "second"]
+            .iter()
+            .map(|test_name| BatchArtifact {
+                url: format!("http://test-daemon-requeue/{}.iso", test_name),
+                ..BatchArtifact::default()
+            })
+            .collect();
+        let test_first = test_daemon.submit(test_artifacts[0].to_owned()).await;
+        let test_requeued = test_daemon.requeue(test_artifacts.to_owned()).await;
+        assert_eq!(test_requeued.len(), 1);
+        assert_eq!(test_requeued[0].artifact, test_artifacts[1]);
+        test_daemon
+            .launch_update(test_first.id, JobStatus::Failed, None)
+            .await;
+        assert_eq!(test_daemon.requeue(test_artifacts).await.len(), 1);
+        assert_eq!(test_daemon.jobs.lock().await.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn export_and_import() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (test_daemon, _) = Daemon::init_state(Vec::new());
+        for (test_url, test_priority) in [
+            ("http://test-daemon-export/first.deb", Priority::Low),
+            ("http://test-daemon-export/second.deb", Priority::High),
+            ("http://test-daemon-export/cancelled.deb", Priority::Normal),
+        ] {
+            test_daemon
+                .submit(BatchArtifact {
+                    url: String::from(test_url),
+                    priority: test_priority,
+                    install: true,
+                    ..BatchArtifact::default()
+                })
+                .await;
+        }
+        test_daemon.pause(0).await;
+        test_daemon.cancel(2).await;
+        let test_queue = test_daemon.export().await;
+        assert_eq!(test_queue.artifacts.len(), 2);
+        assert_eq!(test_queue.artifacts[1].priority, Priority::High);
+        assert!(test_queue.artifacts[1].install);
+        let test_queue = QueueState::parse(&serde_json::to_string(&test_queue)?)?;
+        let (test_replayed, _) = Daemon::init_state(Vec::new());
+        let test_jobs = test_replayed.import(test_queue.to_owned()).await;
+        assert_eq!(test_jobs.len(), 2);
+        assert_eq!(
+            test_jobs[0].artifact.url,
+            "http://test-daemon-export/first.deb"
+        );
+        assert_eq!(test_jobs[1].artifact.priority, Priority::High);
+        assert!(test_replayed.import(test_queue).await.is_empty());
+        assert!(QueueState::parse("test_queue").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn cancel() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (test_daemon, _) = test_daemon().await;
+        let mut test_updates = test_daemon.subscribe();
+        let test_artifact = BatchArtifact {
+            url: String::from("http://test-daemon-cancel/test.deb"),
+            ..BatchArtifact::default()
+        };
+        let test_job = test_daemon.submit(test_artifact).await;
+        let test_cancelled = test_daemon.cancel(test_job.id).await.unwrap();
+        assert!(test_cancelled.status.is_finished());
+        assert!(test_daemon.cancel(42).await.is_none());
+        let mut test_update = test_updates.recv().await?;
+        while !test_update.status.is_finished() {
+            test_update = test_updates.recv().await?;
+        }
+        assert_eq!(test_update.id, test_job.id);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn drain() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_archeon = Archeon::ignite().await;
+        let test_shutdown = test_archeon.shutdown.to_owned();
+        let test_daemon = Daemon::init(test_archeon).await;
+        test_shutdown.trigger();
+        let test_job = test_daemon
+            .submit(BatchArtifact {
+                url: String::from("http://test-daemon-drain.invalid/test.deb"),
+                ..BatchArtifact::default()
+            })
+            .await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), test_daemon.drain()).await?;
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let test_job = test_daemon.job(test_job.id).await.unwrap();
+        assert_eq!(test_job.status, JobStatus::Queued);
+        assert!(test_job.result.is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "journal")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init_with_journal() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_daemon_journal");
+        let test_journal = Journal::open(&test_dir.join("journal.sqlite3"))?;
+        test_journal.record(&Job {
+            id: 0,
+            status: JobStatus::Running,
+            artifact: BatchArtifact {
+                url: String::from("http://test-daemon-journal.invalid/test.deb"),
+                ..BatchArtifact::default()
+            },
+            result: None,
+            progress: None,
+            coalesced: None,
+        })?;
+        let test_daemon =
+            Daemon::init_with_journal(Archeon::ignite().await, test_journal.to_owned()).await?;
+        let mut test_updates = test_daemon.subscribe();
+        let mut test_job = test_daemon.job(0).await.unwrap();
+        while !test_job.status.is_finished() {
+            test_job = test_updates.recv().await?;
+        }
+        assert_eq!(test_job.status, JobStatus::Failed);
+        let test_second = test_daemon
+            .submit(BatchArtifact {
+                url: String::from("http://test-daemon-journal.invalid/second.deb"),
+                ..BatchArtifact::default()
+            })
+            .await;
+        assert_eq!(test_second.id, 1);
+        test_daemon.flush_journal().await;
+        let test_jobs = test_journal.jobs()?;
+        assert_eq!(test_jobs[0].status, JobStatus::Failed);
+        assert_eq!(test_jobs[1].artifact.url, test_second.artifact.url);
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn pause_and_resume() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (test_daemon, _) = Daemon::init_state(Vec::new());
+        let test_job = test_daemon
+            .submit(BatchArtifact {
+                url: String::from("http://test-daemon-pause/test.deb"),
+                ..BatchArtifact::default()
+            })
+            .await;
+        let test_paused = test_daemon.pause(test_job.id).await.unwrap();
+        assert_eq!(test_paused.status, JobStatus::Paused);
+        let test_prioritized = test_daemon
+            .prioritize(test_job.id, Priority::High)
+            .await
+            .unwrap();
+        assert_eq!(test_prioritized.artifact.priority, Priority::High);
+        assert_eq!(
+            test_daemon.resume(test_job.id).await.unwrap().status,
+            JobStatus::Queued
+        );
+        test_daemon.cancel(test_job.id).await;
+        assert_eq!(
+            test_daemon.pause(test_job.id).await.unwrap().status,
+            JobStatus::Cancelled,
+        );
+        assert_eq!(
+            test_daemon
+                .prioritize(test_job.id, Priority::Low)
+                .await
+                .unwrap()
+                .artifact
+                .priority,
+            Priority::High,
+        );
+        assert!(test_daemon.pause(42).await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn coalesce() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (test_daemon, _) = Daemon::init_state(Vec::new());
+        let test_artifact = BatchArtifact {
+            url: String::from("http://test-daemon-coalesce/test.deb"),
+            checksum: Some(String::from("ABC123")),
+            ..BatchArtifact::default()
+        };
+        let test_primary = test_daemon.submit(test_artifact.to_owned()).await;
+        let test_follower = test_daemon.submit(test_artifact.to_owned()).await;
+        let test_mirror = test_daemon
+            .submit(BatchArtifact {
+                url: String::from("http://test-daemon-mirror/test.deb"),
+                checksum: Some(String::from("abc123")),
+                ..BatchArtifact::default()
+            })
+            .await;
+        let test_install = test_daemon
+            .submit(BatchArtifact {
+                install: true,
+                ..test_artifact.to_owned()
+            })
+            .await;
+        assert!(test_primary.coalesced.is_none());
+        assert_eq!(test_follower.coalesced, Some(test_primary.id));
+        assert_eq!(test_mirror.coalesced, Some(test_primary.id));
+        assert!(test_install.coalesced.is_none());
+        assert_eq!(test_daemon.launch_next().await.unwrap().id, test_primary.id);
+        assert_eq!(
+            test_daemon.job(test_follower.id).await.unwrap().status,
+            JobStatus::Running,
+        );
+        assert_eq!(test_daemon.launch_next().await.unwrap().id, test_install.id);
+        assert!(test_daemon.launch_next().await.is_none());
+        let test_result = BatchResult {
+            url: test_artifact.url.to_owned(),
+            file_path: Some(PathBuf::from("/tmp/archeon/test.deb")),
+            installation: None,
+            error: None,
+            error_kind: None,
+            hooks: Vec::new(),
+        };
+        test_daemon
+            .launch_update(test_primary.id, JobStatus::Completed, Some(test_result))
+            .await;
+        let test_mirrored = test_daemon.job(test_mirror.id).await.unwrap();
+        assert_eq!(test_mirrored.status, JobStatus::Completed);
+        let test_mirrored = test_mirrored.result.unwrap();
+        assert_eq!(test_mirrored.url, "http://test-daemon-mirror/test.deb");
+        assert_eq!(
+            test_mirrored.file_path,
+            Some(PathBuf::from("/tmp/archeon/test.deb")),
+        );
+        let test_primary = test_daemon.submit(test_artifact.to_owned()).await;
+        let test_follower = test_daemon.submit(test_artifact.to_owned()).await;
+        assert_eq!(test_follower.coalesced, Some(test_primary.id));
+        test_daemon.cancel(test_primary.id).await;
+        let test_released = test_daemon.job(test_follower.id).await.unwrap();
+        assert_eq!(test_released.status, JobStatus::Queued);
+        assert!(test_released.coalesced.is_none());
+        assert_eq!(
+            test_daemon.launch_next().await.unwrap().id,
+            test_follower.id
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn next_queued() {
+        let test_job = |id, status, priority| Job {
+            id,
+            status,
+            artifact: BatchArtifact {
+                url: format!("http://test-daemon-next-queued/{}.iso", id),
+                priority,
+                ..BatchArtifact::default()
+            },
+            result: None,
+            progress: None,
+            coalesced: None,
+        };
+        let mut test_jobs = vec![
+            test_job(0, JobStatus::Running, Priority::Normal),
+            test_job(1, JobStatus::Queued, Priority::Low),
+            test_job(2, JobStatus::Queued, Priority::Normal),
+            test_job(3, JobStatus::Queued, Priority::Normal),
+            test_job(4, JobStatus::Cancelled, Priority::High),
+            test_job(5, JobStatus::Paused, Priority::High),
+        ];
+        assert_eq!(super::next_queued(&test_jobs), Some(2));
+        test_jobs.push(test_job(6, JobStatus::Queued, Priority::High));
+        assert_eq!(super::next_queued(&test_jobs), Some(6));
+        test_jobs.retain(|test_job| test_job.id <= 1);
+        assert_eq!(super::next_queued(&test_jobs), Some(1));
+        test_jobs.pop();
+        assert_eq!(super::next_queued(&test_jobs), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn job_status_display() {
+        assert_eq!(JobStatus::Queued.to_string(), "queued");
+        assert_eq!(JobStatus::Cancelled.to_string(), "cancelled");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn job_status_from_str() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!("queued".parse::<JobStatus>()?, JobStatus::Queued);
+        assert_eq!("completed".parse::<JobStatus>()?, JobStatus::Completed);
+        assert_eq!("failed".parse::<JobStatus>()?, JobStatus::Failed);
+        assert!("done".parse::<JobStatus>().is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn job_status_is_finished() {
+        assert!(!JobStatus::Queued.is_finished());
+        assert!(!JobStatus::Paused.is_finished());
+        assert!(!JobStatus::Running.is_finished());
+        assert!(JobStatus::Completed.is_finished());
+        assert!(JobStatus::Failed.is_finished());
+        assert!(JobStatus::Cancelled.is_finished());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serve() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        for test_method in ["HEAD", "GET"] {
+            test_server
+                .mock(test_method, "/test_daemon_serve.txt")
+                .with_status(200)
+                .with_header("content-length", "9")
+                .with_body(b"test_body")
+                .create_async()
+                .await;
+        }
+        let (_, test_address) = test_daemon().await;
+        let test_client = Client::new();
+        let test_body = serde_json::json!({
+            "url": format!("{}/test_daemon_serve.txt", test_server.url()),
+            "destination": "/tmp/archeon_test_daemon_serve",
+        });
+        let test_request = Request::post(format!("http://{}/jobs", test_address))
+            .header(AUTHORIZATION, "Bearer test_token")
+            .body(Body::from(test_body.to_string()))?;
+        let test_response = test_client.request(test_request).await?;
+        assert_eq!(test_response.status(), StatusCode::ACCEPTED);
+        let test_job: Job = serde_json::from_slice(&to_bytes(test_response.into_body()).await?)?;
+        assert_eq!(test_job.id, 0);
+        let mut test_status = test_job.status;
+        while !test_status.is_finished() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            let test_uri = format!("http://{}/jobs/0", test_address).parse()?;
+            let test_response = test_client.get(test_uri).await?;
+            let test_job: Job =
+                serde_json::from_slice(&to_bytes(test_response.into_body()).await?)?;
+            test_status = test_job.status;
+        }
+        assert_eq!(test_status, JobStatus::Completed);
+        let test_uri = format!("http://{}/jobs", test_address).parse()?;
+        let test_response = test_client.get(test_uri).await?;
+        let test_jobs: Vec<Job> =
+            serde_json::from_slice(&to_bytes(test_response.into_body()).await?)?;
+        assert_eq!(test_jobs.len(), 1);
+        tokio::fs::remove_dir_all("/tmp/archeon_test_daemon_serve").await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn route_errors() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (_, test_address) = test_daemon().await;
+        let test_client = Client::new();
+        let test_missing = format!("http://{}/jobs/42", test_address).parse()?;
+        let test_invalid = format!("http://{}/jobs/invalid", test_address).parse()?;
+        let test_unknown = format!("http://{}/unknown", test_address).parse()?;
+        let test_bad_body =
+            Request::post(format!("http://{}/jobs", test_address)).body(Body::from("not json"))?;
+        assert_eq!(
+            test_client.get(test_missing).await?.status(),
+            StatusCode::NOT_FOUND,
+        );
+        assert_eq!(
+            test_client.get(test_invalid).await?.status(),
+            StatusCode::BAD_REQUEST,
+        );
+        assert_eq!(
+            test_client.get(test_unknown).await?.status(),
+            StatusCode::NOT_FOUND,
+        );
+        assert_eq!(
+            test_client.request(test_bad_body).await?.status(),
+            StatusCode::BAD_REQUEST,
+        );
+        for test_body in [
+            serde_json::json!({ "url": "http://test-daemon/test.deb", "post_install": ["id"] }),
+            serde_json::json!({
+                "url": "http://test-daemon/test.deb",
+                "installer": { "plugin": "/tmp/test-plugin" },
+            }),
+            serde_json::json!({
+                "url": "http://test-daemon/test.deb",
+                "installer": { "escalation": "sudo" },
+            }),
+            serde_json::json!({ "url": "http://test-daemon/test.deb", "install": true }),
+            serde_json::json!({ "url": "http://test-daemon/test.deb", "destination": "/etc" }),
+        ] {
+            let test_request = Request::post(format!("http://{}/jobs", test_address))
+                .body(Body::from(test_body.to_string()))?;
+            let test_response = test_client.request(test_request).await?;
+            assert_eq!(test_response.status(), StatusCode::FORBIDDEN);
+        }
+        let test_uri = format!("http://{}/jobs", test_address).parse()?;
+        let test_jobs: Vec<Job> =
+            serde_json::from_slice(&to_bytes(test_client.get(test_uri).await?.into_body()).await?)?;
+        assert!(test_jobs.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn check_remote() {
+        let test_remote = RemoteConfig {
+            token: Some(String::from("test_token")),
+            destinations: vec![PathBuf::from("/srv/archeon")],
+        };
+        let test_install = BatchArtifact {
+            url: String::from("http://test-daemon/test.deb"),
+            install: true,
+            ..BatchArtifact::default()
+        };
+        let test_destination = |destination: &str| BatchArtifact {
+            url: String::from("http://test-daemon/test.deb"),
+            destination: Some(PathBuf::from(destination)),
+            ..BatchArtifact::default()
+        };
+        assert!(test_remote.authenticated(Some("Bearer test_token")));
+        assert!(!test_remote.authenticated(Some("Bearer test_other")));
+        assert!(!test_remote.authenticated(Some("test_token")));
+        assert!(!test_remote.authenticated(None));
+        assert!(!RemoteConfig::default().authenticated(Some("Bearer ")));
+        assert_eq!(
+            super::check_remote(&test_install, &test_remote, false)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::PermissionDenied,
+        );
+        assert!(super::check_remote(&test_install, &test_remote, true).is_ok());
+        assert!(
+            super::check_remote(&test_destination("/srv/archeon/debs"), &test_remote, false)
+                .is_ok()
+        );
+        assert!(super::check_remote(
+            &test_destination("/srv/archeon/../../etc"),
+            &test_remote,
+            false
+        )
+        .is_err());
+        assert!(super::check_remote(&test_destination("/etc"), &test_remote, false).is_err());
+        assert!(super::check_remote(&test_destination("/etc"), &test_remote, true).is_ok());
+    }
+}
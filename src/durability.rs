@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use tokio::fs::File;
+
+pub const PERIODIC_SYNC_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Durability {
+    #[default]
+    None,
+    Close,
+    Periodic,
+}
+
+impl std::str::FromStr for Durability {
+    type Err = Error;
+
+    fn from_str(durability: &str) -> Result<Durability, Error> {
+        match durability {
+            "none" => Ok(Durability::None),
+            "close" => Ok(Durability::Close),
+            "periodic" => Ok(Durability::Periodic),
+            _ => {
+                let error = format!("Invalid durability '{}'!", durability);
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+}
+
+impl Durability {
+    pub fn sync_on_close(&self) -> bool {
+        *self != Durability::None
+    }
+
+    pub fn sync_interval(&self) -> Option<u64> {
+        match self {
+            Durability::Periodic => Some(PERIODIC_SYNC_BYTES),
+            _ => None,
+        }
+    }
+}
+
+pub async fn sync_parent(path: &Path) -> Result<(), Error> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    File::open(parent).await?.sync_all().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn from_str() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!("none".parse::<Durability>()?, Durability::None);
+        assert_eq!("close".parse::<Durability>()?, Durability::Close);
+        assert_eq!("periodic".parse::<Durability>()?, Durability::Periodic);
+        assert!("always".parse::<Durability>().is_err());
+        assert!(!Durability::None.sync_on_close());
+        assert!(Durability::Close.sync_on_close());
+        assert!(Durability::Close.sync_interval().is_none());
+        assert_eq!(
+            Durability::Periodic.sync_interval(),
+            Some(PERIODIC_SYNC_BYTES),
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sync_parent() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_sync_parent.txt");
+        super::sync_parent(&test_path).await?;
+        super::sync_parent(Path::new("archeon_test_sync_parent.txt")).await?;
+        assert!(
+            super::sync_parent(Path::new("/archeon_test_missing/test.txt"))
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+}
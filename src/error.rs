@@ -0,0 +1,281 @@
+use hyper::StatusCode;
+
+use serde::{Deserialize, Serialize};
+
+pub const EXIT_FAILURE: u8 = 1;
+pub const EXIT_USAGE: u8 = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorKind {
+    Network,
+    Client,
+    Server,
+    Integrity,
+    Install,
+    Local,
+    Cancelled,
+}
+
+impl ErrorKind {
+    pub fn code(&self) -> u8 {
+        match self {
+            ErrorKind::Network => 2,
+            ErrorKind::Integrity => 3,
+            ErrorKind::Install => 4,
+            ErrorKind::Client => 5,
+            ErrorKind::Server => 6,
+            ErrorKind::Local => 7,
+            ErrorKind::Cancelled => 8,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            ErrorKind::Network => "network",
+            ErrorKind::Client => "client",
+            ErrorKind::Server => "server",
+            ErrorKind::Integrity => "integrity",
+            ErrorKind::Install => "install",
+            ErrorKind::Local => "local",
+            ErrorKind::Cancelled => "cancelled",
+        };
+
+        f.write_str(kind)
+    }
+}
+
+#[derive(Debug)]
+pub enum ArcheonError {
+    Network(Box<dyn std::error::Error + Send + Sync>),
+    Unavailable(String),
+    Status {
+        uri: String,
+        status: StatusCode,
+    },
+    Integrity {
+        source: String,
+        expected: String,
+        actual: String,
+    },
+    Install(std::io::Error),
+    Failed {
+        kind: ErrorKind,
+        message: String,
+    },
+    Cancelled,
+    Io(std::io::Error),
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl ArcheonError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ArcheonError::Network(_) | ArcheonError::Unavailable(_) => ErrorKind::Network,
+            ArcheonError::Status { status, .. } if status.is_server_error() => ErrorKind::Server,
+            ArcheonError::Status { .. } => ErrorKind::Client,
+            ArcheonError::Integrity { .. } => ErrorKind::Integrity,
+            ArcheonError::Install(_) => ErrorKind::Install,
+            ArcheonError::Failed { kind, .. } => *kind,
+            ArcheonError::Cancelled => ErrorKind::Cancelled,
+            ArcheonError::Io(_) | ArcheonError::Other(_) => ErrorKind::Local,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ArcheonError::Network(_) => true,
+            ArcheonError::Status { status, .. } => {
+                (status.is_server_error()
+                    && *status != StatusCode::NOT_IMPLEMENTED
+                    && *status != StatusCode::HTTP_VERSION_NOT_SUPPORTED)
+                    || *status == StatusCode::TOO_MANY_REQUESTS
+                    || *status == StatusCode::REQUEST_TIMEOUT
+            }
+            _ => false,
+        }
+    }
+
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ArcheonError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ArcheonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArcheonError::Network(error) => write!(f, "{}", error),
+            ArcheonError::Unavailable(error) => f.write_str(error),
+            ArcheonError::Status { uri, status } => write!(f, "{} returned HTTP {}!", uri, status),
+            ArcheonError::Integrity {
+                source,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Checksum mismatch for {}: expected {}, found {}!",
+                source, expected, actual,
+            ),
+            ArcheonError::Install(error) => write!(f, "{}", error),
+            ArcheonError::Failed { message, .. } => f.write_str(message),
+            ArcheonError::Cancelled => f.write_str("Cancelled by shutdown!"),
+            ArcheonError::Io(error) => write!(f, "{}", error),
+            ArcheonError::Other(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ArcheonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ArcheonError::Network(error) | ArcheonError::Other(error) => Some(error.as_ref()),
+            ArcheonError::Install(error) | ArcheonError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<hyper::Error> for ArcheonError {
+    fn from(error: hyper::Error) -> ArcheonError {
+        ArcheonError::Network(Box::new(error))
+    }
+}
+
+impl From<std::io::Error> for ArcheonError {
+    fn from(error: std::io::Error) -> ArcheonError {
+        ArcheonError::Io(error)
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for ArcheonError {
+    fn from(error: Box<dyn std::error::Error + Send + Sync>) -> ArcheonError {
+        let error = match error.downcast::<ArcheonError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        let error = match error.downcast::<hyper::Error>() {
+            Ok(error) => return ArcheonError::from(*error),
+            Err(error) => error,
+        };
+
+        match error.downcast::<std::io::Error>() {
+            Ok(error) => ArcheonError::Io(*error),
+            Err(error) => ArcheonError::Other(error),
+        }
+    }
+}
+
+pub fn exit_code(error: &(dyn std::error::Error + 'static)) -> u8 {
+    if let Some(error) = error.downcast_ref::<ArcheonError>() {
+        return error.kind().code();
+    }
+
+    if error.is::<hyper::Error>() {
+        return ErrorKind::Network.code();
+    }
+
+    if error.is::<std::io::Error>() {
+        return ErrorKind::Local.code();
+    }
+
+    EXIT_FAILURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_status(status: StatusCode) -> ArcheonError {
+        ArcheonError::Status {
+            uri: String::from("http://test-error/test.deb"),
+            status,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn kind() {
+        assert_eq!(test_status(StatusCode::NOT_FOUND).kind(), ErrorKind::Client);
+        assert_eq!(
+            test_status(StatusCode::BAD_GATEWAY).kind(),
+            ErrorKind::Server
+        );
+        assert_eq!(
+            ArcheonError::Unavailable(String::from("test")).kind(),
+            ErrorKind::Network,
+        );
+        assert_eq!(
+            ArcheonError::from(std::io::Error::other("test")).kind(),
+            ErrorKind::Local,
+        );
+        assert_eq!(ErrorKind::Integrity.to_string(), "integrity");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn exit_code() {
+        let test_codes: Vec<u8> = [
+            ErrorKind::Network,
+            ErrorKind::Integrity,
+            ErrorKind::Install,
+            ErrorKind::Client,
+            ErrorKind::Server,
+            ErrorKind::Local,
+            ErrorKind::Cancelled,
+        ]
+        .iter()
+        .map(ErrorKind::code)
+        .collect();
+        assert_eq!(test_codes, [2, 3, 4, 5, 6, 7, 8]);
+        assert!(!ArcheonError::Cancelled.is_retryable());
+        assert_eq!(super::exit_code(&test_status(StatusCode::NOT_FOUND)), 5);
+        assert_eq!(
+            super::exit_code(&ArcheonError::Install(std::io::Error::other("test"))),
+            4,
+        );
+        assert_eq!(
+            super::exit_code(&ArcheonError::Failed {
+                kind: ErrorKind::Integrity,
+                message: String::from("test_failed"),
+            }),
+            3,
+        );
+        assert_eq!(super::exit_code(&std::io::Error::other("test")), 7);
+        let test_other: Box<dyn std::error::Error + Send + Sync> = "test_other".into();
+        assert_eq!(super::exit_code(test_other.as_ref()), EXIT_FAILURE);
+        assert_eq!(ErrorKind::Install.to_string(), "install");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn is_retryable() {
+        assert!(test_status(StatusCode::SERVICE_UNAVAILABLE).is_retryable());
+        assert!(test_status(StatusCode::TOO_MANY_REQUESTS).is_retryable());
+        assert!(!test_status(StatusCode::NOT_IMPLEMENTED).is_retryable());
+        assert!(!test_status(StatusCode::FORBIDDEN).is_retryable());
+        assert!(!ArcheonError::Unavailable(String::from("test")).is_retryable());
+        assert!(!ArcheonError::Integrity {
+            source: String::from("http://test-error/test.deb"),
+            expected: String::from("expected"),
+            actual: String::from("actual"),
+        }
+        .is_retryable());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn from_boxed() {
+        let test_io: Box<dyn std::error::Error + Send + Sync> =
+            Box::new(std::io::Error::other("test_io"));
+        let test_archeon: Box<dyn std::error::Error + Send + Sync> =
+            Box::new(test_status(StatusCode::NOT_FOUND));
+        let test_other: Box<dyn std::error::Error + Send + Sync> = "test_other".into();
+        assert!(matches!(ArcheonError::from(test_io), ArcheonError::Io(_)));
+        assert_eq!(
+            ArcheonError::from(test_archeon).status(),
+            Some(StatusCode::NOT_FOUND),
+        );
+        assert_eq!(ArcheonError::from(test_other).to_string(), "test_other");
+    }
+}
@@ -0,0 +1,106 @@
+use hyper::StatusCode;
+
+use js_sys::{Promise, Uint8Array};
+
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use web_sys::{ReadableStreamDefaultReader, ReadableStreamReadResult, Request, Response};
+
+use crate::error::ArcheonError;
+use crate::progress::BatchProgress;
+use crate::stream::StreamVerifier;
+
+#[wasm_bindgen::prelude::wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = fetch)]
+    fn fetch_with_request(input: &Request) -> Promise;
+}
+
+#[derive(Clone, Debug)]
+pub struct Fetch {
+    pub url: String,
+    pub checksum: Option<String>,
+    pub progress: Option<BatchProgress>,
+}
+
+impl Fetch {
+    pub fn init(url: &str) -> Fetch {
+        Fetch {
+            url: url.to_owned(),
+            checksum: None,
+            progress: None,
+        }
+    }
+
+    pub async fn launch(&self) -> Result<Vec<u8>, ArcheonError> {
+        let mut stream = StreamVerifier::init(
+            &self.url,
+            self.checksum.as_deref(),
+            self.progress.to_owned(),
+        );
+
+        match self.launch_body(&mut stream).await {
+            Ok(body) => stream.finish().map(|_| body),
+            Err(error) => Err(stream.fail(error)),
+        }
+    }
+
+    async fn launch_body(&self, stream: &mut StreamVerifier) -> Result<Vec<u8>, ArcheonError> {
+        let request = Request::new_with_str(&self.url).map_err(network_error)?;
+        let response: Response = JsFuture::from(fetch_with_request(&request))
+            .await
+            .map_err(network_error)?
+            .dyn_into()
+            .map_err(network_error)?;
+
+        if !response.ok() {
+            let status = StatusCode::from_u16(response.status())
+                .map_err(|error| ArcheonError::Network(Box::new(error)))?;
+
+            return Err(ArcheonError::Status {
+                uri: self.url.to_owned(),
+                status,
+            });
+        }
+
+        let size = response
+            .headers()
+            .get("content-length")
+            .ok()
+            .flatten()
+            .and_then(|length| length.parse().ok());
+        let mut body = Vec::with_capacity(size.unwrap_or_default() as usize);
+
+        stream.start(size);
+
+        let Some(readable) = response.body() else {
+            return Ok(body);
+        };
+        let reader = ReadableStreamDefaultReader::new(&readable).map_err(network_error)?;
+
+        loop {
+            let result: ReadableStreamReadResult = JsFuture::from(reader.read())
+                .await
+                .map_err(network_error)?
+                .unchecked_into();
+
+            if result.get_done().unwrap_or_default() {
+                break;
+            }
+
+            let chunk = Uint8Array::new(&result.get_value()).to_vec();
+
+            stream.update(&chunk);
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(body)
+    }
+}
+
+fn network_error(error: JsValue) -> ArcheonError {
+    let error = error.as_string().unwrap_or_else(|| format!("{:?}", error));
+
+    ArcheonError::Network(error.into())
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PathFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl PathFilter {
+    pub fn matches(&self, path: &str) -> bool {
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| matches_path(pattern, path));
+
+        included && !self.excludes(path)
+    }
+
+    pub fn excludes(&self, path: &str) -> bool {
+        self.exclude
+            .iter()
+            .any(|pattern| matches_path(pattern, path))
+    }
+}
+
+pub fn matches_path(pattern: &str, path: &str) -> bool {
+    match pattern.contains('/') {
+        true => wildcard(
+            pattern.trim_start_matches('/'),
+            path.trim_start_matches('/'),
+        ),
+        false => wildcard(pattern, path.rsplit('/').next().unwrap_or(path)),
+    }
+}
+
+pub fn wildcard(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                t += 1;
+            }
+            Some(character) if *character == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|character| *character == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn wildcard() {
+        assert!(super::wildcard("*.deb", "test_1.0_amd64.deb"));
+        assert!(super::wildcard("test_?.?_*", "test_1.0_amd64.deb"));
+        assert!(super::wildcard("*", ""));
+        assert!(super::wildcard("a*b*c", "aXbYbZc"));
+        assert!(!super::wildcard("*.deb", "test.deb.asc"));
+        assert!(!super::wildcard("test?", "test"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn matches() {
+        let test_filter = PathFilter {
+            include: vec![String::from("*.deb"), String::from("docs/*")],
+            exclude: vec![String::from("*-dbgsym_*")],
+        };
+        assert!(test_filter.matches("pool/main/test_1.0_amd64.deb"));
+        assert!(test_filter.matches("docs/README"));
+        assert!(!test_filter.matches("pool/main/test-dbgsym_1.0_amd64.deb"));
+        assert!(!test_filter.matches("pool/main/test_1.0.dsc"));
+        assert!(!test_filter.matches("pool/docs/README"));
+        assert!(test_filter.excludes("pool/main/test-dbgsym_1.0_amd64.deb"));
+        assert!(!test_filter.excludes("pool/main/test_1.0.dsc"));
+        assert!(PathFilter::default().matches("anything/at/all"));
+    }
+}
@@ -0,0 +1,378 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::channel;
+
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tokio_stream::Stream;
+
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+use crate::batch::{self, BatchArtifact};
+use crate::daemon::{self, Daemon};
+
+pub mod proto {
+    tonic::include_proto!("archeon");
+}
+
+pub use proto::archeon_client::ArcheonClient;
+pub use proto::archeon_server::ArcheonServer;
+pub use proto::{Job, JobRequest, JobStatus, Priority, Progress, SubmitJobRequest};
+
+pub struct GrpcService {
+    pub daemon: Daemon,
+}
+
+impl GrpcService {
+    pub fn init(daemon: Daemon) -> GrpcService {
+        GrpcService { daemon }
+    }
+
+    pub async fn serve(self, address: SocketAddr) -> Result<(), tonic::transport::Error> {
+        Server::builder()
+            .add_service(ArcheonServer::new(self))
+            .serve(address)
+            .await
+    }
+
+    pub async fn bind(
+        self,
+        address: SocketAddr,
+    ) -> Result<
+        (
+            SocketAddr,
+            impl Future<Output = Result<(), tonic::transport::Error>>,
+        ),
+        std::io::Error,
+    > {
+        let listener = TcpListener::bind(address).await?;
+        let address = listener.local_addr()?;
+        let server = Server::builder()
+            .add_service(ArcheonServer::new(self))
+            .serve_with_incoming(TcpListenerStream::new(listener));
+
+        Ok((address, server))
+    }
+
+    async fn launch_job(&self, id: u64) -> Result<daemon::Job, Status> {
+        self.daemon
+            .job(id)
+            .await
+            .ok_or_else(|| Status::not_found(format!("job {} not found", id)))
+    }
+}
+
+impl From<daemon::Job> for Job {
+    fn from(job: daemon::Job) -> Job {
+        let status = match job.status {
+            daemon::JobStatus::Queued => JobStatus::Queued,
+            daemon::JobStatus::Paused => JobStatus::Paused,
+            daemon::JobStatus::Running => JobStatus::Running,
+            daemon::JobStatus::Completed => JobStatus::Completed,
+            daemon::JobStatus::Failed => JobStatus::Failed,
+            daemon::JobStatus::Cancelled => JobStatus::Cancelled,
+        };
+        let file_path = job
+            .result
+            .as_ref()
+            .and_then(|result| result.file_path.as_ref())
+            .map(|file_path| file_path.to_string_lossy().into_owned());
+        let installation = job.result.as_ref().and_then(|result| result.installation);
+        let error_kind = job
+            .result
+            .as_ref()
+            .and_then(|result| result.error_kind)
+            .map(|error_kind| error_kind.to_string());
+        let progress = job.progress.map(|progress| Progress {
+            total_bytes: progress.total_bytes,
+            downloaded_bytes: progress.downloaded_bytes,
+            bytes_per_second: progress.bytes_per_second,
+            resumed_bytes: progress.resumed_bytes,
+        });
+
+        Job {
+            id: job.id,
+            status: status.into(),
+            url: job.artifact.url,
+            tags: job.artifact.tags.into_iter().collect(),
+            file_path,
+            installation,
+            error: job.result.and_then(|result| result.error),
+            error_kind,
+            progress,
+        }
+    }
+}
+
+impl From<SubmitJobRequest> for BatchArtifact {
+    fn from(request: SubmitJobRequest) -> BatchArtifact {
+        let priority = match request.priority() {
+            Priority::Normal => batch::Priority::Normal,
+            Priority::High => batch::Priority::High,
+            Priority::Low => batch::Priority::Low,
+        };
+
+        BatchArtifact {
+            url: request.url,
+            checksum: request.checksum,
+            destination: request.destination.map(PathBuf::from),
+            install: request.install,
+            priority,
+            tags: request.tags.into_iter().collect(),
+            ..BatchArtifact::default()
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl proto::archeon_server::Archeon for GrpcService {
+    type WatchJobStream = Pin<Box<dyn Stream<Item = Result<Job, Status>> + Send + 'static>>;
+
+    async fn submit_job(
+        &self,
+        request: Request<SubmitJobRequest>,
+    ) -> Result<Response<Job>, Status> {
+        let authenticated = self.daemon.remote.authenticated(
+            request
+                .metadata()
+                .get("authorization")
+                .and_then(|authorization| authorization.to_str().ok()),
+        );
+        let request = request.into_inner();
+
+        if request.url.is_empty() {
+            return Err(Status::invalid_argument("url is required"));
+        }
+
+        let artifact = BatchArtifact::from(request);
+
+        daemon::check_remote(&artifact, &self.daemon.remote, authenticated)
+            .map_err(|error| Status::permission_denied(error.to_string()))?;
+
+        let job = self.daemon.submit(artifact).await;
+
+        Ok(Response::new(job.into()))
+    }
+
+    async fn get_job(&self, request: Request<JobRequest>) -> Result<Response<Job>, Status> {
+        let job = self.launch_job(request.into_inner().id).await?;
+
+        Ok(Response::new(job.into()))
+    }
+
+    async fn watch_job(
+        &self,
+        request: Request<JobRequest>,
+    ) -> Result<Response<Self::WatchJobStream>, Status> {
+        let id = request.into_inner().id;
+        let mut updates = self.daemon.subscribe();
+        let job = self.launch_job(id).await?;
+        let daemon = self.daemon.to_owned();
+        let (sender, receiver) = channel(16);
+
+        tokio::spawn(async move {
+            let mut finished = job.status.is_finished();
+
+            if sender.send(Ok(job.into())).await.is_err() {
+                return;
+            }
+
+            while !finished {
+                let job = match updates.recv().await {
+                    Ok(job) if job.id == id => job,
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(_)) => match daemon.job(id).await {
+                        Some(job) => job,
+                        None => return,
+                    },
+                    Err(RecvError::Closed) => return,
+                };
+
+                finished = job.status.is_finished();
+
+                if sender.send(Ok(job.into())).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(receiver))))
+    }
+
+    async fn cancel_job(&self, request: Request<JobRequest>) -> Result<Response<Job>, Status> {
+        let id = request.into_inner().id;
+        let job = self
+            .daemon
+            .cancel(id)
+            .await
+            .ok_or_else(|| Status::not_found(format!("job {} not found", id)))?;
+
+        Ok(Response::new(job.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Archeon;
+    use tokio_stream::StreamExt;
+
+    async fn test_client() -> ArcheonClient<tonic::transport::Channel> {
+        let mut test_daemon = Daemon::init(Archeon::ignite().await).await;
+        test_daemon.remote.token = Some(String::from("test_token"));
+        let (test_address, test_server) = GrpcService::init(test_daemon)
+            .bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .unwrap();
+        tokio::spawn(test_server);
+        ArcheonClient::connect(format!("http://{}", test_address))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn from_job() {
+        let test_job = daemon::Job {
+            id: 3,
+            status: daemon::JobStatus::Completed,
+            artifact: BatchArtifact {
+                url: String::from("http://test-grpc/test.deb"),
+                tags: [(String::from("owner"), String::from("platform"))].into(),
+                ..BatchArtifact::default()
+            },
+            result: Some(crate::batch::BatchResult {
+                url: String::from("http://test-grpc/test.deb"),
+                file_path: Some(PathBuf::from("/tmp/archeon/test.deb")),
+                installation: Some(1),
+                error: None,
+                error_kind: None,
+                hooks: Vec::new(),
+            }),
+            progress: None,
+            coalesced: None,
+        };
+        let test_proto_job = Job::from(test_job);
+        assert_eq!(test_proto_job.id, 3);
+        assert_eq!(test_proto_job.status(), JobStatus::Completed);
+        assert_eq!(test_proto_job.url, "http://test-grpc/test.deb");
+        assert_eq!(
+            test_proto_job.file_path.as_deref(),
+            Some("/tmp/archeon/test.deb"),
+        );
+        assert_eq!(test_proto_job.installation, Some(1));
+        assert!(test_proto_job.error.is_none());
+        assert!(test_proto_job.error_kind.is_none());
+        assert_eq!(test_proto_job.tags["owner"], "platform");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn from_submit_job_request() {
+        let test_request = SubmitJobRequest {
+            url: String::from("http://test-grpc/test.deb"),
+            checksum: Some(String::from("test_checksum")),
+            destination: Some(String::from("/tmp/archeon_test_grpc")),
+            install: true,
+            priority: Priority::High.into(),
+            tags: [(String::from("ticket"), String::from("OPS-42"))].into(),
+        };
+        let test_artifact = BatchArtifact::from(test_request);
+        assert_eq!(test_artifact.url, "http://test-grpc/test.deb");
+        assert_eq!(test_artifact.checksum.as_deref(), Some("test_checksum"));
+        assert_eq!(
+            test_artifact.destination,
+            Some(PathBuf::from("/tmp/archeon_test_grpc")),
+        );
+        assert!(test_artifact.install);
+        assert_eq!(test_artifact.priority, batch::Priority::High);
+        assert_eq!(test_artifact.tags["ticket"], "OPS-42");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn submit_and_watch_job() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        for test_method in ["HEAD", "GET"] {
+            test_server
+                .mock(test_method, "/test_grpc_watch.txt")
+                .with_status(200)
+                .with_header("content-length", "9")
+                .with_body(b"test_body")
+                .create_async()
+                .await;
+        }
+        let mut test_client = test_client().await;
+        let mut test_request = Request::new(SubmitJobRequest {
+            url: format!("{}/test_grpc_watch.txt", test_server.url()),
+            checksum: None,
+            destination: Some(String::from("/tmp/archeon_test_grpc_watch")),
+            install: false,
+            ..SubmitJobRequest::default()
+        });
+        test_request
+            .metadata_mut()
+            .insert("authorization", "Bearer test_token".parse()?);
+        let test_job = test_client.submit_job(test_request).await?.into_inner();
+        let mut test_stream = test_client
+            .watch_job(JobRequest { id: test_job.id })
+            .await?
+            .into_inner();
+        let mut test_updates = Vec::new();
+        while let Some(test_update) = test_stream.next().await {
+            test_updates.push(test_update?);
+        }
+        assert!(test_updates.iter().any(|test_update| test_update
+            .progress
+            .as_ref()
+            .is_some_and(|test_progress| test_progress.downloaded_bytes == 9)));
+        assert_eq!(test_updates.last().unwrap().status(), JobStatus::Completed);
+        let test_job = test_client
+            .get_job(JobRequest { id: test_job.id })
+            .await?
+            .into_inner();
+        assert_eq!(
+            test_job.file_path.as_deref(),
+            Some("/tmp/archeon_test_grpc_watch/test_grpc_watch.txt"),
+        );
+        tokio::fs::remove_dir_all("/tmp/archeon_test_grpc_watch").await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn errors() {
+        let mut test_client = test_client().await;
+        let test_invalid = test_client
+            .submit_job(SubmitJobRequest::default())
+            .await
+            .unwrap_err();
+        let test_missing = test_client
+            .get_job(JobRequest { id: 42 })
+            .await
+            .unwrap_err();
+        let test_cancel_missing = test_client
+            .cancel_job(JobRequest { id: 42 })
+            .await
+            .unwrap_err();
+        for test_request in [
+            SubmitJobRequest {
+                url: String::from("http://test-grpc/test.deb"),
+                install: true,
+                ..SubmitJobRequest::default()
+            },
+            SubmitJobRequest {
+                url: String::from("http://test-grpc/test.deb"),
+                destination: Some(String::from("/etc")),
+                ..SubmitJobRequest::default()
+            },
+        ] {
+            let test_denied = test_client.submit_job(test_request).await.unwrap_err();
+            assert_eq!(test_denied.code(), tonic::Code::PermissionDenied);
+        }
+        assert_eq!(test_invalid.code(), tonic::Code::InvalidArgument);
+        assert_eq!(test_missing.code(), tonic::Code::NotFound);
+        assert_eq!(test_cancel_missing.code(), tonic::Code::NotFound);
+    }
+}
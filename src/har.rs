@@ -0,0 +1,384 @@
+use hyper::header::{HeaderMap, CONTENT_TYPE, LOCATION};
+use hyper::{Body, Request, Response};
+
+use serde::{Deserialize, Serialize};
+
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::fs::{create_dir_all, write};
+
+use crate::aws::civil_from_days;
+use crate::wire::{redact_headers, redact_uri, REDACTED};
+
+pub const HAR_VERSION: &str = "1.2";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Har {
+    pub log: HarLog,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HarLog {
+    pub version: String,
+    pub creator: HarCreator,
+    pub entries: Vec<HarEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HarCreator {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    pub started_date_time: String,
+    pub time: f64,
+    pub request: HarRequest,
+    pub response: HarResponse,
+    pub cache: HarCache,
+    pub timings: HarTimings,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    pub http_version: String,
+    pub cookies: Vec<HarPair>,
+    pub headers: Vec<HarPair>,
+    pub query_string: Vec<HarPair>,
+    pub headers_size: i64,
+    pub body_size: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub http_version: String,
+    pub cookies: Vec<HarPair>,
+    pub headers: Vec<HarPair>,
+    pub content: HarContent,
+    #[serde(rename = "redirectURL")]
+    pub redirect_url: String,
+    pub headers_size: i64,
+    pub body_size: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarContent {
+    pub size: i64,
+    pub mime_type: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HarPair {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HarCache {}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HarTimings {
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HarEntryId(usize);
+
+#[derive(Clone, Debug, Default)]
+pub struct HarRecorder {
+    path: Option<PathBuf>,
+    entries: Arc<Mutex<Vec<HarEntry>>>,
+}
+
+impl Default for Har {
+    fn default() -> Har {
+        Har {
+            log: HarLog {
+                version: String::from(HAR_VERSION),
+                creator: HarCreator {
+                    name: String::from(env!("CARGO_PKG_NAME")),
+                    version: String::from(env!("CARGO_PKG_VERSION")),
+                },
+                entries: Vec::new(),
+            },
+        }
+    }
+}
+
+impl HarRequest {
+    pub fn init(request: &Request<Body>) -> HarRequest {
+        let url = redact_uri(request.uri());
+        let query_string = url
+            .split_once('?')
+            .map(|(_, query)| query)
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((name, value)) => HarPair::init(name, value),
+                None => HarPair::init(pair, ""),
+            })
+            .collect();
+
+        HarRequest {
+            method: request.method().to_string(),
+            url,
+            http_version: format!("{:?}", request.version()),
+            cookies: Vec::new(),
+            headers: pairs(request.headers()),
+            query_string,
+            headers_size: -1,
+            body_size: 0,
+        }
+    }
+}
+
+impl HarPair {
+    fn init(name: &str, value: &str) -> HarPair {
+        HarPair {
+            name: name.to_owned(),
+            value: value.to_owned(),
+        }
+    }
+}
+
+impl HarRecorder {
+    pub fn init(path: &Path) -> HarRecorder {
+        HarRecorder {
+            path: Some(path.to_owned()),
+            ..HarRecorder::default()
+        }
+    }
+
+    pub fn record(
+        &self,
+        request: HarRequest,
+        response: &Response<Body>,
+        started: SystemTime,
+        wait: Duration,
+    ) -> HarEntryId {
+        let headers = response.headers();
+        let response = HarResponse {
+            status: response.status().as_u16(),
+            status_text: response
+                .status()
+                .canonical_reason()
+                .unwrap_or_default()
+                .to_owned(),
+            http_version: format!("{:?}", response.version()),
+            cookies: Vec::new(),
+            headers: pairs(headers),
+            content: HarContent {
+                size: 0,
+                mime_type: header(headers, CONTENT_TYPE.as_str()),
+            },
+            redirect_url: header(headers, LOCATION.as_str()),
+            headers_size: -1,
+            body_size: 0,
+        };
+        let entry = HarEntry {
+            started_date_time: format_date_time(started),
+            time: milliseconds(wait),
+            request,
+            response,
+            cache: HarCache::default(),
+            timings: HarTimings {
+                send: 0.0,
+                wait: milliseconds(wait),
+                receive: 0.0,
+            },
+        };
+        let mut entries = self.lock();
+
+        entries.push(entry);
+
+        HarEntryId(entries.len() - 1)
+    }
+
+    pub fn complete(&self, id: HarEntryId, size: u64, receive: Duration) {
+        if let Some(entry) = self.lock().get_mut(id.0) {
+            let size = i64::try_from(size).unwrap_or(i64::MAX);
+
+            entry.response.content.size = size;
+            entry.response.body_size = size;
+            entry.timings.receive = milliseconds(receive);
+            entry.time = entry.timings.send + entry.timings.wait + entry.timings.receive;
+        }
+    }
+
+    pub fn har(&self) -> Har {
+        let mut har = Har::default();
+
+        har.log.entries = self.lock().to_owned();
+
+        har
+    }
+
+    pub async fn save(&self) -> Result<(), Error> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            create_dir_all(parent).await?;
+        }
+
+        write(path, serde_json::to_vec_pretty(&self.har())?).await
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<HarEntry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn pairs(headers: &HeaderMap) -> Vec<HarPair> {
+    redact_headers(headers)
+        .into_iter()
+        .map(|(name, value)| HarPair { name, value })
+        .collect()
+}
+
+fn header(headers: &HeaderMap, name: &str) -> String {
+    pairs(headers)
+        .into_iter()
+        .find(|pair| pair.name == name && pair.value != REDACTED)
+        .map(|pair| pair.value)
+        .unwrap_or_default()
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn format_date_time(time: SystemTime) -> String {
+    let since = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let timestamp = since.as_secs();
+    let (year, month, day) = civil_from_days((timestamp / 86400) as i64);
+    let seconds = timestamp % 86400;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        since.subsec_millis(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn format_date_time() {
+        assert_eq!(
+            super::format_date_time(UNIX_EPOCH + Duration::from_millis(1369353600250)),
+            "2013-05-24T00:00:00.250Z",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn record() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir()
+            .join("archeon_test_har_record")
+            .join("test.har");
+        let test_recorder = HarRecorder::init(&test_path);
+        let test_request = Request::builder()
+            .uri("http://test-har.invalid/test.deb?version=1&token=test_token")
+            .header("authorization", "Bearer test_token")
+            .body(Body::empty())?;
+        let test_response = Response::builder()
+            .status(302)
+            .header(
+                "location",
+                "http://test-har-mirror.invalid/test.deb?X-Amz-Signature=test_token",
+            )
+            .body(Body::empty())?;
+        let test_started = UNIX_EPOCH + Duration::from_secs(1369353600);
+        let test_id = test_recorder.record(
+            HarRequest::init(&test_request),
+            &test_response,
+            test_started,
+            Duration::from_millis(20),
+        );
+        let test_response = Response::builder()
+            .status(200)
+            .header("content-type", "application/vnd.debian.binary-package")
+            .body(Body::empty())?;
+        let test_completed = test_recorder.record(
+            HarRequest::init(&test_request),
+            &test_response,
+            test_started,
+            Duration::from_millis(10),
+        );
+        test_recorder.complete(test_completed, 9, Duration::from_millis(5));
+        test_recorder.save().await?;
+        let test_har: Har = serde_json::from_slice(&tokio::fs::read(&test_path).await?)?;
+        assert_eq!(test_har.log.version, "1.2");
+        assert_eq!(test_har.log.creator.name, "archeon");
+        assert_eq!(test_har.log.entries.len(), 2);
+        let test_redirect = &test_har.log.entries[test_id.0];
+        assert_eq!(test_redirect.started_date_time, "2013-05-24T00:00:00.000Z");
+        assert_eq!(test_redirect.request.method, "GET");
+        assert_eq!(
+            test_redirect.request.url,
+            "http://test-har.invalid/test.deb?version=1&token=<redacted>",
+        );
+        assert_eq!(
+            test_redirect.request.query_string,
+            vec![
+                HarPair::init("version", "1"),
+                HarPair::init("token", REDACTED)
+            ],
+        );
+        assert_eq!(
+            test_redirect.request.headers,
+            vec![HarPair::init("authorization", REDACTED)],
+        );
+        assert_eq!(test_redirect.response.status, 302);
+        assert_eq!(test_redirect.response.status_text, "Found");
+        assert_eq!(
+            test_redirect.response.redirect_url,
+            "http://test-har-mirror.invalid/test.deb?X-Amz-Signature=<redacted>",
+        );
+        let test_download = &test_har.log.entries[1];
+        assert_eq!(test_download.response.content.size, 9);
+        assert_eq!(
+            test_download.response.content.mime_type,
+            "application/vnd.debian.binary-package",
+        );
+        assert_eq!(test_download.timings.wait, 10.0);
+        assert_eq!(test_download.timings.receive, 5.0);
+        assert_eq!(test_download.time, 15.0);
+        let test_json: serde_json::Value =
+            serde_json::from_slice(&tokio::fs::read(&test_path).await?)?;
+        assert!(test_json["log"]["entries"][0]["response"]["redirectURL"].is_string());
+        assert!(test_json["log"]["entries"][0]["startedDateTime"].is_string());
+        assert!(!test_json.to_string().contains("test_token"));
+        tokio::fs::remove_dir_all(test_path.parent().unwrap()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use hyper::{Body, Request};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io::Error;
+use std::path::Path;
+use std::pin::Pin;
+
+use tokio::process::Command;
+
+use crate::installer::Installation;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookOutput {
+    pub command: String,
+    pub status: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+pub type PreRequestFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>> + Send + 'a>>;
+
+pub trait PreRequest: Send + Sync {
+    fn before<'a>(&'a self, request: &'a mut Request<Body>) -> PreRequestFuture<'a>;
+}
+
+impl<F> PreRequest for F
+where
+    F: Fn(&mut Request<Body>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> + Send + Sync,
+{
+    fn before<'a>(&'a self, request: &'a mut Request<Body>) -> PreRequestFuture<'a> {
+        let result = self(request);
+
+        Box::pin(async move { result })
+    }
+}
+
+impl HookOutput {
+    pub fn success(&self) -> bool {
+        self.status == Some(0)
+    }
+}
+
+pub async fn run(
+    command: &str,
+    directory: Option<&Path>,
+    variables: &[(&str, String)],
+) -> Result<HookOutput, Error> {
+    let mut child = Command::new("sh");
+
+    child.arg("-c").arg(command).kill_on_drop(true);
+
+    if let Some(directory) = directory {
+        child.current_dir(directory);
+    }
+
+    for (name, value) in variables {
+        child.env(name, value);
+    }
+
+    let output = child.output().await?;
+
+    Ok(HookOutput {
+        command: command.to_owned(),
+        status: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+pub async fn post_install(
+    commands: &[String],
+    installation: &Installation,
+    file_path: &Path,
+    tags: &BTreeMap<String, String>,
+) -> Result<Vec<HookOutput>, Error> {
+    let tag_names: Vec<String> = tags
+        .keys()
+        .map(|name| {
+            format!(
+                "ARCHEON_TAG_{}",
+                name.to_ascii_uppercase().replace('-', "_")
+            )
+        })
+        .collect();
+    let mut variables = vec![
+        ("ARCHEON_ARTIFACT", file_path.to_string_lossy().into_owned()),
+        ("ARCHEON_SOURCE", installation.source.to_owned()),
+        ("ARCHEON_PACKAGE", installation.package.to_owned()),
+        ("ARCHEON_VERSION", installation.version.to_owned()),
+        ("ARCHEON_CHECKSUM", installation.checksum.to_owned()),
+        ("ARCHEON_INSTALLATION", installation.id.to_string()),
+        (
+            "ARCHEON_INSTALL_REPORT",
+            serde_json::to_string(installation)?,
+        ),
+        ("ARCHEON_TAGS", serde_json::to_string(tags)?),
+    ];
+
+    variables.extend(
+        tag_names
+            .iter()
+            .map(String::as_str)
+            .zip(tags.values().map(String::to_owned)),
+    );
+    let mut outputs = Vec::with_capacity(commands.len());
+
+    for command in commands {
+        let output = run(command, None, &variables).await?;
+        let success = output.success();
+
+        outputs.push(output);
+
+        if !success {
+            break;
+        }
+    }
+
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::installer::Installer;
+    use std::path::PathBuf;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn before() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_hook =
+            |request: &mut Request<Body>| -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+                request
+                    .headers_mut()
+                    .insert("x-test-signature", "test_signature".parse()?);
+                *request.uri_mut() = format!("{}?token=test_token", request.uri()).parse()?;
+
+                Ok(())
+            };
+        let test_hook: &dyn PreRequest = &test_hook;
+        let mut test_request = Request::builder()
+            .uri("http://test-hook.invalid/test.deb")
+            .body(Body::empty())?;
+        test_hook.before(&mut test_request).await?;
+        assert_eq!(
+            test_request.uri(),
+            "http://test-hook.invalid/test.deb?token=test_token",
+        );
+        assert_eq!(test_request.headers()["x-test-signature"], "test_signature",);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn post_install() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_installation = Installation {
+            id: 3,
+            package: String::from("archeon-test-hook"),
+            version: String::from("1.0.0"),
+            source: String::from("http://test-hook.invalid/test.deb"),
+            checksum: String::from("test_checksum"),
+            installed: 1_686_000_000,
+            artifact: PathBuf::from("/tmp/archeon/test.deb"),
+            installer: Installer::default(),
+        };
+        let test_commands = [
+            String::from("echo $ARCHEON_PACKAGE $ARCHEON_VERSION $ARCHEON_INSTALLATION"),
+            String::from("echo $ARCHEON_ARTIFACT >&2; exit 3"),
+            String::from("echo skipped"),
+        ];
+        let test_outputs = super::post_install(
+            &test_commands,
+            &test_installation,
+            Path::new("/tmp/archeon/test.deb"),
+            &BTreeMap::new(),
+        )
+        .await?;
+        assert_eq!(test_outputs.len(), 2);
+        assert!(test_outputs[0].success());
+        assert_eq!(test_outputs[0].stdout, "archeon-test-hook 1.0.0 3\n");
+        assert_eq!(test_outputs[1].status, Some(3));
+        assert!(!test_outputs[1].success());
+        assert_eq!(test_outputs[1].stderr, "/tmp/archeon/test.deb\n");
+        let test_outputs = super::post_install(
+            &[String::from("printf %s \"$ARCHEON_INSTALL_REPORT\"")],
+            &test_installation,
+            Path::new("/tmp/archeon/test.deb"),
+            &BTreeMap::new(),
+        )
+        .await?;
+        assert_eq!(
+            serde_json::from_str::<Installation>(&test_outputs[0].stdout)?,
+            test_installation,
+        );
+        let test_tags = BTreeMap::from([
+            (String::from("ticket"), String::from("OPS-42")),
+            (String::from("owner-team"), String::from("platform")),
+        ]);
+        let test_outputs = super::post_install(
+            &[String::from(
+                "printf '%s %s %s' \"$ARCHEON_TAG_TICKET\" \"$ARCHEON_TAG_OWNER_TEAM\" \"$ARCHEON_TAGS\"",
+            )],
+            &test_installation,
+            Path::new("/tmp/archeon/test.deb"),
+            &test_tags,
+        )
+        .await?;
+        assert_eq!(
+            test_outputs[0].stdout,
+            "OPS-42 platform {\"owner-team\":\"platform\",\"ticket\":\"OPS-42\"}",
+        );
+        Ok(())
+    }
+}
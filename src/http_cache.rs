@@ -0,0 +1,322 @@
+use hyper::header::{HeaderMap, HeaderValue, AGE, CACHE_CONTROL, ETAG, LAST_MODIFIED, VARY};
+use hyper::header::{IF_MODIFIED_SINCE, IF_NONE_MATCH};
+
+use serde::{Deserialize, Serialize};
+
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use tokio::fs::{read, remove_file, write};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpCacheEntry {
+    pub stored: u64,
+    pub age: u64,
+    pub max_age: Option<u64>,
+    pub immutable: bool,
+    pub no_cache: bool,
+    pub no_store: bool,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub vary: Vec<(String, Option<String>)>,
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+impl HttpCacheEntry {
+    pub fn init(request_headers: &HeaderMap, response_headers: &HeaderMap) -> HttpCacheEntry {
+        let mut entry = HttpCacheEntry {
+            stored: crate::unix_timestamp(),
+            ..HttpCacheEntry::default()
+        };
+
+        for value in response_headers.get_all(CACHE_CONTROL) {
+            for directive in value.to_str().unwrap_or_default().split(',') {
+                let directive = directive.trim().to_ascii_lowercase();
+
+                match directive.split_once('=') {
+                    Some(("max-age", seconds)) => {
+                        entry.max_age = seconds.trim_matches('"').parse().ok();
+                    }
+                    Some(_) => {}
+                    None => match directive.as_str() {
+                        "immutable" => entry.immutable = true,
+                        "no-cache" => entry.no_cache = true,
+                        "no-store" => entry.no_store = true,
+                        _ => {}
+                    },
+                }
+            }
+        }
+
+        entry.age = Self::init_header(response_headers, AGE.as_str())
+            .and_then(|age| age.parse().ok())
+            .unwrap_or_default();
+        entry.etag = Self::init_header(response_headers, ETAG.as_str());
+        entry.last_modified = Self::init_header(response_headers, LAST_MODIFIED.as_str());
+
+        for value in response_headers.get_all(VARY) {
+            for name in value.to_str().unwrap_or_default().split(',') {
+                let name = name.trim().to_ascii_lowercase();
+
+                if !name.is_empty() {
+                    let request_value = Self::init_header(request_headers, &name);
+
+                    entry.vary.push((name, request_value));
+                }
+            }
+        }
+
+        entry
+    }
+
+    fn init_header(headers: &HeaderMap, name: &str) -> Option<String> {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    }
+
+    pub fn storable(&self) -> bool {
+        !self.no_store
+            && (self.immutable
+                || self.max_age.is_some()
+                || self.etag.is_some()
+                || self.last_modified.is_some())
+    }
+
+    pub fn current_age(&self, now: u64) -> u64 {
+        self.age + now.saturating_sub(self.stored)
+    }
+
+    pub fn is_fresh(&self, now: u64) -> bool {
+        if self.no_cache || self.no_store {
+            return false;
+        }
+
+        if self.immutable {
+            return true;
+        }
+
+        match self.max_age {
+            Some(max_age) => self.current_age(now) < max_age,
+            None => false,
+        }
+    }
+
+    pub fn matches(&self, request_headers: &HeaderMap) -> bool {
+        self.vary.iter().all(|(name, value)| {
+            name != "*" && Self::init_header(request_headers, name).as_ref() == value.as_ref()
+        })
+    }
+
+    pub fn revalidate(&self, request_headers: &mut HeaderMap) {
+        if let Some(etag) = self.etag.as_deref().and_then(|etag| etag.parse().ok()) {
+            request_headers.insert(IF_NONE_MATCH, etag);
+        }
+
+        if let Some(last_modified) = self
+            .last_modified
+            .as_deref()
+            .and_then(|last_modified| last_modified.parse().ok())
+        {
+            request_headers.insert(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    pub fn if_range(&self) -> Option<HeaderValue> {
+        let validator = match self.etag.as_deref() {
+            Some(etag) if !etag.starts_with("W/") => etag,
+            _ => self.last_modified.as_deref()?,
+        };
+
+        validator.parse().ok()
+    }
+
+    pub fn path(file_path: &Path) -> PathBuf {
+        let mut path = file_path.as_os_str().to_owned();
+
+        path.push(".http-cache.json");
+
+        PathBuf::from(path)
+    }
+
+    pub async fn load(file_path: &Path) -> Option<HttpCacheEntry> {
+        let contents = read(Self::path(file_path)).await.ok()?;
+
+        serde_json::from_slice(&contents).ok()
+    }
+
+    pub async fn save(&self, file_path: &Path) -> Result<(), Error> {
+        if self.storable() {
+            let contents = serde_json::to_vec(self)?;
+
+            write(Self::path(file_path), contents).await
+        } else {
+            match remove_file(Self::path(file_path)).await {
+                Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::ACCEPT_ENCODING;
+
+    fn test_headers(headers: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut test_headers = HeaderMap::new();
+        for (name, value) in headers {
+            test_headers.append(*name, HeaderValue::from_static(value));
+        }
+        test_headers
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() {
+        let test_request_headers = test_headers(&[("accept-encoding", "identity")]);
+        let test_response_headers = test_headers(&[
+            ("cache-control", "public, max-age=600, immutable"),
+            ("age", "30"),
+            ("etag", "\"test_etag\""),
+            ("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT"),
+            ("vary", "Accept-Encoding, User-Agent"),
+        ]);
+        let test_entry = HttpCacheEntry::init(&test_request_headers, &test_response_headers);
+        assert!(test_entry.stored > 0);
+        assert_eq!(test_entry.age, 30);
+        assert_eq!(test_entry.max_age, Some(600));
+        assert!(test_entry.immutable);
+        assert!(!test_entry.no_cache);
+        assert!(!test_entry.no_store);
+        assert_eq!(test_entry.etag.as_deref(), Some("\"test_etag\""));
+        assert_eq!(
+            test_entry.last_modified.as_deref(),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(
+            test_entry.vary,
+            [
+                (
+                    String::from("accept-encoding"),
+                    Some(String::from("identity"))
+                ),
+                (String::from("user-agent"), None),
+            ],
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn storable() {
+        let test_request_headers = HeaderMap::new();
+        let test_no_store = test_headers(&[("cache-control", "no-store, max-age=60")]);
+        let test_validator = test_headers(&[("etag", "\"test_etag\"")]);
+        let test_nothing = HeaderMap::new();
+        assert!(!HttpCacheEntry::init(&test_request_headers, &test_no_store).storable());
+        assert!(HttpCacheEntry::init(&test_request_headers, &test_validator).storable());
+        assert!(!HttpCacheEntry::init(&test_request_headers, &test_nothing).storable());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn is_fresh() {
+        let test_entry = HttpCacheEntry {
+            stored: 1000,
+            age: 10,
+            max_age: Some(60),
+            ..HttpCacheEntry::default()
+        };
+        assert_eq!(test_entry.current_age(1020), 30);
+        assert!(test_entry.is_fresh(1020));
+        assert!(!test_entry.is_fresh(1050));
+        let test_no_cache = HttpCacheEntry {
+            no_cache: true,
+            ..test_entry.to_owned()
+        };
+        assert!(!test_no_cache.is_fresh(1000));
+        let test_immutable = HttpCacheEntry {
+            immutable: true,
+            max_age: None,
+            ..test_entry
+        };
+        assert!(test_immutable.is_fresh(u64::MAX));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn matches() {
+        let test_entry = HttpCacheEntry {
+            vary: vec![(String::from("accept-encoding"), Some(String::from("gzip")))],
+            ..HttpCacheEntry::default()
+        };
+        let test_wildcard = HttpCacheEntry {
+            vary: vec![(String::from("*"), None)],
+            ..HttpCacheEntry::default()
+        };
+        let mut test_request_headers = HeaderMap::new();
+        assert!(!test_entry.matches(&test_request_headers));
+        test_request_headers.insert(ACCEPT_ENCODING, HeaderValue::from_static("gzip"));
+        assert!(test_entry.matches(&test_request_headers));
+        assert!(!test_wildcard.matches(&test_request_headers));
+        assert!(HttpCacheEntry::default().matches(&test_request_headers));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn revalidate() {
+        let test_entry = HttpCacheEntry {
+            etag: Some(String::from("\"test_etag\"")),
+            last_modified: Some(String::from("Wed, 21 Oct 2015 07:28:00 GMT")),
+            ..HttpCacheEntry::default()
+        };
+        let mut test_request_headers = HeaderMap::new();
+        test_entry.revalidate(&mut test_request_headers);
+        assert_eq!(test_request_headers[IF_NONE_MATCH], "\"test_etag\"");
+        assert_eq!(
+            test_request_headers[IF_MODIFIED_SINCE],
+            "Wed, 21 Oct 2015 07:28:00 GMT",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn if_range() {
+        let mut test_entry = HttpCacheEntry {
+            etag: Some(String::from("\"test_etag\"")),
+            last_modified: Some(String::from("Wed, 21 Oct 2015 07:28:00 GMT")),
+            ..HttpCacheEntry::default()
+        };
+        assert_eq!(test_entry.if_range().unwrap(), "\"test_etag\"");
+        test_entry.etag = Some(String::from("W/\"test_weak_etag\""));
+        assert_eq!(
+            test_entry.if_range().unwrap(),
+            "Wed, 21 Oct 2015 07:28:00 GMT",
+        );
+        test_entry.last_modified = None;
+        assert!(test_entry.if_range().is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn path() {
+        assert_eq!(
+            HttpCacheEntry::path(Path::new("/tmp/archeon/test.deb")),
+            Path::new("/tmp/archeon/test.deb.http-cache.json"),
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn save() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_file_path = std::env::temp_dir().join("archeon_test_http_cache_save.txt");
+        let test_entry = HttpCacheEntry {
+            max_age: Some(60),
+            ..HttpCacheEntry::default()
+        };
+        test_entry.save(&test_file_path).await?;
+        assert_eq!(
+            HttpCacheEntry::load(&test_file_path).await,
+            Some(test_entry)
+        );
+        HttpCacheEntry::default().save(&test_file_path).await?;
+        assert!(HttpCacheEntry::load(&test_file_path).await.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,844 @@
+use serde::{Deserialize, Serialize};
+
+use std::env::{split_paths, var_os};
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use tokio::process::Command;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Escalation {
+    Sudo,
+    Doas,
+    Pkexec,
+    #[default]
+    None,
+}
+
+impl Escalation {
+    pub fn program(&self) -> Option<&'static str> {
+        match self {
+            Escalation::Sudo => Some("sudo"),
+            Escalation::Doas => Some("doas"),
+            Escalation::Pkexec => Some("pkexec"),
+            Escalation::None => None,
+        }
+    }
+}
+
+impl std::str::FromStr for Escalation {
+    type Err = Error;
+
+    fn from_str(escalation: &str) -> Result<Escalation, Error> {
+        match escalation {
+            "sudo" => Ok(Escalation::Sudo),
+            "doas" => Ok(Escalation::Doas),
+            "pkexec" => Ok(Escalation::Pkexec),
+            "none" => Ok(Escalation::None),
+            _ => {
+                let error = format!("Invalid escalation '{}'!", escalation);
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Conffile {
+    Old,
+    New,
+    Default,
+    #[default]
+    Unset,
+}
+
+impl Conffile {
+    pub fn options(&self) -> &'static [&'static str] {
+        match self {
+            Conffile::Old => &["--force-confdef", "--force-confold"],
+            Conffile::New => &["--force-confnew"],
+            Conffile::Default => &["--force-confdef"],
+            Conffile::Unset => &[],
+        }
+    }
+}
+
+impl std::str::FromStr for Conffile {
+    type Err = Error;
+
+    fn from_str(conffile: &str) -> Result<Conffile, Error> {
+        match conffile {
+            "old" => Ok(Conffile::Old),
+            "new" => Ok(Conffile::New),
+            "default" => Ok(Conffile::Default),
+            "unset" => Ok(Conffile::Unset),
+            _ => {
+                let error = format!("Invalid conffile policy '{}'!", conffile);
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Dpkg,
+    Cargo,
+    Pip,
+    Rpm,
+}
+
+impl std::str::FromStr for Backend {
+    type Err = Error;
+
+    fn from_str(backend: &str) -> Result<Backend, Error> {
+        match backend {
+            "dpkg" => Ok(Backend::Dpkg),
+            "cargo" => Ok(Backend::Cargo),
+            "pip" => Ok(Backend::Pip),
+            "rpm" => Ok(Backend::Rpm),
+            _ => {
+                let error = format!("Invalid installer backend '{}'!", backend);
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Installation {
+    pub id: u64,
+    pub package: String,
+    pub version: String,
+    pub source: String,
+    pub checksum: String,
+    pub installed: u64,
+    pub artifact: PathBuf,
+    pub installer: Installer,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Installer {
+    pub escalation: Escalation,
+    pub noninteractive: bool,
+    pub conffile: Conffile,
+    pub plugin: Option<String>,
+    pub backend: Backend,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginPackage {
+    pub package: String,
+    pub version: String,
+}
+
+#[derive(Deserialize)]
+struct CrateManifest {
+    package: CratePackage,
+}
+
+#[derive(Deserialize)]
+struct CratePackage {
+    name: String,
+    version: String,
+}
+
+impl Default for Installer {
+    fn default() -> Installer {
+        Installer {
+            escalation: Escalation::None,
+            noninteractive: true,
+            conffile: Conffile::Unset,
+            plugin: None,
+            backend: Backend::Dpkg,
+        }
+    }
+}
+
+impl Installer {
+    pub async fn init(escalation: Escalation) -> Installer {
+        Installer {
+            escalation,
+            ..Installer::default()
+        }
+    }
+
+    pub async fn install_package(
+        &self,
+        directory: &Path,
+        filename: &Path,
+    ) -> Result<Output, Error> {
+        let mut command = self.launch_command("dpkg").await?;
+
+        command
+            .args(self.conffile.options())
+            .arg("--install")
+            .arg(filename)
+            .current_dir(directory)
+            .output()
+            .await
+    }
+
+    pub async fn install_crate(&self, directory: &Path, filename: &Path) -> Result<Output, Error> {
+        let source = Self::launch_extract_crate(directory, filename).await?;
+        let mut command = self.launch_command("cargo").await?;
+
+        command
+            .arg("install")
+            .arg("--path")
+            .arg(source)
+            .current_dir(directory)
+            .output()
+            .await
+    }
+
+    pub async fn install_wheel(&self, directory: &Path, filename: &Path) -> Result<Output, Error> {
+        let mut command = self.launch_command("pip").await?;
+
+        command
+            .arg("install")
+            .arg(filename)
+            .current_dir(directory)
+            .output()
+            .await
+    }
+
+    pub async fn install_rpm(&self, directory: &Path, filename: &Path) -> Result<Output, Error> {
+        let mut command = self.launch_command("rpm").await?;
+
+        command
+            .arg("--upgrade")
+            .arg(filename)
+            .current_dir(directory)
+            .output()
+            .await
+    }
+
+    pub async fn install(&self, directory: &Path, filename: &Path) -> Result<String, Error> {
+        let package = match (&self.plugin, self.backend) {
+            (Some(plugin), _) => {
+                self.inspect_plugin(plugin, directory, filename)
+                    .await?
+                    .package
+            }
+            (None, Backend::Dpkg) => Self::package_name(directory, filename).await?,
+            (None, Backend::Cargo) => Self::crate_package(directory, filename).await?.package,
+            (None, Backend::Pip) => Self::python_package(filename)?.package,
+            (None, Backend::Rpm) => Self::rpm_name(directory, filename).await?,
+        };
+        let output = match (&self.plugin, self.backend) {
+            (Some(plugin), _) => {
+                let mut command = self.launch_command(&plugin_program(plugin)).await?;
+
+                command
+                    .arg("install")
+                    .arg(filename)
+                    .current_dir(directory)
+                    .output()
+                    .await?
+            }
+            (None, Backend::Dpkg) => self.install_package(directory, filename).await?,
+            (None, Backend::Cargo) => self.install_crate(directory, filename).await?,
+            (None, Backend::Pip) => self.install_wheel(directory, filename).await?,
+            (None, Backend::Rpm) => self.install_rpm(directory, filename).await?,
+        };
+
+        Self::launch_check_status("install", &package, output).await?;
+
+        Ok(package)
+    }
+
+    pub async fn uninstall(&self, package: &str) -> Result<Output, Error> {
+        let (program, action): (String, &[&str]) = match (&self.plugin, self.backend) {
+            (Some(plugin), _) => (plugin_program(plugin), &["uninstall"]),
+            (None, Backend::Dpkg) => (String::from("dpkg"), &["--remove"]),
+            (None, Backend::Cargo) => (String::from("cargo"), &["uninstall"]),
+            (None, Backend::Pip) => (String::from("pip"), &["uninstall", "--yes"]),
+            (None, Backend::Rpm) => (String::from("rpm"), &["--erase"]),
+        };
+        let mut command = self.launch_command(&program).await?;
+        let output = command.args(action).arg(package).output().await?;
+
+        Self::launch_check_status("uninstall", package, output).await
+    }
+
+    pub async fn version(&self, directory: &Path, filename: &Path) -> Result<String, Error> {
+        match (&self.plugin, self.backend) {
+            (Some(plugin), _) => Ok(self
+                .inspect_plugin(plugin, directory, filename)
+                .await?
+                .version),
+            (None, Backend::Dpkg) => Self::package_version(directory, filename).await,
+            (None, Backend::Cargo) => Ok(Self::crate_package(directory, filename).await?.version),
+            (None, Backend::Pip) => Ok(Self::python_package(filename)?.version),
+            (None, Backend::Rpm) => Self::rpm_version(directory, filename).await,
+        }
+    }
+
+    async fn inspect_plugin(
+        &self,
+        plugin: &str,
+        directory: &Path,
+        filename: &Path,
+    ) -> Result<PluginPackage, Error> {
+        let output = Command::new(plugin_program(plugin))
+            .arg("inspect")
+            .arg(filename)
+            .current_dir(directory)
+            .output()
+            .await?;
+        let output =
+            Self::launch_check_status("inspect", &filename.to_string_lossy(), output).await?;
+
+        serde_json::from_slice(&output.stdout).map_err(|error| {
+            let error = format!("Invalid inspect output from plugin '{}': {}", plugin, error);
+
+            Error::new(ErrorKind::InvalidData, error)
+        })
+    }
+
+    pub async fn package_name(directory: &Path, filename: &Path) -> Result<String, Error> {
+        Self::launch_package_field(directory, filename, "Package").await
+    }
+
+    pub async fn package_version(directory: &Path, filename: &Path) -> Result<String, Error> {
+        Self::launch_package_field(directory, filename, "Version").await
+    }
+
+    pub async fn rpm_name(directory: &Path, filename: &Path) -> Result<String, Error> {
+        Self::launch_rpm_query(directory, filename, "%{NAME}").await
+    }
+
+    pub async fn rpm_version(directory: &Path, filename: &Path) -> Result<String, Error> {
+        Self::launch_rpm_query(directory, filename, "%{VERSION}-%{RELEASE}").await
+    }
+
+    pub async fn crate_package(directory: &Path, filename: &Path) -> Result<PluginPackage, Error> {
+        let source = Self::launch_extract_crate(directory, filename).await?;
+        let manifest = tokio::fs::read_to_string(source.join("Cargo.toml")).await?;
+        let manifest: CrateManifest = toml::from_str(&manifest).map_err(|error| {
+            let error = format!("Invalid Cargo.toml in {:?}: {}", filename, error);
+
+            Error::new(ErrorKind::InvalidData, error)
+        })?;
+
+        Ok(PluginPackage {
+            package: manifest.package.name,
+            version: manifest.package.version,
+        })
+    }
+
+    pub fn python_package(filename: &Path) -> Result<PluginPackage, Error> {
+        let name = filename.to_string_lossy();
+        let parts = match name.strip_suffix(".whl") {
+            Some(stem) => stem
+                .splitn(3, '-')
+                .map(str::to_owned)
+                .collect::<Vec<String>>(),
+            None => [".tar.gz", ".zip"]
+                .iter()
+                .find_map(|extension| name.strip_suffix(extension))
+                .and_then(|stem| stem.rsplit_once('-'))
+                .map(|(package, version)| vec![package.to_owned(), version.to_owned()])
+                .unwrap_or_default(),
+        };
+
+        match parts.as_slice() {
+            [package, version, ..] if !package.is_empty() && !version.is_empty() => {
+                Ok(PluginPackage {
+                    package: package.replace('_', "-"),
+                    version: version.to_owned(),
+                })
+            }
+            _ => {
+                let error = format!("{:?} is not a wheel or sdist!", filename);
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+
+    async fn launch_extract_crate(directory: &Path, filename: &Path) -> Result<PathBuf, Error> {
+        let stem = match filename.to_string_lossy().strip_suffix(".crate") {
+            Some(stem) if !stem.is_empty() => stem.to_owned(),
+            _ => {
+                let error = format!("{:?} is not a .crate file!", filename);
+
+                return Err(Error::new(ErrorKind::InvalidInput, error));
+            }
+        };
+        let output = Command::new("tar")
+            .arg("--extract")
+            .arg("--gzip")
+            .arg("--file")
+            .arg(filename)
+            .current_dir(directory)
+            .output()
+            .await?;
+
+        Self::launch_check_status("extract", &filename.to_string_lossy(), output).await?;
+
+        Ok(directory.join(stem))
+    }
+
+    async fn launch_package_field(
+        directory: &Path,
+        filename: &Path,
+        field: &str,
+    ) -> Result<String, Error> {
+        let output = Command::new("dpkg-deb")
+            .arg("--field")
+            .arg(filename)
+            .arg(field)
+            .current_dir(directory)
+            .output()
+            .await?;
+        let output =
+            Self::launch_check_status("inspect", &filename.to_string_lossy(), output).await?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+        if value.is_empty() {
+            let error = format!("No '{}' field found in {:?}!", field, filename);
+
+            return Err(Error::new(ErrorKind::InvalidData, error));
+        }
+
+        Ok(value)
+    }
+
+    async fn launch_rpm_query(
+        directory: &Path,
+        filename: &Path,
+        format: &str,
+    ) -> Result<String, Error> {
+        let output = Command::new("rpm")
+            .arg("--query")
+            .arg("--package")
+            .arg("--queryformat")
+            .arg(format)
+            .arg(filename)
+            .current_dir(directory)
+            .output()
+            .await?;
+        let output =
+            Self::launch_check_status("inspect", &filename.to_string_lossy(), output).await?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+        if value.is_empty() {
+            let error = format!("No '{}' found in {:?}!", format, filename);
+
+            return Err(Error::new(ErrorKind::InvalidData, error));
+        }
+
+        Ok(value)
+    }
+
+    async fn launch_check_status(
+        action: &str,
+        package: &str,
+        output: Output,
+    ) -> Result<Output, Error> {
+        if output.status.success() {
+            Ok(output)
+        } else {
+            let error = format!(
+                "Could not {} '{}' ({}): {}",
+                action,
+                package,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim(),
+            );
+
+            Err(Error::other(error))
+        }
+    }
+
+    async fn launch_command(&self, program: &str) -> Result<Command, Error> {
+        let environment = self.launch_environment().await;
+
+        match self.escalation.program() {
+            Some(escalation_program) => {
+                if Self::launch_find_program(escalation_program)
+                    .await
+                    .is_none()
+                {
+                    let error = format!(
+                        "Escalation with '{}' requested, but it was not found in PATH!",
+                        escalation_program,
+                    );
+
+                    return Err(Error::new(ErrorKind::NotFound, error));
+                }
+
+                let mut command = Command::new(escalation_program);
+
+                if self.escalation == Escalation::Sudo {
+                    command.arg("--non-interactive");
+                }
+
+                if !environment.is_empty() {
+                    command.arg("env");
+
+                    for (key, value) in environment {
+                        command.arg(format!("{}={}", key, value));
+                    }
+                }
+
+                command.arg(program);
+
+                Ok(command)
+            }
+            None => {
+                let mut command = Command::new(program);
+
+                command.envs(environment);
+
+                Ok(command)
+            }
+        }
+    }
+
+    async fn launch_environment(&self) -> Vec<(&'static str, &'static str)> {
+        let mut environment = Vec::with_capacity(2);
+
+        if self.noninteractive {
+            environment.push(("DEBIAN_FRONTEND", "noninteractive"));
+            environment.push(("DEBCONF_NONINTERACTIVE_SEEN", "true"));
+        }
+
+        environment
+    }
+
+    async fn launch_find_program(program: &str) -> Option<PathBuf> {
+        let paths = var_os("PATH")?;
+
+        split_paths(&paths)
+            .map(|path| path.join(program))
+            .find(|candidate| candidate.is_file())
+    }
+}
+
+pub fn plugin_program(plugin: &str) -> String {
+    match plugin.contains('/') {
+        true => plugin.to_owned(),
+        false => format!("archeon-installer-{}", plugin),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() {
+        let test_installer = Installer::init(Escalation::Doas).await;
+        assert_eq!(test_installer.escalation, Escalation::Doas);
+        assert!(test_installer.noninteractive);
+        assert_eq!(test_installer.conffile, Conffile::Unset);
+        assert_eq!(Installer::default().escalation, Escalation::None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn conffile_options() {
+        assert_eq!(
+            Conffile::Old.options(),
+            ["--force-confdef", "--force-confold"],
+        );
+        assert_eq!(Conffile::New.options(), ["--force-confnew"]);
+        assert_eq!(Conffile::Default.options(), ["--force-confdef"]);
+        assert!(Conffile::Unset.options().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn escalation_program() {
+        assert_eq!(Escalation::Sudo.program(), Some("sudo"));
+        assert_eq!(Escalation::Doas.program(), Some("doas"));
+        assert_eq!(Escalation::Pkexec.program(), Some("pkexec"));
+        assert_eq!(Escalation::None.program(), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn from_str() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!("doas".parse::<Escalation>()?, Escalation::Doas);
+        assert_eq!("none".parse::<Escalation>()?, Escalation::None);
+        assert!("su".parse::<Escalation>().is_err());
+        assert_eq!("old".parse::<Conffile>()?, Conffile::Old);
+        assert!("keep".parse::<Conffile>().is_err());
+        assert_eq!("cargo".parse::<Backend>()?, Backend::Cargo);
+        assert_eq!("pip".parse::<Backend>()?, Backend::Pip);
+        assert_eq!("rpm".parse::<Backend>()?, Backend::Rpm);
+        assert!("brew".parse::<Backend>().is_err());
+        Ok(())
+    }
+
+    async fn build_test_package(
+        name: &str,
+    ) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        let test_directory = std::env::temp_dir().join("archeon").join(name);
+        let test_control_directory = test_directory.join("DEBIAN");
+        tokio::fs::create_dir_all(&test_control_directory).await?;
+        let test_control = format!(
+            "Package: {}\nVersion: 1.0.0\nArchitecture: all\nMaintainer: archeon <archeon@localhost>\nDescription: test package\n",
+            name,
+        );
+        tokio::fs::write(test_control_directory.join("control"), test_control).await?;
+        let test_package = std::env::temp_dir()
+            .join("archeon")
+            .join(format!("{}.deb", name));
+        let test_output = Command::new("dpkg-deb")
+            .arg("--build")
+            .arg(&test_directory)
+            .arg(&test_package)
+            .output()
+            .await?;
+        assert!(test_output.status.success());
+        tokio::fs::remove_dir_all(&test_directory).await?;
+        Ok(test_package)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn package_name() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_package = build_test_package("archeon-test-package-name").await?;
+        let test_directory = test_package.parent().unwrap();
+        let test_filename = Path::new(test_package.file_name().unwrap());
+        let test_package_name = Installer::package_name(test_directory, test_filename).await?;
+        let test_package_version =
+            Installer::package_version(test_directory, test_filename).await?;
+        assert_eq!(test_package_name, "archeon-test-package-name");
+        assert_eq!(test_package_version, "1.0.0");
+        tokio::fs::remove_file(&test_package).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn package_name_invalid() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_directory = std::env::temp_dir().join("archeon");
+        tokio::fs::create_dir_all(&test_directory).await?;
+        tokio::fs::write(test_directory.join("test_not_a_package.deb"), "test").await?;
+        let test_error =
+            Installer::package_name(&test_directory, Path::new("test_not_a_package.deb")).await;
+        assert!(test_error.is_err());
+        tokio::fs::remove_file(test_directory.join("test_not_a_package.deb")).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn uninstall() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_installer = Installer::default();
+        let test_output = test_installer
+            .uninstall("archeon-test-package-not-installed")
+            .await?;
+        assert!(test_output.status.success());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn plugin() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let test_directory = std::env::temp_dir().join("archeon_test_installer_plugin");
+        let test_plugin = test_directory.join("archeon-installer-test");
+        tokio::fs::create_dir_all(&test_directory).await?;
+        tokio::fs::write(
+            &test_plugin,
+            concat!(
+                "#!/bin/sh\n",
+                "case \"$1\" in\n",
+                "  inspect) echo '{\"package\":\"test-plugin-package\",\"version\":\"2.1.0\"}' ;;\n",
+                "  install) test -f \"$2\" ;;\n",
+                "  uninstall) test \"$2\" = test-plugin-package ;;\n",
+                "  *) exit 1 ;;\n",
+                "esac\n",
+            ),
+        )
+        .await?;
+        tokio::fs::set_permissions(&test_plugin, std::fs::Permissions::from_mode(0o755)).await?;
+        tokio::fs::write(test_directory.join("test.pkg"), "test").await?;
+        let test_installer = Installer {
+            plugin: Some(test_plugin.to_string_lossy().into_owned()),
+            ..Installer::default()
+        };
+        let test_filename = Path::new("test.pkg");
+        assert_eq!(
+            test_installer
+                .install(&test_directory, test_filename)
+                .await?,
+            "test-plugin-package",
+        );
+        assert_eq!(
+            test_installer
+                .version(&test_directory, test_filename)
+                .await?,
+            "2.1.0",
+        );
+        assert!(test_installer
+            .install(&test_directory, Path::new("missing.pkg"))
+            .await
+            .is_err());
+        assert!(test_installer
+            .uninstall("test-plugin-package")
+            .await?
+            .status
+            .success());
+        assert!(test_installer.uninstall("other-package").await.is_err());
+        tokio::fs::remove_dir_all(&test_directory).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn crate_package() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_directory = std::env::temp_dir().join("archeon_test_crate_package");
+        let test_source = test_directory.join("test-crate-1.2.0");
+        tokio::fs::create_dir_all(&test_source).await?;
+        tokio::fs::write(
+            test_source.join("Cargo.toml"),
+            "[package]\nname = \"test-crate\"\nversion = \"1.2.0\"\n",
+        )
+        .await?;
+        let test_output = Command::new("tar")
+            .arg("--create")
+            .arg("--gzip")
+            .arg("--file")
+            .arg("test-crate-1.2.0.crate")
+            .arg("test-crate-1.2.0")
+            .current_dir(&test_directory)
+            .output()
+            .await?;
+        assert!(test_output.status.success());
+        tokio::fs::remove_dir_all(&test_source).await?;
+        let test_installer = Installer {
+            backend: Backend::Cargo,
+            ..Installer::default()
+        };
+        let test_filename = Path::new("test-crate-1.2.0.crate");
+        let test_package = Installer::crate_package(&test_directory, test_filename).await?;
+        assert_eq!(test_package.package, "test-crate");
+        assert_eq!(
+            test_installer
+                .version(&test_directory, test_filename)
+                .await?,
+            "1.2.0",
+        );
+        assert!(test_source.join("Cargo.toml").is_file());
+        assert_eq!(
+            Installer::crate_package(&test_directory, Path::new("test.deb"))
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidInput,
+        );
+        tokio::fs::remove_dir_all(&test_directory).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn python_package() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_package = Installer::python_package(Path::new(
+            "test_package-2.1.0-cp311-cp311-manylinux_2_17_x86_64.whl",
+        ))?;
+        assert_eq!(test_package.package, "test-package");
+        assert_eq!(test_package.version, "2.1.0");
+        let test_package = Installer::python_package(Path::new("test-package-1.0.0rc1.tar.gz"))?;
+        assert_eq!(test_package.package, "test-package");
+        assert_eq!(test_package.version, "1.0.0rc1");
+        assert!(Installer::python_package(Path::new("test.deb")).is_err());
+        assert!(Installer::python_package(Path::new("test.whl")).is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn plugin_program() {
+        assert_eq!(super::plugin_program("rpm"), "archeon-installer-rpm");
+        assert_eq!(
+            super::plugin_program("/opt/plugins/internal"),
+            "/opt/plugins/internal",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_check_status() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_success = Command::new("true").output().await?;
+        let test_failure = Command::new("false").output().await?;
+        assert!(
+            Installer::launch_check_status("install", "test", test_success)
+                .await
+                .is_ok()
+        );
+        assert!(
+            Installer::launch_check_status("install", "test", test_failure)
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_command() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_installer = Installer::init(Escalation::None).await;
+        let test_command = test_installer.launch_command("dpkg").await?;
+        let test_envs: Vec<_> = test_command.as_std().get_envs().collect();
+        assert_eq!(test_command.as_std().get_program(), "dpkg");
+        assert_eq!(test_command.as_std().get_args().count(), 0);
+        assert!(test_envs.contains(&(
+            std::ffi::OsStr::new("DEBIAN_FRONTEND"),
+            Some(std::ffi::OsStr::new("noninteractive")),
+        )));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_environment() {
+        let mut test_installer = Installer::default();
+        assert_eq!(
+            test_installer.launch_environment().await,
+            [
+                ("DEBIAN_FRONTEND", "noninteractive"),
+                ("DEBCONF_NONINTERACTIVE_SEEN", "true"),
+            ],
+        );
+        test_installer.noninteractive = false;
+        assert!(test_installer.launch_environment().await.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_command_escalated() {
+        let test_installer = Installer::init(Escalation::Sudo).await;
+        match test_installer.launch_command("dpkg").await {
+            Ok(test_command) => {
+                let test_args: Vec<_> = test_command.as_std().get_args().collect();
+                assert_eq!(test_command.as_std().get_program(), "sudo");
+                assert_eq!(
+                    test_args,
+                    [
+                        "--non-interactive",
+                        "env",
+                        "DEBIAN_FRONTEND=noninteractive",
+                        "DEBCONF_NONINTERACTIVE_SEEN=true",
+                        "dpkg",
+                    ],
+                );
+            }
+            Err(test_error) => {
+                assert_eq!(test_error.kind(), ErrorKind::NotFound);
+                assert!(test_error.to_string().contains("'sudo'"));
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_find_program() {
+        assert!(Installer::launch_find_program("sh").await.is_some());
+        assert!(Installer::launch_find_program("archeon_missing_program")
+            .await
+            .is_none());
+    }
+}
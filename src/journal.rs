@@ -0,0 +1,375 @@
+use hyper::Uri;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::env::var_os;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::daemon::{Job, JobStatus};
+use crate::lock::FileLock;
+
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+    PRAGMA journal_mode = WAL;
+    CREATE TABLE IF NOT EXISTS transfers (
+        id INTEGER PRIMARY KEY,
+        status TEXT NOT NULL,
+        url TEXT NOT NULL,
+        updated INTEGER NOT NULL,
+        job TEXT NOT NULL
+    );
+";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HistoryFilter {
+    pub host: Option<String>,
+    pub status: Option<JobStatus>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl HistoryFilter {
+    pub fn matches_tags(&self, tags: &BTreeMap<String, String>) -> bool {
+        self.tags
+            .iter()
+            .all(|(name, value)| tags.get(name) == Some(value))
+    }
+
+    pub fn matches_host(&self, url: &str) -> bool {
+        let host = match &self.host {
+            Some(host) => host,
+            None => return true,
+        };
+
+        url.parse::<Uri>()
+            .ok()
+            .and_then(|uri| {
+                uri.host()
+                    .map(|uri_host| uri_host.eq_ignore_ascii_case(host))
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub updated: u64,
+    pub job: Job,
+}
+
+#[derive(Clone)]
+pub struct Journal {
+    pub path: PathBuf,
+    connection: Arc<Mutex<Connection>>,
+    lock: Option<Arc<FileLock>>,
+}
+
+pub fn default_journal_path() -> PathBuf {
+    match (var_os("XDG_DATA_HOME"), var_os("HOME")) {
+        (Some(data_dir), _) => PathBuf::from(data_dir).join("archeon/journal.sqlite3"),
+        (None, Some(home)) => PathBuf::from(home).join(".local/share/archeon/journal.sqlite3"),
+        (None, None) => PathBuf::from("archeon-journal.sqlite3"),
+    }
+}
+
+impl Journal {
+    pub fn open(path: &Path) -> Result<Journal, Error> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let connection = Connection::open(path).map_err(sqlite_error)?;
+
+        connection
+            .busy_timeout(BUSY_TIMEOUT)
+            .map_err(sqlite_error)?;
+        connection.execute_batch(SCHEMA).map_err(sqlite_error)?;
+
+        Ok(Journal {
+            path: path.to_owned(),
+            connection: Arc::new(Mutex::new(connection)),
+            lock: None,
+        })
+    }
+
+    pub fn open_exclusive(path: &Path) -> Result<Journal, Error> {
+        let mut journal = Self::open(path)?;
+
+        journal.lock = Some(Arc::new(FileLock::try_exclusive(path)?));
+
+        Ok(journal)
+    }
+
+    pub fn is_exclusive(&self) -> bool {
+        self.lock.is_some()
+    }
+
+    pub fn record(&self, job: &Job) -> Result<(), Error> {
+        let id =
+            i64::try_from(job.id).map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+        let contents = serde_json::to_string(job)?;
+
+        self.lock()
+            .execute(
+                "INSERT INTO transfers (id, status, url, updated, job) VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (id) DO UPDATE SET
+                    status = excluded.status,
+                    url = excluded.url,
+                    updated = excluded.updated,
+                    job = excluded.job",
+                params![
+                    id,
+                    job.status.to_string(),
+                    job.artifact.url,
+                    crate::unix_timestamp() as i64,
+                    contents,
+                ],
+            )
+            .map_err(sqlite_error)?;
+
+        Ok(())
+    }
+
+    pub fn jobs(&self) -> Result<Vec<Job>, Error> {
+        let history = self.history(&HistoryFilter::default())?;
+
+        Ok(history.into_iter().map(|entry| entry.job).collect())
+    }
+
+    pub fn history(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>, Error> {
+        let connection = self.lock();
+        let mut stmt = connection
+            .prepare(
+                "SELECT updated, job FROM transfers
+                 WHERE updated >= ?1 AND updated <= ?2 AND (?3 = '' OR status = ?3)
+                 ORDER BY id",
+            )
+            .map_err(sqlite_error)?;
+        let status = filter
+            .status
+            .map(|status| status.to_string())
+            .unwrap_or_default();
+        let rows = stmt
+            .query_map(
+                params![
+                    filter.since.unwrap_or_default().min(i64::MAX as u64) as i64,
+                    filter.until.unwrap_or(u64::MAX).min(i64::MAX as u64) as i64,
+                    status,
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(sqlite_error)?;
+        let mut history = Vec::with_capacity(10);
+
+        for row in rows {
+            let (updated, job): (i64, String) = row.map_err(sqlite_error)?;
+            let entry = Self::read_entry(updated, &job)?;
+
+            if filter.matches_host(&entry.job.artifact.url)
+                && filter.matches_tags(&entry.job.artifact.tags)
+            {
+                history.push(entry);
+            }
+        }
+
+        Ok(history)
+    }
+
+    pub fn entry(&self, id: u64) -> Result<Option<HistoryEntry>, Error> {
+        let id = i64::try_from(id).map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+        let row: Option<(i64, String)> = self
+            .lock()
+            .query_row(
+                "SELECT updated, job FROM transfers WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(sqlite_error)?;
+
+        match row {
+            Some((updated, job)) => Self::read_entry(updated, &job).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn read_entry(updated: i64, job: &str) -> Result<HistoryEntry, Error> {
+        let job =
+            serde_json::from_str(job).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+        Ok(HistoryEntry {
+            updated: updated.max(0) as u64,
+            job,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        self.connection
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn restore(&self) -> Result<Vec<Job>, Error> {
+        let mut jobs = self.jobs()?;
+
+        for job in jobs.iter_mut() {
+            if job.status == JobStatus::Running {
+                job.status = JobStatus::Queued;
+                job.progress = None;
+
+                self.record(job)?;
+            }
+        }
+
+        Ok(jobs)
+    }
+}
+
+fn sqlite_error(error: rusqlite::Error) -> Error {
+    Error::other(format!("SQLite error: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch::{BatchArtifact, BatchResult};
+
+    fn test_job(id: u64, status: JobStatus) -> Job {
+        Job {
+            id,
+            status,
+            artifact: BatchArtifact {
+                url: format!("http://test.invalid/test_journal_{}.txt", id),
+                ..BatchArtifact::default()
+            },
+            result: None,
+            progress: None,
+            coalesced: None,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn open_exclusive() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_journal_exclusive");
+        let test_path = test_dir.join("journal.sqlite3");
+        let test_journal = Journal::open_exclusive(&test_path)?;
+        assert!(test_journal.is_exclusive());
+        assert!(Journal::open_exclusive(&test_path).is_err());
+        let test_reader = Journal::open(&test_path)?;
+        assert!(!test_reader.is_exclusive());
+        test_journal.record(&test_job(0, JobStatus::Queued))?;
+        assert_eq!(test_reader.jobs()?.len(), 1);
+        drop(test_journal);
+        assert!(Journal::open_exclusive(&test_path)?.is_exclusive());
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn record() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_journal_record");
+        let test_journal = Journal::open(&test_dir.join("journal.sqlite3"))?;
+        let mut test_completed = test_job(0, JobStatus::Running);
+        test_journal.record(&test_completed)?;
+        test_journal.record(&test_job(1, JobStatus::Queued))?;
+        test_completed.status = JobStatus::Completed;
+        test_completed.result = Some(BatchResult {
+            url: test_completed.artifact.url.to_owned(),
+            file_path: Some(PathBuf::from("/tmp/test_journal_0.txt")),
+            installation: None,
+            error: None,
+            error_kind: None,
+            hooks: Vec::new(),
+        });
+        test_journal.record(&test_completed)?;
+        let test_jobs = Journal::open(&test_journal.path)?.jobs()?;
+        assert_eq!(test_jobs.len(), 2);
+        assert_eq!(test_jobs[0], test_completed);
+        assert_eq!(test_jobs[1].status, JobStatus::Queued);
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn history() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_journal_history");
+        let test_journal = Journal::open(&test_dir.join("journal.sqlite3"))?;
+        let mut test_other = test_job(2, JobStatus::Completed);
+        test_other.artifact.url = String::from("http://other.invalid/test.txt");
+        test_other
+            .artifact
+            .tags
+            .insert(String::from("ticket"), String::from("OPS-42"));
+        test_journal.record(&test_job(0, JobStatus::Completed))?;
+        test_journal.record(&test_job(1, JobStatus::Failed))?;
+        test_journal.record(&test_other)?;
+        let test_now = crate::unix_timestamp();
+        assert_eq!(test_journal.history(&HistoryFilter::default())?.len(), 3);
+        let test_completed = test_journal.history(&HistoryFilter {
+            status: Some(JobStatus::Completed),
+            ..HistoryFilter::default()
+        })?;
+        assert_eq!(test_completed.len(), 2);
+        let test_host = test_journal.history(&HistoryFilter {
+            host: Some(String::from("TEST.invalid")),
+            status: Some(JobStatus::Completed),
+            ..HistoryFilter::default()
+        })?;
+        assert_eq!(test_host.len(), 1);
+        assert_eq!(test_host[0].job.id, 0);
+        let test_tagged = test_journal.history(&HistoryFilter {
+            tags: BTreeMap::from([(String::from("ticket"), String::from("OPS-42"))]),
+            ..HistoryFilter::default()
+        })?;
+        assert_eq!(test_tagged.len(), 1);
+        assert_eq!(test_tagged[0].job.artifact.tags["ticket"], "OPS-42");
+        assert!(test_host[0].updated <= test_now);
+        assert!(test_journal
+            .history(&HistoryFilter {
+                since: Some(test_now + 60),
+                ..HistoryFilter::default()
+            })?
+            .is_empty());
+        assert_eq!(
+            test_journal
+                .history(&HistoryFilter {
+                    until: Some(test_now + 60),
+                    ..HistoryFilter::default()
+                })?
+                .len(),
+            3,
+        );
+        assert_eq!(
+            test_journal.entry(1)?.unwrap().job.status,
+            JobStatus::Failed
+        );
+        assert!(test_journal.entry(3)?.is_none());
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn restore() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_journal_restore");
+        let test_journal = Journal::open(&test_dir.join("journal.sqlite3"))?;
+        test_journal.record(&test_job(0, JobStatus::Failed))?;
+        test_journal.record(&test_job(1, JobStatus::Running))?;
+        let test_jobs = test_journal.restore()?;
+        assert_eq!(test_jobs[0].status, JobStatus::Failed);
+        assert_eq!(test_jobs[1].status, JobStatus::Queued);
+        assert_eq!(test_journal.jobs()?[1].status, JobStatus::Queued);
+        assert!(Journal::open(Path::new("archeon_test\0journal.sqlite3")).is_err());
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+}
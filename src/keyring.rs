@@ -0,0 +1,116 @@
+use keyring::Entry;
+
+use std::io::{Error, ErrorKind};
+
+use tokio::task::spawn_blocking;
+
+use crate::credentials::{Credential, CredentialFuture, CredentialStore};
+
+pub const KEYRING_SERVICE: &str = "archeon";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyringStore {
+    pub service: String,
+}
+
+impl Default for KeyringStore {
+    fn default() -> KeyringStore {
+        KeyringStore {
+            service: String::from(KEYRING_SERVICE),
+        }
+    }
+}
+
+impl KeyringStore {
+    pub async fn token(&self, host: &str) -> Result<Option<String>, Error> {
+        self.launch_entry(host, |entry| match entry.get_password() {
+            Ok(token) => Ok(Some(token)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(error) => Err(error),
+        })
+        .await
+    }
+
+    pub async fn set_token(&self, host: &str, token: &str) -> Result<(), Error> {
+        let token = token.to_owned();
+
+        self.launch_entry(host, move |entry| entry.set_password(&token))
+            .await
+    }
+
+    pub async fn delete_token(&self, host: &str) -> Result<bool, Error> {
+        self.launch_entry(host, |entry| match entry.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(error) => Err(error),
+        })
+        .await
+    }
+
+    async fn launch_entry<T, F>(&self, host: &str, action: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(Entry) -> Result<T, keyring::Error> + Send + 'static,
+    {
+        let service = self.service.to_owned();
+        let host = host.to_ascii_lowercase();
+        let result = spawn_blocking(move || {
+            let entry = Entry::new(&service, &host)?;
+
+            action(entry)
+        })
+        .await
+        .map_err(Error::other)?;
+
+        result.map_err(|error| {
+            let error = format!("Keyring access failed: {}!", error);
+
+            Error::new(ErrorKind::PermissionDenied, error)
+        })
+    }
+}
+
+impl CredentialStore for KeyringStore {
+    fn get<'a>(&'a self, host: &'a str) -> CredentialFuture<'a> {
+        Box::pin(async move {
+            match self.token(host).await {
+                Ok(token) => Ok(token.map(Credential::Token)),
+                Err(error) => {
+                    eprintln!("{}", error);
+
+                    Ok(None)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn default() {
+        assert_eq!(KeyringStore::default().service, "archeon");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_entry() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let test_store = KeyringStore {
+            service: String::from("archeon_test_keyring"),
+        };
+        let test_token = test_store
+            .launch_entry("Mirror.Test", |entry| {
+                entry.set_password("test_token")?;
+                let token = entry.get_password()?;
+                entry.delete_credential()?;
+                Ok(token)
+            })
+            .await?;
+        assert_eq!(test_token, "test_token");
+        assert_eq!(test_store.token("mirror.test").await?, None);
+        assert!(!test_store.delete_token("mirror.test").await?);
+        Ok(())
+    }
+}
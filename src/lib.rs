@@ -1,27 +1,2143 @@
-mod transfer;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod audit;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod auth;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod aws;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bench;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cache;
+pub mod checksum;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod chunk;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod circuit;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod config;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod control;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cookies;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod credential_helper;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod credentials;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod daemon;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod durability;
+pub mod error;
+#[cfg(target_arch = "wasm32")]
+pub mod fetch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod filter;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod grpc;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod har;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod hook;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod http_cache;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod installer;
+#[cfg(all(not(target_arch = "wasm32"), feature = "journal"))]
+pub mod journal;
+#[cfg(all(not(target_arch = "wasm32"), feature = "keyring"))]
+pub mod keyring;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod limits;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lock;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod manifest;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod mirror;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod netrc;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod normalize;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod overwrite;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod partial;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod presign;
+pub mod progress;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod proxy;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod recipe;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod redirect;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod repair;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod resolver;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod resume;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod retry;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sbom;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod scheduler;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod shutdown;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod source;
+pub mod stream;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tee;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod template;
+#[cfg(all(not(target_arch = "wasm32"), any(test, feature = "testing")))]
+pub mod testing;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod transfer;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod transport;
+#[cfg(all(not(target_arch = "wasm32"), feature = "tui"))]
+pub mod tui;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod verify;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod wire;
 
-use crate::transfer::Transfer;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::audit::{AuditAction, AuditEntry, AuditLog};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::auth::{AuthChain, Authenticate, OAuth2};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::aws::AwsSigV4;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::batch::{Batch, BatchArtifact, BatchReport, BatchResult};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::cache::{Cache, Retention};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::checksum::Algorithm;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::circuit::CircuitBreaker;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::config::Profile;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::cookies::CookieJar;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::credential_helper::CredentialHelper;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::credentials::StoreAuth;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::ArcheonError;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::har::HarRecorder;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::hook::PreRequest;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::http_cache::HttpCacheEntry;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::installer::Installation;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::limits::{BufferLimit, HostLimit, HostLimiter};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::manifest::{Manifest, Outdated, Pin};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::mirror::{default_ranking_path, MirrorRanking};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::netrc::{default_netrc_path, NetrcAuth};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::overwrite::OverwritePolicy;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::presign::RefreshUrl;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::progress::BatchProgress;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::recipe::{Recipe, RecipeReport};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::repair::{PieceHashes, RepairSummary};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::retry::{NoRetry, RetryPolicy};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::sbom::Sbom;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::scheduler::Scheduler;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::shutdown::Shutdown;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::source::index::DirectoryIndex;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transfer::{HttpClient, Transfer};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::verify::Verification;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::wire::WireLog;
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::BTreeMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{Error, ErrorKind};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use std::process::Output;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(not(target_arch = "wasm32"))]
 pub struct Archeon {
     pub ignited: bool,
+    pub manifest: Manifest,
+    pub audit: Option<AuditLog>,
+    pub cache: Option<Cache>,
+    pub scheduler: Scheduler,
+    pub limiter: HostLimiter,
+    pub buffer: BufferLimit,
+    pub mirrors: Vec<String>,
+    pub client: HttpClient,
+    pub cookies: Option<CookieJar>,
+    pub retry: Arc<dyn RetryPolicy>,
+    pub circuit: CircuitBreaker,
+    pub progress: BatchProgress,
+    pub profile: Profile,
+    pub refresh_url: Option<Arc<dyn RefreshUrl>>,
+    pub auth: Option<Arc<dyn Authenticate>>,
+    pub pre_request: Option<Arc<dyn PreRequest>>,
+    pub shutdown: Shutdown,
+    pub wire: WireLog,
+    pub har: Option<HarRecorder>,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl Archeon {
     pub async fn ignite() -> Archeon {
-        let _transfer = Transfer::init("http://some_test_authority/with/path/and/query").await;
-        // transfer.launch().await;
+        Archeon {
+            ignited: true,
+            manifest: Manifest::default(),
+            audit: None,
+            cache: None,
+            scheduler: Scheduler::default(),
+            limiter: HostLimiter::default(),
+            buffer: BufferLimit::default(),
+            mirrors: Vec::new(),
+            client: Transfer::init_client(),
+            cookies: None,
+            retry: Arc::new(NoRetry),
+            circuit: CircuitBreaker::default(),
+            progress: BatchProgress::default(),
+            profile: Profile::default(),
+            refresh_url: None,
+            auth: None,
+            pre_request: None,
+            shutdown: Shutdown::default(),
+            wire: WireLog::default(),
+            har: None,
+        }
+    }
+
+    pub async fn ignite_with_client(client: HttpClient) -> Archeon {
+        let mut archeon = Self::ignite().await;
+
+        archeon.client = client;
+
+        archeon
+    }
+
+    pub async fn ignite_with_manifest(path: &Path) -> Result<Archeon, Error> {
+        let mut archeon = Self::ignite().await;
+
+        archeon.manifest = Manifest::init(path).await?;
+
+        Ok(archeon)
+    }
+
+    pub fn configure(&mut self, profile: Profile) -> Result<(), Error> {
+        self.client = Transfer::init_client_with_options(profile.client_options()?);
+
+        let mut auth: Vec<Arc<dyn Authenticate>> = Vec::new();
+
+        if let Some(oauth2) = &profile.oauth2 {
+            auth.push(Arc::new(OAuth2::init(
+                oauth2.to_owned(),
+                self.client.to_owned(),
+            )));
+        }
+
+        if let Some(aws) = &profile.aws {
+            auth.push(Arc::new(AwsSigV4::init(aws, self.client.to_owned())));
+        }
+
+        #[cfg(feature = "keyring")]
+        if profile.keyring.unwrap_or_default() {
+            auth.push(Arc::new(StoreAuth::init(Arc::new(
+                crate::keyring::KeyringStore::default(),
+            ))));
+        }
+
+        if let Some(helper) = &profile.credential_helper {
+            auth.push(Arc::new(StoreAuth::init(Arc::new(CredentialHelper::init(
+                helper,
+            )?))));
+        }
+
+        if profile.netrc.unwrap_or(true) {
+            if let Some(path) = default_netrc_path() {
+                auth.push(Arc::new(NetrcAuth::init(path)));
+            }
+        }
+
+        self.auth = match auth.len() {
+            0 | 1 => auth.pop(),
+            _ => Some(Arc::new(AuthChain {
+                authenticators: auth,
+            })),
+        };
+
+        if let Some(concurrency) = profile.concurrency {
+            self.limiter = HostLimiter::init(HostLimit {
+                max_connections: Some(concurrency),
+                bytes_per_second: None,
+            });
+        }
+
+        if let Some(buffer_limit) = profile.buffer_limit {
+            self.buffer = BufferLimit::init(buffer_limit);
+        }
+
+        self.mirrors = match profile.mirrors.is_empty() {
+            true => Vec::new(),
+            false => std::fs::read(default_ranking_path())
+                .ok()
+                .and_then(|contents| serde_json::from_slice::<MirrorRanking>(&contents).ok())
+                .unwrap_or_default()
+                .order(&profile.mirrors),
+        };
+        self.progress.set_quiet(profile.quiet.unwrap_or_default());
+        self.wire
+            .set_enabled(profile.debug_http.unwrap_or_default());
+        self.har = profile.har.as_deref().map(HarRecorder::init);
+
+        if let Some(path) = &profile.audit_log {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            self.audit = Some(AuditLog {
+                path: path.to_owned(),
+            });
+        }
+
+        self.profile = profile;
+
+        Ok(())
+    }
+
+    pub async fn launch(&mut self, transfer: &Transfer) -> Result<(), ArcheonError> {
+        let source = transfer.uri.to_string();
+
+        if let Some(checksum) = self.launch_from_cache(transfer, &source).await? {
+            self.launch_audit::<(), Error>(
+                AuditAction::Download,
+                &source,
+                Some(checksum),
+                &transfer.tags,
+                &Ok(()),
+            )
+            .await?;
+
+            return Ok(());
+        }
+
+        let (mut result, mut checksum) = match transfer.launch_digest().await {
+            Ok(digest) => (Ok(()), digest),
+            Err(error) => {
+                let actual = match &error {
+                    ArcheonError::Integrity { actual, .. } => Some(actual.to_owned()),
+                    _ => None,
+                };
+
+                (Err(error), actual)
+            }
+        };
+
+        if result.is_ok() {
+            let actual = match checksum {
+                Some(actual) => actual,
+                None => checksum::digest(&transfer.file_path, transfer.digest_algorithm()).await?,
+            };
+
+            match &transfer.checksum {
+                Some(expected) if !checksum::matches(expected, &actual) => {
+                    result = Err(ArcheonError::Integrity {
+                        source: source.to_owned(),
+                        expected: expected.to_owned(),
+                        actual: actual.to_owned(),
+                    });
+                }
+                expected => {
+                    let algorithm = transfer.digest_algorithm();
+
+                    if expected.is_some() && algorithm.is_weak() {
+                        eprintln!(
+                            "Verified {} against a weak {} checksum only",
+                            source, algorithm,
+                        );
+                    }
+
+                    if let Some(cache) = &mut self.cache {
+                        cache.insert(&source, &transfer.file_path).await?;
+                    }
+                }
+            }
+
+            checksum = Some(actual);
+        }
+
+        self.launch_audit(
+            AuditAction::Download,
+            &source,
+            checksum,
+            &transfer.tags,
+            &result,
+        )
+        .await?;
+
+        result
+    }
+
+    async fn launch_from_cache(
+        &self,
+        transfer: &Transfer,
+        source: &str,
+    ) -> Result<Option<String>, Error> {
+        if transfer.overwrite != OverwritePolicy::Overwrite && transfer.file_path.exists() {
+            return Ok(None);
+        }
+
+        if let Some(cache) = &self.cache {
+            let checksum = match (&transfer.checksum, transfer.refresh) {
+                (Some(checksum), _) if transfer.digest_algorithm() == Algorithm::Sha256 => {
+                    Some(checksum::strip_prefix(checksum))
+                }
+                (Some(_), _) | (None, true) => None,
+                (None, false) => cache.lookup(source),
+            };
+
+            if let Some(checksum) = checksum {
+                if cache.link(checksum, &transfer.file_path).await? {
+                    return Ok(Some(checksum.to_owned()));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub async fn install(&mut self, transfer: &Transfer) -> Result<u64, Error> {
+        let source = transfer.uri.to_string();
+        let checksum = checksum::sha256(&transfer.file_path).await.ok();
+        let result = self
+            .launch_install(transfer, &source, checksum.as_deref())
+            .await;
+
+        self.launch_audit(
+            AuditAction::Install,
+            &source,
+            checksum,
+            &transfer.tags,
+            &result,
+        )
+        .await?;
+
+        result
+    }
+
+    async fn launch_install(
+        &mut self,
+        transfer: &Transfer,
+        source: &str,
+        checksum: Option<&str>,
+    ) -> Result<u64, Error> {
+        let checksum = match checksum {
+            Some(checksum) => checksum.to_owned(),
+            None => checksum::sha256(&transfer.file_path).await?,
+        };
+
+        if let Some(installation) = self.manifest.find(source, &checksum) {
+            return Ok(installation.id);
+        }
+
+        let version = transfer
+            .installer
+            .version(&transfer.temp_dir, &transfer.filename)
+            .await?;
+
+        if let Some(pinned) = self
+            .manifest
+            .pinned(source)
+            .and_then(|pin| pin.version.as_deref())
+        {
+            if pinned != version {
+                let error = format!(
+                    "Pinned version {} of {} does not match {}!",
+                    pinned, source, version,
+                );
+
+                return Err(Error::new(ErrorKind::InvalidData, error));
+            }
+        }
+        let package = transfer
+            .installer
+            .install(&transfer.temp_dir, &transfer.filename)
+            .await?;
+        let id = self.manifest.next_id();
+        let installed = unix_timestamp();
+
+        self.manifest
+            .record(Installation {
+                id,
+                package,
+                version,
+                source: source.to_owned(),
+                checksum,
+                installed,
+                artifact: transfer.file_path.to_owned(),
+                installer: transfer.installer.to_owned(),
+            })
+            .await?;
+
+        Ok(id)
+    }
+
+    pub async fn rollback(&mut self, id: u64) -> Result<Output, Error> {
+        let installation = self
+            .manifest
+            .installations
+            .iter()
+            .find(|installation| installation.id == id);
+
+        if let Some(installation) = installation {
+            let source = installation.source.to_owned();
+            let checksum = Some(installation.checksum.to_owned());
+            let result = installation
+                .installer
+                .uninstall(&installation.package)
+                .await;
+
+            self.launch_audit(
+                AuditAction::Uninstall,
+                &source,
+                checksum,
+                &BTreeMap::new(),
+                &result,
+            )
+            .await?;
+
+            let output = result?;
+
+            self.manifest.remove(id).await?;
+
+            Ok(output)
+        } else {
+            let error = format!("No installation found with id {}!", id);
+
+            Err(Error::new(ErrorKind::NotFound, error))
+        }
+    }
+
+    pub async fn apply(&mut self, batch: &Batch) -> BatchReport {
+        let mut report = BatchReport {
+            results: Vec::with_capacity(batch.artifacts.len()),
+        };
+
+        self.progress.begin(batch.artifacts.len());
+
+        for artifact in &batch.artifacts {
+            let mut result = BatchResult {
+                url: artifact.url.to_owned(),
+                file_path: None,
+                installation: None,
+                error: None,
+                error_kind: None,
+                hooks: Vec::new(),
+            };
+
+            if let Err(error) = self.launch_artifact(artifact, &mut result).await {
+                result.error = Some(error.to_string());
+                result.error_kind = Some(error.kind());
+            }
+
+            self.progress.finish(&result.url, result.error.to_owned());
+
+            report.results.push(result);
+        }
+
+        self.progress.end();
+
+        report
+    }
+
+    pub async fn mirror_index(
+        &mut self,
+        index: &DirectoryIndex,
+        destination: &Path,
+    ) -> Result<BatchReport, Error> {
+        let batch = index.batch(destination).await?;
+
+        Ok(self.apply(&batch).await)
+    }
+
+    pub async fn interrupted(&self) -> Vec<BatchArtifact> {
+        let directory = match &self.profile.destination {
+            Some(destination) => destination.to_owned(),
+            None => Transfer::staging_dir(),
+        };
+
+        if let Err(error) = self.profile.partial_cleanup().sweep(&directory).await {
+            eprintln!("Unable to sweep expired partial downloads: {}", error);
+        }
+
+        resume::interrupted(&directory)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|url| BatchArtifact {
+                url,
+                destination: Some(directory.to_owned()),
+                ..BatchArtifact::default()
+            })
+            .collect()
+    }
+
+    pub async fn recover(&mut self) -> BatchReport {
+        let batch = Batch {
+            artifacts: self.interrupted().await,
+        };
+
+        self.apply(&batch).await
+    }
+
+    async fn launch_artifact(
+        &mut self,
+        artifact: &BatchArtifact,
+        result: &mut BatchResult,
+    ) -> Result<(), ArcheonError> {
+        if self.shutdown.is_triggered() {
+            return Err(ArcheonError::Cancelled);
+        }
+
+        let mut transfer = self.launch_transfer(artifact).await?;
+        let source = transfer.uri.to_string();
+
+        self.launch_pinned(&mut transfer).await?;
+
+        if artifact.pin && self.manifest.pinned(&source).is_none() {
+            let pin = Pin {
+                source,
+                checksum: checksum::sha256(&transfer.file_path).await?,
+                version: None,
+            };
+
+            self.manifest.pin(pin).await?;
+        }
+
+        result.file_path = Some(transfer.file_path.to_owned());
+
+        if artifact.install {
+            result.installation = Some(
+                self.install(&transfer)
+                    .await
+                    .map_err(ArcheonError::Install)?,
+            );
+
+            let installation = self
+                .manifest
+                .installations
+                .iter()
+                .find(|installation| Some(installation.id) == result.installation);
+
+            if let (Some(installation), false) = (installation, transfer.post_install.is_empty()) {
+                result.hooks = hook::post_install(
+                    &transfer.post_install,
+                    installation,
+                    &transfer.file_path,
+                    &transfer.tags,
+                )
+                .await
+                .map_err(ArcheonError::Install)?;
+
+                if let Some(failed) = result.hooks.iter().find(|output| !output.success()) {
+                    let error = format!(
+                        "Post-install hook '{}' exited with {:?}!",
+                        failed.command, failed.status
+                    );
+
+                    return Err(ArcheonError::Install(Error::other(error)));
+                }
+            }
+
+            let retained = transfer
+                .retention
+                .apply(
+                    &transfer.uri.to_string(),
+                    &transfer.file_path,
+                    self.cache.as_mut(),
+                )
+                .await?;
+
+            if transfer.retention == Retention::Cache {
+                result.file_path = retained;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn launch_pinned(&mut self, transfer: &mut Transfer) -> Result<(), ArcheonError> {
+        let source = transfer.uri.to_string();
+        let pinned = self
+            .manifest
+            .pinned(&source)
+            .map(|pin| pin.checksum.to_owned());
+
+        if let Some(pinned) = &pinned {
+            match &transfer.checksum {
+                Some(checksum)
+                    if transfer.digest_algorithm() == Algorithm::Sha256
+                        && !checksum::matches(checksum, pinned) =>
+                {
+                    return Err(ArcheonError::Integrity {
+                        source,
+                        expected: pinned.to_owned(),
+                        actual: checksum.to_owned(),
+                    });
+                }
+                Some(_) => {}
+                None => transfer.checksum = Some(pinned.to_owned()),
+            }
+        }
+
+        self.launch(transfer).await?;
+
+        if let Some(pinned) = pinned {
+            let actual = checksum::sha256(&transfer.file_path).await?;
+
+            if !checksum::matches(&pinned, &actual) {
+                tokio::fs::remove_file(&transfer.file_path).await?;
+
+                return Err(ArcheonError::Integrity {
+                    source,
+                    expected: pinned,
+                    actual,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn launch_transfer(&self, artifact: &BatchArtifact) -> Result<Transfer, ArcheonError> {
+        let mut artifact = self.profile.apply(artifact);
+
+        if let Some(url) = mirror::rewrite(&self.mirrors, &artifact.url) {
+            artifact.url = url;
+        }
+
+        let mut transfer = artifact
+            .transfer_with_client(self.client.to_owned())
+            .await?;
+
+        transfer.limiter = self.limiter.to_owned();
+        transfer.buffer = self.buffer.to_owned();
+        transfer.cookies = self.cookies.to_owned();
+        transfer.retry = self.retry.to_owned();
+        transfer.circuit = self.circuit.to_owned();
+        transfer.progress = Some(self.progress.to_owned());
+        transfer.refresh_url = self.refresh_url.to_owned();
+        transfer.auth = self.auth.to_owned();
+        transfer.pre_request = self.pre_request.to_owned();
+        transfer.shutdown = self.shutdown.to_owned();
+        transfer.wire = self.wire.to_owned();
+        transfer.har = self.har.to_owned();
+
+        if let Some(write_buffer) = self.profile.write_buffer {
+            transfer.write_buffer = write_buffer;
+        }
+
+        if let Some(durability) = self.profile.durability {
+            transfer.durability = durability;
+        }
+
+        if let Some(segments) = self.profile.segments {
+            transfer.segments = segments;
+        }
+
+        if let Some(resume_check) = self.profile.resume_check {
+            transfer.resume_check = resume_check;
+        }
+
+        transfer.partial = self.profile.partial_cleanup();
+        transfer.timestamping |= self.profile.timestamping.unwrap_or_default();
+
+        if transfer.overwrite == OverwritePolicy::RenameUnique {
+            transfer.file_path = overwrite::unique_path(&transfer.file_path);
+        }
+
+        Ok(transfer)
+    }
+
+    pub async fn outdated(&mut self) -> Vec<Outdated> {
+        let installations = self.manifest.installations.to_owned();
+        let mut outdated = Vec::with_capacity(installations.len());
+
+        for installation in installations {
+            let mut result = Outdated {
+                id: installation.id,
+                package: installation.package.to_owned(),
+                source: installation.source.to_owned(),
+                installed_version: installation.version.to_owned(),
+                available_version: None,
+                installed_checksum: installation.checksum.to_owned(),
+                available_checksum: None,
+                error: None,
+            };
+
+            if let Err(error) = self.launch_outdated(&installation, &mut result).await {
+                result.error = Some(error.to_string());
+            }
+
+            let changed = result
+                .available_checksum
+                .as_deref()
+                .is_some_and(|checksum| !checksum.eq_ignore_ascii_case(&result.installed_checksum));
+
+            if changed || result.error.is_some() {
+                outdated.push(result);
+            }
+        }
+
+        outdated
+    }
+
+    async fn launch_outdated(
+        &mut self,
+        installation: &Installation,
+        result: &mut Outdated,
+    ) -> Result<(), ArcheonError> {
+        let staging = tempfile::Builder::new()
+            .prefix("archeon_outdated")
+            .tempdir()?;
+        let artifact = BatchArtifact {
+            url: result.source.to_owned(),
+            installer: installation.installer.to_owned(),
+            destination: Some(staging.path().to_owned()),
+            refresh: true,
+            ..BatchArtifact::default()
+        };
+        let transfer = self.launch_transfer(&artifact).await?;
+
+        if let Some(entry) = HttpCacheEntry::load(&installation.artifact).await {
+            let probe = transfer.probe().await?;
+            let recorded = entry
+                .sha256
+                .as_deref()
+                .is_none_or(|sha256| sha256.eq_ignore_ascii_case(&installation.checksum));
+            let unchanged = match (&entry.etag, &probe.etag) {
+                (Some(local), Some(remote)) => local == remote,
+                _ => matches!(
+                    (&entry.last_modified, &probe.last_modified),
+                    (Some(local), Some(remote)) if local == remote,
+                ),
+            };
+
+            if recorded && unchanged {
+                result.available_version = Some(installation.version.to_owned());
+                result.available_checksum = Some(installation.checksum.to_owned());
+
+                return Ok(());
+            }
+        }
+
+        transfer.launch().await?;
+
+        result.available_checksum = Some(checksum::sha256(&transfer.file_path).await?);
+        result.available_version = transfer
+            .installer
+            .version(&transfer.temp_dir, &transfer.filename)
+            .await
+            .ok();
+
+        Ok(())
+    }
+
+    pub async fn launch_scheduled(&mut self, now: u64) -> BatchReport {
+        let batch = Batch {
+            artifacts: self.scheduler.take_due(now),
+        };
+
+        self.apply(&batch).await
+    }
+
+    pub async fn run_scheduler(&mut self) {
+        while let Some(next) = self.scheduler.next_due() {
+            let now = unix_timestamp();
+
+            if next > now {
+                tokio::time::sleep(Duration::from_secs(next - now)).await;
+            }
+
+            let report = self.launch_scheduled(unix_timestamp()).await;
+
+            for result in &report.results {
+                if let Some(error) = &result.error {
+                    eprintln!("scheduled download of {} failed: {}", result.url, error);
+                }
+            }
+        }
+    }
+
+    pub async fn sbom(&self) -> Sbom {
+        Sbom::init(&self.manifest)
+    }
+
+    pub async fn verify(&self, manifest: &Path) -> Result<Vec<Verification>, Error> {
+        let expected = verify::load(manifest).await?;
+
+        Ok(verify::verify(&expected).await)
+    }
+
+    pub async fn apply_recipe(&mut self, recipe: &Recipe) -> Result<RecipeReport, ArcheonError> {
+        let mut variables = recipe.variables(&self.profile.variables);
+        let source = template::expand(&recipe.source, &variables)?;
+        let root = match &self.profile.destination {
+            Some(destination) => destination.to_owned(),
+            None => Transfer::staging_dir(),
+        };
+        let artifact = BatchArtifact {
+            url: source.to_owned(),
+            destination: Some(root.to_owned()),
+            ..BatchArtifact::default()
+        };
+        let mut transfer = self.launch_transfer(&artifact).await?;
+        let filename = transfer.filename.to_string_lossy().into_owned();
+        let checksum = match (&recipe.checksum, &recipe.checksum_url) {
+            (Some(checksum), _) => checksum.to_ascii_lowercase(),
+            (None, Some(checksum_url)) => {
+                let sums = BatchArtifact {
+                    url: template::expand(checksum_url, &variables)?,
+                    destination: Some(root.to_owned()),
+                    ..BatchArtifact::default()
+                };
+                let mut sums = self.launch_transfer(&sums).await?;
+
+                sums.file_path = root.join(format!("{}.checksums", filename));
+                sums.progress = None;
+                sums.launch().await?;
+
+                let contents = tokio::fs::read_to_string(&sums.file_path).await?;
+
+                tokio::fs::remove_file(&sums.file_path).await?;
+
+                recipe::select_checksum(&contents, &filename)?
+            }
+            (None, None) => {
+                let error = format!("Recipe {} has no checksum or checksum_url!", recipe.name);
+
+                return Err(Error::new(ErrorKind::InvalidData, error).into());
+            }
+        };
+
+        transfer.checksum = Some(checksum.to_owned());
+
+        self.launch_pinned(&mut transfer).await?;
+
+        let directory = match &recipe.extract {
+            Some(rules) => {
+                let directory = recipe.staging_dir(&root);
+
+                recipe::extract(&transfer.file_path, &directory, rules)
+                    .await
+                    .map_err(ArcheonError::Install)?;
+
+                directory
+            }
+            None => root,
+        };
+        let environment = [
+            ("ARCHEON_RECIPE", recipe.name.to_owned()),
+            (
+                "ARCHEON_VERSION",
+                recipe.version.to_owned().unwrap_or_default(),
+            ),
+            ("ARCHEON_SOURCE", source.to_owned()),
+            ("ARCHEON_CHECKSUM", checksum.to_owned()),
+            (
+                "ARCHEON_ARTIFACT",
+                transfer.file_path.to_string_lossy().into_owned(),
+            ),
+            ("ARCHEON_DIR", directory.to_string_lossy().into_owned()),
+        ];
+        let mut report = RecipeReport {
+            name: recipe.name.to_owned(),
+            version: recipe.version.to_owned(),
+            source,
+            checksum,
+            file_path: transfer.file_path.to_owned(),
+            directory: directory.to_owned(),
+            installed: Vec::with_capacity(recipe.install.len()),
+            steps: Vec::with_capacity(recipe.install.len()),
+        };
+
+        variables.insert(String::from("file"), filename);
+        variables.insert(
+            String::from("dir"),
+            directory.to_string_lossy().into_owned(),
+        );
+
+        for step in &recipe.install {
+            let (installed, output) =
+                recipe::install_step(step, &directory, &variables, &environment)
+                    .await
+                    .map_err(ArcheonError::Install)?;
+
+            report.installed.extend(installed);
+
+            if let Some(output) = output {
+                if !output.success() {
+                    let error = format!(
+                        "Recipe step '{}' exited with {:?}!",
+                        output.command, output.status
+                    );
+
+                    return Err(ArcheonError::Install(Error::other(error)));
+                }
+
+                report.steps.push(output);
+            }
+        }
+
+        Ok(report)
+    }
 
-        Archeon { ignited: true }
+    pub async fn repair(
+        &self,
+        verification: &Verification,
+        pieces: Option<&PieceHashes>,
+    ) -> Result<RepairSummary, ArcheonError> {
+        let url = match &verification.url {
+            Some(url) => url.to_owned(),
+            None => {
+                let error = format!(
+                    "No source URL to repair {} from!",
+                    verification.path.display()
+                );
+
+                return Err(Error::new(ErrorKind::NotFound, error).into());
+            }
+        };
+        let artifact = BatchArtifact {
+            url,
+            destination: verification.path.parent().map(Path::to_path_buf),
+            ..BatchArtifact::default()
+        };
+        let mut transfer = self.launch_transfer(&artifact).await?;
+
+        transfer.file_path = verification.path.to_owned();
+        transfer.progress = None;
+
+        transfer.repair(&verification.expected, pieces).await
+    }
+
+    async fn launch_audit<T, E: std::fmt::Display>(
+        &self,
+        action: AuditAction,
+        source: &str,
+        checksum: Option<String>,
+        tags: &BTreeMap<String, String>,
+        result: &Result<T, E>,
+    ) -> Result<(), Error> {
+        if let Some(audit) = &self.audit {
+            let result = match result {
+                Ok(_) => Ok(()),
+                Err(error) => Err(error.to_string()),
+            };
+            let mut entry = AuditEntry::init(action, source, checksum, result);
+
+            entry.tags = tags.to_owned();
+
+            audit.append(&entry).await?;
+        }
+
+        Ok(())
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::installer::Installer;
+    use std::path::PathBuf;
+
+    fn test_installation(id: u64, source: &str, checksum: &str) -> Installation {
+        Installation {
+            id,
+            package: String::from("archeon-test-package-not-installed"),
+            version: String::from("1.0.0"),
+            source: source.to_owned(),
+            checksum: checksum.to_owned(),
+            installed: 1_686_000_000,
+            artifact: PathBuf::from("/tmp/archeon/archeon-test-package-not-installed.deb"),
+            installer: Installer::default(),
+        }
+    }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn ignite() {
         let test_archeon = Archeon::ignite().await;
         assert!(test_archeon.ignited);
+        assert!(test_archeon.manifest.installations.is_empty());
+        assert!(test_archeon.manifest.path.is_none());
+        assert!(test_archeon.audit.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_path = std::env::temp_dir().join("archeon_test_launch_audit.log");
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.audit = Some(AuditLog::init(&test_path).await?);
+        let test_uri = format!("{}/test_archeon_launch.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer
+            .tags
+            .insert(String::from("environment"), String::from("staging"));
+        let mock_get_request = test_server
+            .mock("GET", "/test_archeon_launch.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        test_archeon.launch(&test_transfer).await?;
+        mock_get_request.assert_async().await;
+        let test_entries = test_archeon.audit.as_ref().unwrap().entries().await?;
+        assert_eq!(test_entries.len(), 1);
+        assert_eq!(test_entries[0].action, AuditAction::Download);
+        assert_eq!(test_entries[0].source, test_uri);
+        assert_eq!(
+            test_entries[0].checksum.as_deref(),
+            Some("4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6"),
+        );
+        assert!(test_entries[0].result.is_ok());
+        assert_eq!(test_entries[0].tags["environment"], "staging");
+        tokio::fs::remove_file(&test_path).await?;
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ignite_with_manifest() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_ignite_with_manifest.json");
+        let test_archeon = Archeon::ignite_with_manifest(&test_path).await?;
+        assert!(test_archeon.ignited);
+        assert_eq!(test_archeon.manifest.path, Some(test_path));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_from_cache() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_root = std::env::temp_dir().join("archeon_test_launch_from_cache");
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.cache = Some(Cache::init(&test_root).await?);
+        let test_first_uri = format!("{}/test_archeon_cache_first.txt", test_server.url());
+        let test_second_uri = format!("{}/test_archeon_cache_second.txt", test_server.url());
+        let test_first_transfer = Transfer::init(&test_first_uri).await?;
+        let mut test_second_transfer = Transfer::init(&test_second_uri).await?;
+        let mock_get_request = test_server
+            .mock("GET", "/test_archeon_cache_first.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_body(b"test_body")
+            .expect(1)
+            .create_async()
+            .await;
+        test_archeon.launch(&test_first_transfer).await?;
+        test_archeon.launch(&test_first_transfer).await?;
+        test_second_transfer.checksum = Some(String::from(
+            "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6",
+        ));
+        test_archeon.launch(&test_second_transfer).await?;
+        mock_get_request.assert_async().await;
+        assert_eq!(
+            tokio::fs::read(&test_second_transfer.file_path).await?,
+            b"test_body",
+        );
+        tokio::fs::remove_dir_all(&test_root).await?;
+        tokio::fs::remove_file(&test_first_transfer.file_path).await?;
+        tokio::fs::remove_file(&test_second_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn install() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_archeon = Archeon::ignite().await;
+        let test_transfer = Transfer::init("http://test-install/test_install_missing.deb").await?;
+        let test_path = std::env::temp_dir().join("archeon_test_install_audit.log");
+        test_archeon.audit = Some(AuditLog::init(&test_path).await?);
+        assert!(test_archeon.install(&test_transfer).await.is_err());
+        assert!(test_archeon.manifest.installations.is_empty());
+        let test_entries = test_archeon.audit.as_ref().unwrap().entries().await?;
+        assert_eq!(test_entries.len(), 1);
+        assert_eq!(test_entries[0].action, AuditAction::Install);
+        assert!(test_entries[0].checksum.is_none());
+        assert!(test_entries[0].result.is_err());
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply_install_retention() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_archeon = Archeon::ignite().await;
+        let test_root = std::env::temp_dir().join("archeon_test_apply_install_retention_cache");
+        let test_uri = format!("{}/test_apply_install_retention.deb", test_server.url());
+        test_server
+            .mock("GET", "/test_apply_install_retention.deb")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        test_archeon.cache = Some(Cache::init(&test_root).await?);
+        test_archeon.profile.retention = Some(Retention::Cache);
+        test_archeon.manifest.installations.push(test_installation(
+            7,
+            &test_uri,
+            "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6",
+        ));
+        let test_batch = Batch {
+            artifacts: vec![BatchArtifact {
+                url: test_uri,
+                destination: Some(PathBuf::from("/tmp/archeon_test_apply_install_retention")),
+                install: true,
+                ..BatchArtifact::default()
+            }],
+        };
+        let test_report = test_archeon.apply(&test_batch).await;
+        assert!(test_report.results[0].error.is_none());
+        let test_file_path = test_report.results[0].file_path.as_ref().unwrap();
+        assert!(test_file_path.starts_with(&test_root));
+        assert_eq!(tokio::fs::read(test_file_path).await?, b"test_body");
+        assert!(tokio::fs::metadata(
+            "/tmp/archeon_test_apply_install_retention/test_apply_install_retention.deb"
+        )
+        .await
+        .is_err());
+        tokio::fs::remove_dir_all("/tmp/archeon_test_apply_install_retention").await?;
+        tokio::fs::remove_dir_all(&test_root).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply_install_hooks() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_archeon = Archeon::ignite().await;
+        let test_uri = format!("{}/test_apply_install_hooks.deb", test_server.url());
+        test_server
+            .mock("GET", "/test_apply_install_hooks.deb")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_body(b"test_body")
+            .expect_at_least(1)
+            .create_async()
+            .await;
+        test_archeon.manifest.installations.push(test_installation(
+            7,
+            &test_uri,
+            "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6",
+        ));
+        test_archeon.profile.post_install = vec![String::from("cat \"$ARCHEON_ARTIFACT\"")];
+        let test_artifact = BatchArtifact {
+            url: test_uri,
+            destination: Some(PathBuf::from("/tmp/archeon_test_apply_install_hooks")),
+            install: true,
+            delete: true,
+            ..BatchArtifact::default()
+        };
+        let test_report = test_archeon
+            .apply(&Batch {
+                artifacts: vec![test_artifact.to_owned()],
+            })
+            .await;
+        assert!(test_report.results[0].error.is_none());
+        assert_eq!(test_report.results[0].hooks.len(), 1);
+        assert_eq!(test_report.results[0].hooks[0].stdout, "test_body");
+        let test_report = test_archeon
+            .apply(&Batch {
+                artifacts: vec![BatchArtifact {
+                    post_install: vec![String::from("exit 4")],
+                    ..test_artifact
+                }],
+            })
+            .await;
+        assert_eq!(
+            test_report.results[0].error_kind,
+            Some(crate::error::ErrorKind::Install),
+        );
+        assert_eq!(test_report.results[0].hooks[0].status, Some(4));
+        let test_file_path = test_report.results[0].file_path.as_ref().unwrap();
+        assert!(tokio::fs::metadata(test_file_path).await.is_ok());
+        tokio::fs::remove_dir_all("/tmp/archeon_test_apply_install_hooks").await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply_recipe() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_archeon = Archeon::ignite().await;
+        let test_root = std::env::temp_dir().join("archeon_test_apply_recipe");
+        let test_source = test_root.join("source");
+        tokio::fs::create_dir_all(test_source.join("test-tool-1.2.3/bin")).await?;
+        tokio::fs::write(
+            test_source.join("test-tool-1.2.3/bin/test-tool"),
+            b"test_body",
+        )
+        .await?;
+        let test_output = tokio::process::Command::new("tar")
+            .args([
+                "--create",
+                "--gzip",
+                "--file",
+                "test-tool.tar.gz",
+                "test-tool-1.2.3",
+            ])
+            .current_dir(&test_source)
+            .output()
+            .await?;
+        assert!(test_output.status.success());
+        let test_archive = tokio::fs::read(test_source.join("test-tool.tar.gz")).await?;
+        test_server
+            .mock("GET", "/1.2.3/test-tool.tar.gz")
+            .with_status(200)
+            .with_body(&test_archive)
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/1.2.3/SHA256SUMS")
+            .with_status(200)
+            .with_body(format!(
+                "{}  test-tool.tar.gz\n{}  other.tar.gz\n",
+                checksum::sha256_bytes(&test_archive),
+                "0".repeat(64),
+            ))
+            .create_async()
+            .await;
+        test_archeon.profile.destination = Some(test_root.join("staging"));
+        test_archeon.profile.variables.insert(
+            String::from("prefix"),
+            test_root.to_string_lossy().into_owned(),
+        );
+        let test_recipe = Recipe::parse(&format!(
+            r#"
+            name = "test-tool"
+            version = "1.2.3"
+            source = "{0}/{{version}}/{{name}}.tar.gz"
+            checksum_url = "{0}/{{version}}/SHA256SUMS"
+
+            [extract]
+            strip_components = 1
+
+            [[install]]
+            copy = "bin/{{name}}"
+            to = "{{prefix}}/bin/{{name}}"
+            mode = "755"
+
+            [[install]]
+            run = "echo {{file}} $ARCHEON_VERSION"
+            "#,
+            test_server.url(),
+        ))?;
+        let test_report = test_archeon.apply_recipe(&test_recipe).await?;
+        assert_eq!(test_report.checksum, checksum::sha256_bytes(&test_archive));
+        assert_eq!(test_report.installed, vec![test_root.join("bin/test-tool")]);
+        assert_eq!(
+            tokio::fs::read(test_root.join("bin/test-tool")).await?,
+            b"test_body"
+        );
+        assert_eq!(test_report.steps[0].stdout, "test-tool.tar.gz 1.2.3\n");
+        assert!(
+            tokio::fs::metadata(test_root.join("staging/test-tool.tar.gz.checksums"))
+                .await
+                .is_err()
+        );
+        let test_pinned = Recipe {
+            checksum: Some(format!(
+                "md5:{}",
+                checksum::digest_bytes(&test_archive, Algorithm::Md5),
+            )),
+            ..test_recipe.to_owned()
+        };
+        test_archeon
+            .manifest
+            .pin(Pin {
+                source: format!("{}/1.2.3/test-tool.tar.gz", test_server.url()),
+                checksum: checksum::sha256_bytes(b"test_tags"),
+                version: None,
+            })
+            .await?;
+        assert_eq!(
+            test_archeon
+                .apply_recipe(&test_pinned)
+                .await
+                .unwrap_err()
+                .kind(),
+            crate::error::ErrorKind::Integrity,
+        );
+        let test_recipe = Recipe {
+            checksum: Some("0".repeat(64)),
+            ..test_recipe
+        };
+        assert_eq!(
+            test_archeon
+                .apply_recipe(&test_recipe)
+                .await
+                .unwrap_err()
+                .kind(),
+            crate::error::ErrorKind::Integrity,
+        );
+        tokio::fs::remove_dir_all(&test_root).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply_install_delete() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_archeon = Archeon::ignite().await;
+        let test_uri = format!("{}/test_apply_install_delete.deb", test_server.url());
+        for test_method in ["HEAD", "GET"] {
+            test_server
+                .mock(test_method, "/test_apply_install_delete.deb")
+                .with_status(200)
+                .with_header("content-length", "9")
+                .with_body(b"test_body")
+                .create_async()
+                .await;
+        }
+        test_archeon.manifest.installations.push(test_installation(
+            7,
+            &test_uri,
+            "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6",
+        ));
+        let test_batch = Batch {
+            artifacts: vec![BatchArtifact {
+                url: test_uri,
+                destination: Some(PathBuf::from("/tmp/archeon_test_apply_install_delete")),
+                install: true,
+                delete: true,
+                ..BatchArtifact::default()
+            }],
+        };
+        let test_report = test_archeon.apply(&test_batch).await;
+        assert!(test_report.results[0].error.is_none());
+        assert_eq!(test_report.results[0].installation, Some(7));
+        let test_file_path = test_report.results[0].file_path.as_ref().unwrap();
+        assert!(tokio::fs::metadata(test_file_path).await.is_err());
+        tokio::fs::remove_dir_all("/tmp/archeon_test_apply_install_delete").await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply_pin() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_archeon = Archeon::ignite().await;
+        let test_uri = format!("{}/test_apply_pin.txt", test_server.url());
+        let test_first = test_server
+            .mock("GET", "/test_apply_pin.txt")
+            .with_status(200)
+            .with_body(b"test_body")
+            .expect(1)
+            .create_async()
+            .await;
+        let test_batch = Batch {
+            artifacts: vec![BatchArtifact {
+                url: test_uri.to_owned(),
+                destination: Some(PathBuf::from("/tmp/archeon_test_apply_pin")),
+                pin: true,
+                ..BatchArtifact::default()
+            }],
+        };
+        assert_eq!(test_archeon.apply(&test_batch).await.succeeded(), 1);
+        assert_eq!(
+            test_archeon.manifest.pinned(&test_uri).unwrap().checksum,
+            "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6",
+        );
+        test_first.assert_async().await;
+        test_server
+            .mock("GET", "/test_apply_pin.txt")
+            .with_status(200)
+            .with_body(b"test_tags")
+            .create_async()
+            .await;
+        let test_report = test_archeon.apply(&test_batch).await;
+        assert_eq!(
+            test_report.results[0].error_kind,
+            Some(error::ErrorKind::Integrity),
+        );
+        let mut test_batch = test_batch;
+        test_batch.artifacts[0].checksum = Some(String::from("test_other_checksum"));
+        let test_report = test_archeon.apply(&test_batch).await;
+        assert_eq!(
+            test_report.results[0].error_kind,
+            Some(error::ErrorKind::Integrity),
+        );
+        test_batch.artifacts[0].checksum = Some(format!(
+            "md5:{}",
+            checksum::digest_bytes(b"test_tags", Algorithm::Md5),
+        ));
+        let test_report = test_archeon.apply(&test_batch).await;
+        assert_eq!(
+            test_report.results[0].error_kind,
+            Some(error::ErrorKind::Integrity),
+        );
+        assert!(
+            tokio::fs::metadata("/tmp/archeon_test_apply_pin/test_apply_pin.txt")
+                .await
+                .is_err()
+        );
+        tokio::fs::remove_dir_all("/tmp/archeon_test_apply_pin").await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn configure() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_archeon = Archeon::ignite().await;
+        let mut test_mocks = Vec::with_capacity(1);
+        for test_method in ["GET"] {
+            test_mocks.push(
+                test_server
+                    .mock(test_method, "/test_configure.txt")
+                    .match_header("host", "test-configure.invalid")
+                    .match_header("authorization", "Bearer test_token")
+                    .with_status(200)
+                    .with_header("content-length", "9")
+                    .with_body(b"test_body")
+                    .create_async()
+                    .await,
+            );
+        }
+        let mut test_headers = std::collections::BTreeMap::new();
+        test_headers.insert(
+            String::from("authorization"),
+            String::from("Bearer test_token"),
+        );
+        test_archeon.configure(Profile {
+            destination: Some(PathBuf::from("/tmp/archeon_test_configure")),
+            proxy: Some(test_server.url()),
+            concurrency: Some(2),
+            buffer_limit: Some(4),
+            headers: test_headers,
+            audit_log: Some(PathBuf::from("/tmp/archeon_test_configure_audit/audit.log")),
+            ..Profile::default()
+        })?;
+        assert_eq!(
+            test_archeon
+                .audit
+                .as_ref()
+                .map(|audit| audit.path.to_owned()),
+            Some(PathBuf::from("/tmp/archeon_test_configure_audit/audit.log")),
+        );
+        assert!(tokio::fs::metadata("/tmp/archeon_test_configure_audit")
+            .await?
+            .is_dir());
+        assert_eq!(
+            test_archeon
+                .limiter
+                .limit("test-configure.invalid")
+                .max_connections,
+            Some(2),
+        );
+        assert_eq!(test_archeon.buffer.limit(), 4);
+        let test_batch = Batch {
+            artifacts: vec![BatchArtifact {
+                url: String::from("http://test-configure.invalid/test_configure.txt"),
+                ..BatchArtifact::default()
+            }],
+        };
+        let test_report = test_archeon.apply(&test_batch).await;
+        assert!(test_report.results[0].error.is_none());
+        assert_eq!(
+            test_report.results[0].file_path,
+            Some(PathBuf::from(
+                "/tmp/archeon_test_configure/test_configure.txt"
+            )),
+        );
+        for test_mock in test_mocks {
+            test_mock.assert_async().await;
+        }
+        assert!(test_archeon
+            .configure(Profile {
+                proxy: Some(String::from("test-invalid-proxy")),
+                ..Profile::default()
+            })
+            .is_err());
+        tokio::fs::remove_dir_all("/tmp/archeon_test_configure").await?;
+        tokio::fs::remove_dir_all("/tmp/archeon_test_configure_audit").await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn outdated() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_archeon = Archeon::ignite().await;
+        for test_path in ["/test_outdated_current.deb", "/test_outdated_newer.deb"] {
+            for test_method in ["HEAD", "GET"] {
+                test_server
+                    .mock(test_method, test_path)
+                    .with_status(200)
+                    .with_header("content-length", "9")
+                    .with_body(b"test_body")
+                    .create_async()
+                    .await;
+            }
+        }
+        test_server
+            .mock("HEAD", "/test_outdated_missing.deb")
+            .with_status(404)
+            .create_async()
+            .await;
+        let test_checksum = "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6";
+        test_archeon.manifest.installations = vec![
+            test_installation(
+                0,
+                &format!("{}/test_outdated_current.deb", test_server.url()),
+                test_checksum,
+            ),
+            test_installation(
+                1,
+                &format!("{}/test_outdated_newer.deb", test_server.url()),
+                "test_old_checksum",
+            ),
+            test_installation(
+                2,
+                &format!("{}/test_outdated_missing.deb", test_server.url()),
+                test_checksum,
+            ),
+        ];
+        let test_outdated = test_archeon.outdated().await;
+        assert_eq!(test_outdated.len(), 2);
+        assert_eq!(test_outdated[0].id, 1);
+        assert_eq!(test_outdated[0].installed_version, "1.0.0");
+        assert_eq!(
+            test_outdated[0].available_checksum.as_deref(),
+            Some(test_checksum),
+        );
+        assert!(test_outdated[0].available_version.is_none());
+        assert!(test_outdated[0].error.is_none());
+        assert_eq!(test_outdated[1].id, 2);
+        assert!(test_outdated[1].error.is_some());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn outdated_unchanged() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_archeon = Archeon::ignite().await;
+        let test_head = test_server
+            .mock("HEAD", "/test_outdated_unchanged.deb")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_header("etag", "\"test_etag\"")
+            .create_async()
+            .await;
+        let test_get = test_server
+            .mock("GET", "/test_outdated_unchanged.deb")
+            .expect(0)
+            .create_async()
+            .await;
+        let test_artifact = std::env::temp_dir().join("archeon_test_outdated_unchanged.deb");
+        HttpCacheEntry {
+            etag: Some(String::from("\"test_etag\"")),
+            ..HttpCacheEntry::default()
+        }
+        .save(&test_artifact)
+        .await?;
+        let mut test_installed = test_installation(
+            0,
+            &format!("{}/test_outdated_unchanged.deb", test_server.url()),
+            "test_checksum",
+        );
+        test_installed.artifact = test_artifact.to_owned();
+        test_archeon.manifest.installations = vec![test_installed];
+        assert!(test_archeon.outdated().await.is_empty());
+        test_head.assert_async().await;
+        test_get.assert_async().await;
+        tokio::fs::remove_file(HttpCacheEntry::path(&test_artifact)).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn install_idempotent() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_archeon = Archeon::ignite().await;
+        let test_uri = "http://test-install-idempotent/test_install_idempotent.deb";
+        let test_transfer = Transfer::init(test_uri).await?;
+        tokio::fs::write(&test_transfer.file_path, "test_body").await?;
+        let test_checksum = checksum::sha256(&test_transfer.file_path).await?;
+        test_archeon
+            .manifest
+            .installations
+            .push(test_installation(3, test_uri, &test_checksum));
+        assert_eq!(test_archeon.install(&test_transfer).await?, 3);
+        assert_eq!(test_archeon.manifest.installations.len(), 1);
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_checksum_mismatch() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_archeon = Archeon::ignite().await;
+        let test_uri = format!("{}/test_archeon_checksum_mismatch.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.checksum = Some(String::from("test_checksum"));
+        test_server
+            .mock("HEAD", "/test_archeon_checksum_mismatch.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/test_archeon_checksum_mismatch.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        let test_error = test_archeon.launch(&test_transfer).await.unwrap_err();
+        assert!(test_error.to_string().contains("Checksum mismatch"));
+        assert_eq!(test_error.kind(), crate::error::ErrorKind::Integrity);
+        assert!(tokio::fs::metadata(&test_transfer.file_path).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_archeon = Archeon::ignite().await;
+        let test_batch = Batch::parse(&format!(
+            r#"
+            [[artifact]]
+            url = "{url}/test_archeon_apply.txt"
+            checksum = "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6"
+            destination = "/tmp/archeon_test_apply"
+
+            [[artifact]]
+            url = "{url}/test_archeon_apply_missing.deb"
+            install = true
+            "#,
+            url = test_server.url(),
+        ))?;
+        for test_method in ["HEAD", "GET"] {
+            test_server
+                .mock(test_method, "/test_archeon_apply.txt")
+                .with_status(200)
+                .with_header("content-length", "9")
+                .with_body(b"test_body")
+                .create_async()
+                .await;
+        }
+        test_server
+            .mock("HEAD", "/test_archeon_apply_missing.deb")
+            .with_status(404)
+            .with_header("content-length", "0")
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/test_archeon_apply_missing.deb")
+            .with_status(404)
+            .with_header("content-length", "0")
+            .create_async()
+            .await;
+        let mut test_updates = test_archeon.progress.subscribe();
+        let test_report = test_archeon.apply(&test_batch).await;
+        assert_eq!(test_report.succeeded(), 1);
+        assert_eq!(test_report.failed(), 1);
+        let test_aggregate = test_archeon.progress.aggregate();
+        assert_eq!(test_aggregate.files_total, 2);
+        assert_eq!(test_aggregate.files_completed, 1);
+        assert_eq!(test_aggregate.files_failed, 1);
+        assert_eq!(test_aggregate.files_remaining(), 0);
+        assert_eq!(test_aggregate.downloaded_bytes, 9);
+        assert_eq!(
+            test_updates.recv().await?.event,
+            crate::progress::ProgressEvent::Started {
+                url: test_report.results[0].url.to_owned(),
+                size: Some(9),
+            },
+        );
+        assert_eq!(
+            test_report.results[0].file_path.as_deref(),
+            Some(std::path::Path::new(
+                "/tmp/archeon_test_apply/test_archeon_apply.txt"
+            )),
+        );
+        assert!(test_report.results[0].error_kind.is_none());
+        assert!(test_report.results[1].error.is_some());
+        assert_eq!(
+            test_report.results[1].error_kind,
+            Some(crate::error::ErrorKind::Client),
+        );
+        assert!(test_report.results[1].installation.is_none());
+        tokio::fs::remove_dir_all("/tmp/archeon_test_apply").await?;
+        Ok(())
+    }
+
+    struct TestTransport;
+
+    impl crate::transport::Transport for TestTransport {
+        fn send(
+            &self,
+            request: hyper::Request<hyper::Body>,
+        ) -> crate::transport::TransportFuture<'_> {
+            Box::pin(async move {
+                let body = match request.method() == hyper::Method::HEAD {
+                    true => hyper::Body::empty(),
+                    false => hyper::Body::from(request.uri().path().to_owned()),
+                };
+                let response = hyper::Response::builder()
+                    .header("content-length", request.uri().path().len())
+                    .body(body)?;
+
+                Ok(response)
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ignite_with_client() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_archeon = Archeon::ignite_with_client(Arc::new(TestTransport)).await;
+        let test_batch = Batch {
+            artifacts: vec![BatchArtifact {
+                url: String::from("http://test-transport.invalid/test_ignite_with_client.txt"),
+                destination: Some(PathBuf::from("/tmp/archeon_test_ignite_with_client")),
+                ..BatchArtifact::default()
+            }],
+        };
+        let test_report = test_archeon.apply(&test_batch).await;
+        assert!(test_report.results[0].error.is_none());
+        assert_eq!(
+            tokio::fs::read(test_report.results[0].file_path.as_ref().unwrap()).await?,
+            b"/test_ignite_with_client.txt",
+        );
+        tokio::fs::remove_dir_all("/tmp/archeon_test_ignite_with_client").await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply_shared_client() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        use hyper::service::{make_service_fn, service_fn};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let test_connections = Arc::new(AtomicUsize::new(0));
+        let test_counter = test_connections.to_owned();
+        let test_make_service = make_service_fn(move |_| {
+            test_counter.fetch_add(1, Ordering::SeqCst);
+            async {
+                Ok::<_, std::convert::Infallible>(service_fn(|_| async {
+                    Ok::<_, std::convert::Infallible>(hyper::Response::new(hyper::Body::from(
+                        "test_body",
+                    )))
+                }))
+            }
+        });
+        let test_server =
+            hyper::Server::try_bind(&([127, 0, 0, 1], 0).into())?.serve(test_make_service);
+        let test_address = test_server.local_addr();
+        tokio::spawn(test_server);
+        let mut test_archeon = Archeon::ignite().await;
+        let test_batch = Batch {
+            artifacts: ["first", "second", "third", "fourth"]
+                .iter()
+                .map(|test_name| BatchArtifact {
+                    url: format!(
+                        "http://{}/test_shared_client_{}.txt",
+                        test_address, test_name
+                    ),
+                    destination: Some(PathBuf::from("/tmp/archeon_test_apply_shared_client")),
+                    ..BatchArtifact::default()
+                })
+                .collect(),
+        };
+        let test_report = test_archeon.apply(&test_batch).await;
+        assert_eq!(test_report.succeeded(), 4);
+        assert!(test_connections.load(Ordering::SeqCst) < 4);
+        tokio::fs::remove_dir_all("/tmp/archeon_test_apply_shared_client").await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply_circuit_open() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        test_server
+            .mock("HEAD", mockito::Matcher::Any)
+            .with_status(200)
+            .with_header("content-length", "0")
+            .create_async()
+            .await;
+        let mock_get_request = test_server
+            .mock("GET", mockito::Matcher::Any)
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.circuit = CircuitBreaker::init(1, std::time::Duration::from_secs(60));
+        let test_batch = Batch {
+            artifacts: ["first", "second", "third"]
+                .iter()
+                .map(|test_name| BatchArtifact {
+                    url: format!("{}/test_circuit_{}.iso", test_server.url(), test_name),
+                    destination: Some(PathBuf::from("/tmp/archeon_test_apply_circuit_open")),
+                    ..BatchArtifact::default()
+                })
+                .collect(),
+        };
+        let test_report = test_archeon.apply(&test_batch).await;
+        mock_get_request.assert_async().await;
+        assert_eq!(
+            test_report.results[0].error_kind,
+            Some(crate::error::ErrorKind::Server),
+        );
+        for test_result in &test_report.results[1..] {
+            assert!(test_result
+                .error
+                .as_deref()
+                .unwrap_or_default()
+                .contains("Circuit open"));
+            assert_eq!(
+                test_result.error_kind,
+                Some(crate::error::ErrorKind::Network),
+            );
+        }
+        tokio::fs::remove_dir_all("/tmp/archeon_test_apply_circuit_open").await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply_mirror() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        use crate::testing::{MockArtifact, MockServer};
+
+        let test_best = MockServer::start().await?;
+        let test_other = MockServer::start().await?;
+        test_best.add("debian/test_mirror.txt", MockArtifact::sized(16));
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.mirrors = vec![test_best.url("debian/"), test_other.url("debian/")];
+        let test_batch = Batch {
+            artifacts: vec![BatchArtifact {
+                url: test_other.url("debian/test_mirror.txt"),
+                destination: Some(PathBuf::from("/tmp/archeon_test_apply_mirror")),
+                ..BatchArtifact::default()
+            }],
+        };
+        let test_report = test_archeon.apply(&test_batch).await;
+        assert_eq!(test_report.succeeded(), 1);
+        assert_eq!(test_best.hits("debian/test_mirror.txt"), 1);
+        assert_eq!(test_other.hits("debian/test_mirror.txt"), 0);
+        tokio::fs::remove_dir_all("/tmp/archeon_test_apply_mirror").await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn recover() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        use crate::testing::{MockArtifact, MockServer};
+
+        let test_server = MockServer::start().await?;
+        let test_destination = std::env::temp_dir().join("archeon_test_recover");
+        let test_url = test_server.add(
+            "test_recover.bin",
+            MockArtifact {
+                drop_after: Some(1024),
+                ..MockArtifact::sized(4096)
+            },
+        );
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.configure(Profile {
+            destination: Some(test_destination.to_owned()),
+            ..Profile::default()
+        })?;
+        let test_batch = Batch {
+            artifacts: vec![BatchArtifact {
+                url: test_url.to_owned(),
+                ..BatchArtifact::default()
+            }],
+        };
+        assert_eq!(test_archeon.apply(&test_batch).await.failed(), 1);
+        let test_interrupted = test_archeon.interrupted().await;
+        assert_eq!(test_interrupted.len(), 1);
+        assert_eq!(test_interrupted[0].url, test_url);
+        assert_eq!(
+            test_interrupted[0].destination.as_ref(),
+            Some(&test_destination)
+        );
+        test_server.add("test_recover.bin", MockArtifact::sized(4096));
+        let test_report = test_archeon.recover().await;
+        assert_eq!(test_report.succeeded(), 1);
+        assert_eq!(
+            tokio::fs::read(test_destination.join("test_recover.bin")).await?,
+            MockArtifact::sized(4096).body,
+        );
+        assert!(test_archeon.interrupted().await.is_empty());
+        tokio::fs::remove_dir_all(&test_destination).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_scheduled() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_root = std::env::temp_dir().join("archeon_test_launch_scheduled_cache");
+        let test_destination = std::env::temp_dir().join("archeon_test_launch_scheduled");
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.cache = Some(Cache::init(&test_root).await?);
+        let mock_get_request = test_server
+            .mock("GET", "/test_archeon_scheduled.txt")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_header("etag", "\"test_etag\"")
+            .with_body(b"test_body")
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_conditional_request = test_server
+            .mock("GET", "/test_archeon_scheduled.txt")
+            .match_header("if-none-match", "\"test_etag\"")
+            .with_status(304)
+            .with_header("etag", "\"test_etag\"")
+            .expect(1)
+            .create_async()
+            .await;
+        let test_artifact = BatchArtifact {
+            url: format!("{}/test_archeon_scheduled.txt", test_server.url()),
+            destination: Some(test_destination.to_owned()),
+            ..BatchArtifact::default()
+        };
+        test_archeon
+            .scheduler
+            .add(test_artifact, "@every 60s".parse()?, 1000);
+        assert!(test_archeon.launch_scheduled(1059).await.results.is_empty());
+        assert_eq!(test_archeon.launch_scheduled(1060).await.succeeded(), 1);
+        assert_eq!(test_archeon.launch_scheduled(1120).await.succeeded(), 1);
+        mock_get_request.assert_async().await;
+        mock_conditional_request.assert_async().await;
+        assert_eq!(
+            tokio::fs::read(test_destination.join("test_archeon_scheduled.txt")).await?,
+            b"test_body",
+        );
+        tokio::fs::remove_dir_all(&test_root).await?;
+        tokio::fs::remove_dir_all(&test_destination).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sbom() {
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.manifest.installations.push(test_installation(
+            0,
+            "http://test-sbom/archeon-test-package-not-installed.deb",
+            "test_checksum",
+        ));
+        let test_sbom = test_archeon.sbom().await;
+        assert_eq!(test_sbom.components.len(), 1);
+        assert_eq!(
+            test_sbom.components[0].name,
+            "archeon-test-package-not-installed",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_archeon_verify");
+        let test_manifest = test_dir.join("manifest.json");
+        let mut test_archeon = Archeon::ignite_with_manifest(&test_manifest).await?;
+        let mut test_installation = test_installation(
+            0,
+            "http://test-verify/archeon-test-package-not-installed.deb",
+            "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6",
+        );
+        test_installation.artifact = test_dir.join("archeon-test-package-not-installed.deb");
+        test_archeon.manifest.record(test_installation).await?;
+        let test_results = test_archeon.verify(&test_manifest).await?;
+        assert_eq!(test_results[0].status, verify::VerifyStatus::Missing);
+        tokio::fs::write(
+            test_dir.join("archeon-test-package-not-installed.deb"),
+            b"test_body",
+        )
+        .await?;
+        let test_results = test_archeon.verify(&test_manifest).await?;
+        assert!(!test_results[0].is_drift());
+        assert!(test_archeon
+            .verify(&test_dir.join("missing.json"))
+            .await
+            .is_err());
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn repair() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_server = crate::testing::MockServer::start().await?;
+        let test_artifact = crate::testing::MockArtifact::sized(32 * 1024);
+        let test_body = test_artifact.body.to_owned();
+        let test_url = test_server.add("test_archeon_repair.deb", test_artifact);
+        let test_dir = std::env::temp_dir().join("archeon_test_archeon_repair");
+        let test_manifest = test_dir.join("manifest.json");
+        let mut test_archeon = Archeon::ignite_with_manifest(&test_manifest).await?;
+        let mut test_installation =
+            test_installation(0, &test_url, &checksum::sha256_bytes(&test_body));
+        test_installation.artifact = test_dir.join("test_archeon_repair.deb");
+        test_archeon.manifest.record(test_installation).await?;
+        let mut test_damaged = test_body.to_vec();
+        test_damaged[100] ^= 0xff;
+        tokio::fs::write(test_dir.join("test_archeon_repair.deb"), &test_damaged).await?;
+        let test_results = test_archeon.verify(&test_manifest).await?;
+        assert_eq!(test_results[0].status, verify::VerifyStatus::Mismatch);
+        let test_summary = test_archeon.repair(&test_results[0], None).await?;
+        assert!(test_summary.refetched);
+        assert_eq!(test_summary.damaged, vec![(0, 32 * 1024 - 1)]);
+        assert!(!test_archeon.verify(&test_manifest).await?[0].is_drift());
+        let mut test_unknown = test_results[0].to_owned();
+        test_unknown.url = None;
+        assert!(test_archeon.repair(&test_unknown, None).await.is_err());
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rollback() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.manifest.installations.push(test_installation(
+            0,
+            "http://test-rollback/archeon-test-package-not-installed.deb",
+            "test_checksum",
+        ));
+        let test_error = test_archeon.rollback(1).await.unwrap_err();
+        assert_eq!(test_error.kind(), ErrorKind::NotFound);
+        assert_eq!(test_archeon.manifest.installations.len(), 1);
+        test_archeon.rollback(0).await?;
+        assert!(test_archeon.manifest.installations.is_empty());
+        Ok(())
     }
 }
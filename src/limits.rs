@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio::time::{sleep_until, Instant};
+
+pub const DEFAULT_BUFFER_LIMIT: usize = 8 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostLimit {
+    pub max_connections: Option<usize>,
+    pub bytes_per_second: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HostLimiter {
+    inner: Arc<StdMutex<HostLimiterInner>>,
+}
+
+#[derive(Debug, Default)]
+struct HostLimiterInner {
+    default: HostLimit,
+    hosts: HashMap<String, HostLimit>,
+    states: HashMap<String, Arc<HostState>>,
+}
+
+#[derive(Debug)]
+struct HostState {
+    limit: HostLimit,
+    connections: Option<Arc<Semaphore>>,
+    next: Mutex<Instant>,
+}
+
+#[derive(Debug)]
+pub struct HostPermit {
+    state: Arc<HostState>,
+    _connection: Option<OwnedSemaphorePermit>,
+}
+
+#[derive(Clone, Debug)]
+pub struct BufferLimit {
+    limit: usize,
+    semaphore: Arc<Semaphore>,
+    buffered: Arc<AtomicUsize>,
+    high_watermark: Arc<AtomicUsize>,
+}
+
+#[derive(Debug)]
+pub struct BufferPermit {
+    bytes: usize,
+    buffered: Arc<AtomicUsize>,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl HostLimiter {
+    pub fn init(default: HostLimit) -> HostLimiter {
+        let limiter = HostLimiter::default();
+
+        limiter.lock().default = default;
+
+        limiter
+    }
+
+    pub fn set(&self, host: &str, limit: HostLimit) {
+        let mut inner = self.lock();
+        let host = host.to_ascii_lowercase();
+
+        inner.states.remove(&host);
+        inner.hosts.insert(host, limit);
+    }
+
+    pub fn limit(&self, host: &str) -> HostLimit {
+        let inner = self.lock();
+
+        inner
+            .hosts
+            .get(&host.to_ascii_lowercase())
+            .copied()
+            .unwrap_or(inner.default)
+    }
+
+    pub async fn acquire(&self, host: &str) -> HostPermit {
+        let state = self.launch_state(host);
+        let connection = match &state.connections {
+            Some(connections) => connections.to_owned().acquire_owned().await.ok(),
+            None => None,
+        };
+
+        HostPermit {
+            state,
+            _connection: connection,
+        }
+    }
+
+    fn launch_state(&self, host: &str) -> Arc<HostState> {
+        let mut inner = self.lock();
+        let host = host.to_ascii_lowercase();
+        let limit = inner.hosts.get(&host).copied().unwrap_or(inner.default);
+
+        inner
+            .states
+            .entry(host)
+            .or_insert_with(|| {
+                Arc::new(HostState {
+                    limit,
+                    connections: limit
+                        .max_connections
+                        .map(|max_connections| Arc::new(Semaphore::new(max_connections.max(1)))),
+                    next: Mutex::new(Instant::now()),
+                })
+            })
+            .to_owned()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HostLimiterInner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl HostPermit {
+    pub async fn throttle(&self, bytes: usize) {
+        let bytes_per_second = match self.state.limit.bytes_per_second {
+            Some(bytes_per_second) if bytes_per_second > 0 => bytes_per_second,
+            _ => return,
+        };
+        let mut next = self.state.next.lock().await;
+        let start = (*next).max(Instant::now());
+
+        *next = start + Duration::from_secs_f64(bytes as f64 / bytes_per_second as f64);
+
+        drop(next);
+
+        sleep_until(start).await;
+    }
+}
+
+impl BufferLimit {
+    pub fn init(limit: usize) -> BufferLimit {
+        let limit = limit.clamp(1, Semaphore::MAX_PERMITS.min(u32::MAX as usize));
+
+        BufferLimit {
+            limit,
+            semaphore: Arc::new(Semaphore::new(limit)),
+            buffered: Arc::new(AtomicUsize::new(0)),
+            high_watermark: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.buffered.load(Ordering::SeqCst)
+    }
+
+    pub fn high_watermark(&self) -> usize {
+        self.high_watermark.load(Ordering::SeqCst)
+    }
+
+    pub async fn reserve(&self, bytes: usize) -> BufferPermit {
+        let permits = bytes.min(self.limit) as u32;
+        let permit = self
+            .semaphore
+            .to_owned()
+            .acquire_many_owned(permits)
+            .await
+            .ok();
+        let buffered = self.buffered.fetch_add(bytes, Ordering::SeqCst) + bytes;
+
+        self.high_watermark.fetch_max(buffered, Ordering::SeqCst);
+
+        BufferPermit {
+            bytes,
+            buffered: self.buffered.to_owned(),
+            _permit: permit,
+        }
+    }
+}
+
+impl Default for BufferLimit {
+    fn default() -> BufferLimit {
+        BufferLimit::init(DEFAULT_BUFFER_LIMIT)
+    }
+}
+
+impl Drop for BufferPermit {
+    fn drop(&mut self) {
+        self.buffered.fetch_sub(self.bytes, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn limit() {
+        let test_default = HostLimit {
+            max_connections: Some(4),
+            bytes_per_second: None,
+        };
+        let test_mirror = HostLimit {
+            max_connections: Some(1),
+            bytes_per_second: Some(1024),
+        };
+        let test_limiter = HostLimiter::init(test_default);
+        test_limiter.set("Mirror.Test:8080", test_mirror);
+        assert_eq!(test_limiter.limit("mirror.test:8080"), test_mirror);
+        assert_eq!(test_limiter.limit("other.test"), test_default);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn acquire() {
+        let test_limiter = HostLimiter::default();
+        test_limiter.set(
+            "mirror.test",
+            HostLimit {
+                max_connections: Some(1),
+                bytes_per_second: None,
+            },
+        );
+        let test_permit = test_limiter.acquire("mirror.test").await;
+        let test_other = test_limiter.to_owned();
+        let test_waiting = tokio::spawn(async move { test_other.acquire("mirror.test").await });
+        let _test_unlimited = test_limiter.acquire("other.test").await;
+        let _test_unlimited_again = test_limiter.acquire("other.test").await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!test_waiting.is_finished());
+        drop(test_permit);
+        assert!(tokio::time::timeout(Duration::from_secs(1), test_waiting)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn throttle() {
+        let test_limiter = HostLimiter::init(HostLimit {
+            max_connections: None,
+            bytes_per_second: Some(100),
+        });
+        let test_start = Instant::now();
+        let test_first = test_limiter.acquire("mirror.test").await;
+        let test_second = test_limiter.acquire("mirror.test").await;
+        test_first.throttle(100).await;
+        test_second.throttle(100).await;
+        test_first.throttle(100).await;
+        assert!(test_start.elapsed() >= Duration::from_secs(2));
+        let test_unthrottled = HostLimiter::default().acquire("mirror.test").await;
+        let test_start = Instant::now();
+        test_unthrottled.throttle(1_000_000).await;
+        assert_eq!(test_start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reserve() {
+        let test_buffer = BufferLimit::init(100);
+        let test_first = test_buffer.reserve(60).await;
+        let test_other = test_buffer.to_owned();
+        let test_waiting = tokio::spawn(async move { test_other.reserve(60).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!test_waiting.is_finished());
+        assert_eq!(test_buffer.buffered(), 60);
+        drop(test_first);
+        let test_second = tokio::time::timeout(Duration::from_secs(1), test_waiting)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(test_buffer.buffered(), 60);
+        drop(test_second);
+        let test_oversized = test_buffer.reserve(500).await;
+        assert_eq!(test_buffer.buffered(), 500);
+        drop(test_oversized);
+        assert_eq!(test_buffer.buffered(), 0);
+        assert_eq!(test_buffer.high_watermark(), 500);
+        assert_eq!(BufferLimit::init(0).limit(), 1);
+        let test_large = BufferLimit::init(8 << 30);
+        assert_eq!(test_large.limit(), u32::MAX as usize);
+        drop(test_large.reserve(8 << 30).await);
+        assert_eq!(test_large.high_watermark(), 8 << 30);
+        assert_eq!(BufferLimit::default().limit(), DEFAULT_BUFFER_LIMIT);
+    }
+}
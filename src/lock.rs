@@ -0,0 +1,130 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::time::sleep;
+
+pub const LOCK_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Debug)]
+pub struct FileLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl FileLock {
+    pub fn path(target: &Path) -> PathBuf {
+        let mut path = target.as_os_str().to_owned();
+
+        path.push(".lock");
+
+        PathBuf::from(path)
+    }
+
+    pub fn try_acquire(target: &Path) -> Result<Option<FileLock>, Error> {
+        let path = Self::path(target);
+
+        loop {
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&path)?;
+
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(TryLockError::WouldBlock) => return Ok(None),
+                Err(TryLockError::Error(error)) => return Err(error),
+            }
+
+            if is_current(&path, &file)? {
+                return Ok(Some(FileLock { path, file }));
+            }
+        }
+    }
+
+    pub async fn acquire(target: &Path) -> Result<FileLock, Error> {
+        loop {
+            if let Some(lock) = Self::try_acquire(target)? {
+                return Ok(lock);
+            }
+
+            sleep(LOCK_POLL).await;
+        }
+    }
+
+    pub fn try_exclusive(target: &Path) -> Result<FileLock, Error> {
+        match Self::try_acquire(target)? {
+            Some(lock) => Ok(lock),
+            None => {
+                let error = format!("{} is in use by another archeon process!", target.display());
+
+                Err(Error::new(ErrorKind::WouldBlock, error))
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(&self.path);
+
+        let _ = self.file.unlock();
+    }
+}
+
+#[cfg(unix)]
+fn is_current(path: &Path, file: &File) -> Result<bool, Error> {
+    use std::os::unix::fs::MetadataExt;
+
+    let locked = file.metadata()?;
+
+    match std::fs::metadata(path) {
+        Ok(current) => Ok(locked.dev() == current.dev() && locked.ino() == current.ino()),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(not(unix))]
+fn is_current(_: &Path, _: &File) -> Result<bool, Error> {
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn path() {
+        assert_eq!(
+            FileLock::path(Path::new("/tmp/archeon/test.iso")),
+            PathBuf::from("/tmp/archeon/test.iso.lock"),
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn acquire() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_lock.iso");
+        let test_lock = FileLock::acquire(&test_path).await?;
+        assert!(FileLock::path(&test_path).exists());
+        assert!(FileLock::try_acquire(&test_path)?.is_none());
+        assert_eq!(
+            FileLock::try_exclusive(&test_path).unwrap_err().kind(),
+            ErrorKind::WouldBlock,
+        );
+        let test_waiting = tokio::spawn(async move { FileLock::acquire(&test_path).await });
+        tokio::time::sleep(LOCK_POLL * 2).await;
+        assert!(!test_waiting.is_finished());
+        drop(test_lock);
+        let test_lock = test_waiting.await??;
+        let test_path = std::env::temp_dir().join("archeon_test_lock.iso");
+        assert!(FileLock::path(&test_path).exists());
+        drop(test_lock);
+        assert!(!FileLock::path(&test_path).exists());
+        assert!(FileLock::try_acquire(&test_path)?.is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,1351 @@
+use archeon::batch::{parse_tag, Batch, BatchArtifact, BatchReport, BatchResult, Priority};
+use archeon::bench::{self, BenchResult};
+use archeon::cache::Retention;
+use archeon::checksum::Algorithm;
+use archeon::config::{default_config_path, Config};
+use archeon::control::{default_socket_path, ControlClient, ControlResponse};
+#[cfg(feature = "journal")]
+use archeon::daemon::JobStatus;
+use archeon::daemon::{Daemon, Job, QueueState};
+use archeon::durability::Durability;
+use archeon::error::{exit_code, ArcheonError, ErrorKind, EXIT_USAGE};
+use archeon::filter::PathFilter;
+use archeon::grpc::GrpcService;
+use archeon::installer::{Backend, Conffile, Escalation, Installer};
+#[cfg(feature = "journal")]
+use archeon::journal::{default_journal_path, HistoryFilter, Journal};
+use archeon::manifest::Outdated;
+use archeon::mirror::{default_ranking_path, MirrorRanking};
+use archeon::overwrite::OverwritePolicy;
+use archeon::partial::PartialPolicy;
+use archeon::recipe::Recipe;
+use archeon::resume::ResumeCheck;
+use archeon::shutdown::{wait_for_signal, Shutdown, SHUTDOWN_GRACE};
+use archeon::source::index::{DirectoryIndex, DEFAULT_DEPTH};
+use archeon::template::parse_variable;
+use archeon::verify::{self, Verification, VerifyStatus};
+#[cfg(unix)]
+use archeon::wire::toggle_on_signal;
+use archeon::Archeon;
+
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(name = "archeon", version, about)]
+struct Cli {
+    #[arg(
+        long,
+        global = true,
+        help = "Path of the daemon control socket, or ARCHEON_SOCKET"
+    )]
+    socket: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        help = "Path of the configuration file, or ARCHEON_CONFIG"
+    )]
+    config: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        help = "Name of the configuration profile, or ARCHEON_PROFILE"
+    )]
+    profile: Option<String>,
+    #[arg(long, global = true, help = "HTTP proxy, or ARCHEON_PROXY")]
+    proxy: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        help = "Connect timeout in seconds, or ARCHEON_CONNECT_TIMEOUT"
+    )]
+    connect_timeout: Option<u64>,
+    #[arg(
+        long,
+        global = true,
+        help = "Maximum connections per host, or ARCHEON_CONCURRENCY"
+    )]
+    concurrency: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        help = "Maximum bytes buffered in memory while downloading, or ARCHEON_BUFFER_LIMIT"
+    )]
+    buffer_limit: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        help = "Bytes collected before each write to disk, or ARCHEON_WRITE_BUFFER"
+    )]
+    write_buffer: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        help = "none, close or periodic fsync of downloads, or ARCHEON_DURABILITY"
+    )]
+    durability: Option<Durability>,
+    #[arg(
+        long,
+        global = true,
+        help = "Parallel ranged requests per download, or ARCHEON_SEGMENTS"
+    )]
+    segments: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        help = "none, trailing or full check of partial files before resuming, or ARCHEON_RESUME_CHECK"
+    )]
+    resume_check: Option<ResumeCheck>,
+    #[arg(
+        long,
+        global = true,
+        help = "delete, keep or quarantine partial downloads on error or cancel, or ARCHEON_PARTIAL"
+    )]
+    partial: Option<PartialPolicy>,
+    #[arg(
+        long,
+        global = true,
+        help = "Seconds to keep partial downloads for resuming, or ARCHEON_PARTIAL_TTL"
+    )]
+    partial_ttl: Option<u64>,
+    #[arg(
+        long,
+        global = true,
+        help = "Directory for quarantined partial downloads, or ARCHEON_QUARANTINE_DIR"
+    )]
+    quarantine_dir: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        help = "delete, keep or cache artifacts after installing them, or ARCHEON_RETENTION"
+    )]
+    retention: Option<Retention>,
+    #[arg(
+        short = 'N',
+        long,
+        global = true,
+        help = "Skip downloads whose local file matches the server's size and date, or ARCHEON_TIMESTAMPING"
+    )]
+    timestamping: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "error, overwrite, skip or rename-unique when the destination exists, or ARCHEON_OVERWRITE"
+    )]
+    overwrite: Option<OverwritePolicy>,
+    #[arg(
+        short,
+        long,
+        global = true,
+        help = "Hide progress output, or ARCHEON_QUIET"
+    )]
+    quiet: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Log HTTP request and response headers with secrets redacted, or ARCHEON_DEBUG_HTTP"
+    )]
+    debug_http: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Record requests, responses and timings to an HTTP Archive file, or ARCHEON_HAR"
+    )]
+    har: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        help = "Append a JSON line per download, install and uninstall to this file, or ARCHEON_AUDIT_LOG"
+    )]
+    audit_log: Option<PathBuf>,
+    #[arg(
+        long = "var",
+        global = true,
+        value_name = "NAME=VALUE",
+        value_parser = parse_variable,
+        help = "Template variable for {name} placeholders in URLs, or ARCHEON_VAR_<NAME>"
+    )]
+    variables: Vec<(String, String)>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    #[command(about = "Run the long-lived archeon daemon")]
+    Daemon {
+        #[arg(long, help = "Address for the REST API")]
+        listen: Option<SocketAddr>,
+        #[arg(long, help = "Address for the gRPC API")]
+        grpc: Option<SocketAddr>,
+    },
+    #[command(about = "Queue a download on the running daemon")]
+    Add {
+        url: String,
+        #[arg(long)]
+        checksum: Option<String>,
+        #[arg(
+            long,
+            help = "sha256, sha512, blake3, md5 or crc32, detected from the checksum by default"
+        )]
+        algorithm: Option<Algorithm>,
+        #[arg(long)]
+        destination: Option<PathBuf>,
+        #[arg(long)]
+        install: bool,
+        #[arg(long, default_value_t = Priority::Normal, help = "high, normal or low")]
+        priority: Priority,
+        #[arg(
+            long = "tag",
+            value_name = "KEY=VALUE",
+            value_parser = parse_tag,
+            help = "Metadata tag carried into jobs, the journal, audit log and hooks, repeatable"
+        )]
+        tags: Vec<(String, String)>,
+    },
+    #[command(about = "Download URLs given as arguments, in a file or on stdin")]
+    Get {
+        urls: Vec<String>,
+        #[arg(
+            short = 'i',
+            long,
+            help = "Read newline-delimited URLs from a file, or - for stdin"
+        )]
+        input_file: Option<PathBuf>,
+        #[arg(long)]
+        destination: Option<PathBuf>,
+    },
+    #[command(about = "Resume every interrupted download left in the staging directories")]
+    Recover,
+    #[command(about = "Download, verify and install a package in one step")]
+    Install {
+        url: String,
+        #[arg(long)]
+        checksum: Option<String>,
+        #[arg(
+            long,
+            help = "sha256, sha512, blake3, md5 or crc32, detected from the checksum by default"
+        )]
+        algorithm: Option<Algorithm>,
+        #[arg(long)]
+        destination: Option<PathBuf>,
+        #[arg(long, help = "sudo, doas, pkexec or none")]
+        escalation: Option<Escalation>,
+        #[arg(long, help = "old, new, default or unset")]
+        conffile: Option<Conffile>,
+        #[arg(long, help = "dpkg, cargo, pip or rpm")]
+        backend: Option<Backend>,
+        #[arg(
+            long,
+            help = "Installer plugin name or path, run as archeon-installer-<name>"
+        )]
+        plugin: Option<String>,
+        #[arg(long, help = "Path of the installation manifest")]
+        manifest: Option<PathBuf>,
+        #[arg(long, help = "Delete the downloaded artifact after installing")]
+        delete: bool,
+        #[arg(
+            long = "post-install",
+            help = "Shell command to run after a successful install, repeatable"
+        )]
+        post_install: Vec<String>,
+        #[arg(
+            long = "tag",
+            value_name = "KEY=VALUE",
+            value_parser = parse_tag,
+            help = "Metadata tag carried into the audit log and hooks, repeatable"
+        )]
+        tags: Vec<(String, String)>,
+    },
+    #[command(about = "Measure download throughput for each segment count and write buffer")]
+    Bench {
+        #[arg(help = "URL to download, or synthetic data when built with the testing feature")]
+        url: Option<String>,
+        #[arg(long, default_value_t = 64 * 1024 * 1024, help = "Bytes of synthetic data")]
+        size: usize,
+        #[arg(long, value_delimiter = ',', default_values_t = [1, 4])]
+        segments: Vec<usize>,
+        #[arg(long, value_delimiter = ',', default_values_t = [8 * 1024, 256 * 1024])]
+        write_buffers: Vec<usize>,
+    },
+    #[command(about = "Report installed artifacts with newer upstream releases")]
+    Outdated {
+        #[arg(long, help = "Path of the installation manifest")]
+        manifest: PathBuf,
+    },
+    #[command(about = "Hash downloaded or installed artifacts against a checksum manifest")]
+    Verify {
+        #[arg(help = "SHA256SUMS-style file or installation manifest")]
+        manifest: PathBuf,
+        #[arg(long, help = "Re-fetch the damaged regions of mismatched artifacts")]
+        repair: bool,
+        #[arg(long, help = "URL the manifest's relative paths are served under")]
+        base_url: Option<String>,
+    },
+    #[command(about = "Download, verify and install a tool from a recipe")]
+    Recipe {
+        #[arg(help = "Path of the recipe TOML file")]
+        path: PathBuf,
+    },
+    #[command(about = "Show queued, running and finished jobs")]
+    Status { id: Option<u64> },
+    #[command(about = "Cancel a queued or running job")]
+    Cancel { id: u64 },
+    #[command(about = "Export or import the daemon's pending queue")]
+    Queue {
+        #[command(subcommand)]
+        action: QueueAction,
+    },
+    #[cfg(feature = "tui")]
+    #[command(about = "Watch and manage daemon jobs in an interactive dashboard")]
+    Tui,
+    #[cfg(feature = "keyring")]
+    #[command(about = "Store, show or remove per-host tokens in the OS keyring")]
+    Credentials {
+        #[command(subcommand)]
+        action: CredentialsAction,
+    },
+    #[cfg(feature = "journal")]
+    #[command(about = "List past transfers from the journal, or show one job in detail")]
+    History {
+        id: Option<u64>,
+        #[arg(long, help = "Only transfers from this host")]
+        host: Option<String>,
+        #[arg(long, help = "Only transfers with this status")]
+        status: Option<JobStatus>,
+        #[arg(long, help = "Only transfers updated at or after this unix timestamp")]
+        since: Option<u64>,
+        #[arg(long, help = "Only transfers updated at or before this unix timestamp")]
+        until: Option<u64>,
+        #[arg(
+            long = "tag",
+            value_name = "KEY=VALUE",
+            value_parser = parse_tag,
+            help = "Only transfers with this tag, repeatable"
+        )]
+        tags: Vec<(String, String)>,
+    },
+    #[command(about = "Rank the profile's mirrors by throughput and latency")]
+    Mirrors {
+        #[command(subcommand)]
+        action: MirrorsAction,
+    },
+    #[command(about = "Download every file listed by a directory index, keeping its layout")]
+    Index {
+        url: String,
+        #[arg(
+            long,
+            default_value = ".",
+            help = "Directory to recreate the index under"
+        )]
+        destination: PathBuf,
+        #[arg(long, help = "Only download paths matching this pattern, repeatable")]
+        include: Vec<String>,
+        #[arg(long, help = "Skip paths matching this pattern, repeatable")]
+        exclude: Vec<String>,
+        #[arg(long, default_value_t = DEFAULT_DEPTH, help = "Levels of subdirectories to walk")]
+        depth: usize,
+        #[arg(
+            long,
+            help = "Follow links to other hosts, saved under a directory per host"
+        )]
+        span_hosts: bool,
+        #[arg(
+            long,
+            help = "Stop selecting files once their sizes add up to this many bytes"
+        )]
+        max_bytes: Option<u64>,
+    },
+    #[command(about = "Print shell completions for bash, zsh, fish, elvish or powershell")]
+    Completions { shell: Shell },
+}
+
+#[cfg(feature = "keyring")]
+#[derive(Debug, Subcommand)]
+enum CredentialsAction {
+    #[command(about = "Store a token for a host, read from stdin when omitted")]
+    Set { host: String, token: Option<String> },
+    #[command(about = "Print whether a token is stored for a host")]
+    Get { host: String },
+    #[command(about = "Remove the token stored for a host")]
+    Delete { host: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum QueueAction {
+    #[command(about = "Write the daemon's pending jobs as JSON")]
+    Export {
+        #[arg(long, help = "File to write instead of stdout")]
+        output: Option<PathBuf>,
+    },
+    #[command(about = "Queue the jobs from an exported JSON file")]
+    Import { path: PathBuf },
+}
+
+#[derive(Debug, Subcommand)]
+enum MirrorsAction {
+    #[command(about = "Download from every mirror and save the ranking for later transfers")]
+    Test {
+        #[arg(long, default_value = "", help = "Path under each mirror to download")]
+        path: String,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(error) => {
+            let _ = error.print();
+
+            return match error.use_stderr() {
+                true => ExitCode::from(EXIT_USAGE),
+                false => ExitCode::SUCCESS,
+            };
+        }
+    };
+
+    let shutdown = Shutdown::default();
+    let run = run(cli, shutdown.to_owned());
+
+    tokio::pin!(run);
+
+    let result = tokio::select! {
+        result = &mut run => result,
+        signal = wait_for_signal() => match signal {
+            Ok(signal) => {
+                eprintln!("archeon: shutting down, press Ctrl-C again to exit immediately");
+
+                shutdown.trigger();
+
+                tokio::select! {
+                    _ = tokio::time::timeout(SHUTDOWN_GRACE, &mut run) => {}
+                    _ = wait_for_signal() => {}
+                }
+
+                return ExitCode::from(signal.exit_code());
+            }
+            Err(error) => {
+                eprintln!("archeon: {}", error);
+
+                run.await
+            }
+        },
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("archeon: {}", error);
+
+            ExitCode::from(exit_code(error.as_ref()))
+        }
+    }
+}
+
+async fn ignite(shutdown: &Shutdown) -> Archeon {
+    let mut archeon = Archeon::ignite().await;
+
+    archeon.shutdown = shutdown.to_owned();
+
+    #[cfg(unix)]
+    tokio::spawn(toggle_on_signal(archeon.wire.to_owned()));
+
+    archeon
+}
+
+async fn run(cli: Cli, shutdown: Shutdown) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let socket = cli
+        .socket
+        .or_else(|| std::env::var_os("ARCHEON_SOCKET").map(PathBuf::from))
+        .unwrap_or_else(default_socket_path);
+    let config = cli
+        .config
+        .or_else(|| std::env::var_os("ARCHEON_CONFIG").map(PathBuf::from))
+        .unwrap_or_else(default_config_path);
+    let config = Config::init(&config).await?;
+    let profile = cli
+        .profile
+        .or_else(|| std::env::var("ARCHEON_PROFILE").ok());
+    let mut profile = config.profile(profile.as_deref())?;
+
+    profile.apply_env(std::env::vars())?;
+
+    if cli.proxy.is_some() {
+        profile.proxy = cli.proxy;
+    }
+
+    if cli.connect_timeout.is_some() {
+        profile.connect_timeout = cli.connect_timeout;
+    }
+
+    if cli.concurrency.is_some() {
+        profile.concurrency = cli.concurrency;
+    }
+
+    if cli.buffer_limit.is_some() {
+        profile.buffer_limit = cli.buffer_limit;
+    }
+
+    if cli.write_buffer.is_some() {
+        profile.write_buffer = cli.write_buffer;
+    }
+
+    if cli.durability.is_some() {
+        profile.durability = cli.durability;
+    }
+
+    if cli.segments.is_some() {
+        profile.segments = cli.segments;
+    }
+
+    if cli.resume_check.is_some() {
+        profile.resume_check = cli.resume_check;
+    }
+
+    if cli.partial.is_some() {
+        profile.partial = cli.partial;
+    }
+
+    if cli.partial_ttl.is_some() {
+        profile.partial_ttl = cli.partial_ttl;
+    }
+
+    if cli.quarantine_dir.is_some() {
+        profile.quarantine_dir = cli.quarantine_dir;
+    }
+
+    if cli.retention.is_some() {
+        profile.retention = cli.retention;
+    }
+
+    if cli.overwrite.is_some() {
+        profile.overwrite = cli.overwrite;
+    }
+
+    if cli.timestamping {
+        profile.timestamping = Some(true);
+    }
+
+    if cli.quiet {
+        profile.quiet = Some(true);
+    }
+
+    if cli.debug_http {
+        profile.debug_http = Some(true);
+    }
+
+    if cli.har.is_some() {
+        profile.har = cli.har;
+    }
+
+    if cli.audit_log.is_some() {
+        profile.audit_log = cli.audit_log;
+    }
+
+    profile.variables.extend(cli.variables);
+
+    match cli.command {
+        Command::Daemon { listen, grpc } => {
+            let remote = profile.remote.to_owned().unwrap_or_default();
+            let mut archeon = ignite(&shutdown).await;
+
+            archeon.configure(profile)?;
+
+            let interrupted = archeon.interrupted().await;
+
+            #[cfg(feature = "journal")]
+            let mut daemon = {
+                let journal = Journal::open_exclusive(&default_journal_path())?;
+
+                Daemon::init_with_journal(archeon, journal).await?
+            };
+            #[cfg(not(feature = "journal"))]
+            let mut daemon = Daemon::init(archeon).await;
+
+            daemon.remote = remote;
+
+            daemon.requeue(interrupted).await;
+
+            if let Some(address) = listen {
+                if !address.ip().is_loopback() {
+                    eprintln!(
+                        "archeon: anyone who can reach the REST API on {} can queue downloads",
+                        address,
+                    );
+                }
+
+                let (_, server) = daemon.bind(address)?;
+
+                tokio::spawn(server);
+            }
+
+            if let Some(address) = grpc {
+                if !address.ip().is_loopback() {
+                    eprintln!(
+                        "archeon: anyone who can reach the gRPC API on {} can queue downloads",
+                        address,
+                    );
+                }
+
+                let (_, server) = GrpcService::init(daemon.to_owned()).bind(address).await?;
+
+                tokio::spawn(async move {
+                    if let Err(error) = server.await {
+                        eprintln!("archeon: the gRPC API on {} stopped: {}", address, error);
+                    }
+                });
+            }
+
+            tokio::select! {
+                result = daemon.serve_unix(&socket) => result?,
+                _ = shutdown.triggered() => {
+                    daemon.drain().await;
+
+                    let _ = tokio::fs::remove_file(&socket).await;
+                }
+            }
+        }
+        Command::Add {
+            url,
+            checksum,
+            algorithm,
+            destination,
+            install,
+            priority,
+            tags,
+        } => {
+            let artifact = BatchArtifact {
+                url,
+                checksum,
+                algorithm,
+                destination,
+                install,
+                priority,
+                tags: tags.into_iter().collect(),
+                ..BatchArtifact::default()
+            };
+
+            print_response(ControlClient::init(&socket).add(artifact).await?)?;
+        }
+        Command::Get {
+            urls,
+            input_file,
+            destination,
+        } => {
+            let mut batch = Batch::parse_urls(&urls.join("\n"));
+            let input = match input_file {
+                Some(path) if path.as_os_str() == "-" => Some(read_stdin()?),
+                Some(path) => Some(std::fs::read_to_string(path)?),
+                None if urls.is_empty() => Some(read_stdin()?),
+                None => None,
+            };
+
+            if let Some(input) = input {
+                batch.artifacts.extend(Batch::parse_urls(&input).artifacts);
+            }
+
+            for artifact in batch.artifacts.iter_mut() {
+                artifact.destination = destination.to_owned();
+            }
+
+            let mut archeon = ignite(&shutdown).await;
+
+            archeon.configure(profile)?;
+
+            print_report(&archeon.apply(&batch).await)?;
+        }
+        Command::Recover => {
+            let mut archeon = ignite(&shutdown).await;
+
+            archeon.configure(profile)?;
+
+            print_report(&archeon.recover().await)?;
+        }
+        Command::Install {
+            url,
+            checksum,
+            algorithm,
+            destination,
+            escalation,
+            conffile,
+            backend,
+            plugin,
+            manifest,
+            delete,
+            post_install,
+            tags,
+        } => {
+            let mut archeon = match manifest {
+                Some(manifest) => Archeon::ignite_with_manifest(&manifest).await?,
+                None => ignite(&shutdown).await,
+            };
+
+            archeon.shutdown = shutdown.to_owned();
+
+            archeon.configure(profile)?;
+            let mut installer = Installer::default();
+
+            if let Some(escalation) = escalation {
+                installer.escalation = escalation;
+            }
+
+            if let Some(conffile) = conffile {
+                installer.conffile = conffile;
+            }
+
+            if let Some(backend) = backend {
+                installer.backend = backend;
+            }
+
+            installer.plugin = plugin;
+
+            let batch = Batch {
+                artifacts: vec![BatchArtifact {
+                    url,
+                    checksum,
+                    algorithm,
+                    destination,
+                    install: true,
+                    installer,
+                    delete,
+                    post_install,
+                    tags: tags.into_iter().collect(),
+                    ..BatchArtifact::default()
+                }],
+            };
+
+            for result in archeon.apply(&batch).await.results {
+                print_result(&result)?;
+            }
+        }
+        Command::Bench {
+            url,
+            size,
+            segments,
+            write_buffers,
+        } => {
+            #[cfg(feature = "testing")]
+            let server = archeon::testing::MockServer::start().await?;
+            let url = match url {
+                Some(url) => url,
+                #[cfg(feature = "testing")]
+                None => server.add("bench", archeon::testing::MockArtifact::sized(size)),
+                #[cfg(not(feature = "testing"))]
+                None => {
+                    let error = format!(
+                        "A URL is required to benchmark {} bytes without the testing feature!",
+                        size,
+                    );
+
+                    return Err(error.into());
+                }
+            };
+            let mut archeon = ignite(&shutdown).await;
+
+            archeon.configure(profile)?;
+
+            let strategies = bench::strategies(&segments, &write_buffers);
+            let directory = std::env::temp_dir().join("archeon_bench");
+            let results = bench::run(&archeon, &url, &strategies, &directory).await?;
+
+            let _ = std::fs::remove_dir(&directory);
+
+            results.iter().for_each(print_bench);
+        }
+        Command::Outdated { manifest } => {
+            let mut archeon = Archeon::ignite_with_manifest(&manifest).await?;
+
+            archeon.shutdown = shutdown.to_owned();
+
+            archeon.configure(profile)?;
+
+            for outdated in archeon.outdated().await {
+                print_outdated(&outdated);
+            }
+        }
+        Command::Recipe { path } => {
+            let recipe = Recipe::init(&path).await?;
+            let mut archeon = ignite(&shutdown).await;
+
+            archeon.configure(profile)?;
+
+            let report = archeon.apply_recipe(&recipe).await?;
+
+            for step in &report.steps {
+                print!("{}", step.stdout);
+                eprint!("{}", step.stderr);
+            }
+
+            for installed in &report.installed {
+                println!("{}\t{}", report.source, installed.display());
+            }
+        }
+        Command::Verify {
+            manifest,
+            repair,
+            base_url,
+        } => {
+            let mut archeon = ignite(&shutdown).await;
+
+            archeon.configure(profile)?;
+
+            let mut verifications = archeon.verify(&manifest).await?;
+
+            if let (Some(base_url), Some(base)) = (base_url, manifest.parent()) {
+                verify::resolve_urls(&mut verifications, base, &base_url);
+            }
+
+            let mut drifted = 0;
+
+            for verification in &verifications {
+                print_verification(verification);
+
+                if !verification.is_drift() {
+                    continue;
+                }
+
+                if repair && verification.status == VerifyStatus::Mismatch {
+                    match archeon.repair(verification, None).await {
+                        Ok(summary) if summary.refetched => {
+                            println!(
+                                "refetched\t{}\t{} bytes downloaded again, no piece hashes to repair it in place",
+                                summary.file_path.display(),
+                                summary.fetched,
+                            );
+
+                            continue;
+                        }
+                        Ok(summary) => {
+                            println!(
+                                "repaired\t{}\t{} bytes fetched, {} regions rewritten",
+                                summary.file_path.display(),
+                                summary.fetched,
+                                summary.damaged.len()
+                            );
+
+                            continue;
+                        }
+                        Err(error) => eprintln!("{}\t{}", verification.path.display(), error),
+                    }
+                }
+
+                drifted += 1;
+            }
+
+            if drifted > 0 {
+                let error = ArcheonError::Failed {
+                    kind: ErrorKind::Integrity,
+                    message: format!("{} of {} artifacts drifted!", drifted, verifications.len()),
+                };
+
+                return Err(error.into());
+            }
+        }
+        Command::Status { id } => {
+            print_response(ControlClient::init(&socket).status(id).await?)?;
+        }
+        Command::Cancel { id } => {
+            print_response(ControlClient::init(&socket).cancel(id).await?)?;
+        }
+        Command::Queue {
+            action: QueueAction::Export { output },
+        } => match (ControlClient::init(&socket).export().await?, output) {
+            (ControlResponse::Queue { queue }, Some(output)) => {
+                tokio::fs::write(output, serde_json::to_vec_pretty(&queue)?).await?;
+            }
+            (response, _) => print_response(response)?,
+        },
+        Command::Queue {
+            action: QueueAction::Import { path },
+        } => {
+            let queue = QueueState::parse(&tokio::fs::read_to_string(path).await?)?;
+
+            print_response(ControlClient::init(&socket).import(queue).await?)?;
+        }
+        #[cfg(feature = "tui")]
+        Command::Tui => archeon::tui::run(&ControlClient::init(&socket)).await?,
+        #[cfg(feature = "keyring")]
+        Command::Credentials { action } => {
+            let store = archeon::keyring::KeyringStore::default();
+
+            match action {
+                CredentialsAction::Set { host, token } => {
+                    let token = match token {
+                        Some(token) => token,
+                        None => read_stdin()?,
+                    };
+
+                    store.set_token(&host, token.trim()).await?;
+                }
+                CredentialsAction::Get { host } => match store.token(&host).await? {
+                    Some(_) => println!("{}\tstored", host),
+                    None => println!("{}\tmissing", host),
+                },
+                CredentialsAction::Delete { host } => {
+                    if !store.delete_token(&host).await? {
+                        return Err(format!("No token stored for {}!", host).into());
+                    }
+                }
+            }
+        }
+        #[cfg(feature = "journal")]
+        Command::History {
+            id,
+            host,
+            status,
+            since,
+            until,
+            tags,
+        } => {
+            let journal = Journal::open(&default_journal_path())?;
+
+            match id {
+                Some(id) => match journal.entry(id)? {
+                    Some(entry) => println!("{}", serde_json::to_string_pretty(&entry)?),
+                    None => return Err(format!("No job {} in the journal!", id).into()),
+                },
+                None => {
+                    let filter = HistoryFilter {
+                        host,
+                        status,
+                        since,
+                        until,
+                        tags: tags.into_iter().collect(),
+                    };
+
+                    for entry in journal.history(&filter)? {
+                        print_job(&entry.job);
+                    }
+                }
+            }
+        }
+        Command::Mirrors {
+            action: MirrorsAction::Test { path },
+        } => {
+            let mirrors = profile.mirrors.to_owned();
+
+            if mirrors.is_empty() {
+                return Err("No mirrors configured in the profile or ARCHEON_MIRRORS!".into());
+            }
+
+            let mut archeon = ignite(&shutdown).await;
+
+            archeon.configure(profile)?;
+
+            let ranking = MirrorRanking::test(&archeon.client, &mirrors, &path).await;
+
+            ranking.save(&default_ranking_path()).await?;
+
+            for (rank, score) in ranking.mirrors.iter().enumerate() {
+                match &score.error {
+                    Some(error) => println!("{}\t{}\terror: {}", rank + 1, score.url, error),
+                    None => println!(
+                        "{}\t{}\t{} ms\t{} B/s",
+                        rank + 1,
+                        score.url,
+                        score.latency_ms,
+                        score.bytes_per_second,
+                    ),
+                }
+            }
+        }
+        Command::Index {
+            url,
+            destination,
+            include,
+            exclude,
+            depth,
+            span_hosts,
+            max_bytes,
+        } => {
+            let mut archeon = ignite(&shutdown).await;
+
+            archeon.configure(profile)?;
+
+            let mut index = DirectoryIndex::init(&url, archeon.client.to_owned())?;
+
+            index.filter = PathFilter { include, exclude };
+            index.depth = depth;
+            index.same_origin = !span_hosts;
+            index.max_bytes = max_bytes;
+
+            print_report(&archeon.mirror_index(&index, &destination).await?)?;
+        }
+        Command::Completions { shell } => print_completions(shell, &mut std::io::stdout()),
+    }
+
+    Ok(())
+}
+
+fn print_response(
+    response: ControlResponse,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match response {
+        ControlResponse::Job { job } => print_job(&job),
+        ControlResponse::Jobs { jobs } => jobs.iter().for_each(print_job),
+        ControlResponse::Queue { queue } => println!("{}", serde_json::to_string_pretty(&queue)?),
+        ControlResponse::Error { error } => return Err(error.into()),
+    }
+
+    Ok(())
+}
+
+fn read_stdin() -> Result<String, std::io::Error> {
+    let mut input = String::with_capacity(1024);
+
+    std::io::stdin().read_to_string(&mut input)?;
+
+    Ok(input)
+}
+
+fn print_report(report: &BatchReport) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for result in &report.results {
+        if let Err(error) = print_result(result) {
+            eprintln!("{}\t{}", result.url, error);
+        }
+    }
+
+    if report.failed() > 0 {
+        let kind = report
+            .results
+            .iter()
+            .find(|result| result.error.is_some())
+            .and_then(|result| result.error_kind)
+            .unwrap_or(ErrorKind::Local);
+        let message = format!(
+            "{} of {} downloads failed!",
+            report.failed(),
+            report.results.len()
+        );
+
+        return Err(ArcheonError::Failed { kind, message }.into());
+    }
+
+    Ok(())
+}
+
+fn print_result(result: &BatchResult) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(error) = &result.error {
+        let error = ArcheonError::Failed {
+            kind: result.error_kind.unwrap_or(ErrorKind::Local),
+            message: error.to_owned(),
+        };
+
+        return Err(error.into());
+    }
+
+    let file_path = result
+        .file_path
+        .as_ref()
+        .map(|file_path| file_path.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    match result.installation {
+        Some(installation) => println!(
+            "{}\t{}\tinstallation {}",
+            result.url, file_path, installation
+        ),
+        None => println!("{}\t{}", result.url, file_path),
+    }
+
+    for hook in &result.hooks {
+        print!("{}", hook.stdout);
+        eprint!("{}", hook.stderr);
+    }
+
+    Ok(())
+}
+
+fn print_bench(result: &BenchResult) {
+    println!(
+        "segments {}\twrite buffer {}\t{} bytes\t{:.3}s\t{} B/s",
+        result.strategy.segments,
+        result.strategy.write_buffer,
+        result.bytes,
+        result.duration.as_secs_f64(),
+        result.bytes_per_second,
+    );
+}
+
+fn print_outdated(outdated: &Outdated) {
+    match &outdated.error {
+        Some(error) => println!(
+            "{}\t{}\t{}\terror: {}",
+            outdated.id, outdated.package, outdated.installed_version, error
+        ),
+        None => println!(
+            "{}\t{}\t{} -> {}\t{}",
+            outdated.id,
+            outdated.package,
+            outdated.installed_version,
+            outdated.available_version.as_deref().unwrap_or("unknown"),
+            outdated.source
+        ),
+    }
+}
+
+fn print_verification(verification: &Verification) {
+    let detail = match (&verification.error, &verification.actual) {
+        (Some(error), _) => error.to_owned(),
+        (None, Some(actual)) if verification.is_drift() => {
+            format!("expected {} got {}", verification.expected, actual)
+        }
+        (None, Some(_)) if verification.weak => String::from("weak checksum"),
+        _ => String::new(),
+    };
+
+    println!(
+        "{}\t{}\t{}",
+        verification.status,
+        verification.path.display(),
+        detail
+    );
+}
+
+fn print_completions(shell: Shell, writer: &mut dyn Write) {
+    clap_complete::generate(shell, &mut Cli::command(), "archeon", writer);
+}
+
+fn print_job(job: &Job) {
+    let detail = job
+        .result
+        .as_ref()
+        .and_then(|result| result.error.to_owned())
+        .unwrap_or_default();
+
+    println!(
+        "{}\t{}\t{}\t{}",
+        job.id, job.status, job.artifact.url, detail
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli() {
+        let test_cli = Cli::try_parse_from([
+            "archeon",
+            "add",
+            "http://test-cli/test.deb",
+            "--install",
+            "--priority",
+            "high",
+            "--tag",
+            "ticket=OPS-42",
+            "--socket",
+            "/tmp/archeon_test_cli.sock",
+        ])
+        .unwrap();
+        assert_eq!(
+            test_cli.socket,
+            Some(PathBuf::from("/tmp/archeon_test_cli.sock"))
+        );
+        match test_cli.command {
+            Command::Add {
+                url,
+                install,
+                priority,
+                tags,
+                ..
+            } => {
+                assert_eq!(url, "http://test-cli/test.deb");
+                assert!(install);
+                assert_eq!(priority, Priority::High);
+                assert_eq!(tags, [(String::from("ticket"), String::from("OPS-42"))]);
+            }
+            test_command => panic!("unexpected command {:?}", test_command),
+        }
+        assert!(Cli::try_parse_from(["archeon", "status", "not_an_id"]).is_err());
+        assert!(Cli::try_parse_from(["archeon", "add", "url", "--priority", "urgent"]).is_err());
+        assert!(Cli::try_parse_from(["archeon", "add", "url", "--tag", "ticket"]).is_err());
+    }
+
+    #[test]
+    fn get() {
+        let test_cli =
+            Cli::try_parse_from(["archeon", "get", "-i", "urls.txt", "http://test-cli/a.iso"])
+                .unwrap();
+        match test_cli.command {
+            Command::Get {
+                urls, input_file, ..
+            } => {
+                assert_eq!(urls, ["http://test-cli/a.iso"]);
+                assert_eq!(input_file, Some(PathBuf::from("urls.txt")));
+            }
+            test_command => panic!("unexpected command {:?}", test_command),
+        }
+        let test_cli = Cli::try_parse_from([
+            "archeon",
+            "get",
+            "http://test-cli/{version}/app.tar.gz",
+            "--var",
+            "version=1.2.3",
+        ])
+        .unwrap();
+        assert_eq!(
+            test_cli.variables,
+            [(String::from("version"), String::from("1.2.3"))],
+        );
+        assert!(Cli::try_parse_from(["archeon", "get", "url", "--var", "version"]).is_err());
+    }
+
+    #[test]
+    fn queue() {
+        let test_cli =
+            Cli::try_parse_from(["archeon", "queue", "export", "--output", "queue.json"]).unwrap();
+        match test_cli.command {
+            Command::Queue {
+                action: QueueAction::Export { output },
+            } => assert_eq!(output, Some(PathBuf::from("queue.json"))),
+            test_command => panic!("unexpected command {:?}", test_command),
+        }
+        let test_cli = Cli::try_parse_from(["archeon", "queue", "import", "queue.json"]).unwrap();
+        match test_cli.command {
+            Command::Queue {
+                action: QueueAction::Import { path },
+            } => assert_eq!(path, PathBuf::from("queue.json")),
+            test_command => panic!("unexpected command {:?}", test_command),
+        }
+        assert!(Cli::try_parse_from(["archeon", "queue", "import"]).is_err());
+    }
+
+    #[test]
+    fn index() {
+        let test_cli = Cli::try_parse_from([
+            "archeon",
+            "index",
+            "http://test-cli/debian/",
+            "--include",
+            "*.deb",
+            "--exclude",
+            "*-dbgsym_*",
+            "--depth",
+            "2",
+            "--max-bytes",
+            "1048576",
+        ])
+        .unwrap();
+        match test_cli.command {
+            Command::Index {
+                url,
+                destination,
+                include,
+                exclude,
+                depth,
+                span_hosts,
+                max_bytes,
+            } => {
+                assert_eq!(url, "http://test-cli/debian/");
+                assert_eq!(destination, PathBuf::from("."));
+                assert_eq!(include, ["*.deb"]);
+                assert_eq!(exclude, ["*-dbgsym_*"]);
+                assert_eq!(depth, 2);
+                assert!(!span_hosts);
+                assert_eq!(max_bytes, Some(1048576));
+            }
+            test_command => panic!("unexpected command {:?}", test_command),
+        }
+        assert!(Cli::try_parse_from(["archeon", "index", "url", "--depth", "-1"]).is_err());
+    }
+
+    #[test]
+    fn install() {
+        let test_cli = Cli::try_parse_from([
+            "archeon",
+            "install",
+            "http://test-cli/test.deb",
+            "--checksum",
+            "test_checksum",
+            "--algorithm",
+            "blake3",
+            "--escalation",
+            "sudo",
+            "--conffile",
+            "new",
+            "--delete",
+        ])
+        .unwrap();
+        match test_cli.command {
+            Command::Install {
+                url,
+                checksum,
+                algorithm,
+                escalation,
+                conffile,
+                manifest,
+                delete,
+                ..
+            } => {
+                assert_eq!(url, "http://test-cli/test.deb");
+                assert_eq!(checksum.as_deref(), Some("test_checksum"));
+                assert_eq!(algorithm, Some(Algorithm::Blake3));
+                assert_eq!(escalation, Some(Escalation::Sudo));
+                assert_eq!(conffile, Some(Conffile::New));
+                assert!(manifest.is_none());
+                assert!(delete);
+            }
+            test_command => panic!("unexpected command {:?}", test_command),
+        }
+        assert!(Cli::try_parse_from(["archeon", "install", "url", "--escalation", "su"]).is_err());
+    }
+
+    #[test]
+    fn print_result() {
+        let mut test_result = BatchResult {
+            url: String::from("http://test-cli/test.deb"),
+            file_path: Some(PathBuf::from("/tmp/archeon/test.deb")),
+            installation: Some(1),
+            error: None,
+            error_kind: None,
+            hooks: Vec::new(),
+        };
+        assert!(super::print_result(&test_result).is_ok());
+        test_result.error = Some(String::from("test_error"));
+        test_result.error_kind = Some(ErrorKind::Integrity);
+        let test_error = super::print_result(&test_result).unwrap_err();
+        assert_eq!(test_error.to_string(), "test_error");
+        assert_eq!(exit_code(test_error.as_ref()), 3);
+        let test_report = BatchReport {
+            results: vec![test_result],
+        };
+        assert_eq!(
+            exit_code(print_report(&test_report).unwrap_err().as_ref()),
+            3
+        );
+    }
+
+    #[test]
+    fn print_response() {
+        let test_error = ControlResponse::Error {
+            error: String::from("test_error"),
+        };
+        assert!(super::print_response(test_error).is_err());
+        assert!(super::print_response(ControlResponse::Jobs { jobs: Vec::new() }).is_ok());
+    }
+
+    #[test]
+    fn print_completions() {
+        let test_cli = Cli::try_parse_from(["archeon", "completions", "zsh"]).unwrap();
+        assert!(matches!(
+            test_cli.command,
+            Command::Completions { shell: Shell::Zsh }
+        ));
+        assert!(Cli::try_parse_from(["archeon", "completions", "tcsh"]).is_err());
+        for test_shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let mut test_output = Vec::new();
+            super::print_completions(test_shell, &mut test_output);
+            let test_output = String::from_utf8(test_output).unwrap();
+            assert!(test_output.contains("archeon"));
+            assert!(test_output.contains("priority"));
+            assert!(test_output.contains("cancel"));
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use tokio::fs::{create_dir_all, read, write};
+
+use crate::installer::Installation;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    pub installations: Vec<Installation>,
+    #[serde(default)]
+    pub pins: Vec<Pin>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pin {
+    pub source: String,
+    pub checksum: String,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Outdated {
+    pub id: u64,
+    pub package: String,
+    pub source: String,
+    pub installed_version: String,
+    pub available_version: Option<String>,
+    pub installed_checksum: String,
+    pub available_checksum: Option<String>,
+    pub error: Option<String>,
+}
+
+impl Manifest {
+    pub async fn init(path: &Path) -> Result<Manifest, Error> {
+        let mut manifest = match read(path).await {
+            Ok(contents) => serde_json::from_slice::<Manifest>(&contents)?,
+            Err(error) if error.kind() == ErrorKind::NotFound => Manifest::default(),
+            Err(error) => return Err(error),
+        };
+
+        manifest.path = Some(path.to_owned());
+
+        Ok(manifest)
+    }
+
+    pub async fn save(&self) -> Result<(), Error> {
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                create_dir_all(parent).await?;
+            }
+
+            let contents = serde_json::to_vec_pretty(self)?;
+
+            write(path, contents).await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn next_id(&self) -> u64 {
+        self.installations
+            .iter()
+            .map(|installation| installation.id + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn find(&self, source: &str, checksum: &str) -> Option<&Installation> {
+        self.installations
+            .iter()
+            .find(|installation| installation.source == source && installation.checksum == checksum)
+    }
+
+    pub fn pinned(&self, source: &str) -> Option<&Pin> {
+        self.pins.iter().find(|pin| pin.source == source)
+    }
+
+    pub async fn pin(&mut self, pin: Pin) -> Result<(), Error> {
+        self.pins.retain(|pinned| pinned.source != pin.source);
+        self.pins.push(pin);
+        self.save().await
+    }
+
+    pub async fn record(&mut self, installation: Installation) -> Result<(), Error> {
+        self.installations.push(installation);
+        self.save().await
+    }
+
+    pub async fn remove(&mut self, id: u64) -> Result<Option<Installation>, Error> {
+        let position = self
+            .installations
+            .iter()
+            .position(|installation| installation.id == id);
+
+        match position {
+            Some(index) => {
+                let installation = self.installations.remove(index);
+
+                self.save().await?;
+
+                Ok(Some(installation))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::installer::Installer;
+
+    fn test_installation(id: u64, source: &str) -> Installation {
+        Installation {
+            id,
+            package: String::from("test-package"),
+            version: String::from("1.0.0"),
+            source: source.to_owned(),
+            checksum: String::from("test_checksum"),
+            installed: 1_686_000_000,
+            artifact: PathBuf::from("/tmp/archeon/test-package.deb"),
+            installer: Installer::default(),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_manifest_init.json");
+        let test_manifest = Manifest::init(&test_path).await?;
+        assert_eq!(test_manifest.path, Some(test_path));
+        assert!(test_manifest.installations.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn save() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_manifest_save.json");
+        let mut test_manifest = Manifest::init(&test_path).await?;
+        test_manifest
+            .record(test_installation(0, "http://test-save/test.deb"))
+            .await?;
+        let test_loaded_manifest = Manifest::init(&test_path).await?;
+        assert_eq!(test_loaded_manifest, test_manifest);
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn save_without_path() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_manifest = Manifest::default();
+        test_manifest.save().await?;
+        assert!(test_manifest.path.is_none());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn next_id() {
+        let mut test_manifest = Manifest::default();
+        assert_eq!(test_manifest.next_id(), 0);
+        test_manifest
+            .installations
+            .push(test_installation(4, "http://test-next-id/test.deb"));
+        assert_eq!(test_manifest.next_id(), 5);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn find() {
+        let mut test_manifest = Manifest::default();
+        test_manifest
+            .installations
+            .push(test_installation(0, "http://test-find/test.deb"));
+        assert!(test_manifest
+            .find("http://test-find/test.deb", "test_checksum")
+            .is_some());
+        assert!(test_manifest
+            .find("http://test-find/test.deb", "other_checksum")
+            .is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn remove() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_manifest = Manifest::default();
+        test_manifest
+            .record(test_installation(0, "http://test-remove/test.deb"))
+            .await?;
+        assert!(test_manifest.remove(1).await?.is_none());
+        assert_eq!(test_manifest.remove(0).await?.unwrap().id, 0);
+        assert!(test_manifest.installations.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn pin() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_manifest = Manifest::default();
+        assert!(test_manifest.pinned("http://test-pin/test.deb").is_none());
+        for test_checksum in ["test_first_checksum", "test_second_checksum"] {
+            test_manifest
+                .pin(Pin {
+                    source: String::from("http://test-pin/test.deb"),
+                    checksum: test_checksum.to_owned(),
+                    version: None,
+                })
+                .await?;
+        }
+        assert_eq!(test_manifest.pins.len(), 1);
+        assert_eq!(
+            test_manifest
+                .pinned("http://test-pin/test.deb")
+                .unwrap()
+                .checksum,
+            "test_second_checksum",
+        );
+        let test_manifest: Manifest = serde_json::from_str(r#"{"installations":[]}"#)?;
+        assert!(test_manifest.pins.is_empty());
+        Ok(())
+    }
+}
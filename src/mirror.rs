@@ -0,0 +1,227 @@
+use hyper::body::HttpBody;
+use hyper::header::USER_AGENT;
+use hyper::{Body, Method, Request};
+
+use serde::{Deserialize, Serialize};
+
+use std::env::var_os;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use tokio::fs::{create_dir_all, read, write};
+use tokio::time::Instant;
+
+use crate::transfer::{HttpClient, DEFAULT_USER_AGENT};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirrorRanking {
+    pub tested: u64,
+    pub mirrors: Vec<MirrorScore>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirrorScore {
+    pub url: String,
+    pub latency_ms: u64,
+    pub bytes_per_second: u64,
+    pub error: Option<String>,
+}
+
+pub fn default_ranking_path() -> PathBuf {
+    match (var_os("XDG_STATE_HOME"), var_os("HOME")) {
+        (Some(state_dir), _) => PathBuf::from(state_dir).join("archeon/mirrors.json"),
+        (None, Some(home)) => PathBuf::from(home).join(".local/state/archeon/mirrors.json"),
+        (None, None) => PathBuf::from("archeon-mirrors.json"),
+    }
+}
+
+pub fn mirror_base(url: &str) -> String {
+    format!("{}/", url.trim().trim_end_matches('/'))
+}
+
+pub fn rewrite(mirrors: &[String], url: &str) -> Option<String> {
+    let best = mirrors.first()?;
+    let path = mirrors
+        .iter()
+        .find_map(|mirror| url.strip_prefix(mirror.as_str()))?;
+
+    Some(format!("{}{}", best, path))
+}
+
+impl MirrorRanking {
+    pub async fn load(path: &Path) -> Result<MirrorRanking, Error> {
+        let contents = read(path).await?;
+
+        serde_json::from_slice(&contents).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub async fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).await?;
+        }
+
+        let contents = serde_json::to_vec_pretty(self)?;
+
+        write(path, contents).await
+    }
+
+    pub async fn test(client: &HttpClient, mirrors: &[String], path: &str) -> MirrorRanking {
+        let mut ranking = MirrorRanking {
+            tested: crate::unix_timestamp(),
+            mirrors: Vec::with_capacity(mirrors.len()),
+        };
+
+        for mirror in mirrors {
+            let url = mirror_base(mirror);
+            let score = match Self::launch_measure(client, &url, path).await {
+                Ok((latency_ms, bytes_per_second)) => MirrorScore {
+                    url,
+                    latency_ms,
+                    bytes_per_second,
+                    error: None,
+                },
+                Err(error) => MirrorScore {
+                    url,
+                    error: Some(error.to_string()),
+                    ..MirrorScore::default()
+                },
+            };
+
+            ranking.mirrors.push(score);
+        }
+
+        ranking.mirrors.sort_by(|a, b| {
+            a.error
+                .is_some()
+                .cmp(&b.error.is_some())
+                .then(b.bytes_per_second.cmp(&a.bytes_per_second))
+                .then(a.latency_ms.cmp(&b.latency_ms))
+        });
+
+        ranking
+    }
+
+    pub fn order(&self, mirrors: &[String]) -> Vec<String> {
+        let configured: Vec<String> = mirrors.iter().map(|mirror| mirror_base(mirror)).collect();
+        let mut ordered: Vec<String> = self
+            .mirrors
+            .iter()
+            .filter(|score| score.error.is_none() && configured.contains(&score.url))
+            .map(|score| score.url.to_owned())
+            .collect();
+
+        for mirror in configured {
+            if !ordered.contains(&mirror) {
+                ordered.push(mirror);
+            }
+        }
+
+        ordered
+    }
+
+    async fn launch_measure(
+        client: &HttpClient,
+        url: &str,
+        path: &str,
+    ) -> Result<(u64, u64), Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}{}", url, path.trim_start_matches('/'));
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(&url)
+            .header(USER_AGENT, DEFAULT_USER_AGENT)
+            .body(Body::empty())?;
+        let started = Instant::now();
+        let response = client.send(request).await?;
+        let latency = started.elapsed();
+
+        if !response.status().is_success() {
+            return Err(format!("Unexpected status {} from {}!", response.status(), url).into());
+        }
+
+        let mut body = response.into_body();
+        let mut bytes = 0;
+
+        while let Some(chunk) = body.data().await {
+            bytes += chunk?.len() as u64;
+        }
+
+        let elapsed = started.elapsed().as_secs_f64();
+        let bytes_per_second = match elapsed > 0.0 {
+            true => (bytes as f64 / elapsed) as u64,
+            false => 0,
+        };
+
+        Ok((latency.as_millis() as u64, bytes_per_second))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockArtifact, MockServer};
+    use crate::transfer::Transfer;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rewrite() {
+        let test_mirrors = vec![
+            String::from("http://fast.test/debian/"),
+            String::from("http://slow.test/debian/"),
+        ];
+        assert_eq!(
+            super::rewrite(&test_mirrors, "http://slow.test/debian/pool/test.deb").as_deref(),
+            Some("http://fast.test/debian/pool/test.deb"),
+        );
+        assert!(super::rewrite(&test_mirrors, "http://other.test/debian/test.deb").is_none());
+        assert!(super::rewrite(&[], "http://slow.test/debian/test.deb").is_none());
+        assert_eq!(
+            mirror_base(" http://fast.test/debian// "),
+            "http://fast.test/debian/"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_fast = MockServer::start().await?;
+        let test_slow = MockServer::start().await?;
+        test_fast.add("debian/test.bin", MockArtifact::sized(64 * 1024));
+        test_slow.add(
+            "debian/test.bin",
+            MockArtifact {
+                latency: std::time::Duration::from_millis(200),
+                ..MockArtifact::sized(64 * 1024)
+            },
+        );
+        let test_mirrors = vec![
+            test_slow.url("debian"),
+            test_fast.url("debian"),
+            test_fast.url("missing"),
+        ];
+        let test_ranking =
+            MirrorRanking::test(&Transfer::init_client(), &test_mirrors, "test.bin").await;
+        assert_eq!(test_ranking.mirrors.len(), 3);
+        assert_eq!(test_ranking.mirrors[0].url, test_fast.url("debian/"));
+        assert_eq!(test_ranking.mirrors[1].url, test_slow.url("debian/"));
+        assert!(test_ranking.mirrors[1].latency_ms >= 200);
+        assert!(test_ranking.mirrors[2].error.is_some());
+        let test_path = std::env::temp_dir().join("archeon_test_mirrors/mirrors.json");
+        test_ranking.save(&test_path).await?;
+        let test_loaded = MirrorRanking::load(&test_path).await?;
+        assert_eq!(test_loaded, test_ranking);
+        assert_eq!(
+            test_loaded.order(&[
+                test_fast.url("missing"),
+                String::from("http://untested.test/debian"),
+                test_slow.url("debian"),
+                test_fast.url("debian"),
+            ]),
+            vec![
+                test_fast.url("debian/"),
+                test_slow.url("debian/"),
+                test_fast.url("missing/"),
+                String::from("http://untested.test/debian/"),
+            ],
+        );
+        tokio::fs::remove_dir_all(test_path.parent().unwrap()).await?;
+        Ok(())
+    }
+}
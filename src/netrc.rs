@@ -0,0 +1,252 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use hyper::header::{HeaderValue, AUTHORIZATION};
+use hyper::{Body, Request};
+
+use std::collections::HashMap;
+use std::env::var_os;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::fs::read_to_string;
+use tokio::sync::OnceCell;
+
+use crate::auth::{AuthFuture, Authenticate};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NetrcEntry {
+    pub login: Option<String>,
+    pub password: Option<String>,
+    pub account: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Netrc {
+    pub machines: HashMap<String, NetrcEntry>,
+    pub default: Option<NetrcEntry>,
+}
+
+#[derive(Clone, Debug)]
+pub struct NetrcAuth {
+    pub path: PathBuf,
+    netrc: Arc<OnceCell<Netrc>>,
+}
+
+pub fn default_netrc_path() -> Option<PathBuf> {
+    match (var_os("NETRC"), var_os("HOME")) {
+        (Some(path), _) => Some(PathBuf::from(path)),
+        (None, Some(home)) => Some(PathBuf::from(home).join(".netrc")),
+        (None, None) => None,
+    }
+}
+
+impl Netrc {
+    pub async fn init(path: &Path) -> Result<Netrc, Error> {
+        match read_to_string(path).await {
+            Ok(contents) => Ok(Self::parse(&contents)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Netrc::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Netrc {
+        let mut netrc = Netrc::default();
+        let mut tokens = Vec::new();
+        let mut macdef = false;
+
+        for line in contents.lines() {
+            if macdef {
+                macdef = !line.trim().is_empty();
+
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+
+            while let Some(word) = words.next() {
+                match word {
+                    "macdef" => {
+                        words.next();
+                        macdef = true;
+                    }
+                    word if word.starts_with('#') => break,
+                    word => tokens.push(word),
+                }
+            }
+        }
+
+        let mut tokens = tokens.into_iter();
+        let mut current: Option<(Option<String>, NetrcEntry)> = None;
+
+        while let Some(token) = tokens.next() {
+            match token {
+                "machine" | "default" => {
+                    if let Some((machine, entry)) = current.take() {
+                        netrc.insert(machine, entry);
+                    }
+
+                    let machine = match token {
+                        "machine" => tokens.next().map(str::to_ascii_lowercase),
+                        _ => None,
+                    };
+
+                    current = Some((machine, NetrcEntry::default()));
+                }
+                "login" | "password" | "account" => {
+                    let value = tokens.next().map(str::to_owned);
+
+                    if let Some((_, entry)) = current.as_mut() {
+                        match token {
+                            "login" => entry.login = value,
+                            "password" => entry.password = value,
+                            _ => entry.account = value,
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some((machine, entry)) = current {
+            netrc.insert(machine, entry);
+        }
+
+        netrc
+    }
+
+    pub fn entry(&self, host: &str) -> Option<&NetrcEntry> {
+        self.machines
+            .get(&host.to_ascii_lowercase())
+            .or(self.default.as_ref())
+    }
+
+    fn insert(&mut self, machine: Option<String>, entry: NetrcEntry) {
+        match machine {
+            Some(machine) => {
+                self.machines.entry(machine).or_insert(entry);
+            }
+            None => {
+                self.default.get_or_insert(entry);
+            }
+        }
+    }
+}
+
+impl NetrcAuth {
+    pub fn init(path: PathBuf) -> NetrcAuth {
+        NetrcAuth {
+            path,
+            netrc: Arc::default(),
+        }
+    }
+}
+
+impl Authenticate for NetrcAuth {
+    fn authenticate<'a>(&'a self, request: &'a mut Request<Body>) -> AuthFuture<'a> {
+        Box::pin(async move {
+            if request.headers().contains_key(AUTHORIZATION) {
+                return Ok(());
+            }
+
+            let netrc = self
+                .netrc
+                .get_or_try_init(|| Netrc::init(&self.path))
+                .await?;
+            let entry = match request.uri().host().and_then(|host| netrc.entry(host)) {
+                Some(entry) => entry,
+                None => return Ok(()),
+            };
+
+            if let Some(login) = &entry.login {
+                let password = entry.password.as_deref().unwrap_or_default();
+                let credentials = STANDARD.encode(format!("{}:{}", login, password));
+                let value = HeaderValue::from_str(&format!("Basic {}", credentials))?;
+
+                request.headers_mut().insert(AUTHORIZATION, value);
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_NETRC: &str = "
+        # internal mirrors
+        machine Mirror.Test login test_user password test_password
+        machine second.test
+            login test_second
+            password \"quoted\"
+            account test_account
+
+        macdef init
+        machine ignored.test login ignored password ignored
+
+        machine mirror.test login test_duplicate password test_duplicate
+        default login anonymous password test@example.com
+    ";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse() {
+        let test_netrc = Netrc::parse(TEST_NETRC);
+        let test_entry = test_netrc.entry("MIRROR.test").unwrap();
+        assert_eq!(test_entry.login.as_deref(), Some("test_user"));
+        assert_eq!(test_entry.password.as_deref(), Some("test_password"));
+        let test_entry = test_netrc.entry("second.test").unwrap();
+        assert_eq!(test_entry.password.as_deref(), Some("\"quoted\""));
+        assert_eq!(test_entry.account.as_deref(), Some("test_account"));
+        assert!(!test_netrc.machines.contains_key("ignored.test"));
+        assert_eq!(
+            test_netrc.entry("other.test").unwrap().login.as_deref(),
+            Some("anonymous"),
+        );
+        assert_eq!(Netrc::parse(""), Netrc::default());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_netrc_init");
+        assert_eq!(Netrc::init(&test_path).await?, Netrc::default());
+        tokio::fs::write(&test_path, TEST_NETRC).await?;
+        assert_eq!(Netrc::init(&test_path).await?.machines.len(), 2);
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn authenticate() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_netrc_authenticate");
+        tokio::fs::write(
+            &test_path,
+            "machine mirror.test login test_user password test_password",
+        )
+        .await?;
+        let test_auth = NetrcAuth::init(test_path.to_owned());
+        let mut test_request = Request::builder()
+            .uri("https://mirror.test/test.deb")
+            .body(Body::empty())?;
+        test_auth.authenticate(&mut test_request).await?;
+        assert_eq!(
+            test_request.headers()[AUTHORIZATION],
+            "Basic dGVzdF91c2VyOnRlc3RfcGFzc3dvcmQ=",
+        );
+        let mut test_request = Request::builder()
+            .uri("https://mirror.test/test.deb")
+            .header(AUTHORIZATION, "Bearer test_token")
+            .body(Body::empty())?;
+        test_auth.authenticate(&mut test_request).await?;
+        assert_eq!(test_request.headers()[AUTHORIZATION], "Bearer test_token");
+        let mut test_request = Request::builder()
+            .uri("https://other.test/test.deb")
+            .body(Body::empty())?;
+        test_auth.authenticate(&mut test_request).await?;
+        assert!(!test_request.headers().contains_key(AUTHORIZATION));
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+}
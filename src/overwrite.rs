@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverwritePolicy {
+    Error,
+    #[default]
+    Overwrite,
+    Skip,
+    RenameUnique,
+}
+
+impl std::str::FromStr for OverwritePolicy {
+    type Err = Error;
+
+    fn from_str(policy: &str) -> Result<OverwritePolicy, Error> {
+        match policy {
+            "error" => Ok(OverwritePolicy::Error),
+            "overwrite" => Ok(OverwritePolicy::Overwrite),
+            "skip" => Ok(OverwritePolicy::Skip),
+            "rename-unique" => Ok(OverwritePolicy::RenameUnique),
+            _ => {
+                let error = format!("Invalid overwrite policy '{}'!", policy);
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+}
+
+pub fn already_exists(path: &Path) -> Error {
+    let error = format!("{} already exists!", path.display());
+
+    Error::new(ErrorKind::AlreadyExists, error)
+}
+
+pub fn unique_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_owned();
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..)
+        .map(|index| path.with_file_name(format!("{}.{}{}", stem, index, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn from_str() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!("error".parse::<OverwritePolicy>()?, OverwritePolicy::Error);
+        assert_eq!("skip".parse::<OverwritePolicy>()?, OverwritePolicy::Skip);
+        assert_eq!(
+            "rename-unique".parse::<OverwritePolicy>()?,
+            OverwritePolicy::RenameUnique,
+        );
+        assert!("replace".parse::<OverwritePolicy>().is_err());
+        assert_eq!(OverwritePolicy::default(), OverwritePolicy::Overwrite);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unique_path() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_overwrite_unique_path");
+        tokio::fs::create_dir_all(&test_dir).await?;
+        let test_path = test_dir.join("test.deb");
+        assert_eq!(super::unique_path(&test_path), test_path);
+        tokio::fs::write(&test_path, b"test_body").await?;
+        tokio::fs::write(test_dir.join("test.1.deb"), b"test_body").await?;
+        assert_eq!(super::unique_path(&test_path), test_dir.join("test.2.deb"));
+        tokio::fs::write(test_dir.join("README"), b"test_body").await?;
+        assert_eq!(
+            super::unique_path(&test_dir.join("README")),
+            test_dir.join("README.1"),
+        );
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+}
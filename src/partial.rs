@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+
+use std::env::temp_dir;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::http_cache::HttpCacheEntry;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PartialPolicy {
+    Delete,
+    #[default]
+    Keep,
+    Quarantine,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialCleanup {
+    pub policy: PartialPolicy,
+    pub ttl: Option<Duration>,
+    pub quarantine_dir: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct PartialGuard {
+    part_path: PathBuf,
+    cleanup: PartialCleanup,
+    armed: bool,
+}
+
+impl std::str::FromStr for PartialPolicy {
+    type Err = Error;
+
+    fn from_str(policy: &str) -> Result<PartialPolicy, Error> {
+        match policy {
+            "delete" => Ok(PartialPolicy::Delete),
+            "keep" => Ok(PartialPolicy::Keep),
+            "quarantine" => Ok(PartialPolicy::Quarantine),
+            _ => {
+                let error = format!("Invalid partial policy '{}'!", policy);
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+}
+
+impl Default for PartialCleanup {
+    fn default() -> PartialCleanup {
+        PartialCleanup {
+            policy: PartialPolicy::default(),
+            ttl: None,
+            quarantine_dir: default_quarantine_dir(),
+        }
+    }
+}
+
+impl PartialCleanup {
+    pub fn apply(&self, part_path: &Path) -> Result<Option<PathBuf>, Error> {
+        let paths = [part_path.to_owned(), HttpCacheEntry::path(part_path)];
+
+        match self.policy {
+            PartialPolicy::Keep => Ok(None),
+            PartialPolicy::Delete => {
+                for path in paths {
+                    remove(&path)?;
+                }
+
+                Ok(None)
+            }
+            PartialPolicy::Quarantine => {
+                if !part_path.exists() {
+                    return Ok(None);
+                }
+
+                std::fs::create_dir_all(&self.quarantine_dir)?;
+
+                let mut quarantined = None;
+
+                for path in paths {
+                    if let Some(filename) = path.file_name() {
+                        let target = self.quarantine_dir.join(filename);
+
+                        if move_file(&path, &target)? && quarantined.is_none() {
+                            quarantined = Some(target);
+                        }
+                    }
+                }
+
+                Ok(quarantined)
+            }
+        }
+    }
+
+    pub fn is_expired(&self, part_path: &Path) -> bool {
+        let ttl = match self.ttl {
+            Some(ttl) => ttl,
+            None => return false,
+        };
+
+        std::fs::metadata(part_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > ttl)
+    }
+
+    pub async fn sweep(&self, directory: &Path) -> Result<Vec<PathBuf>, Error> {
+        let mut entries = match tokio::fs::read_dir(directory).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        let mut expired = Vec::with_capacity(4);
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "part")
+                && self.is_expired(&path)
+            {
+                for path in [HttpCacheEntry::path(&path), path.to_owned()] {
+                    remove(&path)?;
+                }
+
+                expired.push(path);
+            }
+        }
+
+        expired.sort();
+
+        Ok(expired)
+    }
+}
+
+impl PartialGuard {
+    pub fn init(part_path: &Path, cleanup: &PartialCleanup) -> PartialGuard {
+        PartialGuard {
+            part_path: part_path.to_owned(),
+            cleanup: cleanup.to_owned(),
+            armed: true,
+        }
+    }
+
+    pub fn disarm(&mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for PartialGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+
+        if let Err(error) = self.cleanup.apply(&self.part_path) {
+            eprintln!("Unable to clean up {}: {}", self.part_path.display(), error);
+        }
+    }
+}
+
+pub fn default_quarantine_dir() -> PathBuf {
+    temp_dir().join("archeon").join("quarantine")
+}
+
+fn remove(path: &Path) -> Result<(), Error> {
+    match std::fs::remove_file(path) {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+fn move_file(from: &Path, to: &Path) -> Result<bool, Error> {
+    match std::fs::rename(from, to) {
+        Ok(()) => Ok(true),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(false),
+        Err(error) if error.kind() == ErrorKind::CrossesDevices => {
+            std::fs::copy(from, to)?;
+            std::fs::remove_file(from)?;
+
+            Ok(true)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn from_str() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!("delete".parse::<PartialPolicy>()?, PartialPolicy::Delete);
+        assert_eq!("keep".parse::<PartialPolicy>()?, PartialPolicy::Keep);
+        assert_eq!(
+            "quarantine".parse::<PartialPolicy>()?,
+            PartialPolicy::Quarantine,
+        );
+        assert!("shred".parse::<PartialPolicy>().is_err());
+        assert_eq!(PartialPolicy::default(), PartialPolicy::Keep);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_partial_apply");
+        let test_part = test_dir.join("test.iso.part");
+        let test_sidecar = HttpCacheEntry::path(&test_part);
+        let mut test_cleanup = PartialCleanup {
+            quarantine_dir: test_dir.join("quarantine"),
+            ..PartialCleanup::default()
+        };
+        tokio::fs::create_dir_all(&test_dir).await?;
+        tokio::fs::write(&test_part, b"test_body").await?;
+        tokio::fs::write(&test_sidecar, b"{}").await?;
+        assert!(test_cleanup.apply(&test_part)?.is_none());
+        assert!(test_part.exists());
+        test_cleanup.policy = PartialPolicy::Quarantine;
+        assert_eq!(
+            test_cleanup.apply(&test_part)?,
+            Some(test_dir.join("quarantine/test.iso.part")),
+        );
+        assert!(!test_part.exists());
+        assert!(!test_sidecar.exists());
+        assert!(test_dir
+            .join("quarantine")
+            .join(test_sidecar.file_name().unwrap())
+            .exists());
+        assert!(test_cleanup.apply(&test_part)?.is_none());
+        tokio::fs::write(&test_part, b"test_body").await?;
+        tokio::fs::write(&test_sidecar, b"{}").await?;
+        test_cleanup.policy = PartialPolicy::Delete;
+        {
+            let _test_guard = PartialGuard::init(&test_part, &test_cleanup);
+        }
+        assert!(!test_part.exists());
+        assert!(!test_sidecar.exists());
+        tokio::fs::write(&test_part, b"test_body").await?;
+        {
+            let mut test_guard = PartialGuard::init(&test_part, &test_cleanup);
+            test_guard.disarm();
+        }
+        assert!(test_part.exists());
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sweep() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_partial_sweep");
+        let test_part = test_dir.join("test.iso.part");
+        let mut test_cleanup = PartialCleanup::default();
+        tokio::fs::create_dir_all(&test_dir).await?;
+        tokio::fs::write(&test_part, b"test_body").await?;
+        tokio::fs::write(HttpCacheEntry::path(&test_part), b"{}").await?;
+        tokio::fs::write(test_dir.join("test.iso"), b"test_body").await?;
+        assert!(!test_cleanup.is_expired(&test_part));
+        test_cleanup.ttl = Some(Duration::from_secs(3600));
+        assert!(test_cleanup.sweep(&test_dir).await?.is_empty());
+        test_cleanup.ttl = Some(Duration::ZERO);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(test_cleanup.is_expired(&test_part));
+        assert_eq!(
+            test_cleanup.sweep(&test_dir).await?,
+            vec![test_part.to_owned()]
+        );
+        assert!(!test_part.exists());
+        assert!(!HttpCacheEntry::path(&test_part).exists());
+        assert!(test_dir.join("test.iso").exists());
+        assert!(test_cleanup
+            .sweep(&test_dir.join("missing"))
+            .await?
+            .is_empty());
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use hyper::Uri;
+
+use std::future::Future;
+use std::pin::Pin;
+
+pub type RefreshFuture<'a> = Pin<
+    Box<dyn Future<Output = Result<Uri, Box<dyn std::error::Error + Send + Sync>>> + Send + 'a>,
+>;
+
+pub const MAX_REFRESHES: u32 = 3;
+
+pub trait RefreshUrl: Send + Sync {
+    fn refresh<'a>(&'a self, expired: &'a Uri) -> RefreshFuture<'a>;
+}
+
+impl<F, R> RefreshUrl for F
+where
+    F: Fn(Uri) -> R + Send + Sync,
+    R: Future<Output = Result<Uri, Box<dyn std::error::Error + Send + Sync>>> + Send + 'static,
+{
+    fn refresh<'a>(&'a self, expired: &'a Uri) -> RefreshFuture<'a> {
+        Box::pin(self(expired.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn refresh() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_refresh = |expired: Uri| async move {
+            let refreshed = format!("{}?signature=test_fresh", expired.path());
+
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(refreshed.parse()?)
+        };
+        let test_refresh: &dyn RefreshUrl = &test_refresh;
+        let test_expired = Uri::from_static("/test.deb?signature=test_expired");
+        assert_eq!(
+            test_refresh.refresh(&test_expired).await?,
+            "/test.deb?signature=test_fresh",
+        );
+        Ok(())
+    }
+}
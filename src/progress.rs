@@ -0,0 +1,388 @@
+#[cfg(feature = "progress-bar")]
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::broadcast::{channel, Receiver, Sender};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateProgress {
+    pub total_bytes: u64,
+    pub downloaded_bytes: u64,
+    pub files_total: usize,
+    pub files_completed: usize,
+    pub files_failed: usize,
+    pub bytes_per_second: u64,
+    pub buffered_high_watermark: u64,
+    #[serde(default)]
+    pub resumed_bytes: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum ProgressEvent {
+    Started { url: String, size: Option<u64> },
+    Resumed { url: String, offset: u64 },
+    Advanced { url: String, bytes: u64 },
+    Finished { url: String, error: Option<String> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressUpdate {
+    pub event: ProgressEvent,
+    pub aggregate: AggregateProgress,
+}
+
+#[derive(Clone, Debug)]
+pub struct BatchProgress {
+    inner: Arc<Mutex<BatchProgressInner>>,
+    updates: Sender<ProgressUpdate>,
+}
+
+#[derive(Debug)]
+struct BatchProgressInner {
+    files: HashMap<String, FileProgress>,
+    files_total: usize,
+    files_completed: usize,
+    files_failed: usize,
+    buffered_high_watermark: u64,
+    started: Instant,
+    #[cfg(feature = "progress-bar")]
+    bar: Option<ProgressBar>,
+    #[cfg_attr(not(feature = "progress-bar"), allow(dead_code))]
+    quiet: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct FileProgress {
+    size: Option<u64>,
+    downloaded: u64,
+    resumed: u64,
+}
+
+impl AggregateProgress {
+    pub fn files_remaining(&self) -> usize {
+        self.files_total
+            .saturating_sub(self.files_completed + self.files_failed)
+    }
+}
+
+impl Default for BatchProgress {
+    fn default() -> BatchProgress {
+        let (updates, _) = channel(256);
+
+        BatchProgress {
+            inner: Arc::new(Mutex::new(BatchProgressInner {
+                files: HashMap::with_capacity(8),
+                files_total: 0,
+                files_completed: 0,
+                files_failed: 0,
+                buffered_high_watermark: 0,
+                started: Instant::now(),
+                #[cfg(feature = "progress-bar")]
+                bar: None,
+                quiet: false,
+            })),
+            updates,
+        }
+    }
+}
+
+impl BatchProgress {
+    pub fn subscribe(&self) -> Receiver<ProgressUpdate> {
+        self.updates.subscribe()
+    }
+
+    pub fn set_quiet(&self, quiet: bool) {
+        self.lock().quiet = quiet;
+    }
+
+    pub fn begin(&self, files_total: usize) {
+        let mut inner = self.lock();
+
+        inner.files.clear();
+        inner.files_total = files_total;
+        inner.files_completed = 0;
+        inner.files_failed = 0;
+        inner.buffered_high_watermark = 0;
+        inner.started = Instant::now();
+
+        #[cfg(feature = "progress-bar")]
+        {
+            inner.bar = Some(new_bar(inner.quiet));
+        }
+    }
+
+    pub fn end(&self) {
+        #[cfg(feature = "progress-bar")]
+        if let Some(bar) = self.lock().bar.take() {
+            bar.finish();
+        }
+    }
+
+    pub fn start(&self, url: &str, size: Option<u64>) {
+        self.launch_update(ProgressEvent::Started {
+            url: url.to_owned(),
+            size,
+        });
+    }
+
+    pub fn resume(&self, url: &str, offset: u64) {
+        self.launch_update(ProgressEvent::Resumed {
+            url: url.to_owned(),
+            offset,
+        });
+    }
+
+    pub fn advance(&self, url: &str, bytes: u64) {
+        self.launch_update(ProgressEvent::Advanced {
+            url: url.to_owned(),
+            bytes,
+        });
+    }
+
+    pub fn finish(&self, url: &str, error: Option<String>) {
+        self.launch_update(ProgressEvent::Finished {
+            url: url.to_owned(),
+            error,
+        });
+    }
+
+    pub fn buffered(&self, bytes: u64) {
+        let mut inner = self.lock();
+
+        inner.buffered_high_watermark = inner.buffered_high_watermark.max(bytes);
+    }
+
+    pub fn aggregate(&self) -> AggregateProgress {
+        self.lock().aggregate()
+    }
+
+    fn launch_update(&self, event: ProgressEvent) {
+        let mut inner = self.lock();
+
+        match &event {
+            ProgressEvent::Started { url, size } => {
+                inner.files.insert(
+                    url.to_owned(),
+                    FileProgress {
+                        size: *size,
+                        ..FileProgress::default()
+                    },
+                );
+            }
+            ProgressEvent::Resumed { url, offset } => {
+                let file = inner.files.entry(url.to_owned()).or_default();
+
+                file.downloaded = *offset;
+                file.resumed = *offset;
+            }
+            ProgressEvent::Advanced { url, bytes } => {
+                let file = inner.files.entry(url.to_owned()).or_default();
+
+                file.downloaded += bytes;
+            }
+            ProgressEvent::Finished { error: None, .. } => inner.files_completed += 1,
+            ProgressEvent::Finished { error: Some(_), .. } => inner.files_failed += 1,
+        }
+
+        let aggregate = inner.aggregate();
+
+        #[cfg(feature = "progress-bar")]
+        if let Some(bar) = &inner.bar {
+            render(bar, &aggregate);
+        }
+
+        let _ = self.updates.send(ProgressUpdate { event, aggregate });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BatchProgressInner> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl BatchProgressInner {
+    fn aggregate(&self) -> AggregateProgress {
+        let total_bytes = self.files.values().filter_map(|file| file.size).sum();
+        let downloaded_bytes: u64 = self.files.values().map(|file| file.downloaded).sum();
+        let resumed_bytes: u64 = self.files.values().map(|file| file.resumed).sum();
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let bytes_per_second = match elapsed > 0.0 {
+            true => (downloaded_bytes.saturating_sub(resumed_bytes) as f64 / elapsed) as u64,
+            false => 0,
+        };
+
+        AggregateProgress {
+            total_bytes,
+            downloaded_bytes,
+            files_total: self.files_total,
+            files_completed: self.files_completed,
+            files_failed: self.files_failed,
+            bytes_per_second,
+            buffered_high_watermark: self.buffered_high_watermark,
+            resumed_bytes,
+        }
+    }
+}
+
+#[cfg(feature = "progress-bar")]
+fn new_bar(quiet: bool) -> ProgressBar {
+    let bar = match quiet {
+        true => ProgressBar::hidden(),
+        false => ProgressBar::new(0),
+    };
+
+    if let Ok(style) = ProgressStyle::with_template("{bar:40} {bytes}/{total_bytes} {msg}") {
+        bar.set_style(style);
+    }
+
+    bar
+}
+
+#[cfg(feature = "progress-bar")]
+fn render(bar: &ProgressBar, aggregate: &AggregateProgress) {
+    bar.set_length(aggregate.total_bytes.max(aggregate.downloaded_bytes));
+    bar.set_position(aggregate.downloaded_bytes);
+
+    let mut message = format!(
+        "{}/{} files, {} remaining, {} B/s",
+        aggregate.files_completed,
+        aggregate.files_total,
+        aggregate.files_remaining(),
+        aggregate.bytes_per_second,
+    );
+
+    if aggregate.resumed_bytes > 0 {
+        message.push_str(&format!(
+            ", resumed from {}",
+            HumanBytes(aggregate.resumed_bytes)
+        ));
+    }
+
+    bar.set_message(message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn aggregate() {
+        let test_progress = BatchProgress::default();
+        test_progress.begin(3);
+        test_progress.start("http://test-progress/first.deb", Some(10));
+        test_progress.start("http://test-progress/second.deb", Some(20));
+        test_progress.advance("http://test-progress/first.deb", 10);
+        test_progress.advance("http://test-progress/second.deb", 5);
+        test_progress.finish("http://test-progress/first.deb", None);
+        let test_aggregate = test_progress.aggregate();
+        assert_eq!(test_aggregate.total_bytes, 30);
+        assert_eq!(test_aggregate.downloaded_bytes, 15);
+        assert_eq!(test_aggregate.files_total, 3);
+        assert_eq!(test_aggregate.files_completed, 1);
+        assert_eq!(test_aggregate.files_remaining(), 2);
+        test_progress.start("http://test-progress/second.deb", Some(20));
+        assert_eq!(test_progress.aggregate().downloaded_bytes, 10);
+        test_progress.finish(
+            "http://test-progress/third.deb",
+            Some(String::from("test_error")),
+        );
+        assert_eq!(test_progress.aggregate().files_failed, 1);
+        assert_eq!(test_progress.aggregate().files_remaining(), 1);
+        test_progress.start("http://test-progress/large.iso", Some(6 << 30));
+        test_progress.advance("http://test-progress/large.iso", 5 << 30);
+        test_progress.advance("http://test-progress/large.iso", 1 << 30);
+        assert_eq!(test_progress.aggregate().total_bytes, (6 << 30) + 30);
+        assert_eq!(test_progress.aggregate().downloaded_bytes, (6 << 30) + 10);
+        test_progress.buffered(64);
+        test_progress.buffered(16);
+        assert_eq!(test_progress.aggregate().buffered_high_watermark, 64);
+        test_progress.begin(1);
+        assert_eq!(test_progress.aggregate().buffered_high_watermark, 0);
+        test_progress.end();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn set_quiet() {
+        let test_progress = BatchProgress::default();
+        test_progress.set_quiet(true);
+        test_progress.begin(1);
+        #[cfg(feature = "progress-bar")]
+        assert!(test_progress
+            .lock()
+            .bar
+            .as_ref()
+            .is_some_and(ProgressBar::is_hidden));
+        test_progress.advance("http://test-progress/test.deb", 9);
+        assert_eq!(test_progress.aggregate().downloaded_bytes, 9);
+        test_progress.end();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn subscribe() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_progress = BatchProgress::default();
+        let mut test_updates = test_progress.subscribe();
+        test_progress.begin(1);
+        test_progress.start("http://test-progress/test.deb", Some(9));
+        test_progress.advance("http://test-progress/test.deb", 9);
+        let test_started = test_updates.recv().await?;
+        assert_eq!(
+            test_started.event,
+            ProgressEvent::Started {
+                url: String::from("http://test-progress/test.deb"),
+                size: Some(9),
+            },
+        );
+        let test_advanced = test_updates.recv().await?;
+        assert_eq!(test_advanced.aggregate.downloaded_bytes, 9);
+        assert_eq!(test_advanced.aggregate.total_bytes, 9);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resume() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_progress = BatchProgress::default();
+        let mut test_updates = test_progress.subscribe();
+        test_progress.set_quiet(true);
+        test_progress.begin(1);
+        test_progress.start("http://test-progress/test.iso", Some(100));
+        test_progress.resume("http://test-progress/test.iso", 60);
+        test_updates.recv().await?;
+        let test_resumed = test_updates.recv().await?;
+        assert_eq!(
+            test_resumed.event,
+            ProgressEvent::Resumed {
+                url: String::from("http://test-progress/test.iso"),
+                offset: 60,
+            },
+        );
+        assert_eq!(test_resumed.aggregate.downloaded_bytes, 60);
+        assert_eq!(test_resumed.aggregate.resumed_bytes, 60);
+        assert_eq!(test_resumed.aggregate.bytes_per_second, 0);
+        #[cfg(feature = "progress-bar")]
+        assert_eq!(
+            test_progress.lock().bar.as_ref().map(ProgressBar::position),
+            Some(60),
+        );
+        #[cfg(feature = "progress-bar")]
+        assert!(test_progress
+            .lock()
+            .bar
+            .as_ref()
+            .is_some_and(|bar| bar.message().ends_with("resumed from 60 B")));
+        test_progress.advance("http://test-progress/test.iso", 40);
+        assert_eq!(test_progress.aggregate().downloaded_bytes, 100);
+        test_progress.end();
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+use hyper::client::connect::{Connected, Connection, HttpConnector};
+use hyper::service::Service;
+use hyper::Uri;
+
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::net::TcpStream;
+
+use crate::resolver::Resolver;
+
+type ProxyFuture = Pin<
+    Box<dyn Future<Output = Result<ProxyStream, Box<dyn std::error::Error + Send + Sync>>> + Send>,
+>;
+
+#[derive(Clone, Debug)]
+pub struct ProxyConnector {
+    pub http: HttpConnector<Resolver>,
+    pub proxy: Option<Uri>,
+}
+
+#[derive(Debug)]
+pub struct ProxyStream {
+    stream: TcpStream,
+    proxied: bool,
+}
+
+impl ProxyConnector {
+    pub fn init(http: HttpConnector<Resolver>, proxy: Option<Uri>) -> ProxyConnector {
+        ProxyConnector { http, proxy }
+    }
+}
+
+pub fn parse_proxy(proxy: &str) -> Result<Uri, Error> {
+    let uri = Uri::from_str(proxy).map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+
+    match (uri.scheme_str(), uri.authority()) {
+        (Some("http"), Some(_)) => Ok(uri),
+        _ => {
+            let error = format!("Invalid proxy '{}', expected http://host:port!", proxy);
+
+            Err(Error::new(ErrorKind::InvalidInput, error))
+        }
+    }
+}
+
+impl Service<Uri> for ProxyConnector {
+    type Response = ProxyStream;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+    type Future = ProxyFuture;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, destination: Uri) -> ProxyFuture {
+        let mut http = self.http.to_owned();
+        let proxy = self.proxy.to_owned();
+
+        Box::pin(async move {
+            let proxy = match proxy {
+                Some(proxy) => proxy,
+                None => {
+                    let stream = http.call(destination).await?;
+
+                    return Ok(ProxyStream {
+                        stream,
+                        proxied: false,
+                    });
+                }
+            };
+            let mut stream = http.call(proxy).await?;
+
+            match destination.scheme_str() {
+                Some("https") => {
+                    launch_tunnel(&mut stream, &destination).await?;
+
+                    Ok(ProxyStream {
+                        stream,
+                        proxied: false,
+                    })
+                }
+                _ => Ok(ProxyStream {
+                    stream,
+                    proxied: true,
+                }),
+            }
+        })
+    }
+}
+
+async fn launch_tunnel(stream: &mut TcpStream, destination: &Uri) -> Result<(), Error> {
+    let host = destination.host().unwrap_or_default();
+    let port = destination.port_u16().unwrap_or(443);
+    let request = format!(
+        "CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n\r\n",
+        host = host,
+        port = port,
+    );
+
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = Vec::with_capacity(256);
+
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= 8192 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Proxy CONNECT response too large!",
+            ));
+        }
+
+        match stream.read_u8().await {
+            Ok(byte) => response.push(byte),
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => {
+                let error = "Proxy closed the connection during CONNECT!";
+
+                return Err(Error::new(ErrorKind::ConnectionAborted, error));
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status = response.split_whitespace().nth(1).unwrap_or_default();
+
+    match status {
+        "200" => Ok(()),
+        _ => {
+            let error = format!("Proxy CONNECT to {}:{} returned {}!", host, port, status);
+
+            Err(Error::new(ErrorKind::ConnectionRefused, error))
+        }
+    }
+}
+
+impl Connection for ProxyStream {
+    fn connected(&self) -> Connected {
+        self.stream.connected().proxy(self.proxied)
+    }
+}
+
+impl AsyncRead for ProxyStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for ProxyStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_proxy() {
+        assert!(super::parse_proxy("http://test-proxy:3128").is_ok());
+        assert!(super::parse_proxy("socks5://test-proxy:1080").is_err());
+        assert!(super::parse_proxy("test-proxy").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_tunnel() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_listener = TcpListener::bind("127.0.0.1:0").await?;
+        let test_address = test_listener.local_addr()?;
+        let test_proxy = tokio::spawn(async move {
+            let mut test_requests = Vec::with_capacity(2);
+            for test_response in [
+                "HTTP/1.1 200 Connection established",
+                "HTTP/1.1 403 Forbidden",
+            ] {
+                let (mut test_stream, _) = test_listener.accept().await?;
+                let mut test_buffer = [0; 256];
+                let test_length = test_stream.read(&mut test_buffer).await?;
+                test_requests
+                    .push(String::from_utf8_lossy(&test_buffer[..test_length]).into_owned());
+                test_stream
+                    .write_all(format!("{}\r\n\r\n", test_response).as_bytes())
+                    .await?;
+            }
+            Ok::<_, Error>(test_requests)
+        });
+        let test_destination = Uri::from_static("https://test-tunnel.invalid/test.deb");
+        let mut test_stream = TcpStream::connect(test_address).await?;
+        assert!(super::launch_tunnel(&mut test_stream, &test_destination)
+            .await
+            .is_ok());
+        let mut test_stream = TcpStream::connect(test_address).await?;
+        assert!(super::launch_tunnel(&mut test_stream, &test_destination)
+            .await
+            .is_err());
+        let test_requests = test_proxy.await??;
+        assert!(test_requests[0].starts_with("CONNECT test-tunnel.invalid:443 HTTP/1.1\r\n"));
+        Ok(())
+    }
+}
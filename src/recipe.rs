@@ -0,0 +1,457 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::env::var_os;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use tokio::fs::{copy, create_dir_all, read_to_string, remove_dir_all};
+use tokio::process::Command;
+
+use crate::checksum;
+use crate::hook::{self, HookOutput};
+use crate::template;
+use crate::verify;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Recipe {
+    pub name: String,
+    pub version: Option<String>,
+    pub source: String,
+    pub checksum: Option<String>,
+    pub checksum_url: Option<String>,
+    pub variables: BTreeMap<String, String>,
+    pub extract: Option<Extract>,
+    pub install: Vec<RecipeStep>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Extract {
+    pub format: Option<ArchiveFormat>,
+    pub strip_components: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+    TarXz,
+    TarBz2,
+    Zip,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RecipeStep {
+    Copy {
+        copy: String,
+        to: String,
+        #[serde(default)]
+        mode: Option<String>,
+    },
+    Run {
+        run: String,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipeReport {
+    pub name: String,
+    pub version: Option<String>,
+    pub source: String,
+    pub checksum: String,
+    pub file_path: PathBuf,
+    pub directory: PathBuf,
+    pub installed: Vec<PathBuf>,
+    pub steps: Vec<HookOutput>,
+}
+
+impl Recipe {
+    pub async fn init(path: &Path) -> Result<Recipe, Error> {
+        let contents = read_to_string(path).await?;
+
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Recipe, Error> {
+        let recipe: Recipe =
+            toml::from_str(contents).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+        if recipe.name.is_empty() || recipe.source.is_empty() {
+            let error = String::from("A recipe needs a name and a source!");
+
+            return Err(Error::new(ErrorKind::InvalidData, error));
+        }
+
+        Ok(recipe)
+    }
+
+    pub fn variables(&self, overrides: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        let mut variables = self.variables.to_owned();
+
+        variables.insert(String::from("name"), self.name.to_owned());
+
+        if let Some(version) = &self.version {
+            variables.insert(String::from("version"), version.to_owned());
+        }
+
+        if let Some(home) = var_os("HOME") {
+            variables.insert(String::from("home"), home.to_string_lossy().into_owned());
+        }
+
+        variables.extend(overrides.to_owned());
+        variables
+    }
+
+    pub fn staging_dir(&self, root: &Path) -> PathBuf {
+        match &self.version {
+            Some(version) => root.join(format!("{}-{}", self.name, version)),
+            None => root.join(&self.name),
+        }
+    }
+}
+
+impl ArchiveFormat {
+    pub fn detect(filename: &str) -> Option<ArchiveFormat> {
+        let filename = filename.to_ascii_lowercase();
+
+        [
+            (".tar.gz", ArchiveFormat::TarGz),
+            (".tgz", ArchiveFormat::TarGz),
+            (".tar.xz", ArchiveFormat::TarXz),
+            (".txz", ArchiveFormat::TarXz),
+            (".tar.bz2", ArchiveFormat::TarBz2),
+            (".tbz2", ArchiveFormat::TarBz2),
+            (".tar", ArchiveFormat::Tar),
+            (".zip", ArchiveFormat::Zip),
+        ]
+        .into_iter()
+        .find(|(suffix, _)| filename.ends_with(suffix))
+        .map(|(_, format)| format)
+    }
+
+    fn tar_flag(&self) -> Option<&'static str> {
+        match self {
+            ArchiveFormat::TarGz => Some("--gzip"),
+            ArchiveFormat::TarXz => Some("--xz"),
+            ArchiveFormat::TarBz2 => Some("--bzip2"),
+            _ => None,
+        }
+    }
+}
+
+pub fn select_checksum(contents: &str, filename: &str) -> Result<String, Error> {
+    let trimmed = contents.trim();
+
+    if checksum::is_valid(trimmed) {
+        return Ok(trimmed.to_ascii_lowercase());
+    }
+
+    let expected = verify::parse(contents, Path::new(""))?;
+    let selected = match expected.as_slice() {
+        [single] => Some(single),
+        expected => expected
+            .iter()
+            .find(|digest| digest.path.file_name().is_some_and(|name| name == filename)),
+    };
+
+    match selected {
+        Some(digest) => Ok(digest.checksum.to_owned()),
+        None => {
+            let error = format!("No checksum for {} found!", filename);
+
+            Err(Error::new(ErrorKind::NotFound, error))
+        }
+    }
+}
+
+pub async fn extract(archive: &Path, directory: &Path, rules: &Extract) -> Result<(), Error> {
+    let filename = archive
+        .file_name()
+        .map(|filename| filename.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let format = match rules.format.or_else(|| ArchiveFormat::detect(&filename)) {
+        Some(format) => format,
+        None => {
+            let error = format!("Unable to detect the archive format of {}!", filename);
+
+            return Err(Error::new(ErrorKind::InvalidInput, error));
+        }
+    };
+
+    match remove_dir_all(directory).await {
+        Err(error) if error.kind() != ErrorKind::NotFound => return Err(error),
+        _ => create_dir_all(directory).await?,
+    }
+
+    let output = match format {
+        ArchiveFormat::Zip if rules.strip_components > 0 => {
+            let error = String::from("strip_components is not supported for zip archives!");
+
+            return Err(Error::new(ErrorKind::InvalidInput, error));
+        }
+        ArchiveFormat::Zip => {
+            Command::new("unzip")
+                .arg("-q")
+                .arg("-o")
+                .arg(archive)
+                .arg("-d")
+                .arg(directory)
+                .output()
+                .await?
+        }
+        format => {
+            let mut command = Command::new("tar");
+
+            command.arg("--extract").arg("--file").arg(archive);
+
+            if let Some(flag) = format.tar_flag() {
+                command.arg(flag);
+            }
+
+            command
+                .arg(format!("--strip-components={}", rules.strip_components))
+                .arg("--directory")
+                .arg(directory)
+                .output()
+                .await?
+        }
+    };
+
+    if !output.status.success() {
+        let error = format!(
+            "Unable to extract {}: {}",
+            filename,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+
+        return Err(Error::other(error));
+    }
+
+    Ok(())
+}
+
+pub async fn install_step(
+    step: &RecipeStep,
+    directory: &Path,
+    variables: &BTreeMap<String, String>,
+    environment: &[(&str, String)],
+) -> Result<(Option<PathBuf>, Option<HookOutput>), Error> {
+    match step {
+        RecipeStep::Copy {
+            copy: from,
+            to,
+            mode,
+        } => {
+            let from = directory.join(template::expand(from, variables)?);
+            let to = PathBuf::from(template::expand(to, variables)?);
+
+            if let Some(parent) = to.parent() {
+                create_dir_all(parent).await?;
+            }
+
+            copy(&from, &to).await.map_err(|error| {
+                let message = format!("Unable to copy {}: {}", from.display(), error);
+
+                Error::new(error.kind(), message)
+            })?;
+
+            if let Some(mode) = mode {
+                set_mode(&to, mode).await?;
+            }
+
+            Ok((Some(to), None))
+        }
+        RecipeStep::Run { run } => {
+            let command = template::expand(run, variables)?;
+            let output = hook::run(&command, Some(directory), environment).await?;
+
+            Ok((None, Some(output)))
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn set_mode(path: &Path, mode: &str) -> Result<(), Error> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = u32::from_str_radix(mode, 8).map_err(|_| {
+        let error = format!("Invalid file mode '{}'!", mode);
+
+        Error::new(ErrorKind::InvalidInput, error)
+    })?;
+
+    tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).await
+}
+
+#[cfg(not(unix))]
+async fn set_mode(_: &Path, _: &str) -> Result<(), Error> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_RECIPE: &str = r#"
+        name = "test-tool"
+        version = "1.2.3"
+        source = "https://test-recipe.invalid/{name}-{version}-{target}.tar.gz"
+        checksum_url = "https://test-recipe.invalid/SHA256SUMS"
+
+        [variables]
+        target = "x86_64-linux"
+
+        [extract]
+        strip_components = 1
+
+        [[install]]
+        copy = "bin/{name}"
+        to = "/tmp/archeon_test_recipe/bin/{name}"
+        mode = "755"
+
+        [[install]]
+        run = "echo installed {version}"
+    "#;
+
+    const TEST_CHECKSUM: &str = "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_recipe = Recipe::parse(TEST_RECIPE)?;
+        assert_eq!(test_recipe.name, "test-tool");
+        assert_eq!(test_recipe.extract.as_ref().unwrap().strip_components, 1);
+        assert_eq!(test_recipe.install.len(), 2);
+        assert_eq!(
+            test_recipe.install[1],
+            RecipeStep::Run {
+                run: String::from("echo installed {version}"),
+            },
+        );
+        let test_variables = test_recipe.variables(&BTreeMap::from([(
+            String::from("target"),
+            String::from("aarch64-linux"),
+        )]));
+        assert_eq!(
+            template::expand(&test_recipe.source, &test_variables)?,
+            "https://test-recipe.invalid/test-tool-1.2.3-aarch64-linux.tar.gz",
+        );
+        assert_eq!(
+            test_recipe.staging_dir(Path::new("/tmp/archeon")),
+            PathBuf::from("/tmp/archeon/test-tool-1.2.3"),
+        );
+        assert!(Recipe::parse("name = \"test-tool\"").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn detect() {
+        assert_eq!(
+            ArchiveFormat::detect("tool-1.0.TAR.GZ"),
+            Some(ArchiveFormat::TarGz),
+        );
+        assert_eq!(
+            ArchiveFormat::detect("tool.txz"),
+            Some(ArchiveFormat::TarXz)
+        );
+        assert_eq!(ArchiveFormat::detect("tool.zip"), Some(ArchiveFormat::Zip));
+        assert!(ArchiveFormat::detect("tool.deb").is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn select_checksum() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!(
+            super::select_checksum(&format!("{}\n", TEST_CHECKSUM.to_ascii_uppercase()), "a")?,
+            TEST_CHECKSUM,
+        );
+        let test_sums = format!(
+            "{}  other.tar.gz\n{}  test-tool.tar.gz\n",
+            "0".repeat(64),
+            TEST_CHECKSUM
+        );
+        assert_eq!(
+            super::select_checksum(&test_sums, "test-tool.tar.gz")?,
+            TEST_CHECKSUM,
+        );
+        assert_eq!(
+            super::select_checksum(&test_sums, "missing.tar.gz")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NotFound,
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn extract() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_root = std::env::temp_dir().join("archeon_test_recipe_extract");
+        let test_archive = test_root.join("test-tool.tar.gz");
+        create_dir_all(test_root.join("test-tool-1.2.3/bin")).await?;
+        tokio::fs::write(
+            test_root.join("test-tool-1.2.3/bin/test-tool"),
+            b"test_body",
+        )
+        .await?;
+        let test_output = Command::new("tar")
+            .args(["--create", "--gzip", "--file"])
+            .arg(&test_archive)
+            .arg("--directory")
+            .arg(&test_root)
+            .arg("test-tool-1.2.3")
+            .output()
+            .await?;
+        assert!(test_output.status.success());
+        let test_directory = test_root.join("extracted");
+        super::extract(
+            &test_archive,
+            &test_directory,
+            &Extract {
+                strip_components: 1,
+                ..Extract::default()
+            },
+        )
+        .await?;
+        assert_eq!(
+            tokio::fs::read(test_directory.join("bin/test-tool")).await?,
+            b"test_body",
+        );
+        let test_variables = BTreeMap::from([(String::from("name"), String::from("test-tool"))]);
+        let (test_installed, _) = install_step(
+            &RecipeStep::Copy {
+                copy: String::from("bin/{name}"),
+                to: test_root.join("bin/{name}").to_string_lossy().into_owned(),
+                mode: Some(String::from("755")),
+            },
+            &test_directory,
+            &test_variables,
+            &[],
+        )
+        .await?;
+        assert_eq!(test_installed, Some(test_root.join("bin/test-tool")));
+        let (_, test_output) = install_step(
+            &RecipeStep::Run {
+                run: String::from("ls bin; echo {name} $ARCHEON_RECIPE"),
+            },
+            &test_directory,
+            &test_variables,
+            &[("ARCHEON_RECIPE", String::from("test-recipe"))],
+        )
+        .await?;
+        assert_eq!(
+            test_output.unwrap().stdout,
+            "test-tool\ntest-tool test-recipe\n"
+        );
+        assert!(super::extract(
+            &test_root.join("test-tool.deb"),
+            &test_directory,
+            &Extract::default()
+        )
+        .await
+        .is_err());
+        remove_dir_all(&test_root).await?;
+        Ok(())
+    }
+}
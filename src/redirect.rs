@@ -0,0 +1,133 @@
+use hyper::header::{HeaderMap, AUTHORIZATION, PROXY_AUTHORIZATION};
+use hyper::Uri;
+
+use serde::{Deserialize, Serialize};
+
+use std::io::{Error, ErrorKind};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedirectPolicy {
+    pub max_redirects: usize,
+    pub cross_origin: bool,
+    pub strip_authorization: bool,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> RedirectPolicy {
+        RedirectPolicy {
+            max_redirects: 10,
+            cross_origin: true,
+            strip_authorization: true,
+        }
+    }
+}
+
+impl RedirectPolicy {
+    pub fn none() -> RedirectPolicy {
+        RedirectPolicy {
+            max_redirects: 0,
+            ..RedirectPolicy::default()
+        }
+    }
+
+    pub fn check(&self, redirects: usize, from: &Uri, to: &Uri) -> Result<(), Error> {
+        if redirects > self.max_redirects {
+            let error = format!(
+                "Too many redirects while requesting {} (limit {})!",
+                from, self.max_redirects,
+            );
+
+            return Err(Error::other(error));
+        }
+
+        if !self.cross_origin && !same_origin(from, to) {
+            let error = format!("Cross-origin redirect from {} to {} denied!", from, to);
+
+            return Err(Error::new(ErrorKind::PermissionDenied, error));
+        }
+
+        Ok(())
+    }
+
+    pub fn redirect_headers(&self, headers: &mut HeaderMap, from: &Uri, to: &Uri) {
+        if self.strip_authorization && from.host() != to.host() {
+            headers.remove(AUTHORIZATION);
+            headers.remove(PROXY_AUTHORIZATION);
+        }
+    }
+}
+
+pub fn same_origin(from: &Uri, to: &Uri) -> bool {
+    from.scheme_str() == to.scheme_str()
+        && from.host().map(str::to_ascii_lowercase) == to.host().map(str::to_ascii_lowercase)
+        && from.port_u16() == to.port_u16()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn check() {
+        let test_from = Uri::from_static("https://portal.test/file.iso");
+        let test_same = Uri::from_static("https://PORTAL.test/mirror/file.iso");
+        let test_cross = Uri::from_static("https://cdn.test/file.iso");
+        let test_policy = RedirectPolicy {
+            max_redirects: 2,
+            cross_origin: false,
+            strip_authorization: true,
+        };
+        assert!(test_policy.check(2, &test_from, &test_same).is_ok());
+        assert!(test_policy.check(3, &test_from, &test_same).is_err());
+        assert_eq!(
+            test_policy
+                .check(1, &test_from, &test_cross)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::PermissionDenied,
+        );
+        assert!(RedirectPolicy::default()
+            .check(1, &test_from, &test_cross)
+            .is_ok());
+        assert!(RedirectPolicy::none()
+            .check(1, &test_from, &test_same)
+            .is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn redirect_headers() {
+        let test_from = Uri::from_static("https://portal.test/file.iso");
+        let test_same = Uri::from_static("https://portal.test/other.iso");
+        let test_cross = Uri::from_static("https://cdn.test/file.iso");
+        let mut test_headers = HeaderMap::new();
+        test_headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer test"));
+        RedirectPolicy::default().redirect_headers(&mut test_headers, &test_from, &test_same);
+        assert!(test_headers.contains_key(AUTHORIZATION));
+        let test_keep = RedirectPolicy {
+            strip_authorization: false,
+            ..RedirectPolicy::default()
+        };
+        test_keep.redirect_headers(&mut test_headers, &test_from, &test_cross);
+        assert!(test_headers.contains_key(AUTHORIZATION));
+        RedirectPolicy::default().redirect_headers(&mut test_headers, &test_from, &test_cross);
+        assert!(!test_headers.contains_key(AUTHORIZATION));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn same_origin() {
+        assert!(super::same_origin(
+            &Uri::from_static("http://portal.test:8080/a"),
+            &Uri::from_static("http://portal.test:8080/b"),
+        ));
+        assert!(!super::same_origin(
+            &Uri::from_static("http://portal.test/a"),
+            &Uri::from_static("https://portal.test/a"),
+        ));
+        assert!(!super::same_origin(
+            &Uri::from_static("http://portal.test:8080/a"),
+            &Uri::from_static("http://portal.test:9090/a"),
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+
+use crate::checksum;
+
+pub const PIECE_SIZE: u64 = 4 * 1024 * 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PieceHashes {
+    pub piece_size: u64,
+    pub hashes: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepairSummary {
+    pub file_path: PathBuf,
+    pub size: u64,
+    pub damaged: Vec<(u64, u64)>,
+    pub fetched: u64,
+    pub checksum: String,
+    #[serde(default)]
+    pub refetched: bool,
+}
+
+impl PieceHashes {
+    pub async fn hash(path: &Path, piece_size: u64) -> Result<PieceHashes, Error> {
+        let piece_size = piece_size.max(1);
+        let mut file = File::open(path).await?;
+        let mut hashes = Vec::with_capacity(16);
+        let mut piece = Vec::with_capacity(piece_size as usize);
+
+        loop {
+            piece.clear();
+
+            let read = (&mut file).take(piece_size).read_to_end(&mut piece).await?;
+
+            if read == 0 {
+                break;
+            }
+
+            hashes.push(checksum::sha256_bytes(&piece));
+        }
+
+        Ok(PieceHashes { piece_size, hashes })
+    }
+
+    pub fn range(&self, index: usize, size: u64) -> (u64, u64) {
+        piece_range(index, self.piece_size, size)
+    }
+
+    pub fn verify(&self, index: usize, bytes: &[u8]) -> bool {
+        self.hashes
+            .get(index)
+            .is_some_and(|expected| checksum::verify_bytes(bytes, expected))
+    }
+
+    pub fn damaged(&self, local: &PieceHashes) -> Vec<usize> {
+        (0..self.hashes.len())
+            .filter(|index| {
+                local
+                    .hashes
+                    .get(*index)
+                    .is_none_or(|hash| !hash.eq_ignore_ascii_case(&self.hashes[*index]))
+            })
+            .collect()
+    }
+}
+
+pub fn piece_count(size: u64, piece_size: u64) -> usize {
+    size.div_ceil(piece_size.max(1)) as usize
+}
+
+pub fn piece_range(index: usize, piece_size: u64, size: u64) -> (u64, u64) {
+    let start = index as u64 * piece_size;
+    let end = (start + piece_size).min(size).saturating_sub(1);
+
+    (start, end)
+}
+
+pub fn merge_ranges(ranges: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if last.1 + 1 == *start => last.1 = *end,
+            _ => merged.push((*start, *end)),
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn hash() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_piece_hashes.txt");
+        tokio::fs::write(&test_path, b"test_body_test_tags").await?;
+        let test_pieces = PieceHashes::hash(&test_path, 9).await?;
+        assert_eq!(test_pieces.hashes.len(), 3);
+        assert_eq!(test_pieces.hashes[0], checksum::sha256_bytes(b"test_body"));
+        assert!(test_pieces.verify(1, b"_test_tag"));
+        assert!(test_pieces.verify(2, b"s"));
+        assert!(!test_pieces.verify(3, b""));
+        tokio::fs::write(&test_path, b"test_bxdy_test_tag").await?;
+        let test_local = PieceHashes::hash(&test_path, 9).await?;
+        assert_eq!(test_pieces.damaged(&test_local), vec![0, 2]);
+        assert!(test_pieces.damaged(&test_pieces).is_empty());
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn piece_range() {
+        assert_eq!(super::piece_count(19, 9), 3);
+        assert_eq!(super::piece_count(18, 9), 2);
+        assert_eq!(super::piece_count(0, 9), 0);
+        assert_eq!(super::piece_range(0, 9, 19), (0, 8));
+        assert_eq!(super::piece_range(2, 9, 19), (18, 18));
+        assert_eq!(
+            merge_ranges(&[(0, 8), (9, 17), (27, 35)]),
+            vec![(0, 17), (27, 35)],
+        );
+    }
+}
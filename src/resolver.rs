@@ -0,0 +1,336 @@
+use hyper::client::connect::dns::Name;
+use hyper::service::Service;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use tokio::net::lookup_host;
+
+pub type ResolveFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<IpAddr>, Error>> + Send + 'a>>;
+
+pub trait Resolve: Send + Sync {
+    fn resolve<'a>(&'a self, host: &'a str) -> ResolveFuture<'a>;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemResolver;
+
+impl Resolve for SystemResolver {
+    fn resolve<'a>(&'a self, host: &'a str) -> ResolveFuture<'a> {
+        Box::pin(async move {
+            let addresses = lookup_host((host, 0)).await?;
+
+            Ok(addresses.map(|address| address.ip()).collect())
+        })
+    }
+}
+
+#[cfg(feature = "hickory-dns")]
+pub struct HickoryResolver {
+    pub resolver: hickory_resolver::TokioAsyncResolver,
+}
+
+#[cfg(feature = "hickory-dns")]
+impl HickoryResolver {
+    pub fn init() -> Result<HickoryResolver, Error> {
+        let resolver = hickory_resolver::TokioAsyncResolver::tokio_from_system_conf()?;
+
+        Ok(HickoryResolver { resolver })
+    }
+}
+
+#[cfg(feature = "hickory-dns")]
+impl Resolve for HickoryResolver {
+    fn resolve<'a>(&'a self, host: &'a str) -> ResolveFuture<'a> {
+        Box::pin(async move {
+            let lookup = self.resolver.lookup_ip(host).await?;
+
+            Ok(lookup.iter().collect())
+        })
+    }
+}
+
+type CachedLookup = (Instant, Result<Vec<IpAddr>, (ErrorKind, String)>);
+
+pub struct CachingResolver {
+    pub inner: Arc<dyn Resolve>,
+    pub positive_ttl: Duration,
+    pub negative_ttl: Duration,
+    entries: Mutex<HashMap<String, CachedLookup>>,
+}
+
+impl CachingResolver {
+    pub fn init(inner: Arc<dyn Resolve>) -> CachingResolver {
+        CachingResolver {
+            inner,
+            positive_ttl: Duration::from_secs(60),
+            negative_ttl: Duration::from_secs(5),
+            entries: Mutex::new(HashMap::with_capacity(16)),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lookup(&self, host: &str) -> Option<Result<Vec<IpAddr>, Error>> {
+        let entries = self.lock();
+        let (expires, result) = entries.get(host)?;
+
+        if *expires <= Instant::now() {
+            return None;
+        }
+
+        Some(match result {
+            Ok(addresses) => Ok(addresses.to_owned()),
+            Err((kind, error)) => Err(Error::new(*kind, error.to_owned())),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedLookup>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Resolve for CachingResolver {
+    fn resolve<'a>(&'a self, host: &'a str) -> ResolveFuture<'a> {
+        Box::pin(async move {
+            let host = host.to_ascii_lowercase();
+
+            if let Some(result) = self.lookup(&host) {
+                return result;
+            }
+
+            let result = self.inner.resolve(&host).await;
+            let entry = match &result {
+                Ok(addresses) => (Instant::now() + self.positive_ttl, Ok(addresses.to_owned())),
+                Err(error) => (
+                    Instant::now() + self.negative_ttl,
+                    Err((error.kind(), error.to_string())),
+                ),
+            };
+
+            self.lock().insert(host, entry);
+
+            result
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AddressFamily {
+    #[default]
+    Any,
+    PreferIpv4,
+    PreferIpv6,
+    Ipv4Only,
+    Ipv6Only,
+}
+
+impl AddressFamily {
+    pub fn order(&self, mut addresses: Vec<IpAddr>) -> Vec<IpAddr> {
+        match self {
+            AddressFamily::Any => {}
+            AddressFamily::PreferIpv4 => addresses.sort_by_key(|address| address.is_ipv6()),
+            AddressFamily::PreferIpv6 => addresses.sort_by_key(|address| address.is_ipv4()),
+            AddressFamily::Ipv4Only => addresses.retain(|address| address.is_ipv4()),
+            AddressFamily::Ipv6Only => addresses.retain(|address| address.is_ipv6()),
+        }
+
+        addresses
+    }
+}
+
+#[derive(Clone)]
+pub struct Resolver {
+    pub inner: Arc<dyn Resolve>,
+    pub family: AddressFamily,
+}
+
+impl Resolver {
+    pub fn init(resolve: impl Resolve + 'static) -> Resolver {
+        Resolver {
+            inner: Arc::new(resolve),
+            family: AddressFamily::default(),
+        }
+    }
+
+    pub fn cached(resolve: impl Resolve + 'static) -> Resolver {
+        Self::init(CachingResolver::init(Arc::new(resolve)))
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Resolver {
+        Self::cached(SystemResolver)
+    }
+}
+
+impl std::fmt::Debug for Resolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Resolver")
+            .field("family", &self.family)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Service<Name> for Resolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Error>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let inner = self.inner.to_owned();
+        let family = self.family;
+
+        Box::pin(async move {
+            let addresses = family.order(inner.resolve(name.as_str()).await?);
+
+            if addresses.is_empty() {
+                let error = format!("No addresses found for {}!", name);
+
+                return Err(Error::new(ErrorKind::NotFound, error));
+            }
+
+            let addresses: Vec<SocketAddr> = addresses
+                .into_iter()
+                .map(|address| SocketAddr::new(address, 0))
+                .collect();
+
+            Ok(addresses.into_iter())
+        })
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl reqwest::dns::Resolve for Resolver {
+    fn resolve(&self, name: Name) -> reqwest::dns::Resolving {
+        let mut resolver = self.to_owned();
+
+        Box::pin(async move {
+            let addresses = resolver.call(name).await?;
+
+            Ok(Box::new(addresses) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct TestResolver {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Resolve for TestResolver {
+        fn resolve<'a>(&'a self, host: &'a str) -> ResolveFuture<'a> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                match host {
+                    "missing.test" => Err(Error::new(ErrorKind::NotFound, "missing.test")),
+                    _ => Ok(vec![IpAddr::from([127, 0, 0, 1])]),
+                }
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn system_resolver() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_addresses = SystemResolver.resolve("127.0.0.1").await?;
+        assert_eq!(test_addresses, vec![IpAddr::from([127, 0, 0, 1])]);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn caching_resolver() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_calls = Arc::new(AtomicUsize::new(0));
+        let test_resolver = CachingResolver::init(Arc::new(TestResolver {
+            calls: test_calls.to_owned(),
+        }));
+        for _ in 0..3 {
+            test_resolver.resolve("CDN.test").await?;
+            assert!(test_resolver.resolve("missing.test").await.is_err());
+        }
+        assert_eq!(test_calls.load(Ordering::SeqCst), 2);
+        test_resolver.clear();
+        test_resolver.resolve("cdn.test").await?;
+        assert_eq!(test_calls.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn caching_resolver_expired() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_calls = Arc::new(AtomicUsize::new(0));
+        let mut test_resolver = CachingResolver::init(Arc::new(TestResolver {
+            calls: test_calls.to_owned(),
+        }));
+        test_resolver.positive_ttl = Duration::ZERO;
+        test_resolver.resolve("cdn.test").await?;
+        test_resolver.resolve("cdn.test").await?;
+        assert_eq!(test_calls.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn address_family_order() {
+        let test_ipv4 = IpAddr::from([127, 0, 0, 1]);
+        let test_ipv6 = IpAddr::from([0, 0, 0, 0, 0, 0, 0, 1]);
+        let test_addresses = vec![test_ipv4, test_ipv6];
+        assert_eq!(
+            AddressFamily::Any.order(test_addresses.to_owned()),
+            vec![test_ipv4, test_ipv6],
+        );
+        assert_eq!(
+            AddressFamily::PreferIpv6.order(test_addresses.to_owned()),
+            vec![test_ipv6, test_ipv4],
+        );
+        assert_eq!(
+            AddressFamily::PreferIpv4.order(vec![test_ipv6, test_ipv4]),
+            vec![test_ipv4, test_ipv6],
+        );
+        assert_eq!(
+            AddressFamily::Ipv4Only.order(test_addresses.to_owned()),
+            vec![test_ipv4],
+        );
+        assert_eq!(
+            AddressFamily::Ipv6Only.order(test_addresses),
+            vec![test_ipv6]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resolver_service_family() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_resolver = Resolver::init(TestResolver::default());
+        test_resolver.family = AddressFamily::Ipv6Only;
+        assert!(test_resolver.call("cdn.test".parse()?).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resolver_service() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_resolver = Resolver::cached(TestResolver::default());
+        let test_addresses: Vec<SocketAddr> =
+            test_resolver.call("cdn.test".parse()?).await?.collect();
+        assert_eq!(test_addresses, vec![SocketAddr::from(([127, 0, 0, 1], 0))]);
+        assert!(test_resolver.call("missing.test".parse()?).await.is_err());
+        Ok(())
+    }
+}
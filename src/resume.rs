@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+use std::io::{Error, ErrorKind, SeekFrom};
+use std::path::Path;
+
+use tokio::fs::{read_dir, File};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::http_cache::HttpCacheEntry;
+
+pub const TRAILING_BLOCK: u64 = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResumeCheck {
+    #[default]
+    None,
+    Trailing,
+    Full,
+}
+
+impl std::str::FromStr for ResumeCheck {
+    type Err = Error;
+
+    fn from_str(check: &str) -> Result<ResumeCheck, Error> {
+        match check {
+            "none" => Ok(ResumeCheck::None),
+            "trailing" => Ok(ResumeCheck::Trailing),
+            "full" => Ok(ResumeCheck::Full),
+            _ => {
+                let error = format!("Invalid resume check '{}'!", check);
+
+                Err(Error::new(ErrorKind::InvalidInput, error))
+            }
+        }
+    }
+}
+
+pub async fn read_range(path: &Path, start: u64, end: u64) -> Result<Vec<u8>, Error> {
+    let mut file = File::open(path).await?;
+    let mut bytes = vec![0; end.saturating_sub(start) as usize];
+
+    file.seek(SeekFrom::Start(start)).await?;
+    file.read_exact(&mut bytes).await?;
+
+    Ok(bytes)
+}
+
+pub async fn interrupted(directory: &Path) -> Result<Vec<String>, Error> {
+    let mut entries = match read_dir(directory).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+    let mut urls = Vec::with_capacity(4);
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "part")
+        {
+            if let Some(url) = HttpCacheEntry::load(&path)
+                .await
+                .and_then(|entry| entry.url)
+            {
+                urls.push(url);
+            }
+        }
+    }
+
+    urls.sort();
+
+    Ok(urls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn from_str() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!("none".parse::<ResumeCheck>()?, ResumeCheck::None);
+        assert_eq!("trailing".parse::<ResumeCheck>()?, ResumeCheck::Trailing);
+        assert_eq!("full".parse::<ResumeCheck>()?, ResumeCheck::Full);
+        assert!("tail".parse::<ResumeCheck>().is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn read_range() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_read_range.txt");
+        tokio::fs::write(&test_path, b"test_body").await?;
+        assert_eq!(super::read_range(&test_path, 5, 9).await?, b"body");
+        assert!(super::read_range(&test_path, 5, 12).await.is_err());
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn interrupted() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_interrupted");
+        tokio::fs::create_dir_all(&test_dir).await?;
+        for (test_name, test_url) in [
+            ("second.iso.part", Some("http://test.invalid/second.iso")),
+            ("first.iso.part", Some("http://test.invalid/first.iso")),
+            ("untracked.iso.part", None),
+            ("complete.iso", Some("http://test.invalid/complete.iso")),
+        ] {
+            let test_path = test_dir.join(test_name);
+            tokio::fs::write(&test_path, b"test_").await?;
+            HttpCacheEntry {
+                etag: Some(String::from("\"test_etag\"")),
+                url: test_url.map(String::from),
+                ..HttpCacheEntry::default()
+            }
+            .save(&test_path)
+            .await?;
+        }
+        assert_eq!(
+            super::interrupted(&test_dir).await?,
+            vec![
+                "http://test.invalid/first.iso",
+                "http://test.invalid/second.iso"
+            ],
+        );
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        assert!(super::interrupted(&test_dir).await?.is_empty());
+        Ok(())
+    }
+}
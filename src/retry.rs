@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use crate::error::ArcheonError;
+
+pub trait RetryPolicy: Send + Sync {
+    fn retry(&self, attempt: u32, error: &ArcheonError, elapsed: Duration) -> Option<Duration>;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NoRetry;
+
+impl RetryPolicy for NoRetry {
+    fn retry(&self, _: u32, _: &ArcheonError, _: Duration) -> Option<Duration> {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedRetry {
+    pub max_attempts: u32,
+    pub delay: Duration,
+}
+
+impl RetryPolicy for FixedRetry {
+    fn retry(&self, attempt: u32, error: &ArcheonError, _: Duration) -> Option<Duration> {
+        if attempt >= self.max_attempts || !error.is_retryable() {
+            return None;
+        }
+
+        Some(self.delay)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExponentialRetry {
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub multiplier: f64,
+    pub max_delay: Duration,
+    pub max_elapsed: Option<Duration>,
+}
+
+impl Default for ExponentialRetry {
+    fn default() -> ExponentialRetry {
+        ExponentialRetry {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(500),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(30),
+            max_elapsed: Some(Duration::from_secs(300)),
+        }
+    }
+}
+
+impl RetryPolicy for ExponentialRetry {
+    fn retry(&self, attempt: u32, error: &ArcheonError, elapsed: Duration) -> Option<Duration> {
+        if attempt >= self.max_attempts || !error.is_retryable() {
+            return None;
+        }
+
+        let exponent = attempt.saturating_sub(1) as i32;
+        let delay = self
+            .initial_delay
+            .mul_f64(self.multiplier.powi(exponent))
+            .min(self.max_delay);
+
+        match self.max_elapsed {
+            Some(max_elapsed) if elapsed + delay > max_elapsed => None,
+            _ => Some(delay),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::StatusCode;
+
+    fn test_status(status: StatusCode) -> ArcheonError {
+        ArcheonError::Status {
+            uri: String::from("http://test-retry/test.deb"),
+            status,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fixed_retry() {
+        let test_policy = FixedRetry {
+            max_attempts: 3,
+            delay: Duration::from_secs(1),
+        };
+        let test_error = test_status(StatusCode::BAD_GATEWAY);
+        assert_eq!(
+            test_policy.retry(1, &test_error, Duration::ZERO),
+            Some(Duration::from_secs(1)),
+        );
+        assert_eq!(
+            test_policy.retry(2, &test_error, Duration::ZERO),
+            Some(Duration::from_secs(1)),
+        );
+        assert!(test_policy.retry(3, &test_error, Duration::ZERO).is_none());
+        assert!(test_policy
+            .retry(1, &test_status(StatusCode::FORBIDDEN), Duration::ZERO)
+            .is_none());
+        assert!(test_policy
+            .retry(
+                1,
+                &ArcheonError::Integrity {
+                    source: String::from("http://test-retry/test.deb"),
+                    expected: String::from("expected"),
+                    actual: String::from("actual"),
+                },
+                Duration::ZERO,
+            )
+            .is_none());
+        assert!(NoRetry.retry(1, &test_error, Duration::ZERO).is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn exponential_retry() {
+        let test_policy = ExponentialRetry {
+            max_attempts: 10,
+            initial_delay: Duration::from_secs(1),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(5),
+            max_elapsed: Some(Duration::from_secs(20)),
+        };
+        let test_error = test_status(StatusCode::SERVICE_UNAVAILABLE);
+        let test_delays: Vec<Option<Duration>> = (1..=4)
+            .map(|attempt| test_policy.retry(attempt, &test_error, Duration::ZERO))
+            .collect();
+        assert_eq!(
+            test_delays,
+            vec![
+                Some(Duration::from_secs(1)),
+                Some(Duration::from_secs(2)),
+                Some(Duration::from_secs(4)),
+                Some(Duration::from_secs(5)),
+            ],
+        );
+        assert!(test_policy
+            .retry(2, &test_error, Duration::from_secs(19))
+            .is_none());
+        assert!(test_policy.retry(10, &test_error, Duration::ZERO).is_none());
+    }
+}
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+
+use std::io::Error;
+use std::path::Path;
+
+use tokio::fs::write;
+
+use crate::installer::{Backend, Installation};
+use crate::manifest::Manifest;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sbom {
+    pub bom_format: String,
+    pub spec_version: String,
+    pub version: u32,
+    pub metadata: SbomMetadata,
+    pub components: Vec<SbomComponent>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SbomMetadata {
+    pub tools: Vec<SbomTool>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SbomTool {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SbomComponent {
+    #[serde(rename = "type")]
+    pub component_type: String,
+    pub name: String,
+    pub version: String,
+    pub purl: String,
+    pub hashes: Vec<SbomHash>,
+    pub external_references: Vec<SbomReference>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SbomHash {
+    pub alg: String,
+    pub content: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SbomReference {
+    #[serde(rename = "type")]
+    pub reference_type: String,
+    pub url: String,
+}
+
+impl Sbom {
+    pub fn init(manifest: &Manifest) -> Sbom {
+        let components = manifest
+            .installations
+            .iter()
+            .map(Self::init_component)
+            .collect();
+
+        Sbom {
+            bom_format: String::from("CycloneDX"),
+            spec_version: String::from("1.4"),
+            version: 1,
+            metadata: SbomMetadata {
+                tools: vec![SbomTool {
+                    name: String::from(env!("CARGO_PKG_NAME")),
+                    version: String::from(env!("CARGO_PKG_VERSION")),
+                }],
+            },
+            components,
+        }
+    }
+
+    fn init_component(installation: &Installation) -> SbomComponent {
+        SbomComponent {
+            component_type: String::from("application"),
+            name: installation.package.to_owned(),
+            version: installation.version.to_owned(),
+            purl: Self::init_purl(installation),
+            hashes: vec![SbomHash {
+                alg: String::from("SHA-256"),
+                content: installation.checksum.to_owned(),
+            }],
+            external_references: vec![SbomReference {
+                reference_type: String::from("distribution"),
+                url: installation.source.to_owned(),
+            }],
+        }
+    }
+
+    fn init_purl(installation: &Installation) -> String {
+        let package = installation.package.to_owned();
+        let (purl_type, name) = match (
+            &installation.installer.plugin,
+            installation.installer.backend,
+        ) {
+            (Some(_), _) => ("generic", package),
+            (None, Backend::Cargo) => ("cargo", package),
+            (None, Backend::Pip) => ("pypi", package.to_ascii_lowercase().replace('_', "-")),
+            (None, Backend::Rpm) => ("rpm", package),
+            (None, Backend::Dpkg) => ("deb", package),
+        };
+
+        format!("pkg:{}/{}@{}", purl_type, name, installation.version)
+    }
+
+    pub async fn write(&self, path: &Path) -> Result<(), Error> {
+        let contents = serde_json::to_vec_pretty(self)?;
+
+        write(path, contents).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::installer::Installer;
+    use std::path::PathBuf;
+
+    fn test_manifest() -> Manifest {
+        Manifest {
+            path: None,
+            installations: vec![test_installation(Installer::default())],
+            pins: Vec::new(),
+        }
+    }
+
+    fn test_installation(installer: Installer) -> Installation {
+        Installation {
+            id: 0,
+            package: String::from("test-package"),
+            version: String::from("1.0.0"),
+            source: String::from("http://test-sbom/test-package.deb"),
+            checksum: String::from("test_checksum"),
+            installed: 1_686_000_000,
+            artifact: PathBuf::from("/tmp/archeon/test-package.deb"),
+            installer,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() {
+        let test_sbom = Sbom::init(&test_manifest());
+        assert_eq!(test_sbom.bom_format, "CycloneDX");
+        assert_eq!(test_sbom.spec_version, "1.4");
+        assert_eq!(test_sbom.metadata.tools[0].name, "archeon");
+        assert_eq!(test_sbom.components.len(), 1);
+        assert_eq!(test_sbom.components[0].name, "test-package");
+        assert_eq!(test_sbom.components[0].version, "1.0.0");
+        assert_eq!(test_sbom.components[0].purl, "pkg:deb/test-package@1.0.0");
+        assert_eq!(test_sbom.components[0].hashes[0].alg, "SHA-256");
+        assert_eq!(test_sbom.components[0].hashes[0].content, "test_checksum");
+        assert_eq!(
+            test_sbom.components[0].external_references[0].url,
+            "http://test-sbom/test-package.deb",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn purl_cargo() {
+        let test_installation = test_installation(Installer {
+            backend: Backend::Cargo,
+            ..Installer::default()
+        });
+        assert_eq!(
+            Sbom::init_component(&test_installation).purl,
+            "pkg:cargo/test-package@1.0.0",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn purl_pip() {
+        let mut test_installation = test_installation(Installer {
+            backend: Backend::Pip,
+            ..Installer::default()
+        });
+        test_installation.package = String::from("Test_Package");
+        assert_eq!(
+            Sbom::init_component(&test_installation).purl,
+            "pkg:pypi/test-package@1.0.0",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn purl_rpm() {
+        let test_installation = test_installation(Installer {
+            backend: Backend::Rpm,
+            ..Installer::default()
+        });
+        assert_eq!(
+            Sbom::init_component(&test_installation).purl,
+            "pkg:rpm/test-package@1.0.0",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn purl_plugin() {
+        for test_backend in [Backend::Dpkg, Backend::Cargo] {
+            let test_installation = test_installation(Installer {
+                plugin: Some(String::from("test-plugin")),
+                backend: test_backend,
+                ..Installer::default()
+            });
+            assert_eq!(
+                Sbom::init_component(&test_installation).purl,
+                "pkg:generic/test-package@1.0.0",
+            );
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn write() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_path = std::env::temp_dir().join("archeon_test_sbom_write.json");
+        let test_sbom = Sbom::init(&test_manifest());
+        test_sbom.write(&test_path).await?;
+        let test_contents = tokio::fs::read(&test_path).await?;
+        let test_value: serde_json::Value = serde_json::from_slice(&test_contents)?;
+        assert_eq!(test_value["bomFormat"], "CycloneDX");
+        assert_eq!(test_value["specVersion"], "1.4");
+        assert_eq!(test_value["components"][0]["type"], "application");
+        assert_eq!(
+            test_value["components"][0]["externalReferences"][0]["type"],
+            "distribution",
+        );
+        tokio::fs::remove_file(&test_path).await?;
+        Ok(())
+    }
+}
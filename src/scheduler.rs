@@ -0,0 +1,339 @@
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::batch::BatchArtifact;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Schedule {
+    Interval(Duration),
+    Cron(CronExpression),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronExpression {
+    pub minutes: u64,
+    pub hours: u64,
+    pub days_of_month: u64,
+    pub months: u64,
+    pub days_of_week: u64,
+    pub any_day_of_month: bool,
+    pub any_day_of_week: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduledDownload {
+    pub artifact: BatchArtifact,
+    pub schedule: Schedule,
+    pub next: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Scheduler {
+    pub entries: Vec<ScheduledDownload>,
+}
+
+impl FromStr for Schedule {
+    type Err = Error;
+
+    fn from_str(schedule: &str) -> Result<Schedule, Error> {
+        let schedule = schedule.trim();
+
+        let expression = match schedule {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            _ => match schedule.strip_prefix("@every") {
+                Some(interval) => return Self::parse_interval(interval.trim()),
+                None => schedule,
+            },
+        };
+
+        Ok(Schedule::Cron(expression.parse()?))
+    }
+}
+
+impl Schedule {
+    fn parse_interval(interval: &str) -> Result<Schedule, Error> {
+        let split = interval
+            .find(|character: char| !character.is_ascii_digit())
+            .unwrap_or(interval.len());
+        let (value, unit) = interval.split_at(split);
+        let value: u64 = value.parse().map_err(|_| Self::invalid(interval))?;
+        let seconds = match unit {
+            "s" => value,
+            "m" => value * 60,
+            "h" => value * 60 * 60,
+            "d" => value * 60 * 60 * 24,
+            _ => return Err(Self::invalid(interval)),
+        };
+
+        if seconds == 0 {
+            return Err(Self::invalid(interval));
+        }
+
+        Ok(Schedule::Interval(Duration::from_secs(seconds)))
+    }
+
+    fn invalid(schedule: &str) -> Error {
+        let error = format!("Invalid schedule '{}'!", schedule);
+
+        Error::new(ErrorKind::InvalidInput, error)
+    }
+
+    pub fn next_after(&self, timestamp: u64) -> Option<u64> {
+        match self {
+            Schedule::Interval(interval) => Some(timestamp + interval.as_secs()),
+            Schedule::Cron(expression) => expression.next_after(timestamp),
+        }
+    }
+}
+
+impl FromStr for CronExpression {
+    type Err = Error;
+
+    fn from_str(expression: &str) -> Result<CronExpression, Error> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+
+        if fields.len() != 5 {
+            return Err(Schedule::invalid(expression));
+        }
+
+        let parse = |field: &str, minimum: u64, maximum: u64| {
+            Self::parse_field(field, minimum, maximum).ok_or_else(|| Schedule::invalid(expression))
+        };
+        let mut days_of_week = parse(fields[4], 0, 7)?;
+
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+
+        Ok(CronExpression {
+            minutes: parse(fields[0], 0, 59)?,
+            hours: parse(fields[1], 0, 23)?,
+            days_of_month: parse(fields[2], 1, 31)?,
+            months: parse(fields[3], 1, 12)?,
+            days_of_week,
+            any_day_of_month: fields[2] == "*",
+            any_day_of_week: fields[4] == "*",
+        })
+    }
+}
+
+impl CronExpression {
+    fn parse_field(field: &str, minimum: u64, maximum: u64) -> Option<u64> {
+        let mut mask = 0;
+
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse().ok().filter(|step| *step > 0)?),
+                None => (part, 1),
+            };
+            let (start, end) = match range {
+                "*" => (minimum, maximum),
+                _ => match range.split_once('-') {
+                    Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+                    None => {
+                        let value = range.parse().ok()?;
+
+                        (value, if step > 1 { maximum } else { value })
+                    }
+                },
+            };
+
+            if start < minimum || end > maximum || start > end {
+                return None;
+            }
+
+            for value in (start..=end).step_by(step as usize) {
+                mask |= 1 << value;
+            }
+        }
+
+        Some(mask)
+    }
+
+    pub fn next_after(&self, timestamp: u64) -> Option<u64> {
+        let mut candidate = (timestamp / 60 + 1) * 60;
+        let limit = timestamp + 5 * 366 * 24 * 60 * 60;
+
+        while candidate <= limit {
+            let days = candidate / 86400;
+            let (_, month, day) = civil_from_days(days);
+            let weekday = (days + 4) % 7;
+            let hour = (candidate % 86400) / 3600;
+            let minute = (candidate % 3600) / 60;
+            let day_of_month = self.days_of_month & (1 << day) != 0;
+            let day_of_week = self.days_of_week & (1 << weekday) != 0;
+            let day_matches = match (self.any_day_of_month, self.any_day_of_week) {
+                (true, true) => true,
+                (true, false) => day_of_week,
+                (false, true) => day_of_month,
+                (false, false) => day_of_month || day_of_week,
+            };
+
+            if self.months & (1 << month) == 0 || !day_matches {
+                candidate = (days + 1) * 86400;
+            } else if self.hours & (1 << hour) == 0 {
+                candidate = (candidate / 3600 + 1) * 3600;
+            } else if self.minutes & (1 << minute) == 0 {
+                candidate += 60;
+            } else {
+                return Some(candidate);
+            }
+        }
+
+        None
+    }
+}
+
+pub fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year as u64, month as u64, day as u64)
+}
+
+impl Scheduler {
+    pub fn add(&mut self, artifact: BatchArtifact, schedule: Schedule, now: u64) -> Option<u64> {
+        let next = schedule.next_after(now)?;
+
+        self.entries.push(ScheduledDownload {
+            artifact,
+            schedule,
+            next,
+        });
+
+        Some(next)
+    }
+
+    pub fn next_due(&self) -> Option<u64> {
+        self.entries.iter().map(|entry| entry.next).min()
+    }
+
+    pub fn take_due(&mut self, now: u64) -> Vec<BatchArtifact> {
+        let mut due = Vec::with_capacity(self.entries.len());
+
+        self.entries.retain_mut(|entry| {
+            if entry.next > now {
+                return true;
+            }
+
+            let mut artifact = entry.artifact.to_owned();
+
+            artifact.refresh = true;
+            due.push(artifact);
+
+            match entry.schedule.next_after(now) {
+                Some(next) => {
+                    entry.next = next;
+                    true
+                }
+                None => false,
+            }
+        });
+
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn schedule_from_str() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!(
+            "@every 90s".parse::<Schedule>()?,
+            Schedule::Interval(Duration::from_secs(90)),
+        );
+        assert_eq!(
+            "@every 2h".parse::<Schedule>()?,
+            Schedule::Interval(Duration::from_secs(7200)),
+        );
+        assert_eq!(
+            "@daily".parse::<Schedule>()?,
+            "0 0 * * *".parse::<Schedule>()?,
+        );
+        assert!("@every 0s".parse::<Schedule>().is_err());
+        assert!("@every 5y".parse::<Schedule>().is_err());
+        assert!("* * *".parse::<Schedule>().is_err());
+        assert!("61 * * * *".parse::<Schedule>().is_err());
+        assert!("*/0 * * * *".parse::<Schedule>().is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn cron_expression_from_str() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_expression: CronExpression = "0,30 9-17 */10 * 7".parse()?;
+        assert_eq!(test_expression.minutes, (1 << 0) | (1 << 30));
+        assert_eq!(test_expression.hours, 0b111111111 << 9);
+        assert_eq!(
+            test_expression.days_of_month,
+            (1 << 1) | (1 << 11) | (1 << 21) | (1 << 31)
+        );
+        assert_eq!(test_expression.days_of_week, (1 << 7) | 1);
+        assert!(!test_expression.any_day_of_week);
+        assert!(!test_expression.any_day_of_month);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn next_after() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_timestamp = 1_686_830_400;
+        let test_daily: Schedule = "30 2 * * *".parse()?;
+        let test_weekly: Schedule = "@weekly".parse()?;
+        let test_interval: Schedule = "@every 10m".parse()?;
+        let test_impossible: Schedule = "0 0 31 2 *".parse()?;
+        assert_eq!(test_daily.next_after(test_timestamp), Some(1_686_882_600));
+        assert_eq!(test_weekly.next_after(test_timestamp), Some(1_687_046_400));
+        assert_eq!(
+            test_interval.next_after(test_timestamp),
+            Some(1_686_831_000)
+        );
+        assert_eq!(test_impossible.next_after(test_timestamp), None);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn civil_from_days() {
+        assert_eq!(super::civil_from_days(0), (1970, 1, 1));
+        assert_eq!(super::civil_from_days(19_523), (2023, 6, 15));
+        assert_eq!(super::civil_from_days(11_016), (2000, 2, 29));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn scheduler() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_scheduler = Scheduler::default();
+        let test_artifact = BatchArtifact {
+            url: String::from("http://test-scheduler/image.iso"),
+            ..BatchArtifact::default()
+        };
+        assert!(test_scheduler.next_due().is_none());
+        assert_eq!(
+            test_scheduler.add(test_artifact, "@every 60s".parse()?, 1000),
+            Some(1060),
+        );
+        assert_eq!(test_scheduler.next_due(), Some(1060));
+        assert!(test_scheduler.take_due(1059).is_empty());
+        let test_due = test_scheduler.take_due(1060);
+        assert_eq!(test_due.len(), 1);
+        assert!(test_due[0].refresh);
+        assert_eq!(test_scheduler.next_due(), Some(1120));
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::io::Error;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signal {
+    Interrupt,
+    Terminate,
+}
+
+impl Default for Shutdown {
+    fn default() -> Shutdown {
+        let (sender, _) = watch::channel(false);
+
+        Shutdown {
+            sender: Arc::new(sender),
+        }
+    }
+}
+
+impl Shutdown {
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    pub async fn triggered(&self) {
+        let mut receiver = self.sender.subscribe();
+
+        let _ = receiver.wait_for(|triggered| *triggered).await;
+    }
+}
+
+impl Signal {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Signal::Interrupt => 130,
+            Signal::Terminate => 143,
+        }
+    }
+}
+
+#[cfg(unix)]
+pub async fn wait_for_signal() -> Result<Signal, Error> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let mut terminate = signal(SignalKind::terminate())?;
+
+    tokio::select! {
+        _ = interrupt.recv() => Ok(Signal::Interrupt),
+        _ = terminate.recv() => Ok(Signal::Terminate),
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn wait_for_signal() -> Result<Signal, Error> {
+    tokio::signal::ctrl_c().await?;
+
+    Ok(Signal::Interrupt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn trigger() {
+        let test_shutdown = Shutdown::default();
+        let test_waiting = test_shutdown.to_owned();
+        let test_triggered = tokio::spawn(async move { test_waiting.triggered().await });
+        assert!(!test_shutdown.is_triggered());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!test_triggered.is_finished());
+        test_shutdown.trigger();
+        test_triggered.await.unwrap();
+        assert!(test_shutdown.is_triggered());
+        test_shutdown.triggered().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn triggered_clones() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_shutdown = Shutdown::default();
+        let test_clones = vec![test_shutdown.to_owned(), test_shutdown.to_owned()];
+        test_shutdown.trigger();
+        for test_clone in test_clones {
+            assert!(test_clone.is_triggered());
+            tokio::time::timeout(Duration::from_secs(1), test_clone.triggered()).await?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn exit_code() {
+        assert_eq!(Signal::Interrupt.exit_code(), 130);
+        assert_eq!(Signal::Terminate.exit_code(), 143);
+    }
+}
@@ -0,0 +1,98 @@
+use flate2::read::GzDecoder;
+
+use hyper::body::{to_bytes, Bytes};
+use hyper::header::HeaderMap;
+use hyper::{Body, Request, StatusCode};
+
+use std::cmp::Ordering;
+use std::io::{Error, ErrorKind, Read};
+
+use crate::transfer::HttpClient;
+
+pub mod apt;
+pub mod crates;
+pub mod debdelta;
+pub mod helm;
+pub mod index;
+pub mod oci;
+pub mod pypi;
+pub mod yum;
+
+pub(crate) async fn launch_request(
+    client: &HttpClient,
+    request: Request<Body>,
+) -> Result<(StatusCode, HeaderMap, Bytes), Error> {
+    let response = client.send(request).await.map_err(Error::other)?;
+    let status = response.status();
+    let headers = response.headers().to_owned();
+    let body = to_bytes(response.into_body()).await.map_err(Error::other)?;
+
+    Ok((status, headers, body))
+}
+
+pub(crate) fn decompress(path: &str, body: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut contents = Vec::new();
+
+    if path.ends_with(".xz") {
+        lzma_rs::xz_decompress(&mut &body[..], &mut contents)
+            .map_err(|error| Error::new(ErrorKind::InvalidData, format!("{:?}", error)))?;
+    } else if path.ends_with(".gz") {
+        GzDecoder::new(body).read_to_end(&mut contents)?;
+    } else {
+        contents.extend_from_slice(body);
+    }
+
+    Ok(contents)
+}
+
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |version: &str| {
+        let version = version.trim_start_matches('v');
+        let version = version
+            .split_once('+')
+            .map_or(version, |(version, _)| version);
+        let (release, prerelease) = match version.split_once('-') {
+            Some((release, prerelease)) => (release, Some(prerelease.to_owned())),
+            None => (version, None),
+        };
+        let release = release
+            .split('.')
+            .map(|part| part.parse::<u64>().unwrap_or_default())
+            .collect::<Vec<u64>>();
+
+        (release, prerelease)
+    };
+    let (a_release, a_prerelease) = parse(a);
+    let (b_release, b_prerelease) = parse(b);
+
+    a_release
+        .cmp(&b_release)
+        .then_with(|| match (a_prerelease, b_prerelease) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => a.cmp(&b),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn compare_versions() {
+        assert_eq!(
+            super::compare_versions("1.10.0", "1.9.3"),
+            Ordering::Greater
+        );
+        assert_eq!(super::compare_versions("v1.2.0", "1.2.0"), Ordering::Equal);
+        assert_eq!(
+            super::compare_versions("2.0.0-rc.1", "2.0.0"),
+            Ordering::Less
+        );
+        assert_eq!(
+            super::compare_versions("1.0.0+build.5", "1.0.0"),
+            Ordering::Equal
+        );
+    }
+}
@@ -0,0 +1,889 @@
+use hyper::header::ACCEPT;
+use hyper::{Body, Method, Request, StatusCode, Uri};
+
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::auth::Authenticate;
+use crate::checksum;
+use crate::source::{decompress, launch_request};
+use crate::transfer::{HttpClient, Transfer};
+
+pub const DEFAULT_COMPONENT: &str = "main";
+pub const LAUNCHPAD_PPA: &str = "https://ppa.launchpadcontent.net/";
+pub const OS_RELEASE: &str = "/etc/os-release";
+pub const TRUSTED_KEYRING: &str = "/etc/apt/trusted.gpg";
+pub const TRUSTED_KEYRINGS: &str = "/etc/apt/trusted.gpg.d";
+
+const INDEX_FILES: [&str; 3] = ["Packages.xz", "Packages.gz", "Packages"];
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AptPackage {
+    pub package: String,
+    pub version: String,
+    pub architecture: String,
+    pub filename: String,
+    pub sha256: String,
+    pub size: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AptIndex {
+    pub packages: Vec<AptPackage>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AptRelease {
+    pub suite: Option<String>,
+    pub codename: Option<String>,
+    pub sha256: BTreeMap<String, String>,
+}
+
+#[derive(Clone)]
+pub struct AptRepository {
+    pub url: Uri,
+    pub suite: String,
+    pub components: Vec<String>,
+    pub architecture: String,
+    pub keyrings: Vec<PathBuf>,
+    pub trusted: bool,
+    pub client: HttpClient,
+    pub credentials: Option<Arc<dyn Authenticate>>,
+}
+
+pub fn debian_architecture() -> String {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "x86" => "i386",
+        "aarch64" => "arm64",
+        "arm" => "armhf",
+        "powerpc64" => "ppc64el",
+        "loongarch64" => "loong64",
+        architecture => architecture,
+    }
+    .to_owned()
+}
+
+pub fn trusted_keyrings() -> Vec<PathBuf> {
+    let mut keyrings = std::fs::read_dir(TRUSTED_KEYRINGS)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "gpg"))
+                .collect::<Vec<PathBuf>>()
+        })
+        .unwrap_or_default();
+
+    keyrings.sort();
+
+    if Path::new(TRUSTED_KEYRING).is_file() {
+        keyrings.insert(0, PathBuf::from(TRUSTED_KEYRING));
+    }
+
+    keyrings
+}
+
+pub fn ppa_url(shorthand: &str) -> Result<String, Error> {
+    let invalid = || {
+        let error = format!("Invalid PPA '{}', expected ppa:user/name!", shorthand);
+
+        Error::new(ErrorKind::InvalidInput, error)
+    };
+    let ppa = shorthand.strip_prefix("ppa:").ok_or_else(invalid)?;
+    let (user, name) = ppa.split_once('/').unwrap_or((ppa, "ppa"));
+    let valid = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|character| character.is_ascii_alphanumeric() || "-_.+".contains(character))
+    };
+
+    if !valid(user) || !valid(name) {
+        return Err(invalid());
+    }
+
+    Ok(format!("{}{}/{}/ubuntu", LAUNCHPAD_PPA, user, name))
+}
+
+pub fn parse_codename(os_release: &str) -> Option<String> {
+    let field = |key: &str| {
+        os_release.lines().find_map(|line| {
+            let value = line.trim().strip_prefix(key)?.strip_prefix('=')?;
+            let value = value.trim_matches(|character| character == '"' || character == '\'');
+
+            (!value.is_empty()).then(|| value.to_owned())
+        })
+    };
+
+    field("UBUNTU_CODENAME").or_else(|| field("VERSION_CODENAME"))
+}
+
+pub async fn distro_codename() -> Result<String, Error> {
+    let os_release = tokio::fs::read_to_string(OS_RELEASE).await?;
+
+    parse_codename(&os_release).ok_or_else(|| {
+        let error = format!("No distro codename found in {}!", OS_RELEASE);
+
+        Error::new(ErrorKind::NotFound, error)
+    })
+}
+
+pub fn parse_paragraphs(contents: &str) -> Vec<BTreeMap<String, String>> {
+    let mut paragraphs = Vec::new();
+    let mut paragraph: BTreeMap<String, String> = BTreeMap::new();
+    let mut field: Option<String> = None;
+
+    for line in contents.lines() {
+        if line.trim().is_empty() {
+            if !paragraph.is_empty() {
+                paragraphs.push(std::mem::take(&mut paragraph));
+            }
+
+            field = None;
+        } else if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(value) = field.as_ref().and_then(|field| paragraph.get_mut(field)) {
+                value.push('\n');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            paragraph.insert(name.trim().to_owned(), value.trim().to_owned());
+            field = Some(name.trim().to_owned());
+        }
+    }
+
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph);
+    }
+
+    paragraphs
+}
+
+pub fn compare_debian_versions(a: &str, b: &str) -> Ordering {
+    let split = |version: &str| {
+        let (epoch, version) = match version.split_once(':') {
+            Some((epoch, version)) => (epoch.parse::<u64>().unwrap_or_default(), version),
+            None => (0, version),
+        };
+        let (upstream, revision) = version.rsplit_once('-').unwrap_or((version, ""));
+
+        (epoch, upstream.to_owned(), revision.to_owned())
+    };
+    let (a_epoch, a_upstream, a_revision) = split(a);
+    let (b_epoch, b_upstream, b_revision) = split(b);
+
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| compare_fragment(&a_upstream, &b_upstream))
+        .then_with(|| compare_fragment(&a_revision, &b_revision))
+}
+
+fn compare_fragment(a: &str, b: &str) -> Ordering {
+    let order = |character: Option<&u8>| match character {
+        Some(b'~') => -1,
+        None => 0,
+        Some(character) if character.is_ascii_alphabetic() => i32::from(*character),
+        Some(character) => i32::from(*character) + 256,
+    };
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+
+    while !a.is_empty() || !b.is_empty() {
+        let a_text = a.iter().take_while(|byte| !byte.is_ascii_digit()).count();
+        let b_text = b.iter().take_while(|byte| !byte.is_ascii_digit()).count();
+
+        for index in 0..a_text.max(b_text) {
+            let a_order = order(a[..a_text].get(index));
+            let b_order = order(b[..b_text].get(index));
+
+            if a_order != b_order {
+                return a_order.cmp(&b_order);
+            }
+        }
+
+        (a, b) = (&a[a_text..], &b[b_text..]);
+
+        let a_digits = a.iter().take_while(|byte| byte.is_ascii_digit()).count();
+        let b_digits = b.iter().take_while(|byte| byte.is_ascii_digit()).count();
+        let number = |digits: &[u8]| {
+            let digits = String::from_utf8_lossy(digits);
+            let digits = digits.trim_start_matches('0');
+
+            (digits.len(), digits.to_owned())
+        };
+        let ordering = number(&a[..a_digits]).cmp(&number(&b[..b_digits]));
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        (a, b) = (&a[a_digits..], &b[b_digits..]);
+    }
+
+    Ordering::Equal
+}
+
+impl AptIndex {
+    pub fn parse(contents: &str) -> AptIndex {
+        let packages = parse_paragraphs(contents)
+            .into_iter()
+            .filter_map(|mut paragraph| {
+                Some(AptPackage {
+                    package: paragraph.remove("Package")?,
+                    version: paragraph.remove("Version")?,
+                    architecture: paragraph.remove("Architecture").unwrap_or_default(),
+                    filename: paragraph.remove("Filename")?,
+                    sha256: paragraph.remove("SHA256").unwrap_or_default(),
+                    size: paragraph.get("Size").and_then(|size| size.parse().ok()),
+                })
+            })
+            .collect();
+
+        AptIndex { packages }
+    }
+
+    pub fn decode(path: &str, body: &[u8]) -> Result<AptIndex, Error> {
+        let contents = decompress(path, body)?;
+
+        Ok(AptIndex::parse(&String::from_utf8_lossy(&contents)))
+    }
+
+    pub fn package(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        architecture: &str,
+    ) -> Option<&AptPackage> {
+        self.packages
+            .iter()
+            .filter(|package| package.package == name)
+            .filter(|package| package.architecture == architecture || package.architecture == "all")
+            .filter(|package| version.is_none_or(|version| package.version == version))
+            .max_by(|a, b| compare_debian_versions(&a.version, &b.version))
+    }
+}
+
+impl AptRelease {
+    pub fn parse(contents: &str) -> AptRelease {
+        let mut paragraph = parse_paragraphs(contents)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        let sha256 = paragraph
+            .get("SHA256")
+            .map(|files| {
+                files
+                    .lines()
+                    .filter_map(|line| {
+                        let mut parts = line.split_whitespace();
+                        let hash = parts.next()?;
+                        let path = parts.nth(1)?;
+
+                        Some((path.to_owned(), hash.to_owned()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        AptRelease {
+            suite: paragraph.remove("Suite"),
+            codename: paragraph.remove("Codename"),
+            sha256,
+        }
+    }
+
+    pub fn verify(&self, path: &str, body: &[u8]) -> Result<(), Error> {
+        let expected = self.sha256.get(path).ok_or_else(|| {
+            let error = format!("No SHA256 for {} listed in the signed Release!", path);
+
+            Error::new(ErrorKind::InvalidData, error)
+        })?;
+
+        match checksum::verify_bytes(body, expected) {
+            true => Ok(()),
+            false => {
+                let error = format!(
+                    "Checksum mismatch for {}: expected {}, got {}!",
+                    path,
+                    expected,
+                    checksum::sha256_bytes(body),
+                );
+
+                Err(Error::new(ErrorKind::InvalidData, error))
+            }
+        }
+    }
+}
+
+impl AptRepository {
+    pub fn init(url: &str, suite: &str, client: HttpClient) -> Result<AptRepository, Error> {
+        let url = format!("{}/", url.trim_end_matches('/'));
+        let url = Uri::from_str(&url).map_err(|error| {
+            let error = format!("Invalid apt repository '{}': {}!", url, error);
+
+            Error::new(ErrorKind::InvalidInput, error)
+        })?;
+
+        Ok(AptRepository {
+            url,
+            suite: suite.to_owned(),
+            components: vec![String::from(DEFAULT_COMPONENT)],
+            architecture: debian_architecture(),
+            keyrings: trusted_keyrings(),
+            trusted: false,
+            client,
+            credentials: None,
+        })
+    }
+
+    pub fn init_ppa(
+        shorthand: &str,
+        codename: &str,
+        client: HttpClient,
+    ) -> Result<AptRepository, Error> {
+        AptRepository::init(&ppa_url(shorthand)?, codename, client)
+    }
+
+    pub async fn ppa(shorthand: &str, client: HttpClient) -> Result<AptRepository, Error> {
+        AptRepository::init_ppa(shorthand, &distro_codename().await?, client)
+    }
+
+    pub fn index_path(&self, component: &str, file: &str) -> String {
+        format!(
+            "dists/{}/{}/binary-{}/{}",
+            self.suite, component, self.architecture, file,
+        )
+    }
+
+    pub async fn release(&self) -> Result<AptRelease, Error> {
+        if self.keyrings.is_empty() {
+            let error = format!(
+                "No keyring configured to verify {}, and the repository is not trusted!",
+                self.url,
+            );
+
+            return Err(Error::new(ErrorKind::PermissionDenied, error));
+        }
+
+        let path = format!("dists/{}/InRelease", self.suite);
+        let (status, body) = self.launch_get(&path).await?;
+        let contents = match status {
+            status if status.is_success() => self.launch_gpgv(None, &body).await?,
+            StatusCode::NOT_FOUND | StatusCode::GONE => {
+                let path = format!("dists/{}/Release", self.suite);
+                let release = self.launch_get_success(&path).await?;
+                let signature = self.launch_get_success(&format!("{}.gpg", path)).await?;
+
+                self.launch_gpgv(Some(&signature), &release).await?;
+
+                release.to_vec()
+            }
+            status => {
+                let error = format!("Release request for {} failed with {}!", path, status);
+
+                return Err(Error::other(error));
+            }
+        };
+
+        Ok(AptRelease::parse(&String::from_utf8_lossy(&contents)))
+    }
+
+    pub async fn index(
+        &self,
+        component: &str,
+        release: Option<&AptRelease>,
+    ) -> Result<AptIndex, Error> {
+        for file in INDEX_FILES {
+            let path = self.index_path(component, file);
+            let listed = format!("{}/binary-{}/{}", component, self.architecture, file);
+
+            if release.is_some_and(|release| !release.sha256.contains_key(&listed)) {
+                continue;
+            }
+
+            let (status, body) = self.launch_get(&path).await?;
+
+            match status {
+                status if status.is_success() => {
+                    if let Some(release) = release {
+                        release.verify(&listed, &body)?;
+                    }
+
+                    return AptIndex::decode(&path, &body);
+                }
+                StatusCode::NOT_FOUND | StatusCode::GONE => continue,
+                status => {
+                    let error = format!("Index request for {} failed with {}!", path, status);
+
+                    return Err(Error::other(error));
+                }
+            }
+        }
+
+        let error = format!(
+            "No package index for {}/{} found in {}!",
+            self.suite, component, self.url,
+        );
+
+        Err(Error::new(ErrorKind::NotFound, error))
+    }
+
+    pub async fn resolve(&self, name: &str, version: Option<&str>) -> Result<AptPackage, Error> {
+        let release = match self.trusted {
+            true => None,
+            false => Some(self.release().await?),
+        };
+        let mut resolved: Option<AptPackage> = None;
+
+        for component in &self.components {
+            let index = self.index(component, release.as_ref()).await?;
+            if let Some(package) = index.package(name, version, &self.architecture) {
+                let newer = resolved.as_ref().is_none_or(|resolved| {
+                    compare_debian_versions(&package.version, &resolved.version)
+                        == Ordering::Greater
+                });
+
+                if newer {
+                    resolved = Some(package.to_owned());
+                }
+            }
+        }
+
+        resolved.ok_or_else(|| {
+            let error = format!(
+                "No version {} of package '{}' found for {}!",
+                version.unwrap_or("latest"),
+                name,
+                self.architecture,
+            );
+
+            Error::new(ErrorKind::NotFound, error)
+        })
+    }
+
+    pub async fn transfer(
+        &self,
+        package: &AptPackage,
+    ) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}{}", self.url, package.filename.trim_start_matches('/'));
+        let mut transfer = Transfer::init_with_client(&url, self.client.to_owned()).await?;
+
+        if !package.sha256.is_empty() {
+            transfer.checksum = Some(package.sha256.to_owned());
+        }
+
+        transfer.auth = self.credentials.to_owned();
+
+        Ok(transfer)
+    }
+
+    async fn launch_gpgv(&self, signature: Option<&[u8]>, signed: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut command = Command::new("gpgv");
+
+        for keyring in &self.keyrings {
+            command.arg("--keyring").arg(keyring);
+        }
+
+        let signature_file = match signature {
+            Some(signature) => {
+                let mut signature_file = tempfile::Builder::new()
+                    .prefix("archeon_release_")
+                    .suffix(".gpg")
+                    .tempfile()?;
+
+                signature_file.write_all(signature)?;
+                command.arg(signature_file.path()).arg("-");
+
+                Some(signature_file)
+            }
+            None => {
+                command.arg("--output").arg("-").arg("-");
+
+                None
+            }
+        };
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| Error::other("No gpgv stdin!"))?;
+        let signed = signed.to_vec();
+        let writer = tokio::spawn(async move { stdin.write_all(&signed).await });
+        let output = child.wait_with_output().await;
+
+        if let Some(signature_file) = signature_file {
+            signature_file.close()?;
+        }
+
+        let output = output?;
+        let written = writer.await.map_err(Error::other)?;
+
+        match output.status.success() {
+            true => written.map(|_| output.stdout),
+            false => {
+                let error = format!(
+                    "Release signature for {} could not be verified: {}",
+                    self.suite,
+                    String::from_utf8_lossy(&output.stderr).trim(),
+                );
+
+                Err(Error::new(ErrorKind::PermissionDenied, error))
+            }
+        }
+    }
+
+    async fn launch_get_success(&self, path: &str) -> Result<hyper::body::Bytes, Error> {
+        let (status, body) = self.launch_get(path).await?;
+
+        match status.is_success() {
+            true => Ok(body),
+            false => {
+                let error = format!("Request for {}{} failed with {}!", self.url, path, status);
+
+                Err(Error::other(error))
+            }
+        }
+    }
+
+    async fn launch_get(&self, path: &str) -> Result<(StatusCode, hyper::body::Bytes), Error> {
+        let url = format!("{}{}", self.url, path);
+        let mut request = Request::builder()
+            .method(Method::GET)
+            .uri(&url)
+            .header(ACCEPT, "*/*")
+            .body(Body::empty())
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+
+        if let Some(credentials) = &self.credentials {
+            credentials
+                .authenticate(&mut request)
+                .await
+                .map_err(Error::other)?;
+        }
+
+        let (status, _, body) = launch_request(&self.client, request).await?;
+
+        Ok((status, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum;
+    use crate::Archeon;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn compare_debian_versions() {
+        let test_cases = [
+            ("1.0-1", "1.0-2", Ordering::Less),
+            ("1:0.9", "1.0", Ordering::Greater),
+            ("1.0~rc1", "1.0", Ordering::Less),
+            ("1.10", "1.9", Ordering::Greater),
+            ("1.0a", "1.0+", Ordering::Less),
+            ("2.36-9+deb12u4", "2.36-9+deb12u10", Ordering::Less),
+            ("1.0-1", "1.0-1", Ordering::Equal),
+        ];
+        for (a, b, ordering) in test_cases {
+            assert_eq!(
+                super::compare_debian_versions(a, b),
+                ordering,
+                "{} {}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ppa() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!(
+            ppa_url("ppa:deadsnakes/ppa")?,
+            "https://ppa.launchpadcontent.net/deadsnakes/ppa/ubuntu",
+        );
+        assert_eq!(
+            ppa_url("ppa:test-user")?,
+            "https://ppa.launchpadcontent.net/test-user/ppa/ubuntu",
+        );
+        assert!(ppa_url("deadsnakes/ppa").is_err());
+        assert!(ppa_url("ppa:test/../ppa").is_err());
+        assert_eq!(
+            parse_codename("NAME=\"Ubuntu\"\nVERSION_CODENAME=noble\nUBUNTU_CODENAME=noble\n"),
+            Some(String::from("noble")),
+        );
+        assert_eq!(
+            parse_codename("ID=linuxmint\nVERSION_CODENAME=wilma\nUBUNTU_CODENAME=noble\n"),
+            Some(String::from("noble")),
+        );
+        assert_eq!(
+            parse_codename("ID=debian\nVERSION_CODENAME=bookworm\n"),
+            Some(String::from("bookworm")),
+        );
+        assert_eq!(parse_codename("ID=arch\n"), None);
+        let test_repository =
+            AptRepository::init_ppa("ppa:deadsnakes/ppa", "jammy", Transfer::init_client())?;
+        assert_eq!(
+            test_repository.url,
+            "https://ppa.launchpadcontent.net/deadsnakes/ppa/ubuntu/",
+        );
+        assert_eq!(
+            test_repository.index_path("main", "Packages.xz"),
+            format!(
+                "dists/jammy/main/binary-{}/Packages.xz",
+                debian_architecture(),
+            ),
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn release() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_release = AptRelease::parse(
+            "Origin: Test\nSuite: stable\nCodename: bookworm\nSHA256:\n 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae 3 main/binary-amd64/Packages\n 00 10 main/binary-amd64/Packages.gz\n",
+        );
+        assert_eq!(test_release.suite.as_deref(), Some("stable"));
+        assert_eq!(test_release.codename.as_deref(), Some("bookworm"));
+        assert_eq!(test_release.sha256.len(), 2);
+        test_release.verify("main/binary-amd64/Packages", b"foo")?;
+        assert_eq!(
+            test_release
+                .verify("main/binary-amd64/Packages.gz", b"foo")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidData,
+        );
+        assert_eq!(
+            test_release
+                .verify("main/binary-arm64/Packages", b"foo")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidData,
+        );
+        Ok(())
+    }
+
+    async fn build_test_keyring(
+        name: &str,
+    ) -> Result<(PathBuf, PathBuf), Box<dyn std::error::Error + Send + Sync>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let test_home = std::env::temp_dir().join(name);
+        let _ = tokio::fs::remove_dir_all(&test_home).await;
+        tokio::fs::create_dir_all(&test_home).await?;
+        tokio::fs::set_permissions(&test_home, std::fs::Permissions::from_mode(0o700)).await?;
+        let test_output = Command::new("gpg")
+            .arg("--homedir")
+            .arg(&test_home)
+            .args(["--batch", "--passphrase", "", "--quick-generate-key"])
+            .args([
+                "archeon test <archeon@localhost>",
+                "ed25519",
+                "sign",
+                "never",
+            ])
+            .output()
+            .await?;
+        assert!(test_output.status.success());
+        let test_keyring = test_home.join("test-keyring.gpg");
+        let test_output = Command::new("gpg")
+            .arg("--homedir")
+            .arg(&test_home)
+            .arg("--output")
+            .arg(&test_keyring)
+            .arg("--export")
+            .output()
+            .await?;
+        assert!(test_output.status.success());
+        Ok((test_home, test_keyring))
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resolve_signed() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (test_home, test_keyring) = build_test_keyring("archeon_test_apt_signed").await?;
+        let mut test_server = mockito::Server::new_async().await;
+        let test_packages = "Package: test-package\nVersion: 1.0-1\nArchitecture: amd64\nFilename: pool/main/t/test-package/test-package_1.0-1_amd64.deb\nSHA256: 00\n";
+        let test_release = format!(
+            "Suite: stable\nSHA256:\n {} {} main/binary-amd64/Packages\n",
+            checksum::sha256_bytes(test_packages.as_bytes()),
+            test_packages.len(),
+        );
+        tokio::fs::write(test_home.join("Release"), &test_release).await?;
+        let test_output = Command::new("gpg")
+            .arg("--homedir")
+            .arg(&test_home)
+            .args(["--batch", "--yes", "--clearsign", "--output"])
+            .arg(test_home.join("InRelease"))
+            .arg(test_home.join("Release"))
+            .output()
+            .await?;
+        assert!(test_output.status.success());
+        let mock_in_release = test_server
+            .mock("GET", "/debian/dists/stable/InRelease")
+            .with_status(200)
+            .with_body(tokio::fs::read(test_home.join("InRelease")).await?)
+            .expect(2)
+            .create_async()
+            .await;
+        let mock_packages = test_server
+            .mock("GET", "/debian/dists/stable/main/binary-amd64/Packages")
+            .with_status(200)
+            .with_body(test_packages)
+            .create_async()
+            .await;
+        let mut test_repository = AptRepository::init(
+            &format!("{}/debian", test_server.url()),
+            "stable",
+            Transfer::init_client(),
+        )?;
+        test_repository.architecture = String::from("amd64");
+        test_repository.keyrings = vec![test_keyring];
+        assert_eq!(
+            test_repository.resolve("test-package", None).await?.version,
+            "1.0-1",
+        );
+        test_repository.keyrings = vec![test_home.join("missing.gpg")];
+        assert_eq!(
+            test_repository
+                .resolve("test-package", None)
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::PermissionDenied,
+        );
+        test_repository.keyrings.clear();
+        assert_eq!(
+            test_repository
+                .resolve("test-package", None)
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::PermissionDenied,
+        );
+        mock_in_release.assert_async().await;
+        mock_packages.assert_async().await;
+        Command::new("gpgconf")
+            .arg("--homedir")
+            .arg(&test_home)
+            .args(["--kill", "gpg-agent"])
+            .output()
+            .await?;
+        tokio::fs::remove_dir_all(&test_home).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn gpgv_signature_file() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_repository = AptRepository::init(
+            "http://archeon-test-gpgv/debian",
+            "stable",
+            Transfer::init_client(),
+        )?;
+        test_repository.keyrings = vec![std::env::temp_dir().join("archeon_test_gpgv_missing.gpg")];
+        let test_error = test_repository
+            .launch_gpgv(Some(b"test_signature"), b"test_release")
+            .await
+            .unwrap_err();
+        assert_eq!(test_error.kind(), ErrorKind::PermissionDenied);
+        let mut test_entries = tokio::fs::read_dir(std::env::temp_dir()).await?;
+        while let Some(test_entry) = test_entries.next_entry().await? {
+            assert!(!test_entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with("archeon_release_"));
+        }
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse() {
+        let test_index = AptIndex::parse(
+            "Package: test-package\nVersion: 1.0-1\nArchitecture: amd64\nDescription: test\n long description\nFilename: pool/main/t/test-package/test-package_1.0-1_amd64.deb\nSize: 10\nSHA256: abc\n\nPackage: broken\nVersion: 1.0\n",
+        );
+        assert_eq!(test_index.packages.len(), 1);
+        assert_eq!(test_index.packages[0].size, Some(10));
+        assert!(test_index.package("test-package", None, "arm64").is_none());
+        assert_eq!(
+            parse_paragraphs("Description: test\n long description\n")[0]["Description"],
+            "test\nlong description",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn transfer() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_deb = b"test_deb_archive";
+        let test_packages = format!(
+            "Package: test-package\nVersion: 1.0-2\nArchitecture: amd64\nFilename: pool/main/t/test-package/test-package_1.0-2_amd64.deb\nSHA256: {}\n\nPackage: test-package\nVersion: 1.0~rc1-1\nArchitecture: amd64\nFilename: pool/main/t/test-package/test-package_1.0~rc1-1_amd64.deb\nSHA256: 00\n\nPackage: test-package\nVersion: 2.0-1\nArchitecture: arm64\nFilename: pool/main/t/test-package/test-package_2.0-1_arm64.deb\nSHA256: 00\n",
+            checksum::sha256_bytes(test_deb),
+        );
+        let mut test_encoder = GzEncoder::new(Vec::new(), Compression::default());
+        test_encoder.write_all(test_packages.as_bytes())?;
+        let mock_xz = test_server
+            .mock("GET", "/debian/dists/stable/main/binary-amd64/Packages.xz")
+            .with_status(404)
+            .expect(2)
+            .create_async()
+            .await;
+        let mock_gz = test_server
+            .mock("GET", "/debian/dists/stable/main/binary-amd64/Packages.gz")
+            .with_status(200)
+            .with_body(test_encoder.finish()?)
+            .expect(2)
+            .create_async()
+            .await;
+        let mock_get = test_server
+            .mock(
+                "GET",
+                "/debian/pool/main/t/test-package/test-package_1.0-2_amd64.deb",
+            )
+            .with_status(200)
+            .with_body(test_deb)
+            .create_async()
+            .await;
+        let mut test_repository = AptRepository::init(
+            &format!("{}/debian", test_server.url()),
+            "stable",
+            Transfer::init_client(),
+        )?;
+        test_repository.architecture = String::from("amd64");
+        test_repository.trusted = true;
+        let test_package = test_repository.resolve("test-package", None).await?;
+        assert_eq!(test_package.version, "1.0-2");
+        assert_eq!(
+            test_repository
+                .resolve("missing", None)
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NotFound,
+        );
+        let test_transfer = test_repository.transfer(&test_package).await?;
+        assert!(test_transfer
+            .file_path
+            .ends_with("test-package_1.0-2_amd64.deb"));
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.launch(&test_transfer).await?;
+        assert_eq!(tokio::fs::read(&test_transfer.file_path).await?, test_deb);
+        mock_xz.assert_async().await;
+        mock_gz.assert_async().await;
+        mock_get.assert_async().await;
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+use hyper::header::ACCEPT;
+use hyper::{Body, Method, Request, StatusCode, Uri};
+
+use serde::{Deserialize, Serialize};
+
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::auth::Authenticate;
+use crate::installer::Backend;
+use crate::source::{compare_versions, launch_request};
+use crate::transfer::{HttpClient, Transfer};
+
+pub const CRATES_IO_INDEX: &str = "https://index.crates.io/";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexConfig {
+    pub dl: String,
+    #[serde(default)]
+    pub api: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateVersion {
+    pub name: String,
+    pub vers: String,
+    pub cksum: String,
+    #[serde(default)]
+    pub yanked: bool,
+    #[serde(default)]
+    pub rust_version: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct CratesIndex {
+    pub url: Uri,
+    pub client: HttpClient,
+    pub credentials: Option<Arc<dyn Authenticate>>,
+}
+
+pub fn index_path(name: &str) -> String {
+    let name = name.to_ascii_lowercase();
+
+    format!("{}/{}", index_prefix(&name), name)
+}
+
+fn index_prefix(name: &str) -> String {
+    let characters = name.chars().collect::<Vec<char>>();
+
+    match characters.len() {
+        0..=2 => characters.len().to_string(),
+        3 => format!("3/{}", characters[0]),
+        _ => format!(
+            "{}/{}",
+            characters[..2].iter().collect::<String>(),
+            characters[2..4].iter().collect::<String>(),
+        ),
+    }
+}
+
+impl IndexConfig {
+    pub fn download_url(&self, version: &CrateVersion) -> String {
+        let markers = [
+            "{crate}",
+            "{version}",
+            "{prefix}",
+            "{lowerprefix}",
+            "{sha256-checksum}",
+        ];
+
+        match markers.iter().any(|marker| self.dl.contains(marker)) {
+            true => {
+                let prefix = index_prefix(&version.name);
+
+                self.dl
+                    .replace("{crate}", &version.name)
+                    .replace("{version}", &version.vers)
+                    .replace("{prefix}", &prefix)
+                    .replace("{lowerprefix}", &prefix.to_ascii_lowercase())
+                    .replace("{sha256-checksum}", &version.cksum)
+            }
+            false => format!(
+                "{}/{}/{}/download",
+                self.dl.trim_end_matches('/'),
+                version.name,
+                version.vers,
+            ),
+        }
+    }
+}
+
+impl CratesIndex {
+    pub fn init(url: &str, client: HttpClient) -> Result<CratesIndex, Error> {
+        let url = url.strip_prefix("sparse+").unwrap_or(url);
+        let url = format!("{}/", url.trim_end_matches('/'));
+        let url = Uri::from_str(&url).map_err(|error| {
+            let error = format!("Invalid crate index '{}': {}!", url, error);
+
+            Error::new(ErrorKind::InvalidInput, error)
+        })?;
+
+        Ok(CratesIndex {
+            url,
+            client,
+            credentials: None,
+        })
+    }
+
+    pub fn crates_io(client: HttpClient) -> CratesIndex {
+        CratesIndex {
+            url: Uri::from_static(CRATES_IO_INDEX),
+            client,
+            credentials: None,
+        }
+    }
+
+    pub async fn config(&self) -> Result<IndexConfig, Error> {
+        let (status, body) = self.launch_get("config.json").await?;
+
+        if !status.is_success() {
+            let error = format!(
+                "Index config request to {} failed with {}!",
+                self.url, status
+            );
+
+            return Err(Error::other(error));
+        }
+
+        serde_json::from_slice(&body).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub async fn versions(&self, name: &str) -> Result<Vec<CrateVersion>, Error> {
+        let (status, body) = self.launch_get(&index_path(name)).await?;
+
+        match status {
+            status if status.is_success() => {}
+            StatusCode::NOT_FOUND | StatusCode::GONE => {
+                let error = format!("No crate named '{}' found in {}!", name, self.url);
+
+                return Err(Error::new(ErrorKind::NotFound, error));
+            }
+            status => {
+                let error = format!("Index request for '{}' failed with {}!", name, status);
+
+                return Err(Error::other(error));
+            }
+        }
+
+        String::from_utf8_lossy(&body)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .map_err(|error| Error::new(ErrorKind::InvalidData, error))
+            })
+            .collect()
+    }
+
+    pub async fn resolve(&self, name: &str, version: Option<&str>) -> Result<CrateVersion, Error> {
+        let versions = self.versions(name).await?;
+        let resolved = match version {
+            Some(version) => versions
+                .into_iter()
+                .find(|candidate| candidate.vers == version),
+            None => versions
+                .into_iter()
+                .filter(|candidate| !candidate.yanked && !candidate.vers.contains('-'))
+                .max_by(|a, b| compare_versions(&a.vers, &b.vers)),
+        };
+
+        resolved.ok_or_else(|| {
+            let error = format!(
+                "No version {} of crate '{}' found!",
+                version.unwrap_or("stable"),
+                name,
+            );
+
+            Error::new(ErrorKind::NotFound, error)
+        })
+    }
+
+    pub async fn transfer(
+        &self,
+        version: &CrateVersion,
+    ) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        let url = self.config().await?.download_url(version);
+        let mut transfer = Transfer::init_with_client(&url, self.client.to_owned()).await?;
+        let filename = PathBuf::from(format!("{}-{}.crate", version.name, version.vers));
+
+        transfer.file_path = transfer.temp_dir.join(&filename);
+        transfer.filename = filename;
+        transfer.checksum = Some(version.cksum.to_owned());
+        transfer.installer.backend = Backend::Cargo;
+        transfer.auth = self.credentials.to_owned();
+
+        Ok(transfer)
+    }
+
+    async fn launch_get(&self, path: &str) -> Result<(StatusCode, hyper::body::Bytes), Error> {
+        let url = format!("{}{}", self.url, path);
+        let mut request = Request::builder()
+            .method(Method::GET)
+            .uri(&url)
+            .header(ACCEPT, "application/json, text/plain")
+            .body(Body::empty())
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+
+        if let Some(credentials) = &self.credentials {
+            credentials
+                .authenticate(&mut request)
+                .await
+                .map_err(Error::other)?;
+        }
+
+        let (status, _, body) = launch_request(&self.client, request).await?;
+
+        Ok((status, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum;
+    use crate::Archeon;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn index_path() {
+        assert_eq!(super::index_path("a"), "1/a");
+        assert_eq!(super::index_path("cc"), "2/cc");
+        assert_eq!(super::index_path("Syn"), "3/s/syn");
+        assert_eq!(super::index_path("serde_json"), "se/rd/serde_json");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn download_url() {
+        let test_version = CrateVersion {
+            name: String::from("Test-Crate"),
+            vers: String::from("1.0.0"),
+            cksum: String::from("abc123"),
+            ..CrateVersion::default()
+        };
+        let test_config = IndexConfig {
+            dl: String::from("https://static.crates.io/crates/"),
+            api: None,
+        };
+        assert_eq!(
+            test_config.download_url(&test_version),
+            "https://static.crates.io/crates/Test-Crate/1.0.0/download",
+        );
+        let test_config = IndexConfig {
+            dl: String::from("https://dl.test/{prefix}/{lowerprefix}/{crate}-{version}.crate?sum={sha256-checksum}"),
+            api: None,
+        };
+        assert_eq!(
+            test_config.download_url(&test_version),
+            "https://dl.test/Te/st/te/st/Test-Crate-1.0.0.crate?sum=abc123",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn transfer() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_crate = b"test_crate_archive";
+        let test_entries = [
+            format!(
+                "{{\"name\":\"test-crate\",\"vers\":\"1.2.0\",\"cksum\":\"{}\",\"yanked\":false}}",
+                checksum::sha256_bytes(test_crate),
+            ),
+            String::from(
+                "{\"name\":\"test-crate\",\"vers\":\"1.3.0\",\"cksum\":\"00\",\"yanked\":true}",
+            ),
+            String::from(
+                "{\"name\":\"test-crate\",\"vers\":\"2.0.0-beta.1\",\"cksum\":\"00\",\"yanked\":false}",
+            ),
+            String::from(
+                "{\"name\":\"test-crate\",\"vers\":\"1.1.9\",\"cksum\":\"00\",\"yanked\":false}",
+            ),
+        ];
+        let mock_config = test_server
+            .mock("GET", "/index/config.json")
+            .with_status(200)
+            .with_body(format!(
+                "{{\"dl\":\"{}/crates\",\"api\":\"{}\"}}",
+                test_server.url(),
+                test_server.url(),
+            ))
+            .create_async()
+            .await;
+        let mock_index = test_server
+            .mock("GET", "/index/te/st/test-crate")
+            .with_status(200)
+            .with_body(test_entries.join("\n"))
+            .expect(2)
+            .create_async()
+            .await;
+        let mock_missing = test_server
+            .mock("GET", "/index/mi/ss/missing")
+            .with_status(404)
+            .create_async()
+            .await;
+        let mock_get = test_server
+            .mock("GET", "/crates/test-crate/1.2.0/download")
+            .with_status(200)
+            .with_body(test_crate)
+            .create_async()
+            .await;
+        let test_index = CratesIndex::init(
+            &format!("sparse+{}/index", test_server.url()),
+            Transfer::init_client(),
+        )?;
+        let test_resolved = test_index.resolve("test-crate", None).await?;
+        assert_eq!(test_resolved.vers, "1.2.0");
+        assert_eq!(
+            test_index
+                .resolve("test-crate", Some("2.0.0-beta.1"))
+                .await?
+                .vers,
+            "2.0.0-beta.1",
+        );
+        assert_eq!(
+            test_index
+                .resolve("missing", None)
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NotFound,
+        );
+        let test_transfer = test_index.transfer(&test_resolved).await?;
+        assert!(test_transfer.file_path.ends_with("test-crate-1.2.0.crate"));
+        assert_eq!(test_transfer.installer.backend, Backend::Cargo);
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.launch(&test_transfer).await?;
+        assert_eq!(tokio::fs::read(&test_transfer.file_path).await?, test_crate);
+        mock_config.assert_async().await;
+        mock_index.assert_async().await;
+        mock_missing.assert_async().await;
+        mock_get.assert_async().await;
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+use hyper::Uri;
+
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use tokio::process::Command;
+
+use crate::auth::Authenticate;
+use crate::checksum;
+use crate::source::apt::AptPackage;
+use crate::transfer::{HttpClient, Transfer};
+
+pub const DEBDELTA_MIRROR: &str = "http://debdeltas.debian.net/debian-deltas/";
+
+#[derive(Clone)]
+pub struct DebdeltaMirror {
+    pub url: Uri,
+    pub program: String,
+    pub client: HttpClient,
+    pub credentials: Option<Arc<dyn Authenticate>>,
+}
+
+pub fn delta_path(package: &AptPackage, old_version: &str) -> String {
+    let directory = package
+        .filename
+        .rsplit_once('/')
+        .map_or("", |(directory, _)| directory);
+    let filename = format!(
+        "{}_{}_{}_{}.debdelta",
+        package.package,
+        old_version.replace(':', "%3a"),
+        package.version.replace(':', "%3a"),
+        package.architecture,
+    );
+
+    match directory.is_empty() {
+        true => filename,
+        false => format!("{}/{}", directory.trim_start_matches('/'), filename),
+    }
+}
+
+pub async fn installed_version(name: &str) -> Option<String> {
+    let output = Command::new("dpkg-query")
+        .arg("--show")
+        .arg("--showformat=${Version}")
+        .arg(name)
+        .output()
+        .await
+        .ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+    (output.status.success() && !version.is_empty()).then_some(version)
+}
+
+impl DebdeltaMirror {
+    pub fn init(url: &str, client: HttpClient) -> Result<DebdeltaMirror, Error> {
+        let url = format!("{}/", url.trim_end_matches('/'));
+        let url = Uri::from_str(&url).map_err(|error| {
+            let error = format!("Invalid debdelta mirror '{}': {}!", url, error);
+
+            Error::new(ErrorKind::InvalidInput, error)
+        })?;
+
+        Ok(DebdeltaMirror {
+            url,
+            program: String::from("debpatch"),
+            client,
+            credentials: None,
+        })
+    }
+
+    pub fn debian(client: HttpClient) -> DebdeltaMirror {
+        DebdeltaMirror {
+            url: Uri::from_static(DEBDELTA_MIRROR),
+            program: String::from("debpatch"),
+            client,
+            credentials: None,
+        }
+    }
+
+    pub async fn transfer(
+        &self,
+        package: &AptPackage,
+        old_version: &str,
+    ) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}{}", self.url, delta_path(package, old_version));
+        let mut transfer = Transfer::init_with_client(&url, self.client.to_owned()).await?;
+
+        transfer.auth = self.credentials.to_owned();
+
+        Ok(transfer)
+    }
+
+    pub async fn apply(
+        &self,
+        delta: &Path,
+        old: Option<&Path>,
+        package: &AptPackage,
+        directory: &Path,
+    ) -> Result<PathBuf, Error> {
+        let filename = package
+            .filename
+            .rsplit('/')
+            .next()
+            .filter(|filename| !filename.is_empty())
+            .ok_or_else(|| {
+                let error = format!("No filename for package '{}'!", package.package);
+
+                Error::new(ErrorKind::InvalidInput, error)
+            })?;
+        let output_path = directory.join(filename);
+        let output = Command::new(&self.program)
+            .arg(delta)
+            .arg(old.unwrap_or(Path::new("/")))
+            .arg(&output_path)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let error = format!(
+                "Could not apply {:?} ({}): {}",
+                delta,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim(),
+            );
+
+            return Err(Error::other(error));
+        }
+
+        let actual = checksum::sha256(&output_path).await?;
+
+        if !package.sha256.is_empty() && !actual.eq_ignore_ascii_case(&package.sha256) {
+            tokio::fs::remove_file(&output_path).await?;
+
+            let error = format!(
+                "Checksum mismatch for {:?} rebuilt from {:?}: expected {}, got {}!",
+                output_path, delta, package.sha256, actual,
+            );
+
+            return Err(Error::new(ErrorKind::InvalidData, error));
+        }
+
+        Ok(output_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Archeon;
+
+    fn test_package(sha256: &str) -> AptPackage {
+        AptPackage {
+            package: String::from("test-package"),
+            version: String::from("1:1.0-2"),
+            architecture: String::from("amd64"),
+            filename: String::from("pool/main/t/test-package/test-package_1.0-2_amd64.deb"),
+            sha256: sha256.to_owned(),
+            size: None,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn delta_path() {
+        assert_eq!(
+            super::delta_path(&test_package(""), "1:1.0-1"),
+            "pool/main/t/test-package/test-package_1%3a1.0-1_1%3a1.0-2_amd64.debdelta",
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn apply() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let test_directory = std::env::temp_dir().join("archeon_test_debdelta_apply");
+        tokio::fs::create_dir_all(&test_directory).await?;
+        let test_program = test_directory.join("debpatch");
+        tokio::fs::write(&test_program, "#!/bin/sh\ncat \"$2\" \"$1\" > \"$3\"\n").await?;
+        tokio::fs::set_permissions(&test_program, std::fs::Permissions::from_mode(0o755)).await?;
+        let test_old = test_directory.join("test-package_1.0-1_amd64.deb");
+        tokio::fs::write(&test_old, "test_old_").await?;
+        let mut test_server = mockito::Server::new_async().await;
+        let test_delta = b"test_delta";
+        let test_delta_path =
+            "/deltas/pool/main/t/test-package/test-package_1%3a1.0-1_1%3a1.0-2_amd64.debdelta";
+        let mock_get = test_server
+            .mock("GET", test_delta_path)
+            .with_status(200)
+            .with_body(test_delta)
+            .create_async()
+            .await;
+        let mut test_mirror = DebdeltaMirror::init(
+            &format!("{}/deltas", test_server.url()),
+            Transfer::init_client(),
+        )?;
+        test_mirror.program = test_program.to_string_lossy().into_owned();
+        let test_new = test_package(&checksum::sha256_bytes(b"test_old_test_delta"));
+        let test_transfer = test_mirror.transfer(&test_new, "1:1.0-1").await?;
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.launch(&test_transfer).await?;
+        let test_rebuilt = test_mirror
+            .apply(
+                &test_transfer.file_path,
+                Some(&test_old),
+                &test_new,
+                &test_directory,
+            )
+            .await?;
+        assert!(test_rebuilt.ends_with("test-package_1.0-2_amd64.deb"));
+        assert_eq!(
+            tokio::fs::read(&test_rebuilt).await?,
+            b"test_old_test_delta",
+        );
+        assert_eq!(
+            test_mirror
+                .apply(
+                    &test_transfer.file_path,
+                    Some(&test_old),
+                    &test_package("00"),
+                    &test_directory,
+                )
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidData,
+        );
+        assert!(!test_rebuilt.exists());
+        mock_get.assert_async().await;
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        tokio::fs::remove_dir_all(&test_directory).await?;
+        Ok(())
+    }
+}
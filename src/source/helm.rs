@@ -0,0 +1,245 @@
+use hyper::header::ACCEPT;
+use hyper::{Body, Method, Request, Uri};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::auth::Authenticate;
+use crate::source::{compare_versions, launch_request};
+use crate::transfer::{resolve_location, HttpClient, Transfer};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HelmChart {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub app_version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub digest: Option<String>,
+    #[serde(default)]
+    pub urls: Vec<String>,
+    #[serde(default)]
+    pub deprecated: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HelmIndex {
+    #[serde(default)]
+    pub api_version: String,
+    #[serde(default)]
+    pub entries: BTreeMap<String, Vec<HelmChart>>,
+}
+
+#[derive(Clone)]
+pub struct HelmRepository {
+    pub url: Uri,
+    pub client: HttpClient,
+    pub credentials: Option<Arc<dyn Authenticate>>,
+}
+
+impl HelmIndex {
+    pub fn parse(contents: &str) -> Result<HelmIndex, Error> {
+        serde_yaml::from_str(contents).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn chart(&self, name: &str, version: Option<&str>) -> Result<&HelmChart, Error> {
+        let charts = match self.entries.get(name) {
+            Some(charts) => charts,
+            None => {
+                let error = format!("No chart named '{}' found!", name);
+
+                return Err(Error::new(ErrorKind::NotFound, error));
+            }
+        };
+        let chart = match version {
+            Some(version) => {
+                let version = version.trim_start_matches('v');
+
+                charts
+                    .iter()
+                    .find(|chart| chart.version.trim_start_matches('v') == version)
+            }
+            None => charts
+                .iter()
+                .filter(|chart| !chart.version.contains('-'))
+                .max_by(|a, b| compare_versions(&a.version, &b.version)),
+        };
+
+        chart.ok_or_else(|| {
+            let error = format!(
+                "No version {} of chart '{}' found!",
+                version.unwrap_or("stable"),
+                name,
+            );
+
+            Error::new(ErrorKind::NotFound, error)
+        })
+    }
+}
+
+impl HelmRepository {
+    pub fn init(url: &str, client: HttpClient) -> Result<HelmRepository, Error> {
+        let url = format!("{}/", url.trim_end_matches('/'));
+        let url = Uri::from_str(&url).map_err(|error| {
+            let error = format!("Invalid Helm repository '{}': {}!", url, error);
+
+            Error::new(ErrorKind::InvalidInput, error)
+        })?;
+
+        Ok(HelmRepository {
+            url,
+            client,
+            credentials: None,
+        })
+    }
+
+    pub async fn index(&self) -> Result<HelmIndex, Error> {
+        let url = format!("{}index.yaml", self.url);
+        let mut request = Request::builder()
+            .method(Method::GET)
+            .uri(&url)
+            .header(ACCEPT, "application/x-yaml, text/yaml, */*")
+            .body(Body::empty())
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+
+        if let Some(credentials) = &self.credentials {
+            credentials
+                .authenticate(&mut request)
+                .await
+                .map_err(Error::other)?;
+        }
+
+        let (status, _, body) = launch_request(&self.client, request).await?;
+
+        if !status.is_success() {
+            let error = format!("Index request to {} failed with {}!", url, status);
+
+            return Err(Error::other(error));
+        }
+
+        HelmIndex::parse(&String::from_utf8_lossy(&body))
+    }
+
+    pub async fn resolve(&self, name: &str, version: Option<&str>) -> Result<HelmChart, Error> {
+        Ok(self.index().await?.chart(name, version)?.to_owned())
+    }
+
+    pub async fn transfer(
+        &self,
+        chart: &HelmChart,
+    ) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        let url = chart
+            .urls
+            .iter()
+            .find_map(|url| resolve_location(&self.url, url))
+            .ok_or_else(|| {
+                let error = format!(
+                    "No download URL for chart '{}' version {}!",
+                    chart.name, chart.version,
+                );
+
+                Error::new(ErrorKind::NotFound, error)
+            })?;
+        let mut transfer =
+            Transfer::init_with_client(&url.to_string(), self.client.to_owned()).await?;
+
+        transfer.checksum = chart.digest.to_owned();
+        transfer.auth = self.credentials.to_owned();
+
+        Ok(transfer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum;
+    use crate::Archeon;
+
+    const TEST_INDEX: &str = "
+apiVersion: v1
+entries:
+  test-chart:
+    - name: test-chart
+      version: 1.10.0
+      appVersion: \"2.0\"
+      digest: DIGEST
+      urls:
+        - charts/test-chart-1.10.0.tgz
+    - name: test-chart
+      version: 2.0.0-rc.1
+      urls:
+        - https://charts.test/test-chart-2.0.0-rc.1.tgz
+    - name: test-chart
+      version: 1.9.3
+      urls:
+        - https://charts.test/test-chart-1.9.3.tgz
+generated: \"2024-01-01T00:00:00Z\"
+";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn chart() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_index = HelmIndex::parse(TEST_INDEX)?;
+        assert_eq!(test_index.api_version, "v1");
+        let test_chart = test_index.chart("test-chart", None)?;
+        assert_eq!(test_chart.version, "1.10.0");
+        assert_eq!(test_chart.app_version.as_deref(), Some("2.0"));
+        let test_chart = test_index.chart("test-chart", Some("v2.0.0-rc.1"))?;
+        assert_eq!(test_chart.version, "2.0.0-rc.1");
+        assert_eq!(
+            test_index
+                .chart("test-chart", Some("3.0.0"))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NotFound,
+        );
+        assert!(test_index.chart("missing", None).is_err());
+        assert!(HelmIndex::parse("entries: [").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn transfer() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_chart = b"test_helm_chart";
+        let test_index = TEST_INDEX.replace("DIGEST", &checksum::sha256_bytes(test_chart));
+        let mock_index = test_server
+            .mock("GET", "/stable/index.yaml")
+            .with_status(200)
+            .with_body(&test_index)
+            .create_async()
+            .await;
+        let mock_get = test_server
+            .mock("GET", "/stable/charts/test-chart-1.10.0.tgz")
+            .with_status(200)
+            .with_body(test_chart)
+            .create_async()
+            .await;
+        let test_repository = HelmRepository::init(
+            &format!("{}/stable", test_server.url()),
+            Transfer::init_client(),
+        )?;
+        let test_resolved = test_repository.resolve("test-chart", None).await?;
+        let test_transfer = test_repository.transfer(&test_resolved).await?;
+        assert!(test_transfer.file_path.ends_with("test-chart-1.10.0.tgz"));
+        assert_eq!(test_transfer.checksum, test_resolved.digest);
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.launch(&test_transfer).await?;
+        mock_index.assert_async().await;
+        mock_get.assert_async().await;
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        let mut test_tampered = test_repository.transfer(&test_resolved).await?;
+        test_tampered.checksum = Some(checksum::sha256_bytes(b"tampered"));
+        assert!(test_archeon.launch(&test_tampered).await.is_err());
+        tokio::fs::remove_file(&test_tampered.file_path).await.ok();
+        Ok(())
+    }
+}
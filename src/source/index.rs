@@ -0,0 +1,557 @@
+use hyper::header::{ACCEPT, CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::{Body, Method, Request, Uri};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashSet, VecDeque};
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::auth::Authenticate;
+use crate::batch::{Batch, BatchArtifact};
+use crate::filter::PathFilter;
+use crate::normalize::decode_segment;
+use crate::source::launch_request;
+use crate::transfer::{resolve_location, HttpClient};
+
+pub const DEFAULT_DEPTH: usize = 5;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub path: String,
+    pub url: String,
+    pub directory: bool,
+    pub size: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct JsonEntry {
+    name: String,
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    #[serde(default)]
+    is_dir: Option<bool>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    size: Option<u64>,
+}
+
+#[derive(Clone)]
+pub struct DirectoryIndex {
+    pub url: Uri,
+    pub client: HttpClient,
+    pub credentials: Option<Arc<dyn Authenticate>>,
+    pub filter: PathFilter,
+    pub depth: usize,
+    pub same_origin: bool,
+    pub max_bytes: Option<u64>,
+}
+
+impl DirectoryIndex {
+    pub fn init(url: &str, client: HttpClient) -> Result<DirectoryIndex, Error> {
+        let url = format!("{}/", url.trim_end_matches('/'));
+        let url = Uri::from_str(&url).map_err(|error| {
+            let error = format!("Invalid directory index '{}': {}!", url, error);
+
+            Error::new(ErrorKind::InvalidInput, error)
+        })?;
+
+        Ok(DirectoryIndex {
+            url,
+            client,
+            credentials: None,
+            filter: PathFilter::default(),
+            depth: DEFAULT_DEPTH,
+            same_origin: true,
+            max_bytes: None,
+        })
+    }
+
+    pub async fn listing(&self, url: &Uri) -> Result<Vec<IndexEntry>, Error> {
+        let request = self.request(Method::GET, url).await?;
+        let (status, headers, body) = launch_request(&self.client, request).await?;
+
+        if !status.is_success() {
+            let error = format!("Index request to {} failed with {}!", url, status);
+
+            return Err(Error::other(error));
+        }
+
+        let body = String::from_utf8_lossy(&body);
+        let json = headers
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.contains("json"))
+            || body.trim_start().starts_with('[');
+
+        let entries = match json {
+            true => parse_json(&self.url, url, &body)?,
+            false => parse_html(&self.url, url, &body),
+        };
+
+        Ok(entries
+            .into_iter()
+            .filter(|entry| !self.same_origin || same_origin(&self.url, &entry.url))
+            .collect())
+    }
+
+    pub async fn size(&self, url: &Uri) -> Result<Option<u64>, Error> {
+        let request = self.request(Method::HEAD, url).await?;
+        let (status, headers, _) = launch_request(&self.client, request).await?;
+
+        if !status.is_success() {
+            return Ok(None);
+        }
+
+        Ok(headers
+            .get(CONTENT_LENGTH)
+            .and_then(|length| length.to_str().ok())
+            .and_then(|length| length.parse().ok()))
+    }
+
+    pub async fn entries(&self) -> Result<Vec<IndexEntry>, Error> {
+        let mut pending = VecDeque::from([(self.url.to_owned(), 0)]);
+        let mut visited = HashSet::from([self.url.to_string()]);
+        let mut files = Vec::with_capacity(16);
+        let mut budget = self.max_bytes;
+
+        while let Some((url, depth)) = pending.pop_front() {
+            if budget == Some(0) {
+                break;
+            }
+
+            for mut entry in self.listing(&url).await? {
+                if self.filter.excludes(&entry.path) || !visited.insert(entry.url.to_owned()) {
+                    continue;
+                }
+
+                match entry.directory {
+                    true if depth < self.depth => {
+                        if let Ok(url) = Uri::from_str(&entry.url) {
+                            pending.push_back((url, depth + 1));
+                        }
+                    }
+                    true => {}
+                    false if self.filter.matches(&entry.path) => {
+                        if let Some(remaining) = budget {
+                            if entry.size.is_none() {
+                                if let Ok(url) = Uri::from_str(&entry.url) {
+                                    entry.size = self.size(&url).await?;
+                                }
+                            }
+
+                            match entry.size {
+                                Some(size) if size <= remaining => {
+                                    budget = Some(remaining - size);
+                                }
+                                _ => continue,
+                            }
+                        }
+
+                        files.push(entry);
+                    }
+                    false => {}
+                }
+            }
+        }
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files.dedup_by(|a, b| a.path == b.path);
+
+        Ok(files)
+    }
+
+    pub async fn batch(&self, destination: &Path) -> Result<Batch, Error> {
+        let artifacts = self
+            .entries()
+            .await?
+            .into_iter()
+            .map(|entry| {
+                let directory = match entry.path.rsplit_once('/') {
+                    Some((directory, _)) => destination.join(directory),
+                    None => destination.to_owned(),
+                };
+
+                BatchArtifact {
+                    url: entry.url,
+                    destination: Some(directory),
+                    ..BatchArtifact::default()
+                }
+            })
+            .collect();
+
+        Ok(Batch { artifacts })
+    }
+
+    async fn request(&self, method: Method, url: &Uri) -> Result<Request<Body>, Error> {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(url)
+            .header(ACCEPT, "text/html, application/json;q=0.9, */*;q=0.8")
+            .body(Body::empty())
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+
+        if let Some(credentials) = &self.credentials {
+            credentials
+                .authenticate(&mut request)
+                .await
+                .map_err(Error::other)?;
+        }
+
+        Ok(request)
+    }
+}
+
+pub fn parse_html(root: &Uri, listing: &Uri, body: &str) -> Vec<IndexEntry> {
+    let mut entries = Vec::with_capacity(16);
+    let lowercase = body.to_ascii_lowercase();
+    let mut offset = 0;
+
+    while let Some(index) = lowercase[offset..].find("href=") {
+        let start = offset + index + "href=".len();
+        let rest = &body[start..];
+        let (href, length) = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => match rest[1..].find(quote) {
+                Some(end) => (&rest[1..end + 1], end + 2),
+                None => break,
+            },
+            _ => {
+                let end = rest
+                    .find(|character: char| character.is_whitespace() || character == '>')
+                    .unwrap_or(rest.len());
+
+                (&rest[..end], end)
+            }
+        };
+
+        offset = start + length;
+
+        if let Some(entry) = entry(root, listing, &href.replace("&amp;", "&"), None) {
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+    }
+
+    entries
+}
+
+pub fn parse_json(root: &Uri, listing: &Uri, body: &str) -> Result<Vec<IndexEntry>, Error> {
+    let listed: Vec<JsonEntry> =
+        serde_json::from_str(body).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+    Ok(listed
+        .into_iter()
+        .filter_map(|listed| {
+            let directory =
+                listed.is_dir.unwrap_or_default() || listed.kind.as_deref() == Some("directory");
+            let href = match (listed.url, directory) {
+                (Some(url), _) => url,
+                (None, true) => format!("{}/", listed.name.trim_end_matches('/')),
+                (None, false) => listed.name,
+            };
+            let href = match directory && !href.ends_with('/') {
+                true => format!("{}/", href),
+                false => href,
+            };
+
+            entry(root, listing, &href, listed.size)
+        })
+        .collect())
+}
+
+fn entry(root: &Uri, listing: &Uri, href: &str, size: Option<u64>) -> Option<IndexEntry> {
+    if href.is_empty() || href.starts_with(['?', '#']) {
+        return None;
+    }
+
+    let href = href.split(['?', '#']).next().unwrap_or(href);
+    let href = href.trim_start_matches("./");
+    let scheme = href.split('/').next().unwrap_or_default();
+
+    if scheme.contains(':') && !href.contains("://") {
+        return None;
+    }
+
+    let url = resolve_location(listing, href)?;
+    let directory = url.path().ends_with('/');
+
+    if url == *listing || (directory && listing.to_string().starts_with(&url.to_string())) {
+        return None;
+    }
+
+    let (mut segments, relative) = match same_origin(root, &url.to_string()) {
+        true => (Vec::with_capacity(4), url.path().strip_prefix(root.path())?),
+        false => (vec![url.authority()?.to_string()], &url.path()[1..]),
+    };
+
+    for segment in relative.trim_end_matches('/').split('/') {
+        segments.push(decode_segment(segment).filter(|segment| !segment.is_empty())?);
+    }
+
+    let path = segments.join("/");
+
+    Some(IndexEntry {
+        path,
+        url: url.to_string(),
+        directory,
+        size,
+    })
+}
+
+fn same_origin(root: &Uri, url: &str) -> bool {
+    match Uri::from_str(url) {
+        Ok(url) => url.scheme() == root.scheme() && url.authority() == root.authority(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer::Transfer;
+    use crate::Archeon;
+
+    const TEST_HTML: &str = r#"<html><head><title>Index of /debian/</title></head><body>
+<h1>Index of /debian/</h1><table>
+<tr><th><a href="?C=N;O=D">Name</a></th><th><a href="?C=S;O=A">Size</a></th></tr>
+<tr><td><a href="/">Parent Directory</a></td></tr>
+<tr><td><a href="../">../</a></td></tr>
+<tr><td><a href="test_1.0_amd64.deb">test_1.0_amd64.deb</a></td><td>9</td></tr>
+<tr><td><a href='test%20notes.txt'>test notes.txt</a></td></tr>
+<tr><td><a HREF=pool/>pool/</a></td></tr>
+<tr><td><a href="https://elsewhere.test/other.deb">other</a></td></tr>
+<tr><td><a href="test_1.0_amd64.deb"><img src="/icons/deb.png"></a></td></tr>
+<tr><td><a href="mailto:root@test-index">root</a></td></tr>
+</table></body></html>"#;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_html() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_root = Uri::from_static("http://test-index/debian/");
+        let test_entries = super::parse_html(&test_root, &test_root, TEST_HTML);
+        let test_paths: Vec<(&str, bool)> = test_entries
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry.directory))
+            .collect();
+        assert_eq!(
+            test_paths,
+            [
+                ("test_1.0_amd64.deb", false),
+                ("test notes.txt", false),
+                ("pool", true),
+                ("elsewhere.test/other.deb", false),
+            ],
+        );
+        assert_eq!(
+            test_entries[1].url,
+            "http://test-index/debian/test%20notes.txt"
+        );
+        let test_listing = Uri::from_static("http://test-index/debian/pool/");
+        let test_entries = super::parse_html(
+            &test_root,
+            &test_listing,
+            r#"<a href="../">up</a><a href="main/">main/</a><a href="/debian/pool/a.deb">a</a><a href="../../etc/passwd">x</a>"#,
+        );
+        let test_paths: Vec<&str> = test_entries
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect();
+        assert_eq!(test_paths, ["pool/main", "pool/a.deb"]);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_json() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_root = Uri::from_static("http://test-index/files/");
+        let test_entries = super::parse_json(
+            &test_root,
+            &test_root,
+            r#"[
+                {"name": "sub", "type": "directory", "mtime": "Mon, 01 Jan 2024 00:00:00 GMT"},
+                {"name": "test.iso", "type": "file", "size": 9},
+                {"name": "caddy", "is_dir": true, "url": "./caddy/"},
+                {"name": "caddy.txt", "is_dir": false, "url": "./caddy.txt", "size": 4}
+            ]"#,
+        )?;
+        let test_paths: Vec<(&str, bool, Option<u64>)> = test_entries
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry.directory, entry.size))
+            .collect();
+        assert_eq!(
+            test_paths,
+            [
+                ("sub", true, None),
+                ("test.iso", false, Some(9)),
+                ("caddy", true, None),
+                ("caddy.txt", false, Some(4)),
+            ],
+        );
+        assert!(super::parse_json(&test_root, &test_root, "{}").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn entries() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_other = mockito::Server::new_async().await;
+        let test_other_url = format!("{}/other.deb", test_other.url());
+        test_server
+            .mock("GET", "/crawl/")
+            .with_status(200)
+            .with_body(format!(
+                r#"<a href="first.deb">first.deb</a><a href="second.deb">second.deb</a><a href="sub/">sub/</a><a href="debug/">debug/</a><a href="{}">other</a>"#,
+                test_other_url,
+            ))
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/crawl/sub/")
+            .with_status(200)
+            .with_body(r#"<a href="third.deb">third.deb</a><a href="deeper/">deeper/</a>"#)
+            .create_async()
+            .await;
+        let test_deeper = test_server
+            .mock("GET", "/crawl/sub/deeper/")
+            .expect(0)
+            .create_async()
+            .await;
+        let test_debug = test_server
+            .mock("GET", "/crawl/debug/")
+            .expect(0)
+            .create_async()
+            .await;
+        for test_path in [
+            "/crawl/first.deb",
+            "/crawl/second.deb",
+            "/crawl/sub/third.deb",
+        ] {
+            test_server
+                .mock("HEAD", test_path)
+                .with_status(200)
+                .with_header("content-length", "9")
+                .create_async()
+                .await;
+        }
+        test_other
+            .mock("HEAD", "/other.deb")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .create_async()
+            .await;
+        let mut test_index = DirectoryIndex::init(
+            &format!("{}/crawl", test_server.url()),
+            Transfer::init_client(),
+        )?;
+        test_index.depth = 1;
+        test_index.filter.exclude = vec![String::from("debug")];
+        let test_paths: Vec<String> = test_index
+            .entries()
+            .await?
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        assert_eq!(test_paths, ["first.deb", "second.deb", "sub/third.deb"]);
+        test_deeper.assert_async().await;
+        test_debug.assert_async().await;
+        test_index.same_origin = false;
+        test_index.max_bytes = Some(20);
+        let test_entries = test_index.entries().await?;
+        let test_paths: Vec<&str> = test_entries
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect();
+        assert_eq!(test_paths, ["first.deb", "second.deb"]);
+        assert!(test_entries.iter().all(|entry| entry.size == Some(9)));
+        test_index.max_bytes = None;
+        let test_other_path = format!(
+            "{}/other.deb",
+            test_other.url().trim_start_matches("http://")
+        );
+        let test_paths: Vec<String> = test_index
+            .entries()
+            .await?
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        assert!(test_paths.contains(&test_other_path));
+        test_index.max_bytes = Some(0);
+        assert!(test_index.entries().await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mirror_index() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_mocks = vec![
+            test_server
+                .mock("GET", "/mirror/")
+                .with_status(200)
+                .with_header("content-type", "text/html")
+                .with_body(
+                    r#"<a href="../">../</a><a href="first.deb">first.deb</a><a href="notes.txt">notes.txt</a><a href="sub/">sub/</a>"#,
+                )
+                .create_async()
+                .await,
+            test_server
+                .mock("GET", "/mirror/sub/")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(r#"[{"name": "second.deb", "type": "file", "size": 9}]"#)
+                .create_async()
+                .await,
+            test_server
+                .mock("GET", "/mirror/first.deb")
+                .with_status(200)
+                .with_header("content-length", "9")
+                .with_body(b"test_body")
+                .create_async()
+                .await,
+            test_server
+                .mock("GET", "/mirror/sub/second.deb")
+                .with_status(200)
+                .with_header("content-length", "9")
+                .with_body(b"test_body")
+                .create_async()
+                .await,
+        ];
+        let test_skipped = test_server
+            .mock("GET", "/mirror/notes.txt")
+            .expect(0)
+            .create_async()
+            .await;
+        let test_destination = std::env::temp_dir().join("archeon_test_mirror_index");
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.progress.set_quiet(true);
+        let mut test_index = DirectoryIndex::init(
+            &format!("{}/mirror", test_server.url()),
+            test_archeon.client.to_owned(),
+        )?;
+        test_index.filter.include = vec![String::from("*.deb")];
+        let test_report = test_archeon
+            .mirror_index(&test_index, &test_destination)
+            .await?;
+        assert_eq!(test_report.results.len(), 2);
+        assert!(test_report
+            .results
+            .iter()
+            .all(|result| result.error.is_none()));
+        for test_mock in test_mocks {
+            test_mock.assert_async().await;
+        }
+        test_skipped.assert_async().await;
+        assert_eq!(
+            tokio::fs::read(test_destination.join("first.deb")).await?,
+            b"test_body",
+        );
+        assert_eq!(
+            tokio::fs::read(test_destination.join("sub/second.deb")).await?,
+            b"test_body",
+        );
+        assert!(!test_destination.join("notes.txt").exists());
+        tokio::fs::remove_dir_all(&test_destination).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,777 @@
+use hyper::body::Bytes;
+use hyper::header::{HeaderValue, ACCEPT, AUTHORIZATION, WWW_AUTHENTICATE};
+use hyper::{Body, Method, Request, StatusCode};
+
+use percent_encoding::utf8_percent_encode;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::auth::{AuthFuture, Authenticate, UNRESERVED};
+use crate::checksum::{self, Algorithm};
+use crate::source::launch_request;
+use crate::transfer::{HttpClient, Transfer};
+
+pub const MANIFEST_MEDIA_TYPES: &str = "application/vnd.oci.image.manifest.v1+json, \
+    application/vnd.oci.image.index.v1+json, \
+    application/vnd.docker.distribution.manifest.v2+json, \
+    application/vnd.docker.distribution.manifest.list.v2+json";
+
+pub const TITLE_ANNOTATION: &str = "org.opencontainers.image.title";
+
+const DOCKER_HUB: &str = "registry-1.docker.io";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OciReference {
+    pub scheme: String,
+    pub registry: String,
+    pub repository: String,
+    pub reference: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OciPlatform {
+    pub architecture: String,
+    pub os: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OciDescriptor {
+    #[serde(default)]
+    pub media_type: String,
+    pub digest: String,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<OciPlatform>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OciManifest {
+    #[serde(default)]
+    pub media_type: Option<String>,
+    #[serde(default)]
+    pub config: Option<OciDescriptor>,
+    #[serde(default)]
+    pub layers: Vec<OciDescriptor>,
+    #[serde(default)]
+    pub manifests: Vec<OciDescriptor>,
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+}
+
+#[derive(Clone)]
+pub struct OciRegistry {
+    pub reference: OciReference,
+    pub client: HttpClient,
+    pub credentials: Option<Arc<dyn Authenticate>>,
+    token: Arc<Mutex<Option<Option<String>>>>,
+}
+
+#[derive(Deserialize)]
+struct RegistryToken {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+impl OciReference {
+    pub fn parse(input: &str) -> Result<OciReference, Error> {
+        let (scheme, rest) = match input.split_once("://") {
+            Some(("oci", rest)) => ("https", rest),
+            Some((scheme @ ("http" | "https"), rest)) => (scheme, rest),
+            Some((scheme, _)) => {
+                let reason = format!("unsupported scheme '{}'", scheme);
+
+                return Err(invalid(input, &reason));
+            }
+            None => ("https", input),
+        };
+        let (name, reference) = match rest.split_once('@') {
+            Some((name, digest)) => (name, digest.to_owned()),
+            None => match rest.rsplit_once(':') {
+                Some((name, tag)) if !tag.contains('/') => (name, tag.to_owned()),
+                _ => (rest, String::from("latest")),
+            },
+        };
+        let (registry, repository) = match name.split_once('/') {
+            Some((registry, repository))
+                if registry.contains(['.', ':']) || registry == "localhost" =>
+            {
+                (registry.to_owned(), repository.to_owned())
+            }
+            _ => (String::from("docker.io"), name.to_owned()),
+        };
+        let (registry, repository) = match registry.as_str() {
+            "docker.io" | "index.docker.io" => match repository.contains('/') {
+                true => (String::from(DOCKER_HUB), repository),
+                false => (String::from(DOCKER_HUB), format!("library/{}", repository)),
+            },
+            _ => (registry, repository),
+        };
+
+        if repository.is_empty() || reference.is_empty() {
+            return Err(invalid(input, "the repository or reference is missing"));
+        }
+
+        Ok(OciReference {
+            scheme: scheme.to_owned(),
+            registry,
+            repository,
+            reference,
+        })
+    }
+
+    pub fn base(&self) -> String {
+        format!("{}://{}", self.scheme, self.registry)
+    }
+
+    pub fn manifest_url(&self, reference: &str) -> String {
+        format!(
+            "{}/v2/{}/manifests/{}",
+            self.base(),
+            self.repository,
+            reference,
+        )
+    }
+
+    pub fn blob_url(&self, digest: &str) -> String {
+        format!("{}/v2/{}/blobs/{}", self.base(), self.repository, digest)
+    }
+}
+
+impl fmt::Display for OciReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reference.contains(':') {
+            true => write!(
+                f,
+                "{}/{}@{}",
+                self.registry, self.repository, self.reference
+            ),
+            false => write!(
+                f,
+                "{}/{}:{}",
+                self.registry, self.repository, self.reference
+            ),
+        }
+    }
+}
+
+impl OciPlatform {
+    pub fn current() -> OciPlatform {
+        let os = match std::env::consts::OS {
+            "macos" => "darwin",
+            os => os,
+        };
+        let architecture = match std::env::consts::ARCH {
+            "x86_64" => "amd64",
+            "aarch64" => "arm64",
+            "x86" => "386",
+            "powerpc64" => "ppc64le",
+            architecture => architecture,
+        };
+
+        OciPlatform {
+            architecture: architecture.to_owned(),
+            os: os.to_owned(),
+            variant: None,
+        }
+    }
+
+    pub fn matches(&self, other: &OciPlatform) -> bool {
+        self.os == other.os
+            && self.architecture == other.architecture
+            && (self.variant.is_none() || self.variant == other.variant)
+    }
+}
+
+impl OciDescriptor {
+    pub fn title(&self) -> Option<&str> {
+        self.annotations.get(TITLE_ANNOTATION).map(String::as_str)
+    }
+}
+
+impl OciRegistry {
+    pub fn init(reference: OciReference, client: HttpClient) -> OciRegistry {
+        OciRegistry {
+            reference,
+            client,
+            credentials: None,
+            token: Arc::default(),
+        }
+    }
+
+    pub async fn resolve(&self) -> Result<OciManifest, Error> {
+        self.resolve_platform(&OciPlatform::current()).await
+    }
+
+    pub async fn resolve_platform(&self, platform: &OciPlatform) -> Result<OciManifest, Error> {
+        let manifest = self.manifest(&self.reference.reference).await?;
+
+        if manifest.manifests.is_empty() {
+            return Ok(manifest);
+        }
+
+        let descriptor = manifest
+            .manifests
+            .iter()
+            .find(|descriptor| {
+                descriptor
+                    .platform
+                    .as_ref()
+                    .is_some_and(|candidate| platform.matches(candidate))
+            })
+            .or(match manifest.manifests.len() {
+                1 => manifest.manifests.first(),
+                _ => None,
+            });
+
+        match descriptor {
+            Some(descriptor) => self.manifest(&descriptor.digest).await,
+            None => {
+                let error = format!(
+                    "No manifest for {}/{} found in {}!",
+                    platform.os, platform.architecture, self.reference,
+                );
+
+                Err(Error::new(ErrorKind::NotFound, error))
+            }
+        }
+    }
+
+    pub async fn manifest(&self, reference: &str) -> Result<OciManifest, Error> {
+        let url = self.reference.manifest_url(reference);
+        let (status, headers, body) = self.launch_get(&url, MANIFEST_MEDIA_TYPES).await?;
+
+        if !status.is_success() {
+            let error = format!("Manifest request to {} failed with {}!", url, status);
+
+            return Err(Error::new(status_kind(status), error));
+        }
+
+        let expected = match reference.contains(':') {
+            true => Some(reference),
+            false => headers
+                .get("docker-content-digest")
+                .and_then(|digest| digest.to_str().ok()),
+        };
+
+        if let Some(expected) = expected {
+            if !verify_digest(&body, expected)? {
+                let error = format!("Manifest from {} does not match {}!", url, expected);
+
+                return Err(Error::new(ErrorKind::InvalidData, error));
+            }
+        }
+
+        serde_json::from_slice(&body).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub async fn transfer(
+        &self,
+        descriptor: &OciDescriptor,
+    ) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        let (algorithm, hex) = split_digest(&descriptor.digest)?;
+        let algorithm = parse_algorithm(algorithm)?;
+
+        let url = self.reference.blob_url(&descriptor.digest);
+        let mut transfer = Transfer::init_with_client(&url, self.client.to_owned()).await?;
+        let filename = match descriptor.title() {
+            Some(title) if !title.contains(['/', '\\']) && title != ".." && title != "." => {
+                PathBuf::from(title)
+            }
+            _ => PathBuf::from(hex),
+        };
+
+        transfer.file_path = transfer.temp_dir.join(&filename);
+        transfer.filename = filename;
+        transfer.checksum = Some(hex.to_owned());
+        transfer.algorithm = Some(algorithm);
+        transfer.auth = Some(Arc::new(self.to_owned()));
+
+        Ok(transfer)
+    }
+
+    async fn launch_get(
+        &self,
+        url: &str,
+        accept: &str,
+    ) -> Result<(StatusCode, hyper::HeaderMap, Bytes), Error> {
+        let mut retried = false;
+
+        loop {
+            let mut request = Request::builder()
+                .method(Method::GET)
+                .uri(url)
+                .header(ACCEPT, accept)
+                .body(Body::empty())
+                .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+
+            self.launch_authenticate(&mut request).await?;
+
+            let response = launch_request(&self.client, request).await?;
+
+            if response.0 == StatusCode::UNAUTHORIZED && !retried {
+                self.invalidate();
+                retried = true;
+
+                continue;
+            }
+
+            return Ok(response);
+        }
+    }
+
+    async fn launch_authenticate(&self, request: &mut Request<Body>) -> Result<(), Error> {
+        match self.launch_token().await? {
+            Some(token) => {
+                let mut value = HeaderValue::from_str(&format!("Bearer {}", token))
+                    .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+                value.set_sensitive(true);
+                request.headers_mut().insert(AUTHORIZATION, value);
+            }
+            None => {
+                if let Some(credentials) = &self.credentials {
+                    credentials
+                        .authenticate(request)
+                        .await
+                        .map_err(Error::other)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn launch_token(&self) -> Result<Option<String>, Error> {
+        if let Some(token) = self.lock().as_ref() {
+            return Ok(token.to_owned());
+        }
+
+        let url = format!("{}/v2/", self.reference.base());
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(&url)
+            .body(Body::empty())
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+        let (status, headers, _) = launch_request(&self.client, request).await?;
+        let challenge = headers
+            .get(WWW_AUTHENTICATE)
+            .and_then(|challenge| challenge.to_str().ok())
+            .and_then(|challenge| challenge.strip_prefix("Bearer "));
+        let token = match (status, challenge) {
+            (StatusCode::UNAUTHORIZED, Some(challenge)) => {
+                Some(self.launch_bearer_token(challenge).await?)
+            }
+            _ => None,
+        };
+
+        *self.lock() = Some(token.to_owned());
+
+        Ok(token)
+    }
+
+    async fn launch_bearer_token(&self, challenge: &str) -> Result<String, Error> {
+        let parameters = parse_challenge(challenge);
+        let realm = match parameters.get("realm") {
+            Some(realm) => realm,
+            None => {
+                let error = format!("No realm in the challenge from {}!", self.reference);
+
+                return Err(Error::new(ErrorKind::InvalidData, error));
+            }
+        };
+        let scope = format!("repository:{}:pull", self.reference.repository);
+        let mut query = vec![("scope", scope.as_str())];
+
+        if let Some(service) = parameters.get("service") {
+            query.insert(0, ("service", service.as_str()));
+        }
+
+        let query = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, utf8_percent_encode(value, UNRESERVED)))
+            .collect::<Vec<String>>()
+            .join("&");
+        let url = format!("{}?{}", realm, query);
+        let mut request = Request::builder()
+            .method(Method::GET)
+            .uri(&url)
+            .header(ACCEPT, "application/json")
+            .body(Body::empty())
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+
+        if let Some(credentials) = &self.credentials {
+            credentials
+                .authenticate(&mut request)
+                .await
+                .map_err(Error::other)?;
+        }
+
+        let (status, _, body) = launch_request(&self.client, request).await?;
+
+        if !status.is_success() {
+            let error = format!("Token request to {} failed with {}!", realm, status);
+
+            return Err(Error::new(ErrorKind::PermissionDenied, error));
+        }
+
+        let token: RegistryToken = serde_json::from_slice(&body)
+            .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+        match token.token.or(token.access_token) {
+            Some(token) => Ok(token),
+            None => {
+                let error = format!("No token in the response from {}!", realm);
+
+                Err(Error::new(ErrorKind::InvalidData, error))
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Option<String>>> {
+        self.token
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Authenticate for OciRegistry {
+    fn authenticate<'a>(&'a self, request: &'a mut Request<Body>) -> AuthFuture<'a> {
+        Box::pin(async move {
+            if request
+                .uri()
+                .authority()
+                .map(|authority| authority.as_str())
+                != Some(self.reference.registry.as_str())
+            {
+                return Ok(());
+            }
+
+            self.launch_authenticate(request).await?;
+
+            Ok(())
+        })
+    }
+
+    fn invalidate(&self) {
+        *self.lock() = None;
+
+        if let Some(credentials) = &self.credentials {
+            credentials.invalidate();
+        }
+    }
+}
+
+pub fn verify_digest(bytes: &[u8], digest: &str) -> Result<bool, Error> {
+    let (algorithm, hex) = split_digest(digest)?;
+    let actual = checksum::digest_bytes(bytes, parse_algorithm(algorithm)?);
+
+    Ok(checksum::matches(hex, &actual))
+}
+
+fn parse_algorithm(algorithm: &str) -> Result<Algorithm, Error> {
+    algorithm.parse().map_err(|_| {
+        let error = format!("Unsupported digest algorithm '{}'!", algorithm);
+
+        Error::new(ErrorKind::Unsupported, error)
+    })
+}
+
+fn split_digest(digest: &str) -> Result<(&str, &str), Error> {
+    match digest.split_once(':') {
+        Some((algorithm, hex)) if !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Ok((algorithm, hex))
+        }
+        _ => {
+            let error = format!("Invalid digest '{}'!", digest);
+
+            Err(Error::new(ErrorKind::InvalidData, error))
+        }
+    }
+}
+
+fn parse_challenge(challenge: &str) -> BTreeMap<String, String> {
+    let mut parameters = BTreeMap::new();
+    let mut rest = challenge.trim();
+
+    while let Some((name, value)) = rest.split_once('=') {
+        let name = name
+            .trim()
+            .trim_start_matches(',')
+            .trim()
+            .to_ascii_lowercase();
+        let (value, remainder) = match value.strip_prefix('"') {
+            Some(quoted) => match quoted.split_once('"') {
+                Some((value, remainder)) => (value, remainder),
+                None => (quoted, ""),
+            },
+            None => value.split_once(',').unwrap_or((value, "")),
+        };
+
+        parameters.insert(name, value.to_owned());
+        rest = remainder.trim_start_matches([',', ' ']);
+    }
+
+    parameters
+}
+
+fn status_kind(status: StatusCode) -> ErrorKind {
+    match status {
+        StatusCode::NOT_FOUND => ErrorKind::NotFound,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorKind::PermissionDenied,
+        _ => ErrorKind::Other,
+    }
+}
+
+fn invalid(input: &str, reason: &str) -> Error {
+    let error = format!("Invalid OCI reference '{}': {}!", input, reason);
+
+    Error::new(ErrorKind::InvalidInput, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Archeon;
+
+    const TEST_BLOB: &[u8] = b"test_oci_blob";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_cases = [
+            (
+                "ghcr.io/test-org/tool:1.2.0",
+                ("https", "ghcr.io", "test-org/tool", "1.2.0"),
+            ),
+            (
+                "oci://localhost:5000/tool",
+                ("https", "localhost:5000", "tool", "latest"),
+            ),
+            (
+                "http://127.0.0.1:5000/test/tool@sha256:abc123",
+                ("http", "127.0.0.1:5000", "test/tool", "sha256:abc123"),
+            ),
+            (
+                "alpine",
+                ("https", "registry-1.docker.io", "library/alpine", "latest"),
+            ),
+            (
+                "docker.io/test-org/tool:2",
+                ("https", "registry-1.docker.io", "test-org/tool", "2"),
+            ),
+        ];
+        for (test_input, (test_scheme, test_registry, test_repository, test_reference)) in
+            test_cases
+        {
+            let test_parsed = OciReference::parse(test_input)?;
+            assert_eq!(test_parsed.scheme, test_scheme);
+            assert_eq!(test_parsed.registry, test_registry);
+            assert_eq!(test_parsed.repository, test_repository);
+            assert_eq!(test_parsed.reference, test_reference);
+        }
+        let test_parsed = OciReference::parse("ghcr.io/test-org/tool:1.2.0")?;
+        assert_eq!(
+            test_parsed.manifest_url("1.2.0"),
+            "https://ghcr.io/v2/test-org/tool/manifests/1.2.0",
+        );
+        assert_eq!(test_parsed.to_string(), "ghcr.io/test-org/tool:1.2.0");
+        assert!(OciReference::parse("ftp://ghcr.io/tool").is_err());
+        assert!(OciReference::parse("ghcr.io/tool@").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_challenge() {
+        let test_parameters = super::parse_challenge(
+            "realm=\"https://auth.test/token\",service=\"registry.test\",scope=\"repository:a:pull,push\"",
+        );
+        assert_eq!(test_parameters["realm"], "https://auth.test/token");
+        assert_eq!(test_parameters["service"], "registry.test");
+        assert_eq!(test_parameters["scope"], "repository:a:pull,push");
+        let test_parameters = super::parse_challenge("realm=https://auth.test/token, error=none");
+        assert_eq!(test_parameters["realm"], "https://auth.test/token");
+        assert_eq!(test_parameters["error"], "none");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_digest() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_digest = format!("sha256:{}", checksum::sha256_bytes(TEST_BLOB));
+        assert!(super::verify_digest(TEST_BLOB, &test_digest)?);
+        assert!(!super::verify_digest(b"tampered", &test_digest)?);
+        assert!(super::verify_digest(
+            TEST_BLOB,
+            &format!(
+                "sha512:{}",
+                checksum::digest_bytes(TEST_BLOB, Algorithm::Sha512)
+            ),
+        )?);
+        assert_eq!(
+            super::verify_digest(TEST_BLOB, "md4:abcd")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::Unsupported,
+        );
+        assert!(super::verify_digest(TEST_BLOB, "sha256").is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn platform() {
+        let test_platform = OciPlatform {
+            architecture: String::from("arm"),
+            os: String::from("linux"),
+            variant: None,
+        };
+        let test_candidate = OciPlatform {
+            variant: Some(String::from("v7")),
+            ..test_platform.to_owned()
+        };
+        assert!(test_platform.matches(&test_candidate));
+        assert!(!test_candidate.matches(&test_platform));
+        assert!(!OciPlatform::current().os.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn pull() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_platform = OciPlatform::current();
+        let test_blob_digest = format!("sha256:{}", checksum::sha256_bytes(TEST_BLOB));
+        let test_manifest = serde_json::to_vec(&OciManifest {
+            media_type: Some(String::from("application/vnd.oci.image.manifest.v1+json")),
+            layers: vec![OciDescriptor {
+                media_type: String::from("application/vnd.oci.image.layer.v1.tar"),
+                digest: test_blob_digest.to_owned(),
+                size: TEST_BLOB.len() as u64,
+                annotations: BTreeMap::from([(
+                    String::from(TITLE_ANNOTATION),
+                    String::from("test_oci_tool.tar"),
+                )]),
+                platform: None,
+            }],
+            ..OciManifest::default()
+        })?;
+        let test_manifest_digest = format!("sha256:{}", checksum::sha256_bytes(&test_manifest));
+        let test_index = serde_json::to_vec(&OciManifest {
+            media_type: Some(String::from("application/vnd.oci.image.index.v1+json")),
+            manifests: vec![
+                OciDescriptor {
+                    digest: String::from("sha256:0000"),
+                    platform: Some(OciPlatform {
+                        architecture: String::from("test_arch"),
+                        os: String::from("test_os"),
+                        variant: None,
+                    }),
+                    ..OciDescriptor::default()
+                },
+                OciDescriptor {
+                    digest: test_manifest_digest.to_owned(),
+                    platform: Some(test_platform),
+                    ..OciDescriptor::default()
+                },
+            ],
+            ..OciManifest::default()
+        })?;
+        let mock_challenge = test_server
+            .mock("GET", "/v2/")
+            .with_status(401)
+            .with_header(
+                "www-authenticate",
+                &format!(
+                    "Bearer realm=\"{}/token\",service=\"test.registry\"",
+                    test_server.url(),
+                ),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_token = test_server
+            .mock("GET", "/token")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("service".into(), "test.registry".into()),
+                mockito::Matcher::UrlEncoded("scope".into(), "repository:test/tool:pull".into()),
+            ]))
+            .with_status(200)
+            .with_body("{\"token\":\"test_token\"}")
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_index = test_server
+            .mock("GET", "/v2/test/tool/manifests/1.0")
+            .match_header("authorization", "Bearer test_token")
+            .with_status(200)
+            .with_body(&test_index)
+            .create_async()
+            .await;
+        let mock_manifest = test_server
+            .mock(
+                "GET",
+                format!("/v2/test/tool/manifests/{}", test_manifest_digest).as_str(),
+            )
+            .match_header("authorization", "Bearer test_token")
+            .with_status(200)
+            .with_body(&test_manifest)
+            .create_async()
+            .await;
+        let test_blob_path = format!("/v2/test/tool/blobs/{}", test_blob_digest);
+        let mock_blob = test_server
+            .mock("GET", test_blob_path.as_str())
+            .match_header("authorization", "Bearer test_token")
+            .with_status(200)
+            .with_body(TEST_BLOB)
+            .create_async()
+            .await;
+        let test_reference = OciReference::parse(&format!("{}/test/tool:1.0", test_server.url(),))?;
+        let test_registry = OciRegistry::init(test_reference, Transfer::init_client());
+        let test_resolved = test_registry.resolve().await?;
+        assert_eq!(test_resolved.layers.len(), 1);
+        assert_eq!(test_resolved.layers[0].title(), Some("test_oci_tool.tar"));
+        let test_transfer = test_registry.transfer(&test_resolved.layers[0]).await?;
+        assert!(test_transfer.file_path.ends_with("test_oci_tool.tar"));
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.launch(&test_transfer).await?;
+        assert_eq!(tokio::fs::read(&test_transfer.file_path).await?, TEST_BLOB);
+        mock_challenge.assert_async().await;
+        mock_token.assert_async().await;
+        mock_index.assert_async().await;
+        mock_manifest.assert_async().await;
+        mock_blob.assert_async().await;
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn manifest_digest_mismatch() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mock_challenge = test_server
+            .mock("GET", "/v2/")
+            .with_status(200)
+            .create_async()
+            .await;
+        let mock_manifest = test_server
+            .mock("GET", "/v2/test/tool/manifests/1.0")
+            .with_status(200)
+            .with_header("docker-content-digest", "sha256:0000")
+            .with_body("{\"layers\":[]}")
+            .create_async()
+            .await;
+        let test_reference = OciReference::parse(&format!("{}/test/tool:1.0", test_server.url(),))?;
+        let test_registry = OciRegistry::init(test_reference, Transfer::init_client());
+        let test_error = test_registry.resolve().await.unwrap_err();
+        assert_eq!(test_error.kind(), ErrorKind::InvalidData);
+        mock_challenge.assert_async().await;
+        mock_manifest.assert_async().await;
+        Ok(())
+    }
+}
@@ -0,0 +1,477 @@
+use hyper::header::ACCEPT;
+use hyper::{Body, Method, Request, StatusCode, Uri};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use tokio::process::Command;
+
+use crate::auth::Authenticate;
+use crate::installer::Backend;
+use crate::source::launch_request;
+use crate::transfer::{HttpClient, Transfer};
+
+pub const PYPI: &str = "https://pypi.org/";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PyPiInfo {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub requires_python: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PyPiFile {
+    pub filename: String,
+    pub url: String,
+    pub packagetype: String,
+    #[serde(default)]
+    pub digests: BTreeMap<String, String>,
+    #[serde(default)]
+    pub requires_python: Option<String>,
+    #[serde(default)]
+    pub yanked: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PyPiRelease {
+    pub info: PyPiInfo,
+    #[serde(default)]
+    pub urls: Vec<PyPiFile>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PythonTarget {
+    pub version: Option<(u32, u32)>,
+    pub os: String,
+    pub arch: String,
+    pub musl: bool,
+}
+
+#[derive(Clone)]
+pub struct PyPiIndex {
+    pub url: Uri,
+    pub client: HttpClient,
+    pub credentials: Option<Arc<dyn Authenticate>>,
+    pub target: PythonTarget,
+}
+
+pub fn normalize_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+
+    for character in name.chars() {
+        match character {
+            '-' | '_' | '.' => {
+                if !normalized.ends_with('-') {
+                    normalized.push('-');
+                }
+            }
+            character => normalized.push(character.to_ascii_lowercase()),
+        }
+    }
+
+    normalized
+}
+
+impl PythonTarget {
+    pub fn current() -> PythonTarget {
+        PythonTarget {
+            version: None,
+            os: std::env::consts::OS.to_owned(),
+            arch: std::env::consts::ARCH.to_owned(),
+            musl: cfg!(target_env = "musl"),
+        }
+    }
+
+    pub async fn detect() -> PythonTarget {
+        let mut target = Self::current();
+        let output = Command::new("python3")
+            .arg("-c")
+            .arg("import sys; print(sys.version_info[0], sys.version_info[1])")
+            .output()
+            .await;
+
+        if let Ok(output) = output {
+            let version = String::from_utf8_lossy(&output.stdout);
+            let mut parts = version.split_whitespace().map(str::parse::<u32>);
+
+            if let (Some(Ok(major)), Some(Ok(minor))) = (parts.next(), parts.next()) {
+                target.version = Some((major, minor));
+            }
+        }
+
+        target
+    }
+
+    pub fn wheel_score(&self, filename: &str) -> Option<u8> {
+        let stem = filename.strip_suffix(".whl")?;
+        let parts = stem.split('-').collect::<Vec<&str>>();
+
+        if parts.len() != 5 && parts.len() != 6 {
+            return None;
+        }
+
+        let (python, abi, platform) = (
+            parts[parts.len() - 3],
+            parts[parts.len() - 2],
+            parts[parts.len() - 1],
+        );
+
+        if !python.split('.').any(|tag| self.supports_python(tag, abi)) {
+            return None;
+        }
+
+        platform
+            .split('.')
+            .filter_map(|tag| self.platform_score(tag))
+            .max()
+    }
+
+    fn supports_python(&self, tag: &str, abi: &str) -> bool {
+        let major = self.version.map_or(3, |(major, _)| major);
+
+        if let Some(version) = tag.strip_prefix("py") {
+            return version.starts_with(&major.to_string());
+        }
+
+        match (tag.strip_prefix("cp"), self.version) {
+            (Some(version), Some((major, minor))) => {
+                let wheel = version.strip_prefix(&major.to_string());
+                let wheel = wheel.and_then(|minor| minor.parse::<u32>().ok());
+
+                match wheel {
+                    Some(wheel) if abi == "abi3" => wheel <= minor,
+                    Some(wheel) => wheel == minor,
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn platform_score(&self, tag: &str) -> Option<u8> {
+        if tag == "any" {
+            return Some(1);
+        }
+
+        let arch = match (self.os.as_str(), self.arch.as_str()) {
+            ("macos", "aarch64") => "arm64",
+            ("windows", "x86_64") => "amd64",
+            ("windows", "aarch64") => "arm64",
+            ("linux", "x86") => "i686",
+            (_, arch) => arch,
+        };
+        let supported = match self.os.as_str() {
+            "linux" => {
+                let prefixes: &[&str] = match self.musl {
+                    true => &["musllinux_"],
+                    false => &["manylinux", "linux_"],
+                };
+
+                prefixes.iter().any(|prefix| tag.starts_with(prefix))
+                    && tag.ends_with(&format!("_{}", arch))
+            }
+            "macos" => {
+                tag.starts_with("macosx_")
+                    && (tag.ends_with(&format!("_{}", arch)) || tag.ends_with("_universal2"))
+            }
+            "windows" => match self.arch.as_str() {
+                "x86" => tag == "win32",
+                _ => tag == format!("win_{}", arch),
+            },
+            _ => false,
+        };
+
+        supported.then_some(2)
+    }
+}
+
+impl PyPiIndex {
+    pub fn init(url: &str, client: HttpClient) -> Result<PyPiIndex, Error> {
+        let url = format!("{}/", url.trim_end_matches('/'));
+        let url = Uri::from_str(&url).map_err(|error| {
+            let error = format!("Invalid package index '{}': {}!", url, error);
+
+            Error::new(ErrorKind::InvalidInput, error)
+        })?;
+
+        Ok(PyPiIndex {
+            url,
+            client,
+            credentials: None,
+            target: PythonTarget::current(),
+        })
+    }
+
+    pub fn pypi(client: HttpClient) -> PyPiIndex {
+        PyPiIndex {
+            url: Uri::from_static(PYPI),
+            client,
+            credentials: None,
+            target: PythonTarget::current(),
+        }
+    }
+
+    pub async fn release(&self, name: &str, version: Option<&str>) -> Result<PyPiRelease, Error> {
+        let name = normalize_name(name);
+        let url = match version {
+            Some(version) => format!("{}pypi/{}/{}/json", self.url, name, version),
+            None => format!("{}pypi/{}/json", self.url, name),
+        };
+        let mut request = Request::builder()
+            .method(Method::GET)
+            .uri(&url)
+            .header(ACCEPT, "application/json")
+            .body(Body::empty())
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+
+        if let Some(credentials) = &self.credentials {
+            credentials
+                .authenticate(&mut request)
+                .await
+                .map_err(Error::other)?;
+        }
+
+        let (status, _, body) = launch_request(&self.client, request).await?;
+
+        match status {
+            status if status.is_success() => {}
+            StatusCode::NOT_FOUND => {
+                let error = format!(
+                    "No version {} of package '{}' found!",
+                    version.unwrap_or("latest"),
+                    name,
+                );
+
+                return Err(Error::new(ErrorKind::NotFound, error));
+            }
+            status => {
+                let error = format!("Package request to {} failed with {}!", url, status);
+
+                return Err(Error::other(error));
+            }
+        }
+
+        serde_json::from_slice(&body).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn select<'a>(&self, release: &'a PyPiRelease) -> Result<&'a PyPiFile, Error> {
+        let files = release.urls.iter().filter(|file| !file.yanked);
+        let wheel = files
+            .clone()
+            .filter(|file| file.packagetype == "bdist_wheel")
+            .filter_map(|file| Some((self.target.wheel_score(&file.filename)?, file)))
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, file)| file);
+
+        wheel
+            .or_else(|| files.clone().find(|file| file.packagetype == "sdist"))
+            .ok_or_else(|| {
+                let error = format!(
+                    "No compatible wheel or sdist for {} {} found!",
+                    release.info.name, release.info.version,
+                );
+
+                Error::new(ErrorKind::NotFound, error)
+            })
+    }
+
+    pub async fn resolve(&self, name: &str, version: Option<&str>) -> Result<PyPiFile, Error> {
+        let release = self.release(name, version).await?;
+
+        Ok(self.select(&release)?.to_owned())
+    }
+
+    pub async fn transfer(
+        &self,
+        file: &PyPiFile,
+    ) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        let mut transfer = Transfer::init_with_client(&file.url, self.client.to_owned()).await?;
+        let filename = PathBuf::from(&file.filename);
+
+        transfer.file_path = transfer.temp_dir.join(&filename);
+        transfer.filename = filename;
+        transfer.checksum = file.digests.get("sha256").cloned();
+        transfer.installer.backend = Backend::Pip;
+        transfer.auth = self.credentials.to_owned();
+
+        Ok(transfer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum;
+    use crate::Archeon;
+
+    fn test_target(os: &str, arch: &str) -> PythonTarget {
+        PythonTarget {
+            version: Some((3, 11)),
+            os: os.to_owned(),
+            arch: arch.to_owned(),
+            musl: false,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn normalize_name() {
+        assert_eq!(super::normalize_name("Test_Package"), "test-package");
+        assert_eq!(super::normalize_name("zope.interface"), "zope-interface");
+        assert_eq!(super::normalize_name("a__-b"), "a-b");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn wheel_score() {
+        let test_linux = test_target("linux", "x86_64");
+        let test_cases = [
+            ("test-1.0-py3-none-any.whl", Some(1)),
+            ("test-1.0-py2.py3-none-any.whl", Some(1)),
+            ("test-1.0-py2-none-any.whl", None),
+            (
+                "test-1.0-cp311-cp311-manylinux_2_17_x86_64.manylinux2014_x86_64.whl",
+                Some(2),
+            ),
+            ("test-1.0-cp310-cp310-manylinux_2_17_x86_64.whl", None),
+            ("test-1.0-cp38-abi3-manylinux_2_17_x86_64.whl", Some(2)),
+            ("test-1.0-cp312-abi3-manylinux_2_17_x86_64.whl", None),
+            ("test-1.0-cp311-cp311-manylinux_2_17_aarch64.whl", None),
+            ("test-1.0-cp311-cp311-musllinux_1_1_x86_64.whl", None),
+            ("test-1.0-1build-py3-none-any.whl", Some(1)),
+            ("test-1.0.tar.gz", None),
+        ];
+        for (test_filename, test_expected) in test_cases {
+            assert_eq!(
+                test_linux.wheel_score(test_filename),
+                test_expected,
+                "{}",
+                test_filename,
+            );
+        }
+        let test_macos = test_target("macos", "aarch64");
+        assert_eq!(
+            test_macos.wheel_score("test-1.0-cp311-cp311-macosx_11_0_arm64.whl"),
+            Some(2),
+        );
+        assert_eq!(
+            test_macos.wheel_score("test-1.0-cp311-cp311-macosx_10_9_universal2.whl"),
+            Some(2),
+        );
+        let test_windows = test_target("windows", "x86_64");
+        assert_eq!(
+            test_windows.wheel_score("test-1.0-cp311-cp311-win_amd64.whl"),
+            Some(2),
+        );
+        let test_unknown = PythonTarget {
+            version: None,
+            ..test_linux
+        };
+        assert_eq!(
+            test_unknown.wheel_score("test-1.0-cp311-cp311-manylinux_2_17_x86_64.whl"),
+            None,
+        );
+        assert_eq!(
+            test_unknown.wheel_score("test-1.0-py3-none-any.whl"),
+            Some(1)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn transfer() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_wheel = b"test_pypi_wheel";
+        let test_release = PyPiRelease {
+            info: PyPiInfo {
+                name: String::from("Test_Package"),
+                version: String::from("2.1.0"),
+                requires_python: None,
+            },
+            urls: vec![
+                PyPiFile {
+                    filename: String::from("test_package-2.1.0-cp311-cp311-win_amd64.whl"),
+                    url: format!(
+                        "{}/files/test_package-2.1.0-cp311-cp311-win_amd64.whl",
+                        test_server.url()
+                    ),
+                    packagetype: String::from("bdist_wheel"),
+                    ..PyPiFile::default()
+                },
+                PyPiFile {
+                    filename: String::from("test_package-2.1.0-py3-none-any.whl"),
+                    url: format!(
+                        "{}/files/test_package-2.1.0-py3-none-any.whl",
+                        test_server.url()
+                    ),
+                    packagetype: String::from("bdist_wheel"),
+                    digests: BTreeMap::from([(
+                        String::from("sha256"),
+                        checksum::sha256_bytes(test_wheel),
+                    )]),
+                    ..PyPiFile::default()
+                },
+                PyPiFile {
+                    filename: String::from("test_package-2.1.0.tar.gz"),
+                    url: format!("{}/files/test_package-2.1.0.tar.gz", test_server.url()),
+                    packagetype: String::from("sdist"),
+                    ..PyPiFile::default()
+                },
+            ],
+        };
+        let mock_release = test_server
+            .mock("GET", "/pypi/test-package/2.1.0/json")
+            .with_status(200)
+            .with_body(serde_json::to_vec(&test_release)?)
+            .create_async()
+            .await;
+        let mock_missing = test_server
+            .mock("GET", "/pypi/missing/json")
+            .with_status(404)
+            .create_async()
+            .await;
+        let test_path = "/files/test_package-2.1.0-py3-none-any.whl";
+        let mock_get = test_server
+            .mock("GET", test_path)
+            .with_status(200)
+            .with_body(test_wheel)
+            .create_async()
+            .await;
+        let mut test_index = PyPiIndex::init(&test_server.url(), Transfer::init_client())?;
+        test_index.target = test_target("linux", "x86_64");
+        let test_file = test_index.resolve("Test_Package", Some("2.1.0")).await?;
+        assert_eq!(test_file.filename, "test_package-2.1.0-py3-none-any.whl");
+        assert_eq!(
+            test_index
+                .resolve("missing", None)
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NotFound,
+        );
+        let test_transfer = test_index.transfer(&test_file).await?;
+        assert_eq!(test_transfer.installer.backend, Backend::Pip);
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.launch(&test_transfer).await?;
+        assert_eq!(tokio::fs::read(&test_transfer.file_path).await?, test_wheel);
+        mock_release.assert_async().await;
+        mock_missing.assert_async().await;
+        mock_get.assert_async().await;
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        let test_sdist_only = PyPiRelease {
+            urls: test_release.urls[2..].to_vec(),
+            ..test_release.to_owned()
+        };
+        assert_eq!(test_index.select(&test_sdist_only)?.packagetype, "sdist");
+        let test_empty = PyPiRelease {
+            urls: Vec::new(),
+            ..test_release
+        };
+        assert!(test_index.select(&test_empty).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,506 @@
+use hyper::header::ACCEPT;
+use hyper::{Body, Method, Request, StatusCode, Uri};
+
+use roxmltree::{Document, Node};
+
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Ordering;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::auth::Authenticate;
+use crate::checksum;
+use crate::installer::Backend;
+use crate::source::{decompress, launch_request};
+use crate::transfer::{HttpClient, Transfer};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoData {
+    pub data_type: String,
+    pub location: String,
+    pub checksum: String,
+    pub checksum_type: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoMd {
+    pub revision: Option<String>,
+    pub data: Vec<RepoData>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpmPackage {
+    pub name: String,
+    pub arch: String,
+    pub epoch: u64,
+    pub version: String,
+    pub release: String,
+    pub checksum: String,
+    pub checksum_type: String,
+    pub location: String,
+    pub base: Option<String>,
+    pub size: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct YumIndex {
+    pub packages: Vec<RpmPackage>,
+}
+
+#[derive(Clone)]
+pub struct YumRepository {
+    pub url: Uri,
+    pub architecture: String,
+    pub client: HttpClient,
+    pub credentials: Option<Arc<dyn Authenticate>>,
+}
+
+pub fn rpm_architecture() -> String {
+    match std::env::consts::ARCH {
+        "x86" => "i686",
+        "powerpc64" => "ppc64le",
+        "arm" => "armv7hl",
+        architecture => architecture,
+    }
+    .to_owned()
+}
+
+pub fn compare_rpm_versions(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    let separator = |byte: &u8| !byte.is_ascii_alphanumeric() && *byte != b'~' && *byte != b'^';
+
+    loop {
+        while a.first().is_some_and(separator) {
+            a = &a[1..];
+        }
+
+        while b.first().is_some_and(separator) {
+            b = &b[1..];
+        }
+
+        match (a.first(), b.first()) {
+            (Some(b'~'), Some(b'~')) | (Some(b'^'), Some(b'^')) => {
+                (a, b) = (&a[1..], &b[1..]);
+
+                continue;
+            }
+            (Some(b'~'), _) => return Ordering::Less,
+            (_, Some(b'~')) => return Ordering::Greater,
+            (None, Some(b'^')) => return Ordering::Less,
+            (Some(b'^'), None) => return Ordering::Greater,
+            (Some(b'^'), _) => return Ordering::Less,
+            (_, Some(b'^')) => return Ordering::Greater,
+            (None, _) | (_, None) => break,
+            _ => {}
+        }
+
+        let numeric = a[0].is_ascii_digit();
+        let segment = |bytes: &[u8]| match numeric {
+            true => bytes
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count(),
+            false => bytes
+                .iter()
+                .take_while(|byte| byte.is_ascii_alphabetic())
+                .count(),
+        };
+        let (a_length, b_length) = (segment(a), segment(b));
+
+        if b_length == 0 {
+            return match numeric {
+                true => Ordering::Greater,
+                false => Ordering::Less,
+            };
+        }
+
+        let ordering = match numeric {
+            true => {
+                let number = |digits: &[u8]| {
+                    let zeros = digits.iter().take_while(|byte| **byte == b'0').count();
+
+                    (digits.len() - zeros, digits[zeros..].to_vec())
+                };
+
+                number(&a[..a_length]).cmp(&number(&b[..b_length]))
+            }
+            false => a[..a_length].cmp(&b[..b_length]),
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        (a, b) = (&a[a_length..], &b[b_length..]);
+    }
+
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, _) => Ordering::Greater,
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn parse_document(contents: &str) -> Result<Document<'_>, Error> {
+    Document::parse(contents).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+}
+
+impl RepoMd {
+    pub fn parse(contents: &str) -> Result<RepoMd, Error> {
+        let document = parse_document(contents)?;
+        let root = document.root_element();
+        let data = root
+            .children()
+            .filter(|node| node.is_element() && node.tag_name().name() == "data")
+            .filter_map(|node| {
+                let checksum = child(node, "checksum")?;
+
+                Some(RepoData {
+                    data_type: node.attribute("type")?.to_owned(),
+                    location: child(node, "location")?.attribute("href")?.to_owned(),
+                    checksum: checksum.text().unwrap_or_default().trim().to_owned(),
+                    checksum_type: checksum.attribute("type").unwrap_or_default().to_owned(),
+                })
+            })
+            .collect();
+
+        Ok(RepoMd {
+            revision: child(root, "revision")
+                .and_then(|node| node.text())
+                .map(|revision| revision.trim().to_owned()),
+            data,
+        })
+    }
+
+    pub fn primary(&self) -> Result<&RepoData, Error> {
+        self.data
+            .iter()
+            .find(|data| data.data_type == "primary")
+            .ok_or_else(|| {
+                let error = "No primary metadata listed in repomd.xml!";
+
+                Error::new(ErrorKind::NotFound, error)
+            })
+    }
+}
+
+impl RpmPackage {
+    pub fn evr(&self) -> String {
+        match self.epoch {
+            0 => format!("{}-{}", self.version, self.release),
+            epoch => format!("{}:{}-{}", epoch, self.version, self.release),
+        }
+    }
+
+    pub fn compare(&self, other: &RpmPackage) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| compare_rpm_versions(&self.version, &other.version))
+            .then_with(|| compare_rpm_versions(&self.release, &other.release))
+    }
+
+    fn matches(&self, version: &str) -> bool {
+        self.version == version
+            || self.evr() == version
+            || format!("{}-{}", self.version, self.release) == version
+    }
+}
+
+impl YumIndex {
+    pub fn parse(contents: &str) -> Result<YumIndex, Error> {
+        let document = parse_document(contents)?;
+        let packages = document
+            .root_element()
+            .children()
+            .filter(|node| node.is_element() && node.tag_name().name() == "package")
+            .filter_map(|node| {
+                let version = child(node, "version")?;
+                let checksum = child(node, "checksum")?;
+                let location = child(node, "location")?;
+
+                Some(RpmPackage {
+                    name: child(node, "name")?.text()?.trim().to_owned(),
+                    arch: child(node, "arch")?.text()?.trim().to_owned(),
+                    epoch: version
+                        .attribute("epoch")
+                        .and_then(|epoch| epoch.parse().ok())
+                        .unwrap_or_default(),
+                    version: version.attribute("ver")?.to_owned(),
+                    release: version.attribute("rel").unwrap_or_default().to_owned(),
+                    checksum: checksum.text().unwrap_or_default().trim().to_owned(),
+                    checksum_type: checksum.attribute("type").unwrap_or_default().to_owned(),
+                    location: location.attribute("href")?.to_owned(),
+                    base: location
+                        .attribute(("http://www.w3.org/XML/1998/namespace", "base"))
+                        .map(str::to_owned),
+                    size: child(node, "size")
+                        .and_then(|size| size.attribute("package"))
+                        .and_then(|size| size.parse().ok()),
+                })
+            })
+            .collect();
+
+        Ok(YumIndex { packages })
+    }
+
+    pub fn package(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        architecture: &str,
+    ) -> Option<&RpmPackage> {
+        self.packages
+            .iter()
+            .filter(|package| package.name == name)
+            .filter(|package| package.arch == architecture || package.arch == "noarch")
+            .filter(|package| version.is_none_or(|version| package.matches(version)))
+            .max_by(|a, b| a.compare(b))
+    }
+}
+
+impl YumRepository {
+    pub fn init(url: &str, client: HttpClient) -> Result<YumRepository, Error> {
+        let url = format!("{}/", url.trim_end_matches('/'));
+        let url = Uri::from_str(&url).map_err(|error| {
+            let error = format!("Invalid yum repository '{}': {}!", url, error);
+
+            Error::new(ErrorKind::InvalidInput, error)
+        })?;
+
+        Ok(YumRepository {
+            url,
+            architecture: rpm_architecture(),
+            client,
+            credentials: None,
+        })
+    }
+
+    pub async fn repomd(&self) -> Result<RepoMd, Error> {
+        let body = self.launch_get("repodata/repomd.xml").await?;
+
+        RepoMd::parse(&String::from_utf8_lossy(&body))
+    }
+
+    pub async fn index(&self) -> Result<YumIndex, Error> {
+        let repomd = self.repomd().await?;
+        let primary = repomd.primary()?;
+        let body = self.launch_get(&primary.location).await?;
+
+        if primary.checksum_type == "sha256" && !checksum::verify_bytes(&body, &primary.checksum) {
+            let error = format!(
+                "Checksum mismatch for {}: expected {}, got {}!",
+                primary.location,
+                primary.checksum,
+                checksum::sha256_bytes(&body),
+            );
+
+            return Err(Error::new(ErrorKind::InvalidData, error));
+        }
+
+        let contents = decompress(&primary.location, &body)?;
+
+        YumIndex::parse(&String::from_utf8_lossy(&contents))
+    }
+
+    pub async fn resolve(&self, name: &str, version: Option<&str>) -> Result<RpmPackage, Error> {
+        let index = self.index().await?;
+
+        index
+            .package(name, version, &self.architecture)
+            .cloned()
+            .ok_or_else(|| {
+                let error = format!(
+                    "No version {} of package '{}' found for {}!",
+                    version.unwrap_or("latest"),
+                    name,
+                    self.architecture,
+                );
+
+                Error::new(ErrorKind::NotFound, error)
+            })
+    }
+
+    pub async fn transfer(
+        &self,
+        package: &RpmPackage,
+    ) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        let base = match &package.base {
+            Some(base) => format!("{}/", base.trim_end_matches('/')),
+            None => self.url.to_string(),
+        };
+        let url = format!("{}{}", base, package.location.trim_start_matches('/'));
+        let mut transfer = Transfer::init_with_client(&url, self.client.to_owned()).await?;
+
+        if package.checksum_type == "sha256" {
+            transfer.checksum = Some(package.checksum.to_owned());
+        }
+
+        transfer.installer.backend = Backend::Rpm;
+        transfer.auth = self.credentials.to_owned();
+
+        Ok(transfer)
+    }
+
+    async fn launch_get(&self, path: &str) -> Result<hyper::body::Bytes, Error> {
+        let url = format!("{}{}", self.url, path.trim_start_matches('/'));
+        let mut request = Request::builder()
+            .method(Method::GET)
+            .uri(&url)
+            .header(ACCEPT, "*/*")
+            .body(Body::empty())
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, error))?;
+
+        if let Some(credentials) = &self.credentials {
+            credentials
+                .authenticate(&mut request)
+                .await
+                .map_err(Error::other)?;
+        }
+
+        let (status, _, body) = launch_request(&self.client, request).await?;
+
+        match status {
+            status if status.is_success() => Ok(body),
+            StatusCode::NOT_FOUND | StatusCode::GONE => {
+                let error = format!("No {} found in {}!", path, self.url);
+
+                Err(Error::new(ErrorKind::NotFound, error))
+            }
+            status => {
+                let error = format!("Request for {} failed with {}!", url, status);
+
+                Err(Error::other(error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Archeon;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn compare_rpm_versions() {
+        let test_cases = [
+            ("1.0", "1.0", Ordering::Equal),
+            ("1.0", "1.0.1", Ordering::Less),
+            ("2.10", "2.9", Ordering::Greater),
+            ("1.0a", "1.0", Ordering::Greater),
+            ("1.0~rc1", "1.0", Ordering::Less),
+            ("1.0^git1", "1.0", Ordering::Greater),
+            ("1.0^git1", "1.0.1", Ordering::Less),
+            ("5.1", "5a", Ordering::Greater),
+            ("1.01", "1.1", Ordering::Equal),
+        ];
+        for (a, b, ordering) in test_cases {
+            assert_eq!(super::compare_rpm_versions(a, b), ordering, "{} {}", a, b);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn transfer() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_rpm = b"test_rpm_archive";
+        let test_primary = format!(
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<metadata xmlns=\"http://linux.duke.edu/metadata/common\" packages=\"3\">\n",
+                "<package type=\"rpm\"><name>test-package</name><arch>x86_64</arch>",
+                "<version epoch=\"0\" ver=\"1.10\" rel=\"1.fc40\"/>",
+                "<checksum type=\"sha256\" pkgid=\"YES\">{}</checksum>",
+                "<location href=\"Packages/t/test-package-1.10-1.fc40.x86_64.rpm\"/>",
+                "<size package=\"16\"/></package>\n",
+                "<package type=\"rpm\"><name>test-package</name><arch>x86_64</arch>",
+                "<version epoch=\"0\" ver=\"1.9\" rel=\"3.fc40\"/>",
+                "<checksum type=\"sha256\" pkgid=\"YES\">00</checksum>",
+                "<location href=\"Packages/t/test-package-1.9-3.fc40.x86_64.rpm\"/></package>\n",
+                "<package type=\"rpm\"><name>test-package</name><arch>aarch64</arch>",
+                "<version epoch=\"1\" ver=\"0.1\" rel=\"1\"/>",
+                "<checksum type=\"sha256\" pkgid=\"YES\">00</checksum>",
+                "<location href=\"Packages/t/test-package-0.1-1.aarch64.rpm\"/></package>\n",
+                "</metadata>\n",
+            ),
+            checksum::sha256_bytes(test_rpm),
+        );
+        let mut test_encoder = GzEncoder::new(Vec::new(), Compression::default());
+        test_encoder.write_all(test_primary.as_bytes())?;
+        let test_primary = test_encoder.finish()?;
+        let test_repomd = format!(
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<repomd xmlns=\"http://linux.duke.edu/metadata/repo\">\n",
+                "<revision>1700000000</revision>\n",
+                "<data type=\"primary\"><checksum type=\"sha256\">{}</checksum>",
+                "<location href=\"repodata/test-primary.xml.gz\"/></data>\n",
+                "<data type=\"filelists\"><checksum type=\"sha256\">00</checksum>",
+                "<location href=\"repodata/test-filelists.xml.gz\"/></data>\n",
+                "</repomd>\n",
+            ),
+            checksum::sha256_bytes(&test_primary),
+        );
+        let mock_repomd = test_server
+            .mock("GET", "/fedora/repodata/repomd.xml")
+            .with_status(200)
+            .with_body(test_repomd)
+            .expect(2)
+            .create_async()
+            .await;
+        let mock_primary = test_server
+            .mock("GET", "/fedora/repodata/test-primary.xml.gz")
+            .with_status(200)
+            .with_body(test_primary)
+            .expect(2)
+            .create_async()
+            .await;
+        let mock_get = test_server
+            .mock(
+                "GET",
+                "/fedora/Packages/t/test-package-1.10-1.fc40.x86_64.rpm",
+            )
+            .with_status(200)
+            .with_body(test_rpm)
+            .create_async()
+            .await;
+        let mut test_repository = YumRepository::init(
+            &format!("{}/fedora", test_server.url()),
+            Transfer::init_client(),
+        )?;
+        test_repository.architecture = String::from("x86_64");
+        let test_package = test_repository.resolve("test-package", None).await?;
+        assert_eq!(test_package.evr(), "1.10-1.fc40");
+        assert_eq!(test_package.size, Some(16));
+        assert_eq!(
+            test_repository
+                .resolve("test-package", Some("2.0"))
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NotFound,
+        );
+        let test_transfer = test_repository.transfer(&test_package).await?;
+        assert!(test_transfer
+            .file_path
+            .ends_with("test-package-1.10-1.fc40.x86_64.rpm"));
+        assert_eq!(test_transfer.installer.backend, Backend::Rpm);
+        let mut test_archeon = Archeon::ignite().await;
+        test_archeon.launch(&test_transfer).await?;
+        assert_eq!(tokio::fs::read(&test_transfer.file_path).await?, test_rpm);
+        mock_repomd.assert_async().await;
+        mock_primary.assert_async().await;
+        mock_get.assert_async().await;
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use crate::checksum::{self, Algorithm, Digest};
+use crate::error::ArcheonError;
+use crate::progress::BatchProgress;
+
+pub struct StreamVerifier {
+    url: String,
+    checksum: Option<String>,
+    hasher: Option<Digest>,
+    progress: Option<BatchProgress>,
+    received: u64,
+}
+
+impl StreamVerifier {
+    pub fn init(
+        url: &str,
+        checksum: Option<&str>,
+        progress: Option<BatchProgress>,
+    ) -> StreamVerifier {
+        let hasher = checksum.map(|checksum| Digest::new(Algorithm::resolve(checksum, None)));
+
+        StreamVerifier {
+            url: url.to_owned(),
+            checksum: checksum.map(str::to_owned),
+            hasher,
+            progress,
+            received: 0,
+        }
+    }
+
+    pub fn start(&self, size: Option<u64>) {
+        if let Some(progress) = &self.progress {
+            progress.start(&self.url, size);
+        }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(chunk);
+        }
+
+        self.received += chunk.len() as u64;
+
+        if let Some(progress) = &self.progress {
+            progress.advance(&self.url, chunk.len() as u64);
+        }
+    }
+
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    pub fn finish(self) -> Result<u64, ArcheonError> {
+        let result = match (self.checksum, self.hasher) {
+            (Some(expected), Some(hasher)) => {
+                let actual = hasher.finalize();
+
+                match checksum::matches(&expected, &actual) {
+                    true => Ok(self.received),
+                    false => Err(ArcheonError::Integrity {
+                        source: self.url.to_owned(),
+                        expected,
+                        actual,
+                    }),
+                }
+            }
+            _ => Ok(self.received),
+        };
+
+        if let Some(progress) = &self.progress {
+            let error = result.as_ref().err().map(ToString::to_string);
+
+            progress.finish(&self.url, error);
+        }
+
+        result
+    }
+
+    pub fn fail(self, error: ArcheonError) -> ArcheonError {
+        if let Some(progress) = &self.progress {
+            progress.finish(&self.url, Some(error.to_string()));
+        }
+
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify() -> Result<(), ArcheonError> {
+        let test_progress = BatchProgress::default();
+        let test_checksum = checksum::sha256_bytes(b"test_body");
+
+        test_progress.begin(1);
+
+        let mut test_stream = StreamVerifier::init(
+            "http://test-stream/test.deb",
+            Some(&test_checksum),
+            Some(test_progress.to_owned()),
+        );
+
+        test_stream.start(Some(9));
+        test_stream.update(b"test_");
+        test_stream.update(b"body");
+
+        assert_eq!(test_stream.received(), 9);
+        assert_eq!(test_stream.finish()?, 9);
+
+        let test_aggregate = test_progress.aggregate();
+
+        assert_eq!(test_aggregate.total_bytes, 9);
+        assert_eq!(test_aggregate.downloaded_bytes, 9);
+        assert_eq!(test_aggregate.files_completed, 1);
+        assert_eq!(test_aggregate.files_failed, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mismatch() -> Result<(), ArcheonError> {
+        let test_progress = BatchProgress::default();
+        let test_checksum = format!("md5:{}", checksum::to_hex(&[0; 16]));
+
+        test_progress.begin(1);
+
+        let mut test_stream = StreamVerifier::init(
+            "http://test-stream/test.deb",
+            Some(&test_checksum),
+            Some(test_progress.to_owned()),
+        );
+
+        test_stream.start(None);
+        test_stream.update(b"test_body");
+
+        let test_error = test_stream.finish().unwrap_err();
+
+        assert_eq!(test_error.kind(), ErrorKind::Integrity);
+        assert!(test_error
+            .to_string()
+            .contains("http://test-stream/test.deb"));
+        assert_eq!(test_progress.aggregate().files_failed, 1);
+
+        let test_stream = StreamVerifier::init("http://test-stream/unverified.deb", None, None);
+
+        assert_eq!(test_stream.finish()?, 0);
+
+        Ok(())
+    }
+}
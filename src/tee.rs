@@ -0,0 +1,146 @@
+use futures_util::future::try_join_all;
+
+use hyper::body::Bytes;
+
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, Mutex};
+
+pub type SinkFuture<'a> = Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
+
+pub trait Sink: Send + Sync {
+    fn start(&self, _offset: u64) -> SinkFuture<'_> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn write<'a>(&'a self, chunk: &'a Bytes) -> SinkFuture<'a>;
+
+    fn finish(&self) -> SinkFuture<'_> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Tee {
+    sinks: Vec<Arc<dyn Sink>>,
+}
+
+pub struct WriterSink<W> {
+    writer: Mutex<W>,
+}
+
+impl Sink for mpsc::Sender<Bytes> {
+    fn write<'a>(&'a self, chunk: &'a Bytes) -> SinkFuture<'a> {
+        Box::pin(async move {
+            self.send(chunk.to_owned()).await.map_err(|_| {
+                let error = "Tee receiver was dropped!";
+
+                Error::new(ErrorKind::BrokenPipe, error)
+            })
+        })
+    }
+}
+
+impl<W> WriterSink<W> {
+    pub fn init(writer: W) -> WriterSink<W> {
+        WriterSink {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+}
+
+impl<W: AsyncWrite + Unpin + Send> Sink for WriterSink<W> {
+    fn start(&self, offset: u64) -> SinkFuture<'_> {
+        Box::pin(async move {
+            match offset {
+                0 => Ok(()),
+                _ => {
+                    let error = format!("Writer sink cannot resume at byte {}!", offset);
+
+                    Err(Error::new(ErrorKind::Unsupported, error))
+                }
+            }
+        })
+    }
+
+    fn write<'a>(&'a self, chunk: &'a Bytes) -> SinkFuture<'a> {
+        Box::pin(async move { self.writer.lock().await.write_all(chunk).await })
+    }
+
+    fn finish(&self) -> SinkFuture<'_> {
+        Box::pin(async move { self.writer.lock().await.flush().await })
+    }
+}
+
+impl Tee {
+    pub fn push(&mut self, sink: Arc<dyn Sink>) {
+        self.sinks.push(sink);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    pub async fn start(&self, offset: u64) -> Result<(), Error> {
+        try_join_all(self.sinks.iter().map(|sink| sink.start(offset))).await?;
+
+        Ok(())
+    }
+
+    pub async fn write(&self, chunk: &Bytes) -> Result<(), Error> {
+        try_join_all(self.sinks.iter().map(|sink| sink.write(chunk))).await?;
+
+        Ok(())
+    }
+
+    pub async fn finish(&self) -> Result<(), Error> {
+        try_join_all(self.sinks.iter().map(|sink| sink.finish())).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn write() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (test_sender, mut test_receiver) = mpsc::channel::<Bytes>(4);
+        let test_writer = Arc::new(WriterSink::init(Vec::<u8>::new()));
+        let mut test_tee = Tee::default();
+        assert!(test_tee.is_empty());
+        test_tee.push(Arc::new(test_sender));
+        test_tee.push(test_writer.to_owned());
+        assert!(!test_tee.is_empty());
+        test_tee.start(0).await?;
+        test_tee.write(&Bytes::from("test_")).await?;
+        test_tee.write(&Bytes::from("body")).await?;
+        test_tee.finish().await?;
+        assert_eq!(test_receiver.recv().await, Some(Bytes::from("test_")));
+        assert_eq!(test_receiver.recv().await, Some(Bytes::from("body")));
+        assert_eq!(test_writer.writer.lock().await.as_slice(), b"test_body");
+        assert_eq!(
+            test_tee.start(5).await.unwrap_err().kind(),
+            ErrorKind::Unsupported,
+        );
+        drop(test_receiver);
+        assert_eq!(
+            test_tee
+                .write(&Bytes::from("test_"))
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::BrokenPipe,
+        );
+        Ok(())
+    }
+}
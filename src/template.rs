@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+
+pub fn expand(template: &str, variables: &BTreeMap<String, String>) -> Result<String, Error> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut characters = template.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '{' if characters.peek() == Some(&'{') => {
+                characters.next();
+                expanded.push('{');
+            }
+            '}' if characters.peek() == Some(&'}') => {
+                characters.next();
+                expanded.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+
+                loop {
+                    match characters.next() {
+                        Some('}') => break,
+                        Some(character) if is_name(character) => name.push(character),
+                        Some(character) => {
+                            let error = format!(
+                                "Invalid character '{}' in template variable of '{}'!",
+                                character, template,
+                            );
+
+                            return Err(Error::new(ErrorKind::InvalidInput, error));
+                        }
+                        None => {
+                            let error =
+                                format!("Unterminated template variable in '{}'!", template);
+
+                            return Err(Error::new(ErrorKind::InvalidInput, error));
+                        }
+                    }
+                }
+
+                match variables.get(&name) {
+                    Some(value) if !name.is_empty() => expanded.push_str(value),
+                    _ => {
+                        let error =
+                            format!("Unknown template variable '{}' in '{}'!", name, template);
+
+                        return Err(Error::new(ErrorKind::NotFound, error));
+                    }
+                }
+            }
+            '}' => {
+                let error = format!("Unmatched '}}' in template '{}'!", template);
+
+                return Err(Error::new(ErrorKind::InvalidInput, error));
+            }
+            character => expanded.push(character),
+        }
+    }
+
+    Ok(expanded)
+}
+
+pub fn parse_variable(input: &str) -> Result<(String, String), Error> {
+    match input.split_once('=') {
+        Some((name, value)) if !name.is_empty() && name.chars().all(is_name) => {
+            Ok((name.to_owned(), value.to_owned()))
+        }
+        _ => {
+            let error = format!(
+                "Invalid template variable '{}', expected name=value!",
+                input
+            );
+
+            Err(Error::new(ErrorKind::InvalidInput, error))
+        }
+    }
+}
+
+fn is_name(character: char) -> bool {
+    character.is_ascii_alphanumeric() || character == '_' || character == '-'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn expand() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_variables = BTreeMap::from([
+            (String::from("version"), String::from("1.2.3")),
+            (String::from("target"), String::from("x86_64-linux")),
+        ]);
+        assert_eq!(
+            super::expand(
+                "https://host/{version}/{target}/app-{version}.tar.gz",
+                &test_variables,
+            )?,
+            "https://host/1.2.3/x86_64-linux/app-1.2.3.tar.gz",
+        );
+        assert_eq!(
+            super::expand("https://host/{{literal}}", &test_variables)?,
+            "https://host/{literal}",
+        );
+        assert_eq!(
+            super::expand("https://host/plain", &BTreeMap::new())?,
+            "https://host/plain",
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn expand_invalid() {
+        let test_variables = BTreeMap::from([(String::from("version"), String::from("1.2.3"))]);
+        let test_error = super::expand("https://host/{arch}", &test_variables).unwrap_err();
+        assert_eq!(test_error.kind(), ErrorKind::NotFound);
+        assert!(test_error.to_string().contains("'arch'"));
+        for test_template in [
+            "https://host/{version",
+            "https://host/{}",
+            "https://host/{ver sion}",
+            "https://host/version}",
+        ] {
+            assert!(super::expand(test_template, &test_variables).is_err());
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_variable() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        assert_eq!(
+            super::parse_variable("version=1.2.3")?,
+            (String::from("version"), String::from("1.2.3")),
+        );
+        assert_eq!(
+            super::parse_variable("query=a=b")?,
+            (String::from("query"), String::from("a=b")),
+        );
+        assert!(super::parse_variable("=1.2.3").is_err());
+        assert!(super::parse_variable("version").is_err());
+        assert!(super::parse_variable("bad name=1").is_err());
+        Ok(())
+    }
+}
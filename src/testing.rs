@@ -0,0 +1,340 @@
+use hyper::body::Bytes;
+use hyper::header::{ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, ETAG, RANGE};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use crate::checksum::sha256_bytes;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MockArtifact {
+    pub body: Bytes,
+    pub latency: Duration,
+    pub ranges: bool,
+    pub failures: usize,
+    pub drop_after: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct MockServer {
+    address: SocketAddr,
+    artifacts: Arc<Mutex<HashMap<String, MockState>>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    artifact: MockArtifact,
+    failures: usize,
+    hits: usize,
+}
+
+impl MockArtifact {
+    pub fn init(body: impl Into<Bytes>) -> MockArtifact {
+        MockArtifact {
+            body: body.into(),
+            ranges: true,
+            ..MockArtifact::default()
+        }
+    }
+
+    pub fn sized(size: usize) -> MockArtifact {
+        let body: Vec<u8> = (0..size).map(|index| (index % 251) as u8).collect();
+
+        Self::init(body)
+    }
+}
+
+impl MockServer {
+    pub async fn start() -> Result<MockServer, hyper::Error> {
+        let artifacts: Arc<Mutex<HashMap<String, MockState>>> = Arc::default();
+        let shared = artifacts.to_owned();
+        let make_service = make_service_fn(move |_| {
+            let artifacts = shared.to_owned();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    launch_response(artifacts.to_owned(), request)
+                }))
+            }
+        });
+        let server = Server::try_bind(&([127, 0, 0, 1], 0).into())?.serve(make_service);
+        let address = server.local_addr();
+        let (shutdown, receiver) = oneshot::channel();
+
+        tokio::spawn(server.with_graceful_shutdown(async {
+            let _ = receiver.await;
+        }));
+
+        Ok(MockServer {
+            address,
+            artifacts,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}/{}", self.address, path.trim_start_matches('/'))
+    }
+
+    pub fn add(&self, path: &str, artifact: MockArtifact) -> String {
+        let path = format!("/{}", path.trim_start_matches('/'));
+        let state = MockState {
+            failures: artifact.failures,
+            artifact,
+            hits: 0,
+        };
+
+        lock(&self.artifacts).insert(path.to_owned(), state);
+
+        self.url(&path)
+    }
+
+    pub fn hits(&self, path: &str) -> usize {
+        let path = format!("/{}", path.trim_start_matches('/'));
+
+        lock(&self.artifacts)
+            .get(&path)
+            .map(|state| state.hits)
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+fn lock(
+    artifacts: &Mutex<HashMap<String, MockState>>,
+) -> std::sync::MutexGuard<'_, HashMap<String, MockState>> {
+    artifacts
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+async fn launch_response(
+    artifacts: Arc<Mutex<HashMap<String, MockState>>>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let artifact = {
+        let mut artifacts = lock(&artifacts);
+
+        match artifacts.get_mut(request.uri().path()) {
+            Some(state) => {
+                state.hits += 1;
+
+                if state.failures > 0 {
+                    state.failures -= 1;
+
+                    None
+                } else {
+                    Some(state.artifact.to_owned())
+                }
+            }
+            None => return Ok(launch_status(StatusCode::NOT_FOUND)),
+        }
+    };
+    let artifact = match artifact {
+        Some(artifact) => artifact,
+        None => return Ok(launch_status(StatusCode::SERVICE_UNAVAILABLE)),
+    };
+
+    if !artifact.latency.is_zero() {
+        tokio::time::sleep(artifact.latency).await;
+    }
+
+    let total = artifact.body.len();
+    let range = match artifact.ranges {
+        true => request
+            .headers()
+            .get(RANGE)
+            .and_then(|range| range.to_str().ok())
+            .map(|range| parse_range(range, total)),
+        false => None,
+    };
+    let (status, start, end) = match range {
+        Some(Some((start, end))) => (StatusCode::PARTIAL_CONTENT, start, end),
+        Some(None) => {
+            let mut response = launch_status(StatusCode::RANGE_NOT_SATISFIABLE);
+
+            if let Ok(value) = format!("bytes */{}", total).parse() {
+                response.headers_mut().insert(CONTENT_RANGE, value);
+            }
+
+            return Ok(response);
+        }
+        None => (StatusCode::OK, 0, total),
+    };
+    let body = artifact.body.slice(start..end);
+    let mut builder = Response::builder()
+        .status(status)
+        .header(CONTENT_LENGTH, body.len())
+        .header(ETAG, format!("\"{}\"", sha256_bytes(&artifact.body)));
+
+    if artifact.ranges {
+        builder = builder.header(ACCEPT_RANGES, "bytes");
+    }
+
+    if status == StatusCode::PARTIAL_CONTENT {
+        builder = builder.header(
+            CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end.saturating_sub(1), total),
+        );
+    }
+
+    let body = match (request.method(), artifact.drop_after) {
+        (&Method::HEAD, _) => Body::empty(),
+        (_, Some(drop_after)) if drop_after < body.len() => {
+            let (mut sender, channel) = Body::channel();
+            let partial = body.slice(..drop_after);
+
+            tokio::spawn(async move {
+                if sender.send_data(partial).await.is_ok() {
+                    tokio::task::yield_now().await;
+                }
+
+                sender.abort();
+            });
+
+            channel
+        }
+        _ => Body::from(body),
+    };
+
+    Ok(builder
+        .body(body)
+        .unwrap_or_else(|_| launch_status(StatusCode::INTERNAL_SERVER_ERROR)))
+}
+
+fn launch_status(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+
+    *response.status_mut() = status;
+
+    response
+}
+
+fn parse_range(range: &str, total: usize) -> Option<(usize, usize)> {
+    let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+    let start: usize = start.trim().parse().ok()?;
+    let end = match end.trim() {
+        "" => total,
+        end => end.parse::<usize>().ok()?.saturating_add(1).min(total),
+    };
+
+    match start < end {
+        true => Some((start, end)),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::FixedRetry;
+    use crate::transfer::Transfer;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sized() {
+        let test_artifact = MockArtifact::sized(300);
+        assert_eq!(test_artifact.body.len(), 300);
+        assert_eq!(test_artifact.body[251], 0);
+        assert!(test_artifact.ranges);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_range() {
+        assert_eq!(super::parse_range("bytes=0-", 10), Some((0, 10)));
+        assert_eq!(super::parse_range("bytes=2-4", 10), Some((2, 5)));
+        assert_eq!(super::parse_range("bytes=5-99", 10), Some((5, 10)));
+        assert_eq!(super::parse_range("bytes=10-", 10), None);
+        assert_eq!(super::parse_range("items=0-", 10), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serve() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_server = MockServer::start().await?;
+        let test_url = test_server.add("test_serve.bin", MockArtifact::sized(1024));
+        let test_client = hyper::Client::new();
+        let test_response = test_client.get(test_url.parse()?).await?;
+        assert_eq!(test_response.status(), StatusCode::OK);
+        assert_eq!(hyper::body::to_bytes(test_response).await?.len(), 1024);
+        let test_request = Request::builder()
+            .uri(&test_url)
+            .header(RANGE, "bytes=1000-")
+            .body(Body::empty())?;
+        let test_response = test_client.request(test_request).await?;
+        assert_eq!(test_response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            test_response.headers()[CONTENT_RANGE],
+            "bytes 1000-1023/1024"
+        );
+        assert_eq!(hyper::body::to_bytes(test_response).await?.len(), 24);
+        let test_missing = test_client
+            .get(test_server.url("missing.bin").parse()?)
+            .await?;
+        assert_eq!(test_missing.status(), StatusCode::NOT_FOUND);
+        assert_eq!(test_server.hits("test_serve.bin"), 2);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn flaky() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_server = MockServer::start().await?;
+        let test_url = test_server.add(
+            "test_flaky.bin",
+            MockArtifact {
+                failures: 2,
+                latency: Duration::from_millis(10),
+                ..MockArtifact::sized(4096)
+            },
+        );
+        let mut test_transfer = Transfer::init(&test_url).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_flaky.bin");
+        test_transfer.retry = Arc::new(FixedRetry {
+            max_attempts: 3,
+            delay: Duration::from_millis(10),
+        });
+        test_transfer.launch().await?;
+        assert_eq!(tokio::fs::read(&test_transfer.file_path).await?.len(), 4096);
+        assert!(test_server.hits("test_flaky.bin") >= 3);
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn drop_after() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_server = MockServer::start().await?;
+        let test_artifact = MockArtifact {
+            drop_after: Some(3000),
+            ..MockArtifact::sized(4096)
+        };
+        let test_body = test_artifact.body.to_owned();
+        let test_url = test_server.add("test_drop_after.bin", test_artifact);
+        let mut test_transfer = Transfer::init(&test_url).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_drop_after.bin");
+        test_transfer.retry = Arc::new(FixedRetry {
+            max_attempts: 2,
+            delay: Duration::from_millis(10),
+        });
+        test_transfer.launch().await?;
+        assert_eq!(tokio::fs::read(&test_transfer.file_path).await?, test_body);
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+}
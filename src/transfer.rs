@@ -1,32 +1,247 @@
-use hyper::body::{to_bytes, Bytes};
-use hyper::client::connect::HttpConnector;
-use hyper::header::{HeaderValue, CONTENT_LENGTH};
-use hyper::{Body, Client, Request, Uri};
+use hyper::body::{Bytes, HttpBody};
+use hyper::client::connect::{Connect, HttpConnector};
+use hyper::header::{HeaderMap, HeaderValue, CONTENT_LENGTH, COOKIE, LOCATION, USER_AGENT};
+use hyper::header::{
+    ACCEPT_RANGES, CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE,
+    LAST_MODIFIED, RANGE,
+};
+use hyper::http::response::Parts;
+use hyper::{Body, Client, Method, Request, Response, StatusCode, Uri};
 use hyper_tls::HttpsConnector;
 
-use indicatif::ProgressBar;
+use futures_util::future::try_join_all;
 
+use std::collections::BTreeMap;
 use std::env::temp_dir;
+use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use tokio::fs::{create_dir_all, File};
-use tokio::io::AsyncWriteExt;
-use tokio::process::Command;
+use tokio::fs::{create_dir_all, metadata, remove_file, rename, File, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt, BufWriter};
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::time::{sleep, Instant};
+
+use crate::auth::Authenticate;
+use crate::cache::Retention;
+use crate::checksum::{self, Algorithm, Digest};
+use crate::circuit::CircuitBreaker;
+use crate::cookies::{parse_http_date, CookieJar};
+use crate::durability::{self, Durability};
+use crate::error::{ArcheonError, ErrorKind};
+use crate::har::{HarEntryId, HarRecorder, HarRequest};
+use crate::hook::PreRequest;
+use crate::http_cache::HttpCacheEntry;
+use crate::installer::Installer;
+use crate::limits::{BufferLimit, BufferPermit, HostLimiter, HostPermit};
+use crate::lock::FileLock;
+use crate::normalize::{decode_segment, normalize};
+use crate::overwrite::{self, OverwritePolicy};
+use crate::partial::{PartialCleanup, PartialGuard};
+use crate::presign::{RefreshUrl, MAX_REFRESHES};
+use crate::progress::BatchProgress;
+use crate::proxy::ProxyConnector;
+use crate::redirect::RedirectPolicy;
+use crate::repair::{self, PieceHashes, RepairSummary};
+use crate::resolver::Resolver;
+use crate::resume::{self, ResumeCheck, TRAILING_BLOCK};
+use crate::retry::{NoRetry, RetryPolicy};
+use crate::shutdown::Shutdown;
+use crate::tee::Tee;
+use crate::transport::Transport;
+use crate::wire::WireLog;
+
+pub const DEFAULT_USER_AGENT: &str = concat!("archeon/", env!("CARGO_PKG_VERSION"));
+pub const DEFAULT_WRITE_BUFFER: usize = 256 * 1024;
+
+pub type HttpClient = Arc<dyn Transport>;
+
+#[derive(Clone, Debug)]
+pub struct ClientOptions {
+    pub resolver: Resolver,
+    pub happy_eyeballs_timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    pub proxy: Option<Uri>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> ClientOptions {
+        ClientOptions {
+            resolver: Resolver::default(),
+            happy_eyeballs_timeout: Some(Duration::from_millis(250)),
+            connect_timeout: Some(Duration::from_secs(30)),
+            proxy: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Probe {
+    pub resumable: bool,
+    pub size: Option<u64>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_type: Option<String>,
+    pub final_uri: Uri,
+}
+
+impl Probe {
+    pub fn init(uri: &Uri, response: &Response<Body>) -> Probe {
+        let headers = response.headers();
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(str::to_owned)
+        };
+        let partial = response.status() == StatusCode::PARTIAL_CONTENT;
+        let size = match response.status() {
+            StatusCode::PARTIAL_CONTENT | StatusCode::RANGE_NOT_SATISFIABLE => {
+                header(CONTENT_RANGE)
+                    .and_then(|content_range| {
+                        content_range
+                            .rsplit_once('/')
+                            .map(|(_, total)| total.to_owned())
+                    })
+                    .and_then(|total| total.parse().ok())
+            }
+            status if status.is_success() => parse_content_length(headers),
+            _ => None,
+        };
+        let resumable = partial
+            || header(ACCEPT_RANGES).is_some_and(|accept_ranges| {
+                accept_ranges
+                    .split(',')
+                    .any(|unit| unit.trim().eq_ignore_ascii_case("bytes"))
+            });
+
+        Probe {
+            resumable,
+            size,
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            content_type: header(CONTENT_TYPE),
+            final_uri: response
+                .extensions()
+                .get::<Uri>()
+                .cloned()
+                .unwrap_or_else(|| uri.to_owned()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TransferOutcome {
+    #[default]
+    Downloaded,
+    UpToDate,
+    Skipped,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferSummary {
+    pub final_url: String,
+    pub file_path: PathBuf,
+    pub bytes: u64,
+    pub duration: Duration,
+    pub avg_speed: u64,
+    pub checksum: Option<String>,
+    pub resumed: u64,
+    pub outcome: TransferOutcome,
+}
+
+impl TransferSummary {
+    pub fn init(
+        final_uri: &Uri,
+        file_path: &Path,
+        bytes: u64,
+        duration: Duration,
+        checksum: Option<String>,
+        resumed: u64,
+    ) -> TransferSummary {
+        let avg_speed = match duration.as_secs_f64() > 0.0 {
+            true => (bytes as f64 / duration.as_secs_f64()) as u64,
+            false => 0,
+        };
+
+        TransferSummary {
+            final_url: final_uri.to_string(),
+            file_path: file_path.to_owned(),
+            bytes,
+            duration,
+            avg_speed,
+            checksum,
+            resumed,
+            outcome: TransferOutcome::Downloaded,
+        }
+    }
+
+    pub fn up_to_date(final_uri: &Uri, file_path: &Path, duration: Duration) -> TransferSummary {
+        TransferSummary {
+            outcome: TransferOutcome::UpToDate,
+            ..TransferSummary::init(final_uri, file_path, 0, duration, None, 0)
+        }
+    }
+
+    pub fn skipped(final_uri: &Uri, file_path: &Path) -> TransferSummary {
+        TransferSummary {
+            outcome: TransferOutcome::Skipped,
+            ..TransferSummary::init(final_uri, file_path, 0, Duration::ZERO, None, 0)
+        }
+    }
+}
 
 pub struct Transfer {
-    pub client: Client<HttpsConnector<HttpConnector>, Body>,
+    pub client: HttpClient,
     pub uri: Uri,
     pub filename: PathBuf,
     pub temp_dir: PathBuf,
     pub file_path: PathBuf,
+    pub installer: Installer,
+    pub retention: Retention,
+    pub post_install: Vec<String>,
+    pub tags: BTreeMap<String, String>,
+    pub checksum: Option<String>,
+    pub algorithm: Option<Algorithm>,
+    pub refresh: bool,
+    pub timestamping: bool,
+    pub overwrite: OverwritePolicy,
+    pub limiter: HostLimiter,
+    pub buffer: BufferLimit,
+    pub write_buffer: usize,
+    pub durability: Durability,
+    pub segments: usize,
+    pub resume_check: ResumeCheck,
+    pub partial: PartialCleanup,
+    pub user_agent: String,
+    pub cookies: Option<CookieJar>,
+    pub redirect: RedirectPolicy,
+    pub headers: HeaderMap,
+    pub retry: Arc<dyn RetryPolicy>,
+    pub circuit: CircuitBreaker,
+    pub progress: Option<BatchProgress>,
+    pub refresh_url: Option<Arc<dyn RefreshUrl>>,
+    pub auth: Option<Arc<dyn Authenticate>>,
+    pub pre_request: Option<Arc<dyn PreRequest>>,
+    pub tee: Tee,
+    pub shutdown: Shutdown,
+    pub wire: WireLog,
+    pub har: Option<HarRecorder>,
+    probed: Mutex<Option<(Uri, Probe)>>,
 }
 
 impl Transfer {
-    pub async fn init(uri: &str) -> Result<Transfer, Box<dyn std::error::Error>> {
-        let https = HttpsConnector::new();
-        let client = Client::builder().build(https);
-        let uri = Uri::from_str(uri).expect("Unable to parse URI!");
+    pub async fn init(uri: &str) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        Self::init_with_client(uri, Self::init_client()).await
+    }
+
+    pub async fn init_with_client(
+        uri: &str,
+        client: HttpClient,
+    ) -> Result<Transfer, Box<dyn std::error::Error + Send + Sync>> {
+        let uri = normalize(uri)?;
         let filename = Self::init_filename(&uri).await;
         let temp_dir = Self::init_temp_dir().await?;
         let file_path = Self::init_file_path(&temp_dir, &filename).await;
@@ -37,226 +252,2784 @@ impl Transfer {
             filename,
             temp_dir,
             file_path,
+            installer: Installer::default(),
+            retention: Retention::default(),
+            post_install: Vec::new(),
+            tags: BTreeMap::new(),
+            checksum: None,
+            algorithm: None,
+            refresh: false,
+            timestamping: false,
+            overwrite: OverwritePolicy::default(),
+            limiter: HostLimiter::default(),
+            buffer: BufferLimit::default(),
+            write_buffer: DEFAULT_WRITE_BUFFER,
+            durability: Durability::default(),
+            segments: 1,
+            resume_check: ResumeCheck::default(),
+            partial: PartialCleanup::default(),
+            user_agent: String::from(DEFAULT_USER_AGENT),
+            cookies: None,
+            redirect: RedirectPolicy::default(),
+            headers: HeaderMap::new(),
+            retry: Arc::new(NoRetry),
+            circuit: CircuitBreaker::default(),
+            progress: None,
+            refresh_url: None,
+            auth: None,
+            pre_request: None,
+            tee: Tee::default(),
+            shutdown: Shutdown::default(),
+            wire: WireLog::default(),
+            har: None,
+            probed: Mutex::new(None),
+        })
+    }
+
+    fn retarget(&self, file_path: PathBuf) -> Transfer {
+        Transfer {
+            client: self.client.to_owned(),
+            uri: self.uri.to_owned(),
+            filename: self.filename.to_owned(),
+            temp_dir: self.temp_dir.to_owned(),
+            file_path,
+            installer: self.installer.to_owned(),
+            retention: self.retention,
+            post_install: self.post_install.to_owned(),
+            tags: self.tags.to_owned(),
+            checksum: self.checksum.to_owned(),
+            algorithm: self.algorithm,
+            refresh: self.refresh,
+            timestamping: self.timestamping,
+            overwrite: self.overwrite,
+            limiter: self.limiter.to_owned(),
+            buffer: self.buffer.to_owned(),
+            write_buffer: self.write_buffer,
+            durability: self.durability,
+            segments: self.segments,
+            resume_check: self.resume_check,
+            partial: self.partial.to_owned(),
+            user_agent: self.user_agent.to_owned(),
+            cookies: self.cookies.to_owned(),
+            redirect: self.redirect,
+            headers: self.headers.to_owned(),
+            retry: self.retry.to_owned(),
+            circuit: self.circuit.to_owned(),
+            progress: self.progress.to_owned(),
+            refresh_url: self.refresh_url.to_owned(),
+            auth: self.auth.to_owned(),
+            pre_request: self.pre_request.to_owned(),
+            tee: self.tee.to_owned(),
+            shutdown: self.shutdown.to_owned(),
+            wire: self.wire.to_owned(),
+            har: self.har.to_owned(),
+            probed: Mutex::new(None),
+        }
+    }
+
+    pub fn init_client() -> HttpClient {
+        Self::init_client_with_resolver(Resolver::default())
+    }
+
+    pub fn init_client_with_resolver(resolver: Resolver) -> HttpClient {
+        Self::init_client_with_options(ClientOptions {
+            resolver,
+            ..ClientOptions::default()
         })
     }
 
+    #[cfg(feature = "reqwest")]
+    pub fn init_client_with_options(options: ClientOptions) -> HttpClient {
+        let transport = crate::transport::ReqwestTransport::init(&options)
+            .expect("Unable to build reqwest client!");
+
+        Arc::new(transport)
+    }
+
+    #[cfg(all(feature = "curl", not(feature = "reqwest")))]
+    pub fn init_client_with_options(options: ClientOptions) -> HttpClient {
+        Arc::new(crate::transport::CurlTransport::init(&options))
+    }
+
+    #[cfg(not(any(feature = "curl", feature = "reqwest")))]
+    pub fn init_client_with_options(options: ClientOptions) -> HttpClient {
+        Self::init_hyper_client(options)
+    }
+
+    pub fn init_client_with_connector<C>(connector: C) -> HttpClient
+    where
+        C: Connect + Clone + Send + Sync + 'static,
+    {
+        Arc::new(Client::builder().build::<_, Body>(connector))
+    }
+
+    pub fn init_hyper_client(options: ClientOptions) -> HttpClient {
+        let mut http = HttpConnector::new_with_resolver(options.resolver);
+
+        http.enforce_http(false);
+        http.set_happy_eyeballs_timeout(options.happy_eyeballs_timeout);
+        http.set_connect_timeout(options.connect_timeout);
+
+        let proxy = ProxyConnector::init(http, options.proxy);
+        let https = HttpsConnector::new_with_connector(proxy);
+
+        Arc::new(Client::builder().build::<_, Body>(https))
+    }
+
     async fn init_filename(uri: &Uri) -> PathBuf {
         let path_and_query = uri.path_and_query().expect("cannot get filename from URI!");
-        if let Some(filename) = path_and_query.as_str().rsplit_once('/') {
-            Self::init_create_path(filename.1).await
-        } else {
-            Self::init_create_path(path_and_query.as_str()).await
+        let filename = match path_and_query.as_str().rsplit_once('/') {
+            Some((_, filename)) => filename,
+            None => path_and_query.as_str(),
+        };
+        let filename = decode_segment(filename).unwrap_or_else(|| filename.to_owned());
+
+        Self::init_create_path(&filename).await
+    }
+
+    async fn init_create_path(filename: &str) -> PathBuf {
+        let mut path = PathBuf::with_capacity(15);
+        path.push(filename);
+        path
+    }
+
+    pub fn digest_algorithm(&self) -> Algorithm {
+        match &self.checksum {
+            Some(checksum) => Algorithm::resolve(checksum, self.algorithm),
+            None => self.algorithm.unwrap_or_default(),
+        }
+    }
+
+    pub fn staging_dir() -> PathBuf {
+        temp_dir().join("archeon")
+    }
+
+    async fn init_temp_dir() -> Result<PathBuf, std::io::Error> {
+        let path = Self::staging_dir();
+
+        create_dir_all(&path).await?;
+
+        Ok(path)
+    }
+
+    async fn init_file_path(temp_dir: &Path, filename: &Path) -> PathBuf {
+        let mut file_path = PathBuf::with_capacity(15);
+
+        file_path.push(temp_dir);
+        file_path.push(filename);
+
+        file_path
+    }
+
+    pub async fn launch(&self) -> Result<TransferSummary, ArcheonError> {
+        if self.overwrite == OverwritePolicy::RenameUnique {
+            let file_path = overwrite::unique_path(&self.file_path);
+
+            if file_path != self.file_path {
+                let transfer = Transfer {
+                    overwrite: OverwritePolicy::Error,
+                    ..self.retarget(file_path)
+                };
+
+                return Box::pin(transfer.launch()).await;
+            }
+        }
+
+        let _lock = FileLock::acquire(&self.file_path).await?;
+
+        if metadata(&self.file_path).await.is_ok() {
+            match self.overwrite {
+                OverwritePolicy::Error => {
+                    return Err(overwrite::already_exists(&self.file_path).into());
+                }
+                OverwritePolicy::Skip => {
+                    return Ok(TransferSummary::skipped(&self.uri, &self.file_path));
+                }
+                OverwritePolicy::Overwrite | OverwritePolicy::RenameUnique => {}
+            }
+        }
+
+        let mut partial = PartialGuard::init(&part_path(&self.file_path), &self.partial);
+        let result = self.launch_locked().await;
+
+        if result.is_ok() {
+            partial.disarm();
+        }
+
+        if let Some(har) = &self.har {
+            if let Err(error) = har.save().await {
+                eprintln!("Unable to save the HTTP Archive: {}", error);
+            }
+        }
+
+        result
+    }
+
+    async fn launch_locked(&self) -> Result<TransferSummary, ArcheonError> {
+        let launched = Instant::now();
+        let request_headers = self.launch_headers()?;
+        let mut headers = request_headers.to_owned();
+        let cached = self.launch_cached_entry(&request_headers).await;
+
+        if let Some(entry) = &cached {
+            if entry.is_fresh(crate::unix_timestamp()) {
+                return Ok(TransferSummary::up_to_date(
+                    &self.uri,
+                    &self.file_path,
+                    launched.elapsed(),
+                ));
+            }
+
+            entry.revalidate(&mut headers);
+        }
+
+        if self.timestamping && self.launch_up_to_date().await {
+            return Ok(TransferSummary::up_to_date(
+                &self.uri,
+                &self.file_path,
+                launched.elapsed(),
+            ));
+        }
+
+        let host = self.uri.authority().map(|authority| authority.as_str());
+        let host = host.unwrap_or_default();
+        let permit = self.limiter.acquire(host).await;
+        let started = Instant::now();
+        let mut attempt = 1;
+        let mut uri = self.uri.to_owned();
+        let mut refreshes = 0;
+        let (response, digest, resumed) = loop {
+            if self.shutdown.is_triggered() {
+                return Err(ArcheonError::Cancelled);
+            }
+
+            self.circuit.check(host)?;
+
+            let result = self.launch_attempt(&uri, &headers, &permit).await;
+
+            match &result {
+                Err(error) if error.is_retryable() => self.circuit.record_failure(host),
+                Err(error) if matches!(error.kind(), ErrorKind::Local | ErrorKind::Cancelled) => {}
+                _ => self.circuit.record_success(host),
+            }
+
+            if let (Err(error), Some(refresh_url)) = (&result, &self.refresh_url) {
+                if error.status() == Some(StatusCode::FORBIDDEN) && refreshes < MAX_REFRESHES {
+                    uri = refresh_url.refresh(&uri).await?;
+                    refreshes += 1;
+
+                    continue;
+                }
+            }
+
+            let delay = match &result {
+                Ok(_) => None,
+                Err(error) => self.retry.retry(attempt, error, started.elapsed()),
+            };
+
+            match delay {
+                Some(delay) => {
+                    tokio::select! {
+                        _ = sleep(delay) => {}
+                        _ = self.shutdown.triggered() => return Err(ArcheonError::Cancelled),
+                    }
+
+                    attempt += 1;
+                }
+                None => break result?,
+            }
+        };
+        let final_uri = response.extensions.get::<Uri>().unwrap_or(&uri).to_owned();
+        let mut entry = HttpCacheEntry::init(&request_headers, &response.headers);
+
+        entry.url = Some(self.uri.to_string());
+        let digest = match digest {
+            Some(digest) => digest,
+            None => {
+                entry.save(&self.file_path).await?;
+
+                return Ok(TransferSummary::up_to_date(
+                    &final_uri,
+                    &self.file_path,
+                    launched.elapsed(),
+                ));
+            }
+        };
+
+        if let Some(expected) = &self.checksum {
+            if !checksum::matches(expected, &digest) {
+                self.launch_discard_part().await?;
+
+                return Err(ArcheonError::Integrity {
+                    source: self.uri.to_string(),
+                    expected: expected.to_owned(),
+                    actual: digest,
+                });
+            }
+        }
+
+        rename(part_path(&self.file_path), &self.file_path).await?;
+
+        let last_modified = response
+            .headers
+            .get(LAST_MODIFIED)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_http_date);
+
+        if let (true, Some(last_modified)) = (self.timestamping, last_modified) {
+            std::fs::File::options()
+                .write(true)
+                .open(&self.file_path)?
+                .set_modified(UNIX_EPOCH + Duration::from_secs(last_modified))?;
+        }
+
+        if self.durability.sync_on_close() {
+            durability::sync_parent(&self.file_path).await?;
+        }
+
+        self.launch_discard_part().await?;
+
+        entry.save(&self.file_path).await?;
+
+        let bytes = metadata(&self.file_path)
+            .await?
+            .len()
+            .saturating_sub(resumed);
+
+        Ok(TransferSummary::init(
+            &final_uri,
+            &self.file_path,
+            bytes,
+            launched.elapsed(),
+            Some(digest),
+            resumed,
+        ))
+    }
+
+    pub async fn launch_digest(&self) -> Result<Option<String>, ArcheonError> {
+        self.launch().await.map(|summary| summary.checksum)
+    }
+
+    pub async fn repair(
+        &self,
+        expected: &str,
+        pieces: Option<&PieceHashes>,
+    ) -> Result<RepairSummary, ArcheonError> {
+        let lock = FileLock::acquire(&self.file_path).await?;
+        let algorithm = Algorithm::resolve(expected, self.algorithm);
+        let mut summary = RepairSummary {
+            file_path: self.file_path.to_owned(),
+            size: metadata(&self.file_path).await?.len(),
+            damaged: Vec::new(),
+            fetched: 0,
+            checksum: checksum::digest(&self.file_path, algorithm).await?,
+            refetched: false,
+        };
+
+        if checksum::matches(expected, &summary.checksum) {
+            return Ok(summary);
+        }
+
+        let pieces = match pieces {
+            Some(pieces) => pieces,
+            None => {
+                drop(lock);
+
+                return self.launch_refetch(expected).await;
+            }
+        };
+        let probe = self.launch_cached_probe(&self.uri).await?;
+        let size = match (probe.resumable, probe.size) {
+            (true, Some(size)) => size,
+            _ => {
+                drop(lock);
+
+                return self.launch_refetch(expected).await;
+            }
+        };
+        let host = self.uri.authority().map(|authority| authority.as_str());
+        let _permit = self.limiter.acquire(host.unwrap_or_default()).await;
+        let mut headers = self.launch_headers()?;
+        let validator = probe.etag.as_ref().or(probe.last_modified.as_ref());
+
+        if let Some(validator) = validator.and_then(|value| HeaderValue::from_str(value).ok()) {
+            headers.insert(IF_RANGE, validator);
+        }
+
+        let mut file = OpenOptions::new().write(true).open(&self.file_path).await?;
+
+        file.set_len(size).await?;
+
+        let local = PieceHashes::hash(&self.file_path, pieces.piece_size).await?;
+
+        for index in pieces.damaged(&local) {
+            let (start, end) = pieces.range(index, size);
+            let bytes = self.launch_range(&headers, start, end).await?;
+
+            summary.fetched += bytes.len() as u64;
+
+            if !pieces.verify(index, &bytes) {
+                return Err(ArcheonError::Integrity {
+                    source: format!("{} bytes {}-{}", self.uri, start, end),
+                    expected: pieces.hashes[index].to_owned(),
+                    actual: checksum::sha256_bytes(&bytes),
+                });
+            }
+
+            file.seek(SeekFrom::Start(start)).await?;
+            file.write_all(&bytes).await?;
+
+            summary.damaged.push((start, end));
+        }
+
+        file.flush().await?;
+
+        if self.durability.sync_on_close() {
+            file.sync_all().await?;
+        }
+
+        summary.size = size;
+        summary.damaged = repair::merge_ranges(&summary.damaged);
+        summary.checksum = checksum::digest(&self.file_path, algorithm).await?;
+
+        if !checksum::matches(expected, &summary.checksum) {
+            return Err(ArcheonError::Integrity {
+                source: self.uri.to_string(),
+                expected: expected.to_owned(),
+                actual: summary.checksum,
+            });
+        }
+
+        Ok(summary)
+    }
+
+    async fn launch_refetch(&self, expected: &str) -> Result<RepairSummary, ArcheonError> {
+        match remove_file(HttpCacheEntry::path(&self.file_path)).await {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => return Err(error.into()),
+            _ => {}
+        }
+
+        let transferred = self.launch().await?;
+        let algorithm = Algorithm::resolve(expected, self.algorithm);
+        let checksum = match transferred.checksum {
+            Some(checksum) if self.digest_algorithm() == algorithm => checksum,
+            _ => checksum::digest(&self.file_path, algorithm).await?,
+        };
+
+        if !checksum::matches(expected, &checksum) {
+            return Err(ArcheonError::Integrity {
+                source: self.uri.to_string(),
+                expected: expected.to_owned(),
+                actual: checksum,
+            });
+        }
+
+        Ok(RepairSummary {
+            file_path: self.file_path.to_owned(),
+            size: transferred.bytes,
+            damaged: vec![(0, transferred.bytes.saturating_sub(1))],
+            fetched: transferred.bytes,
+            checksum,
+            refetched: true,
+        })
+    }
+
+    async fn launch_range(
+        &self,
+        headers: &HeaderMap,
+        start: u64,
+        end: u64,
+    ) -> Result<Bytes, ArcheonError> {
+        let mut headers = headers.to_owned();
+        let range = format!("bytes={}-{}", start, end);
+
+        headers.insert(RANGE, HeaderValue::from_str(&range).map_err(Box::from)?);
+
+        let (response, body) = self
+            .launch_request(&self.uri, Method::GET, &headers)
+            .await?
+            .into_parts();
+
+        if response.status != StatusCode::PARTIAL_CONTENT {
+            return Err(ArcheonError::Status {
+                uri: self.uri.to_string(),
+                status: response.status,
+            });
+        }
+
+        Ok(hyper::body::to_bytes(body).await?)
+    }
+
+    async fn launch_attempt(
+        &self,
+        uri: &Uri,
+        headers: &HeaderMap,
+        permit: &HostPermit,
+    ) -> Result<(Parts, Option<String>, u64), ArcheonError> {
+        let mut headers = headers.to_owned();
+        let resumed = self.launch_resume(uri, &mut headers).await?;
+        let segmented = self.segments > 1
+            && resumed == 0
+            && self.tee.is_empty()
+            && !headers.contains_key(IF_NONE_MATCH)
+            && !headers.contains_key(IF_MODIFIED_SINCE);
+
+        if segmented {
+            let probe = self.launch_cached_probe(uri).await?;
+
+            if let (true, Some(size)) = (probe.resumable, probe.size) {
+                if size >= self.segments as u64 {
+                    return self
+                        .launch_segmented(uri, &headers, &probe, size, permit)
+                        .await;
+                }
+            }
+        }
+
+        let response = self.launch_request(uri, Method::GET, &headers).await?;
+        let content_length = Probe::init(uri, &response).size;
+        let (response, body) = response.into_parts();
+
+        if !response.status.is_success() && response.status != StatusCode::NOT_MODIFIED {
+            let expired = response.status == StatusCode::FORBIDDEN && self.refresh_url.is_some();
+
+            if !expired {
+                self.launch_discard_part().await?;
+            }
+
+            return Err(ArcheonError::Status {
+                uri: uri.to_string(),
+                status: response.status,
+            });
+        }
+
+        if response.status == StatusCode::NOT_MODIFIED {
+            self.launch_discard_part().await?;
+
+            return Ok((response, None, 0));
+        }
+
+        let part_path = part_path(&self.file_path);
+        let algorithm = self.digest_algorithm();
+        let resumed = match response.status == StatusCode::PARTIAL_CONTENT {
+            true => resumed,
+            false => 0,
+        };
+        let parallel = algorithm == Algorithm::Blake3
+            && content_length
+                .is_some_and(|length| length + resumed >= checksum::PARALLEL_THRESHOLD);
+        let mut hasher = match parallel {
+            true => None,
+            false => Some(Digest::new(algorithm)),
+        };
+        let file = match resumed {
+            0 => File::create(&part_path).await?,
+            _ => {
+                if let Some(hasher) = hasher.as_mut() {
+                    checksum::digest_update(&part_path, hasher).await?;
+                }
+
+                OpenOptions::new().append(true).open(&part_path).await?
+            }
+        };
+
+        let mut entry = HttpCacheEntry::init(&headers, &response.headers);
+
+        entry.url = Some(self.uri.to_string());
+
+        if resumed == 0 {
+            entry.save(&part_path).await?;
+        }
+
+        if let Some(progress) = &self.progress {
+            let url = self.uri.to_string();
+
+            progress.start(&url, content_length.map(|length| length + resumed));
+
+            if resumed > 0 {
+                progress.resume(&url, resumed);
+            }
+        }
+
+        self.tee.start(resumed).await?;
+
+        let receiving = Instant::now();
+        let result = self
+            .launch_body_to_file(body, permit, file, hasher.as_mut(), Some(&self.tee))
+            .await;
+
+        if let Ok(part) = metadata(&part_path).await {
+            self.launch_har_complete(
+                &response,
+                part.len().saturating_sub(resumed),
+                receiving.elapsed(),
+            );
+        }
+        let digest = match hasher {
+            Some(hasher) => hasher.finalize(),
+            None => checksum::digest(&part_path, algorithm).await?,
+        };
+
+        if let Err(error) = result {
+            entry.sha256 = Some(digest);
+            entry.save(&part_path).await?;
+
+            return Err(error);
+        }
+
+        self.tee.finish().await?;
+
+        Ok((response, Some(digest), resumed))
+    }
+
+    async fn launch_segmented(
+        &self,
+        uri: &Uri,
+        headers: &HeaderMap,
+        probe: &Probe,
+        size: u64,
+        permit: &HostPermit,
+    ) -> Result<(Parts, Option<String>, u64), ArcheonError> {
+        let part_path = part_path(&self.file_path);
+        let mut headers = headers.to_owned();
+        let validator = probe.etag.as_ref().or(probe.last_modified.as_ref());
+
+        if let Some(validator) = validator.and_then(|value| HeaderValue::from_str(value).ok()) {
+            headers.insert(IF_RANGE, validator);
+        }
+
+        File::create(&part_path).await?.set_len(size).await?;
+
+        if let Some(progress) = &self.progress {
+            progress.start(&self.uri.to_string(), Some(size));
+        }
+
+        let segments = segment_ranges(size, self.segments)
+            .into_iter()
+            .map(|(start, end)| self.launch_segment(uri, &headers, start, end, permit));
+        let mut responses = match try_join_all(segments).await {
+            Ok(responses) => responses,
+            Err(error) => {
+                self.launch_discard_part().await?;
+
+                return Err(error);
+            }
+        };
+        let digest = checksum::digest(&part_path, self.digest_algorithm()).await?;
+
+        Ok((responses.swap_remove(0), Some(digest), 0))
+    }
+
+    async fn launch_segment(
+        &self,
+        uri: &Uri,
+        headers: &HeaderMap,
+        start: u64,
+        end: u64,
+        permit: &HostPermit,
+    ) -> Result<Parts, ArcheonError> {
+        let mut headers = headers.to_owned();
+        let range = format!("bytes={}-{}", start, end);
+
+        headers.insert(RANGE, HeaderValue::from_str(&range).map_err(Box::from)?);
+
+        let (response, body) = self
+            .launch_request(uri, Method::GET, &headers)
+            .await?
+            .into_parts();
+
+        if response.status != StatusCode::PARTIAL_CONTENT {
+            return Err(ArcheonError::Status {
+                uri: uri.to_string(),
+                status: response.status,
+            });
+        }
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(part_path(&self.file_path))
+            .await?;
+
+        file.seek(SeekFrom::Start(start)).await?;
+
+        let receiving = Instant::now();
+
+        self.launch_body_to_file(body, permit, file, None, None)
+            .await?;
+        self.launch_har_complete(&response, end - start + 1, receiving.elapsed());
+
+        Ok(response)
+    }
+
+    fn launch_har_complete(&self, response: &Parts, size: u64, receive: Duration) {
+        if let (Some(har), Some(har_entry)) = (&self.har, response.extensions.get::<HarEntryId>()) {
+            har.complete(*har_entry, size, receive);
+        }
+    }
+
+    async fn launch_resume(&self, uri: &Uri, headers: &mut HeaderMap) -> Result<u64, ArcheonError> {
+        let part_path = part_path(&self.file_path);
+
+        if self.partial.is_expired(&part_path) {
+            self.launch_discard_part().await?;
+
+            return Ok(0);
         }
+
+        let entry = HttpCacheEntry::load(&part_path).await;
+        let validator = entry.as_ref().and_then(HttpCacheEntry::if_range);
+        let length = match validator {
+            Some(_) => metadata(&part_path)
+                .await
+                .map(|metadata| metadata.len())
+                .unwrap_or_default(),
+            None => 0,
+        };
+        let range = HeaderValue::from_str(&format!("bytes={}-", length));
+
+        let (validator, range) = match (validator, range) {
+            (Some(validator), Ok(range)) if length > 0 => (validator, range),
+            _ => return Ok(0),
+        };
+        let verified = match self.resume_check {
+            ResumeCheck::None => true,
+            ResumeCheck::Trailing => {
+                self.launch_verify_trailing(uri, headers, &validator, length)
+                    .await?
+            }
+            ResumeCheck::Full => match entry.and_then(|entry| entry.sha256) {
+                Some(expected) => checksum::digest(&part_path, self.digest_algorithm())
+                    .await?
+                    .eq_ignore_ascii_case(&expected),
+                None => false,
+            },
+        };
+
+        if !verified {
+            self.launch_discard_part().await?;
+
+            return Ok(0);
+        }
+
+        headers.insert(RANGE, range);
+        headers.insert(IF_RANGE, validator);
+
+        Ok(length)
+    }
+
+    async fn launch_verify_trailing(
+        &self,
+        uri: &Uri,
+        headers: &HeaderMap,
+        validator: &HeaderValue,
+        length: u64,
+    ) -> Result<bool, ArcheonError> {
+        let start = length.saturating_sub(TRAILING_BLOCK);
+        let range = format!("bytes={}-{}", start, length - 1);
+        let mut headers = headers.to_owned();
+
+        headers.insert(RANGE, HeaderValue::from_str(&range).map_err(Box::from)?);
+        headers.insert(IF_RANGE, validator.to_owned());
+
+        let (response, body) = self
+            .launch_request(uri, Method::GET, &headers)
+            .await?
+            .into_parts();
+
+        if response.status != StatusCode::PARTIAL_CONTENT {
+            return Ok(false);
+        }
+
+        let remote = hyper::body::to_bytes(body).await?;
+        let local = resume::read_range(&part_path(&self.file_path), start, length).await?;
+
+        Ok(remote == local)
+    }
+
+    async fn launch_discard_part(&self) -> Result<(), std::io::Error> {
+        let part_path = part_path(&self.file_path);
+
+        for path in [HttpCacheEntry::path(&part_path), part_path] {
+            match remove_file(&path).await {
+                Err(error) if error.kind() != std::io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn launch_headers(&self) -> Result<HeaderMap, Box<dyn std::error::Error + Send + Sync>> {
+        let mut headers = self.headers.to_owned();
+
+        headers.insert(USER_AGENT, HeaderValue::from_str(&self.user_agent)?);
+
+        Ok(headers)
+    }
+
+    async fn launch_request(
+        &self,
+        uri: &Uri,
+        method: Method,
+        headers: &HeaderMap,
+    ) -> Result<Response<Body>, Box<dyn std::error::Error + Send + Sync>> {
+        let origin = uri.to_owned();
+        let mut uri = uri.to_owned();
+        let mut headers = headers.to_owned();
+        let mut redirects = 0;
+        let mut reauthenticated = false;
+
+        loop {
+            let mut request = Request::builder()
+                .method(method.to_owned())
+                .uri(&uri)
+                .body(Body::empty())?;
+
+            *request.headers_mut() = headers.to_owned();
+
+            if let Some(cookie) = self.cookies.as_ref().and_then(|jar| jar.header(&uri)) {
+                request.headers_mut().insert(COOKIE, cookie);
+            }
+
+            let auth = match &self.auth {
+                Some(auth) if !self.redirect.strip_authorization || uri.host() == origin.host() => {
+                    Some(auth)
+                }
+                _ => None,
+            };
+
+            if let Some(auth) = auth {
+                auth.authenticate(&mut request).await?;
+            }
+
+            if let Some(pre_request) = &self.pre_request {
+                pre_request.before(&mut request).await?;
+            }
+
+            self.wire.request(&request);
+
+            let har_request = self.har.as_ref().map(|_| HarRequest::init(&request));
+            let started = SystemTime::now();
+            let waiting = Instant::now();
+            let sent = request.uri().to_owned();
+            let response = self.client.send(request).await?;
+
+            self.wire.response(&sent, &response);
+
+            let har_entry = match (&self.har, har_request) {
+                (Some(har), Some(har_request)) => {
+                    Some(har.record(har_request, &response, started, waiting.elapsed()))
+                }
+                _ => None,
+            };
+
+            if let Some(auth) = auth {
+                if response.status() == StatusCode::UNAUTHORIZED && !reauthenticated {
+                    auth.invalidate();
+                    reauthenticated = true;
+
+                    continue;
+                }
+            }
+
+            if let Some(jar) = &self.cookies {
+                jar.store(&sent, response.headers());
+            }
+
+            let location = match response.status().is_redirection() {
+                true => response.headers().get(LOCATION),
+                false => None,
+            };
+            let location = match location.and_then(|location| location.to_str().ok()) {
+                Some(location) => location,
+                None => {
+                    let mut response = response;
+
+                    response.extensions_mut().insert(sent);
+
+                    if let Some(har_entry) = har_entry {
+                        response.extensions_mut().insert(har_entry);
+                    }
+
+                    return Ok(response);
+                }
+            };
+
+            let location = resolve_location(&sent, location).ok_or_else(|| {
+                format!("Invalid redirect location '{}' from {}!", location, sent)
+            })?;
+
+            redirects += 1;
+
+            self.redirect.check(redirects, &sent, &location)?;
+            self.redirect
+                .redirect_headers(&mut headers, &sent, &location);
+
+            uri = location;
+        }
+    }
+
+    async fn launch_cached_entry(&self, request_headers: &HeaderMap) -> Option<HttpCacheEntry> {
+        let entry = HttpCacheEntry::load(&self.file_path).await?;
+        let same_url = entry.url.as_deref() == Some(self.uri.to_string().as_str());
+
+        if same_url && metadata(&self.file_path).await.is_ok() && entry.matches(request_headers) {
+            Some(entry)
+        } else {
+            None
+        }
+    }
+
+    async fn launch_up_to_date(&self) -> bool {
+        let local = match metadata(&self.file_path).await {
+            Ok(local) => local,
+            Err(_) => return false,
+        };
+        let probe = match self.launch_cached_probe(&self.uri).await {
+            Ok(probe) => probe,
+            Err(_) => return false,
+        };
+
+        if probe.size != Some(local.len()) {
+            return false;
+        }
+
+        let etag = HttpCacheEntry::load(&self.file_path)
+            .await
+            .and_then(|entry| entry.etag);
+
+        if let (Some(remote), Some(local)) = (&probe.etag, &etag) {
+            return remote == local;
+        }
+
+        let remote = probe.last_modified.as_deref().and_then(parse_http_date);
+        let local = local
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok());
+
+        match (remote, local) {
+            (Some(remote), Some(local)) => local.as_secs() >= remote,
+            _ => false,
+        }
+    }
+
+    pub async fn probe(&self) -> Result<Probe, ArcheonError> {
+        self.launch_cached_probe(&self.uri).await
+    }
+
+    async fn launch_cached_probe(&self, uri: &Uri) -> Result<Probe, ArcheonError> {
+        if let Some((probed_uri, probe)) = self.probed.lock().unwrap().as_ref() {
+            if probed_uri == uri {
+                return Ok(probe.to_owned());
+            }
+        }
+
+        let probe = self.launch_probe(uri).await?;
+
+        *self.probed.lock().unwrap() = Some((uri.to_owned(), probe.to_owned()));
+
+        Ok(probe)
+    }
+
+    async fn launch_probe(&self, uri: &Uri) -> Result<Probe, ArcheonError> {
+        let mut headers = self.launch_headers()?;
+        let response = self.launch_request(uri, Method::HEAD, &headers).await?;
+        let unsupported = matches!(
+            response.status(),
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::FORBIDDEN | StatusCode::NOT_IMPLEMENTED,
+        );
+
+        if !unsupported && response.headers().contains_key(CONTENT_LENGTH) {
+            return Ok(Probe::init(uri, &response));
+        }
+
+        headers.insert(RANGE, HeaderValue::from_static("bytes=0-0"));
+
+        let response = self.launch_request(uri, Method::GET, &headers).await?;
+
+        Ok(Probe::init(uri, &response))
+    }
+
+    async fn launch_body_to_file(
+        &self,
+        mut body: Body,
+        permit: &HostPermit,
+        file: File,
+        mut hasher: Option<&mut Digest>,
+        tee: Option<&Tee>,
+    ) -> Result<(), ArcheonError> {
+        let url = self.uri.to_string();
+        let mut file = BufWriter::with_capacity(self.write_buffer, file);
+        let durability = self.durability;
+        let progress = self.progress.to_owned();
+        let (sender, mut receiver) = unbounded_channel::<(Bytes, BufferPermit)>();
+        let writer = tokio::spawn(async move {
+            let mut unsynced = 0;
+
+            while let Some((chunk, _reserved)) = receiver.recv().await {
+                file.write_all(&chunk).await?;
+                unsynced += chunk.len() as u64;
+
+                if let Some(progress) = &progress {
+                    progress.advance(&url, chunk.len() as u64);
+                }
+
+                if durability
+                    .sync_interval()
+                    .is_some_and(|interval| unsynced >= interval)
+                {
+                    file.flush().await?;
+                    file.get_ref().sync_data().await?;
+                    unsynced = 0;
+                }
+            }
+
+            file.flush().await?;
+
+            if durability.sync_on_close() {
+                file.get_ref().sync_all().await?;
+            }
+
+            Ok::<(), std::io::Error>(())
+        });
+        let mut result = Ok(());
+
+        loop {
+            let chunk = tokio::select! {
+                chunk = body.data() => chunk,
+                _ = self.shutdown.triggered() => {
+                    result = Err(ArcheonError::Cancelled);
+
+                    break;
+                }
+            };
+            let chunk = match chunk {
+                Some(Ok(chunk)) => chunk,
+                Some(Err(error)) => {
+                    result = Err(error.into());
+
+                    break;
+                }
+                None => break,
+            };
+
+            permit.throttle(chunk.len()).await;
+
+            if let Some(hasher) = hasher.as_mut() {
+                hasher.update(&chunk);
+            }
+
+            if let Some(tee) = tee {
+                if let Err(error) = tee.write(&chunk).await {
+                    result = Err(error.into());
+
+                    break;
+                }
+            }
+
+            let reserved = self.buffer.reserve(chunk.len()).await;
+
+            if let Some(progress) = &self.progress {
+                progress.buffered(self.buffer.buffered() as u64);
+            }
+
+            if sender.send((chunk, reserved)).is_err() {
+                break;
+            }
+        }
+
+        drop(sender);
+
+        writer.await.map_err(std::io::Error::other)??;
+
+        result
+    }
+
+    pub async fn install_package(&self) -> Result<(), std::io::Error> {
+        self.installer
+            .install(&self.temp_dir, &self.filename)
+            .await?;
+
+        self.retention
+            .apply(&self.uri.to_string(), &self.file_path, None)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn parse_content_length(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(CONTENT_LENGTH)
+        .and_then(|content_length| content_length.to_str().ok())
+        .and_then(|content_length| content_length.parse().ok())
+}
+
+fn segment_ranges(size: u64, segments: usize) -> Vec<(u64, u64)> {
+    let segments = (segments as u64).clamp(1, size.max(1));
+    let length = size.div_ceil(segments).max(1);
+    let mut ranges = Vec::with_capacity(segments as usize);
+    let mut start = 0;
+
+    while start < size {
+        let end = start.saturating_add(length).min(size);
+
+        ranges.push((start, end - 1));
+        start = end;
+    }
+
+    ranges
+}
+
+pub fn part_path(file_path: &Path) -> PathBuf {
+    let mut path = file_path.as_os_str().to_owned();
+
+    path.push(".part");
+
+    PathBuf::from(path)
+}
+
+pub fn resolve_location(base: &Uri, location: &str) -> Option<Uri> {
+    if let Ok(uri) = Uri::from_str(location) {
+        if uri.scheme().is_some() && uri.authority().is_some() {
+            return Some(uri);
+        }
+    }
+
+    let scheme = base.scheme_str()?;
+    let authority = base.authority()?.as_str();
+    let location = if let Some(location) = location.strip_prefix("//") {
+        return Uri::from_str(&format!("{}://{}", scheme, location)).ok();
+    } else if location.starts_with('/') {
+        location.to_owned()
+    } else {
+        let path = base.path();
+        let directory = &path[..path.rfind('/').map(|index| index + 1).unwrap_or(0)];
+
+        format!("{}{}", directory, location)
+    };
+
+    Uri::from_str(&format!("{}://{}{}", scheme, authority, location)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::partial::PartialPolicy;
+    use crate::tee::WriterSink;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_uri = "http://some_test_authority/with/path/and/query";
+        let test_transfer = Transfer::init(test_uri).await?;
+        let test_uri_parts = test_transfer.uri.into_parts();
+        assert_eq!(test_uri_parts.scheme.unwrap().as_str(), "http");
+        assert_eq!(
+            test_uri_parts.authority.unwrap().as_str(),
+            "some_test_authority",
+        );
+        assert_eq!(
+            test_uri_parts.path_and_query.unwrap().as_str(),
+            "/with/path/and/query",
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init_filename() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_uri = "http://some_test_authority/with/path/and/query.extension";
+        let test_transfer = Transfer::init(test_uri).await?;
+        assert_eq!(test_transfer.filename.to_str().unwrap(), "query.extension");
+        let test_transfer = Transfer::init("some_test_authority/with path/my file.deb").await?;
+        assert_eq!(test_transfer.uri.scheme_str(), Some("https"));
+        assert_eq!(test_transfer.filename.to_str().unwrap(), "my file.deb");
+        let test_transfer = Transfer::init("some_test_authority/..%2F..%2Fescape").await?;
+        assert_eq!(test_transfer.filename.to_str().unwrap(), "..%2F..%2Fescape");
+        assert!(Transfer::init("ftp://some_test_authority/file")
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init_create_path() {
+        let test_filename = "some_test_filename.extension";
+        let test_path = Transfer::init_create_path(test_filename).await;
+        assert_eq!(test_path.to_str().unwrap(), "some_test_filename.extension");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init_temp_dir() -> Result<(), std::io::Error> {
+        Transfer::init_temp_dir().await?;
+        let test_temp_dir_metadata = tokio::fs::metadata("/tmp/archeon").await?;
+        assert!(test_temp_dir_metadata.is_dir());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init_file_path() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_uri = "http://some_test_authority/with/path/and/query.extension";
+        let test_transfer = Transfer::init(test_uri).await?;
+        let test_init_file_path =
+            Transfer::init_file_path(&test_transfer.temp_dir, &test_transfer.filename).await;
+        assert_eq!(
+            test_transfer.file_path.to_str().unwrap(),
+            "/tmp/archeon/query.extension",
+        );
+        assert_eq!(
+            test_init_file_path.to_str().unwrap(),
+            "/tmp/archeon/query.extension",
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_mock_url = test_server.url();
+        let test_mock_url_uri = Uri::from_str(&test_mock_url).unwrap();
+        let test_path_and_query = Uri::builder()
+            .scheme(test_mock_url_uri.scheme_str().unwrap())
+            .authority(test_mock_url_uri.authority().unwrap().as_str())
+            .path_and_query("/test_launch_file.txt")
+            .build()
+            .unwrap();
+        let test_transfer = Transfer::init(&test_path_and_query.to_string()).await?;
+        let mock_get_request = test_server
+            .mock("GET", "/test_launch_file.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        let mock_head_request = test_server
+            .mock("HEAD", "/test_launch_file.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .expect(0)
+            .create_async()
+            .await;
+        let test_summary = test_transfer.launch().await?;
+        mock_get_request.assert_async().await;
+        assert!(mock_get_request.matched());
+        mock_head_request.assert_async().await;
+        assert_eq!(
+            test_transfer.file_path.to_str().unwrap(),
+            "/tmp/archeon/test_launch_file.txt",
+        );
+        assert_eq!(test_summary.final_url, test_path_and_query.to_string());
+        assert_eq!(test_summary.file_path, test_transfer.file_path);
+        assert_eq!(test_summary.bytes, 9);
+        assert_eq!(test_summary.resumed, 0);
+        assert_eq!(
+            test_summary.checksum,
+            Some(checksum::sha256_bytes(b"test_body")),
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_empty() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_empty.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_empty.txt");
+        test_transfer.segments = 4;
+        let test_progress = BatchProgress::default();
+        test_transfer.progress = Some(test_progress.to_owned());
+        test_server
+            .mock("HEAD", "/test_launch_empty.txt")
+            .with_status(405)
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/test_launch_empty.txt")
+            .match_header("range", "bytes=0-0")
+            .with_status(416)
+            .with_header("content-range", "bytes */0")
+            .create_async()
+            .await;
+        let mock_get = test_server
+            .mock("GET", "/test_launch_empty.txt")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_header("content-length", "0")
+            .expect(1)
+            .create_async()
+            .await;
+        assert_eq!(test_transfer.probe().await?.size, Some(0));
+        assert_eq!(
+            test_transfer.launch_digest().await?,
+            Some(checksum::sha256_bytes(b"")),
+        );
+        mock_get.assert_async().await;
+        assert_eq!(metadata(&test_transfer.file_path).await?.len(), 0);
+        assert!(metadata(part_path(&test_transfer.file_path)).await.is_err());
+        let test_aggregate = test_progress.aggregate();
+        assert_eq!(test_aggregate.total_bytes, 0);
+        assert_eq!(test_aggregate.downloaded_bytes, 0);
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_durability() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_durability.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_durability.txt");
+        test_server
+            .mock("HEAD", "/test_launch_durability.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/test_launch_durability.txt")
+            .with_status(200)
+            .with_body(b"test_body")
+            .expect(2)
+            .create_async()
+            .await;
+        for test_durability in [Durability::Close, Durability::Periodic] {
+            test_transfer.durability = test_durability;
+            test_transfer.launch().await?;
+            assert_eq!(
+                tokio::fs::read(&test_transfer.file_path).await?,
+                b"test_body"
+            );
+            assert!(metadata(part_path(&test_transfer.file_path)).await.is_err());
+            tokio::fs::remove_file(&test_transfer.file_path).await?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_digest() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_digest.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_digest.txt");
+        test_server
+            .mock("HEAD", "/test_launch_digest.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .expect(2)
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/test_launch_digest.txt")
+            .with_status(200)
+            .with_body(b"test_body")
+            .expect(2)
+            .create_async()
+            .await;
+        test_transfer.checksum = Some(String::from("00"));
+        let test_error = test_transfer.launch_digest().await.unwrap_err();
+        assert_eq!(test_error.kind(), ErrorKind::Integrity);
+        assert!(tokio::fs::metadata(&test_transfer.file_path).await.is_err());
+        test_transfer.checksum = Some(checksum::sha256_bytes(b"test_body").to_uppercase());
+        assert_eq!(
+            test_transfer.launch_digest().await?,
+            Some(checksum::sha256_bytes(b"test_body")),
+        );
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_tee() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_tee.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_tee.txt");
+        test_transfer.segments = 4;
+        let test_head = test_server
+            .mock("HEAD", "/test_launch_tee.txt")
+            .expect(0)
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/test_launch_tee.txt")
+            .with_status(200)
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        let (test_sender, mut test_receiver) = tokio::sync::mpsc::channel(16);
+        let test_writer = Arc::new(WriterSink::init(Vec::<u8>::new()));
+        test_transfer.tee.push(Arc::new(test_sender));
+        test_transfer.tee.push(test_writer.to_owned());
+        let test_summary = test_transfer.launch().await?;
+        test_head.assert_async().await;
+        assert_eq!(
+            test_summary.checksum,
+            Some(checksum::sha256_bytes(b"test_body")),
+        );
+        drop(test_transfer);
+        let mut test_received = Vec::new();
+        while let Some(test_chunk) = test_receiver.recv().await {
+            test_received.extend_from_slice(&test_chunk);
+        }
+        assert_eq!(test_received, b"test_body");
+        assert_eq!(
+            Arc::into_inner(test_writer).unwrap().into_inner(),
+            b"test_body"
+        );
+        tokio::fs::remove_file(std::env::temp_dir().join("archeon_test_launch_tee.txt")).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_timestamping() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_timestamping.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_timestamping.txt");
+        test_transfer.timestamping = true;
+        let test_head = test_server
+            .mock("HEAD", "/test_launch_timestamping.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_header("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+            .expect(1)
+            .create_async()
+            .await;
+        let test_get = test_server
+            .mock("GET", "/test_launch_timestamping.txt")
+            .with_status(200)
+            .with_header("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+            .with_body(b"test_body")
+            .expect(1)
+            .create_async()
+            .await;
+        let test_summary = test_transfer.launch().await?;
+        assert_eq!(test_summary.outcome, TransferOutcome::Downloaded);
+        assert_eq!(
+            metadata(&test_transfer.file_path).await?.modified()?,
+            UNIX_EPOCH + Duration::from_secs(1_445_412_480),
+        );
+        let _ = remove_file(HttpCacheEntry::path(&test_transfer.file_path)).await;
+        let test_summary = test_transfer.launch().await?;
+        assert_eq!(test_summary.outcome, TransferOutcome::UpToDate);
+        assert_eq!(test_summary.bytes, 0);
+        test_head.assert_async().await;
+        test_get.assert_async().await;
+        tokio::fs::write(&test_transfer.file_path, b"test_tags_changed").await?;
+        test_server
+            .mock("GET", "/test_launch_timestamping.txt")
+            .with_status(200)
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        let test_summary = test_transfer.launch().await?;
+        assert_eq!(test_summary.outcome, TransferOutcome::Downloaded);
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        remove_file(&test_transfer.file_path).await?;
+        let _ = remove_file(HttpCacheEntry::path(&test_transfer.file_path)).await;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_overwrite() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_overwrite.txt", test_server.url());
+        let test_dir = std::env::temp_dir().join("archeon_test_launch_overwrite");
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = test_dir.join("test.txt");
+        create_dir_all(&test_dir).await?;
+        tokio::fs::write(&test_transfer.file_path, b"test_tags").await?;
+        let test_get = test_server
+            .mock("GET", "/test_launch_overwrite.txt")
+            .with_status(200)
+            .with_body(b"test_body")
+            .expect(2)
+            .create_async()
+            .await;
+        test_transfer.overwrite = OverwritePolicy::Error;
+        let test_error = test_transfer.launch().await.unwrap_err();
+        assert_eq!(test_error.kind(), ErrorKind::Local);
+        assert!(test_error.to_string().contains("already exists"));
+        test_transfer.overwrite = OverwritePolicy::Skip;
+        let test_summary = test_transfer.launch().await?;
+        assert_eq!(test_summary.outcome, TransferOutcome::Skipped);
+        assert_eq!(test_summary.file_path, test_transfer.file_path);
+        test_transfer.overwrite = OverwritePolicy::RenameUnique;
+        let test_summary = test_transfer.launch().await?;
+        assert_eq!(test_summary.outcome, TransferOutcome::Downloaded);
+        assert_eq!(test_summary.file_path, test_dir.join("test.1.txt"));
+        assert_eq!(
+            tokio::fs::read(test_dir.join("test.1.txt")).await?,
+            b"test_body"
+        );
+        test_transfer.overwrite = OverwritePolicy::Overwrite;
+        test_transfer.launch().await?;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        test_get.assert_async().await;
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_algorithm() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_algorithm.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_algorithm.txt");
+        test_server
+            .mock("GET", "/test_launch_algorithm.txt")
+            .with_status(200)
+            .with_body(b"test_body")
+            .expect(3)
+            .create_async()
+            .await;
+        let test_sha512 = checksum::digest_bytes(b"test_body", Algorithm::Sha512);
+        let test_blake3 = checksum::digest_bytes(b"test_body", Algorithm::Blake3);
+        test_transfer.checksum = Some(test_sha512.to_owned());
+        assert_eq!(test_transfer.digest_algorithm(), Algorithm::Sha512);
+        assert_eq!(test_transfer.launch_digest().await?, Some(test_sha512));
+        test_transfer.checksum = Some(format!("blake3:{}", test_blake3));
+        assert_eq!(
+            test_transfer.launch_digest().await?,
+            Some(test_blake3.to_owned()),
+        );
+        test_transfer.checksum = Some(test_blake3.to_owned());
+        test_transfer.algorithm = Some(Algorithm::Blake3);
+        assert_eq!(test_transfer.launch_digest().await?, Some(test_blake3));
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_host_limit() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_host = test_server.host_with_port();
+        for test_method in ["HEAD", "GET"] {
+            test_server
+                .mock(test_method, "/test_launch_host_limit.txt")
+                .with_status(200)
+                .with_header("content-length", "9")
+                .with_body(b"test_body")
+                .create_async()
+                .await;
+        }
+        let test_transfer =
+            Transfer::init(&format!("{}/test_launch_host_limit.txt", test_server.url())).await?;
+        test_transfer.limiter.set(
+            &test_host,
+            crate::limits::HostLimit {
+                max_connections: Some(1),
+                bytes_per_second: Some(1024),
+            },
+        );
+        let test_permit = test_transfer.limiter.acquire(&test_host).await;
+        let test_file_path = test_transfer.file_path.to_owned();
+        let test_launch = tokio::spawn(async move { test_transfer.launch().await.is_ok() });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!test_launch.is_finished());
+        drop(test_permit);
+        assert!(test_launch.await?);
+        assert_eq!(tokio::fs::read(&test_file_path).await?, b"test_body");
+        tokio::fs::remove_file(&test_file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init_client_with_resolver() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        struct TestResolver;
+
+        impl crate::resolver::Resolve for TestResolver {
+            fn resolve<'a>(&'a self, _: &'a str) -> crate::resolver::ResolveFuture<'a> {
+                Box::pin(async { Ok(vec![std::net::IpAddr::from([127, 0, 0, 1])]) })
+            }
+        }
+
+        let mut test_server = mockito::Server::new_async().await;
+        let test_port = test_server
+            .host_with_port()
+            .rsplit(':')
+            .next()
+            .unwrap()
+            .to_owned();
+        for test_method in ["HEAD", "GET"] {
+            test_server
+                .mock(test_method, "/test_init_client_with_resolver.txt")
+                .with_status(200)
+                .with_header("content-length", "9")
+                .with_body(b"test_body")
+                .create_async()
+                .await;
+        }
+        let test_client = Transfer::init_client_with_resolver(Resolver::init(TestResolver));
+        let test_transfer = Transfer::init_with_client(
+            &format!(
+                "http://test-resolver.invalid:{}/test_init_client_with_resolver.txt",
+                test_port,
+            ),
+            test_client,
+        )
+        .await?;
+        test_transfer.launch().await?;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init_client_with_options() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        struct TestResolver;
+
+        impl crate::resolver::Resolve for TestResolver {
+            fn resolve<'a>(&'a self, _: &'a str) -> crate::resolver::ResolveFuture<'a> {
+                Box::pin(async {
+                    Ok(vec![
+                        std::net::IpAddr::from([0x100, 0, 0, 0, 0, 0, 0, 1]),
+                        std::net::IpAddr::from([127, 0, 0, 1]),
+                    ])
+                })
+            }
+        }
+
+        let mut test_server = mockito::Server::new_async().await;
+        let test_port = test_server
+            .host_with_port()
+            .rsplit(':')
+            .next()
+            .unwrap()
+            .to_owned();
+        for test_method in ["HEAD", "GET"] {
+            test_server
+                .mock(test_method, "/test_init_client_with_options.txt")
+                .with_status(200)
+                .with_header("content-length", "9")
+                .with_body(b"test_body")
+                .create_async()
+                .await;
+        }
+        let mut test_resolver = Resolver::init(TestResolver);
+        test_resolver.family = crate::resolver::AddressFamily::PreferIpv6;
+        let test_client = Transfer::init_client_with_options(ClientOptions {
+            resolver: test_resolver,
+            happy_eyeballs_timeout: Some(Duration::from_millis(50)),
+            connect_timeout: Some(Duration::from_secs(10)),
+            proxy: None,
+        });
+        let test_transfer = Transfer::init_with_client(
+            &format!(
+                "http://test-happy-eyeballs.invalid:{}/test_init_client_with_options.txt",
+                test_port,
+            ),
+            test_client,
+        )
+        .await?;
+        tokio::time::timeout(Duration::from_secs(5), test_transfer.launch()).await??;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[derive(Clone)]
+    struct TestConnector {
+        http: HttpConnector,
+        calls: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl hyper::service::Service<Uri> for TestConnector {
+        type Response = <HttpConnector as hyper::service::Service<Uri>>::Response;
+        type Error = <HttpConnector as hyper::service::Service<Uri>>::Error;
+        type Future = <HttpConnector as hyper::service::Service<Uri>>::Future;
+
+        fn poll_ready(
+            &mut self,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            self.http.poll_ready(cx)
+        }
+
+        fn call(&mut self, uri: Uri) -> Self::Future {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.http.call(uri)
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init_client_with_connector() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        for test_method in ["HEAD", "GET"] {
+            test_server
+                .mock(test_method, "/test_init_client_with_connector.txt")
+                .with_status(200)
+                .with_header("content-length", "9")
+                .with_body(b"test_body")
+                .create_async()
+                .await;
+        }
+        let test_calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let test_client = Transfer::init_client_with_connector(TestConnector {
+            http: HttpConnector::new(),
+            calls: test_calls.to_owned(),
+        });
+        let test_transfer = Transfer::init_with_client(
+            &format!("{}/test_init_client_with_connector.txt", test_server.url()),
+            test_client,
+        )
+        .await?;
+        test_transfer.launch().await?;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        assert!(test_calls.load(std::sync::atomic::Ordering::SeqCst) >= 1);
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn init_client_with_proxy() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_mocks = Vec::with_capacity(1);
+        for test_method in ["GET"] {
+            test_mocks.push(
+                test_server
+                    .mock(test_method, "/test_init_client_with_proxy.txt")
+                    .match_header("host", "test-proxied.invalid")
+                    .with_status(200)
+                    .with_header("content-length", "9")
+                    .with_body(b"test_body")
+                    .create_async()
+                    .await,
+            );
+        }
+        let test_client = Transfer::init_client_with_options(ClientOptions {
+            proxy: Some(crate::proxy::parse_proxy(&test_server.url())?),
+            ..ClientOptions::default()
+        });
+        let test_transfer = Transfer::init_with_client(
+            "http://test-proxied.invalid/test_init_client_with_proxy.txt",
+            test_client,
+        )
+        .await?;
+        tokio::time::timeout(Duration::from_secs(5), test_transfer.launch()).await??;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        for test_mock in test_mocks {
+            test_mock.assert_async().await;
+        }
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_wire() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!(
+            "{}/test_launch_wire.txt?token=test_token",
+            test_server.url()
+        );
+        let test_mock = test_server
+            .mock("GET", "/test_launch_wire.txt?token=test_token")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_header("set-cookie", "session=test_session")
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer
+            .headers
+            .insert("authorization", "Bearer test_token".parse()?);
+        let mut test_records = test_transfer.wire.subscribe();
+        test_transfer.wire.set_enabled(true);
+        test_transfer.launch().await?;
+        test_mock.assert_async().await;
+        let test_request = test_records.recv().await?;
+        let test_response = test_records.recv().await?;
+        assert!(test_request.starts_with(&format!(
+            "> GET {}/test_launch_wire.txt?token=<redacted> HTTP/1.1",
+            test_server.url(),
+        )));
+        assert!(test_request.contains("> authorization: <redacted>"));
+        assert!(test_request.contains("> user-agent: archeon/"));
+        assert!(test_response.starts_with("< HTTP/1.1 200 OK"));
+        assert!(test_response.contains("< set-cookie: <redacted>"));
+        assert!(test_response.contains("< content-length: 9"));
+        assert!(![test_request, test_response]
+            .iter()
+            .any(|test_record| test_record.contains("test_token")
+                || test_record.contains("test_session")));
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_har() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_har.txt", test_server.url());
+        let test_redirect = test_server
+            .mock("GET", "/test_launch_har.txt")
+            .with_status(302)
+            .with_header("location", "/test_launch_har_mirror.txt?token=test_token")
+            .create_async()
+            .await;
+        let test_mock = test_server
+            .mock("GET", "/test_launch_har_mirror.txt?token=test_token")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_header("content-type", "text/plain")
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        let test_path = std::env::temp_dir().join("archeon_test_launch_har.har");
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.har = Some(HarRecorder::init(&test_path));
+        test_transfer.launch().await?;
+        test_redirect.assert_async().await;
+        test_mock.assert_async().await;
+        let test_har: crate::har::Har =
+            serde_json::from_slice(&tokio::fs::read(&test_path).await?)?;
+        assert_eq!(test_har.log.entries.len(), 2);
+        assert_eq!(test_har.log.entries[0].request.url, test_uri);
+        assert_eq!(test_har.log.entries[0].response.status, 302);
+        assert_eq!(
+            test_har.log.entries[0].response.redirect_url,
+            "/test_launch_har_mirror.txt?token=<redacted>",
+        );
+        assert_eq!(
+            test_har.log.entries[1].request.url,
+            format!(
+                "{}/test_launch_har_mirror.txt?token=<redacted>",
+                test_server.url()
+            ),
+        );
+        assert_eq!(test_har.log.entries[1].response.status, 200);
+        assert_eq!(test_har.log.entries[1].response.content.size, 9);
+        assert_eq!(
+            test_har.log.entries[1].response.content.mime_type,
+            "text/plain"
+        );
+        assert_eq!(test_har.log.entries[0].response.content.size, 0);
+        tokio::fs::remove_file(&test_path).await?;
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
     }
 
-    async fn init_create_path(filename: &str) -> PathBuf {
-        let mut path = PathBuf::with_capacity(15);
-        path.push(filename);
-        path
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_user_agent() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_user_agent.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        assert!(test_transfer.user_agent.starts_with("archeon/"));
+        let mut test_mocks = Vec::with_capacity(1);
+        for test_method in ["GET"] {
+            test_mocks.push(
+                test_server
+                    .mock(test_method, "/test_launch_user_agent.txt")
+                    .match_header("user-agent", "test-product/2.0")
+                    .with_status(200)
+                    .with_header("content-length", "9")
+                    .with_body(b"test_body")
+                    .create_async()
+                    .await,
+            );
+        }
+        test_transfer.user_agent = String::from("test-product/2.0");
+        test_transfer.launch().await?;
+        for test_mock in test_mocks {
+            test_mock.assert_async().await;
+        }
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
     }
 
-    async fn init_temp_dir() -> Result<PathBuf, std::io::Error> {
-        let mut path = PathBuf::with_capacity(15);
-        let temp_dir = temp_dir();
-        let temp_dir_path = "archeon";
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_auth() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        struct TestAuth {
+            tokens: std::sync::atomic::AtomicU32,
+        }
 
-        path.push(temp_dir);
-        path.push(temp_dir_path);
+        impl Authenticate for TestAuth {
+            fn authenticate<'a>(
+                &'a self,
+                request: &'a mut Request<Body>,
+            ) -> crate::auth::AuthFuture<'a> {
+                Box::pin(async move {
+                    let token = self.tokens.load(std::sync::atomic::Ordering::SeqCst);
+                    let value = HeaderValue::from_str(&format!("Bearer test_token_{}", token))?;
+                    request
+                        .headers_mut()
+                        .insert(hyper::header::AUTHORIZATION, value);
+                    Ok(())
+                })
+            }
 
-        create_dir_all(&path).await?;
+            fn invalidate(&self) {
+                self.tokens
+                    .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            }
+        }
 
-        Ok(path)
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_auth.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_auth.txt");
+        test_server
+            .mock("HEAD", "/test_launch_auth.txt")
+            .with_status(401)
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/test_launch_auth.txt")
+            .match_header("authorization", "Bearer test_token_0")
+            .with_status(401)
+            .create_async()
+            .await;
+        let mock_authorized = test_server
+            .mock("GET", "/test_launch_auth.txt")
+            .match_header("authorization", "Bearer test_token_1")
+            .with_status(200)
+            .with_body(b"test_body")
+            .expect_at_least(1)
+            .create_async()
+            .await;
+        test_transfer.auth = Some(Arc::new(TestAuth {
+            tokens: std::sync::atomic::AtomicU32::new(0),
+        }));
+        test_transfer.launch().await?;
+        mock_authorized.assert_async().await;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
     }
 
-    async fn init_file_path(temp_dir: &Path, filename: &Path) -> PathBuf {
-        let mut file_path = PathBuf::with_capacity(15);
-
-        file_path.push(temp_dir);
-        file_path.push(filename);
-
-        file_path
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_pre_request() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_pre_request_old.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_pre_request.txt");
+        let mock_redirect = test_server
+            .mock(
+                "GET",
+                "/test_pre_request_old.txt?signature=test_signature_0",
+            )
+            .match_header("x-test-signature", "test_signature_0")
+            .with_status(302)
+            .with_header("location", "/test_pre_request.txt")
+            .create_async()
+            .await;
+        let mock_signed = test_server
+            .mock("GET", "/test_pre_request.txt?signature=test_signature_1")
+            .match_header("x-test-signature", "test_signature_1")
+            .with_status(200)
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        let test_signatures = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let test_counter = test_signatures.to_owned();
+        test_transfer.pre_request = Some(Arc::new(
+            move |request: &mut Request<Body>| -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+                let signature = format!(
+                    "test_signature_{}",
+                    test_counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst),
+                );
+                request
+                    .headers_mut()
+                    .insert("x-test-signature", HeaderValue::from_str(&signature)?);
+                *request.uri_mut() =
+                    format!("{}?signature={}", request.uri(), signature).parse()?;
+                Ok(())
+            },
+        ));
+        let test_summary = test_transfer.launch().await?;
+        mock_redirect.assert_async().await;
+        mock_signed.assert_async().await;
+        assert_eq!(test_signatures.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert!(test_summary
+            .final_url
+            .ends_with("/test_pre_request.txt?signature=test_signature_1"));
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
     }
 
-    pub async fn launch(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let uri = self.uri.to_owned();
-        let content_length = self.launch_content_length().await?;
-        let response = self.client.get(uri).await?;
-        let response_body = response.into_body();
-        let bytes = Self::launch_body_to_bytes(response_body).await?;
-
-        self.launch_create_file(bytes, content_length).await?;
-
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_redirect_cookies() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/portal/login", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_redirect_cookies.txt");
+        test_transfer.cookies = Some(CookieJar::default());
+        let mock_login = test_server
+            .mock("GET", "/portal/login")
+            .with_status(302)
+            .with_header("set-cookie", "session=abc; Path=/")
+            .with_header("location", "files/test.txt")
+            .expect(1)
+            .create_async()
+            .await;
+        test_server
+            .mock("HEAD", "/portal/login")
+            .with_status(302)
+            .with_header("set-cookie", "session=abc; Path=/")
+            .with_header("location", "/portal/files/test.txt")
+            .create_async()
+            .await;
+        let mut test_mocks = Vec::with_capacity(1);
+        for test_method in ["GET"] {
+            test_mocks.push(
+                test_server
+                    .mock(test_method, "/portal/files/test.txt")
+                    .match_header("cookie", "session=abc")
+                    .with_status(200)
+                    .with_header("content-length", "9")
+                    .with_body(b"test_body")
+                    .create_async()
+                    .await,
+            );
+        }
+        let test_summary = test_transfer.launch().await?;
+        mock_login.assert_async().await;
+        for test_mock in test_mocks {
+            test_mock.assert_async().await;
+        }
+        assert_eq!(
+            test_summary.final_url,
+            format!("{}/portal/files/test.txt", test_server.url()),
+        );
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
         Ok(())
     }
 
-    async fn launch_content_length(&self) -> Result<HeaderValue, hyper::Error> {
-        let request = Request::head(&self.uri)
-            .body(Body::empty())
-            .expect("Could not Build Request!");
-
-        let response = self.client.request(request).await?;
-        let response_parts = response.into_parts();
-        let content_length = response_parts.0.headers.get(CONTENT_LENGTH);
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_too_many_redirects() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_loop", test_server.url());
+        let test_transfer = Transfer::init(&test_uri).await?;
+        test_server
+            .mock("GET", "/test_loop")
+            .with_status(301)
+            .with_header("location", "/test_loop")
+            .create_async()
+            .await;
+        let test_error = test_transfer.launch().await.unwrap_err();
+        assert!(test_error.to_string().contains("Too many redirects"));
+        Ok(())
+    }
 
-        if let Some(header_value) = content_length {
-            Ok(header_value.to_owned())
-        } else {
-            panic!("Could not retrieve 'Content-Length' header!")
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_redirect_policy() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_portal = mockito::Server::new_async().await;
+        let mut test_cdn = mockito::Server::new_async().await;
+        let test_cdn_port = test_cdn
+            .host_with_port()
+            .rsplit(':')
+            .next()
+            .unwrap()
+            .to_owned();
+        let test_location = format!(
+            "http://localhost:{}/test_redirect_policy.txt",
+            test_cdn_port
+        );
+        for test_method in ["HEAD", "GET"] {
+            test_portal
+                .mock(test_method, "/test_redirect_policy.txt")
+                .match_header("authorization", "Bearer test_token")
+                .with_status(307)
+                .with_header("location", &test_location)
+                .create_async()
+                .await;
+            test_cdn
+                .mock(test_method, "/test_redirect_policy.txt")
+                .match_header("authorization", mockito::Matcher::Missing)
+                .with_status(200)
+                .with_header("content-length", "9")
+                .with_body(b"test_body")
+                .create_async()
+                .await;
         }
+        let test_uri = format!("{}/test_redirect_policy.txt", test_portal.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_redirect_policy.txt");
+        test_transfer.headers.insert(
+            hyper::header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer test_token"),
+        );
+        test_transfer.redirect.cross_origin = false;
+        let test_error = test_transfer.launch().await.unwrap_err();
+        assert!(test_error.to_string().contains("Cross-origin redirect"));
+        test_transfer.redirect.cross_origin = true;
+        test_transfer.launch().await?;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
     }
 
-    async fn launch_body_to_bytes(body: Body) -> Result<Bytes, hyper::Error> {
-        let bytes = to_bytes(body).await?;
-        Ok(bytes)
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_retry() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_retry.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_retry.txt");
+        test_server
+            .mock("HEAD", "/test_launch_retry.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .create_async()
+            .await;
+        let mock_unavailable = test_server
+            .mock("GET", "/test_launch_retry.txt")
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+        let mock_available = test_server
+            .mock("GET", "/test_launch_retry.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_body(b"test_body")
+            .expect(1)
+            .create_async()
+            .await;
+        test_transfer.retry = Arc::new(crate::retry::FixedRetry {
+            max_attempts: 3,
+            delay: Duration::from_millis(10),
+        });
+        test_transfer.launch().await?;
+        mock_unavailable.assert_async().await;
+        mock_available.assert_async().await;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
     }
 
-    async fn launch_create_file(
-        &self,
-        bytes: Bytes,
-        content_length: HeaderValue,
-    ) -> Result<(), std::io::Error> {
-        let mut file = File::create(&self.file_path).await?;
-
-        file.write_all(&bytes).await?;
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_status() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_status.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_status.txt");
+        test_server
+            .mock("HEAD", "/test_launch_status.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .create_async()
+            .await;
+        let mock_not_found = test_server
+            .mock("GET", "/test_launch_status.txt")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+        test_transfer.retry = Arc::new(crate::retry::FixedRetry {
+            max_attempts: 3,
+            delay: Duration::from_millis(10),
+        });
+        let test_error = test_transfer.launch().await.unwrap_err();
+        mock_not_found.assert_async().await;
+        assert_eq!(test_error.status(), Some(StatusCode::NOT_FOUND));
+        assert_eq!(test_error.kind(), ErrorKind::Client);
+        assert!(!test_error.is_retryable());
+        assert!(tokio::fs::metadata(&test_transfer.file_path).await.is_err());
+        Ok(())
+    }
 
-        let mut initial_size = self.launch_get_file_length().await?;
-        let content_length_str = content_length.to_str().unwrap();
-        let total_size = u64::from_str(content_length_str).unwrap();
-        let progress_bar = ProgressBar::new(total_size);
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_segmented() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_segmented.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_segmented.txt");
+        test_transfer.segments = 2;
+        test_transfer.checksum = Some(checksum::sha256_bytes(b"test_body"));
+        test_server
+            .mock("HEAD", "/test_launch_segmented.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_header("accept-ranges", "bytes")
+            .with_header("etag", "\"test_etag\"")
+            .create_async()
+            .await;
+        let mock_first = test_server
+            .mock("GET", "/test_launch_segmented.txt")
+            .match_header("range", "bytes=0-4")
+            .match_header("if-range", "\"test_etag\"")
+            .with_status(206)
+            .with_header("content-range", "bytes 0-4/9")
+            .with_chunked_body(|test_writer| {
+                std::thread::sleep(Duration::from_millis(200));
+                test_writer.write_all(b"test_")
+            })
+            .create_async()
+            .await;
+        let mock_second = test_server
+            .mock("GET", "/test_launch_segmented.txt")
+            .match_header("range", "bytes=5-8")
+            .match_header("if-range", "\"test_etag\"")
+            .with_status(206)
+            .with_header("content-range", "bytes 5-8/9")
+            .with_body(b"body")
+            .create_async()
+            .await;
+        assert_eq!(
+            test_transfer.launch_digest().await?,
+            Some(checksum::sha256_bytes(b"test_body")),
+        );
+        mock_first.assert_async().await;
+        mock_second.assert_async().await;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        assert!(metadata(part_path(&test_transfer.file_path)).await.is_err());
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
 
-        while initial_size < total_size {
-            let current_size = self.launch_get_file_length().await?;
-            initial_size = current_size;
-            progress_bar.set_position(current_size);
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_probed() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_probed.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_probed.txt");
+        test_transfer.segments = 2;
+        let mock_head = test_server
+            .mock("HEAD", "/test_launch_probed.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_header("accept-ranges", "bytes")
+            .expect(1)
+            .create_async()
+            .await;
+        for (test_range, test_content_range, test_body) in [
+            ("bytes=0-4", "bytes 0-4/9", "test_"),
+            ("bytes=5-8", "bytes 5-8/9", "body"),
+        ] {
+            test_server
+                .mock("GET", "/test_launch_probed.txt")
+                .match_header("range", test_range)
+                .with_status(206)
+                .with_header("content-range", test_content_range)
+                .with_body(test_body)
+                .create_async()
+                .await;
         }
-
-        progress_bar.finish();
-
+        assert_eq!(test_transfer.probe().await?.size, Some(9));
+        test_transfer.launch().await?;
+        mock_head.assert_async().await;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
         Ok(())
     }
 
-    async fn launch_get_file_length(&self) -> Result<u64, std::io::Error> {
-        let open_file = File::open(&self.file_path).await?;
-        let open_file_metadata = open_file.metadata().await?;
-        Ok(open_file_metadata.len())
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_locked() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_server = crate::testing::MockServer::start().await?;
+        let test_artifact = crate::testing::MockArtifact {
+            latency: Duration::from_millis(200),
+            ..crate::testing::MockArtifact::sized(256 * 1024)
+        };
+        let test_body = test_artifact.body.to_owned();
+        let test_url = test_server.add("test_launch_locked.bin", test_artifact);
+        let test_path = std::env::temp_dir().join("archeon_test_launch_locked.bin");
+        let mut test_transfers = Vec::with_capacity(2);
+        for _ in 0..2 {
+            let mut test_transfer = Transfer::init(&test_url).await?;
+            test_transfer.file_path = test_path.to_owned();
+            test_transfers.push(tokio::spawn(async move { test_transfer.launch().await }));
+        }
+        for test_transfer in test_transfers {
+            assert_eq!(test_transfer.await??.file_path, test_path);
+        }
+        assert_eq!(test_server.hits("test_launch_locked.bin"), 2);
+        assert_eq!(tokio::fs::read(&test_path).await?, test_body);
+        assert!(!FileLock::path(&test_path).exists());
+        assert!(!part_path(&test_path).exists());
+        tokio::fs::remove_file(&test_path).await?;
+        let _ = tokio::fs::remove_file(HttpCacheEntry::path(&test_path)).await;
+        Ok(())
     }
 
-    pub async fn install_package(&self) -> Result<(), std::io::Error> {
-        let command = Command::new("dpkg")
-            .arg("--install")
-            .arg(&self.filename)
-            .current_dir(&self.temp_dir)
-            .output()
+    #[tokio::test(flavor = "multi_thread")]
+    async fn repair() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_server = crate::testing::MockServer::start().await?;
+        let test_artifact = crate::testing::MockArtifact::sized(64 * 1024);
+        let test_body = test_artifact.body.to_owned();
+        let test_checksum = checksum::sha256_bytes(&test_body);
+        let test_url = test_server.add("test_repair.bin", test_artifact);
+        let mut test_transfer = Transfer::init(&test_url).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_repair.bin");
+        tokio::fs::write(&test_transfer.file_path, &test_body).await?;
+        let test_pieces = PieceHashes::hash(&test_transfer.file_path, 4096).await?;
+        let test_summary = test_transfer.repair(&test_checksum, None).await?;
+        assert!(test_summary.damaged.is_empty());
+        assert_eq!(test_summary.fetched, 0);
+        assert_eq!(test_server.hits("test_repair.bin"), 0);
+        let mut test_damaged = test_body.to_vec();
+        test_damaged[5000..5010].fill(0);
+        test_damaged[9000] ^= 0xff;
+        test_damaged.truncate(60 * 1024);
+        tokio::fs::write(&test_transfer.file_path, &test_damaged).await?;
+        let test_summary = test_transfer
+            .repair(&test_checksum, Some(&test_pieces))
             .await?;
+        assert!(!test_summary.refetched);
+        assert_eq!(test_summary.damaged, vec![(4096, 12287), (61440, 65535)]);
+        assert_eq!(test_summary.fetched, 3 * 4096);
+        assert_eq!(test_summary.checksum, test_checksum);
+        assert_eq!(tokio::fs::read(&test_transfer.file_path).await?, test_body);
+        tokio::fs::write(&test_transfer.file_path, &test_damaged).await?;
+        let test_hits = test_server.hits("test_repair.bin");
+        let test_summary = test_transfer.repair(&test_checksum, None).await?;
+        assert!(test_summary.refetched);
+        assert!(test_server.hits("test_repair.bin") - test_hits <= 2);
+        assert_eq!(test_summary.damaged, vec![(0, 64 * 1024 - 1)]);
+        assert_eq!(test_summary.fetched, 64 * 1024);
+        assert_eq!(tokio::fs::read(&test_transfer.file_path).await?, test_body);
+        let mut test_wrong = test_pieces.to_owned();
+        test_wrong.hashes[0] = checksum::sha256_bytes(b"test_body");
+        assert!(test_transfer
+            .repair(&checksum::sha256_bytes(b"test_body"), Some(&test_wrong))
+            .await
+            .is_err());
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
+    }
 
-        println!("{:?}", command.status);
-        println!("{:#?}", String::from_utf8(command.stdout));
-        println!("{:#?}", String::from_utf8(command.stderr));
-
+    #[tokio::test(flavor = "multi_thread")]
+    async fn repair_without_ranges() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_server = crate::testing::MockServer::start().await?;
+        let test_artifact = crate::testing::MockArtifact {
+            ranges: false,
+            ..crate::testing::MockArtifact::sized(16 * 1024)
+        };
+        let test_body = test_artifact.body.to_owned();
+        let test_url = test_server.add("test_repair_without_ranges.bin", test_artifact);
+        let mut test_transfer = Transfer::init(&test_url).await?;
+        test_transfer.file_path =
+            std::env::temp_dir().join("archeon_test_repair_without_ranges.bin");
+        tokio::fs::write(&test_transfer.file_path, b"test_body").await?;
+        let test_summary = test_transfer
+            .repair(&checksum::sha256_bytes(&test_body), None)
+            .await?;
+        assert!(test_summary.refetched);
+        assert_eq!(test_summary.damaged, vec![(0, 16 * 1024 - 1)]);
+        assert_eq!(test_summary.fetched, 16 * 1024);
+        assert_eq!(tokio::fs::read(&test_transfer.file_path).await?, test_body);
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        let _ = tokio::fs::remove_file(HttpCacheEntry::path(&test_transfer.file_path)).await;
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test(flavor = "multi_thread")]
+    async fn segment_ranges() {
+        assert_eq!(super::segment_ranges(9, 2), vec![(0, 4), (5, 8)]);
+        assert_eq!(super::segment_ranges(9, 3), vec![(0, 2), (3, 5), (6, 8)]);
+        assert_eq!(super::segment_ranges(2, 4), vec![(0, 0), (1, 1)]);
+        assert_eq!(super::segment_ranges(5, 1), vec![(0, 4)]);
+        assert!(super::segment_ranges(0, 4).is_empty());
+        let test_large = super::segment_ranges(5 << 30, 4);
+        assert_eq!(test_large.len(), 4);
+        assert_eq!(test_large[1], (1342177280, 2684354559));
+        assert_eq!(test_large[3].1, (5 << 30) - 1);
+    }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn init() -> Result<(), Box<dyn std::error::Error>> {
-        let test_uri = "http://some_test_authority/with/path/and/query";
-        let test_transfer = Transfer::init(test_uri).await?;
-        let test_uri_parts = test_transfer.uri.into_parts();
-        assert_eq!(test_uri_parts.scheme.unwrap().as_str(), "http");
+    async fn launch_resume() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_resume.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_resume.txt");
+        let test_part_path = part_path(&test_transfer.file_path);
+        test_server
+            .mock("HEAD", "/test_launch_resume.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .create_async()
+            .await;
+        let mock_partial = test_server
+            .mock("GET", "/test_launch_resume.txt")
+            .match_header("range", "bytes=5-")
+            .match_header("if-range", "\"test_etag\"")
+            .with_status(206)
+            .with_header("etag", "\"test_etag\"")
+            .with_header("content-range", "bytes 5-8/9")
+            .with_body(b"body")
+            .expect(1)
+            .create_async()
+            .await;
+        tokio::fs::write(&test_part_path, b"test_").await?;
+        HttpCacheEntry {
+            etag: Some(String::from("\"test_etag\"")),
+            ..HttpCacheEntry::default()
+        }
+        .save(&test_part_path)
+        .await?;
+        let test_summary = test_transfer.launch().await?;
         assert_eq!(
-            test_uri_parts.authority.unwrap().as_str(),
-            "some_test_authority",
+            test_summary.checksum,
+            Some(checksum::sha256_bytes(b"test_body")),
         );
+        assert_eq!(test_summary.resumed, 5);
+        assert_eq!(test_summary.bytes, 4);
+        mock_partial.assert_async().await;
         assert_eq!(
-            test_uri_parts.path_and_query.unwrap().as_str(),
-            "/with/path/and/query",
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
         );
+        assert!(tokio::fs::metadata(&test_part_path).await.is_err());
+        assert!(HttpCacheEntry::load(&test_part_path).await.is_none());
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn init_filename() -> Result<(), Box<dyn std::error::Error>> {
-        let test_uri = "http://some_test_authority/with/path/and/query.extension";
-        let test_transfer = Transfer::init(test_uri).await?;
-        assert_eq!(test_transfer.filename.to_str().unwrap(), "query.extension");
+    async fn launch_resume_check() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_resume_check.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_resume_check.txt");
+        let test_part_path = part_path(&test_transfer.file_path);
+        test_server
+            .mock("HEAD", "/test_launch_resume_check.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .create_async()
+            .await;
+        let mock_trailing = test_server
+            .mock("GET", "/test_launch_resume_check.txt")
+            .match_header("range", "bytes=0-4")
+            .match_header("if-range", "\"test_etag\"")
+            .with_status(206)
+            .with_header("content-range", "bytes 0-4/9")
+            .with_body(b"test_")
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_partial = test_server
+            .mock("GET", "/test_launch_resume_check.txt")
+            .match_header("range", "bytes=5-")
+            .with_status(206)
+            .with_header("etag", "\"test_etag\"")
+            .with_header("content-range", "bytes 5-8/9")
+            .with_body(b"body")
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_full = test_server
+            .mock("GET", "/test_launch_resume_check.txt")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"test_etag\"")
+            .with_body(b"test_body")
+            .expect(1)
+            .create_async()
+            .await;
+        for (test_check, test_part) in [
+            (ResumeCheck::Trailing, b"test_"),
+            (ResumeCheck::Full, b"tesX_"),
+        ] {
+            test_transfer.resume_check = test_check;
+            tokio::fs::write(&test_part_path, test_part).await?;
+            HttpCacheEntry {
+                etag: Some(String::from("\"test_etag\"")),
+                sha256: Some(checksum::sha256_bytes(b"test_")),
+                ..HttpCacheEntry::default()
+            }
+            .save(&test_part_path)
+            .await?;
+            test_transfer.launch().await?;
+            assert_eq!(
+                tokio::fs::read(&test_transfer.file_path).await?,
+                b"test_body"
+            );
+            tokio::fs::remove_file(&test_transfer.file_path).await?;
+        }
+        mock_trailing.assert_async().await;
+        mock_partial.assert_async().await;
+        mock_full.assert_async().await;
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn init_create_path() {
-        let test_filename = "some_test_filename.extension";
-        let test_path = Transfer::init_create_path(test_filename).await;
-        assert_eq!(test_path.to_str().unwrap(), "some_test_filename.extension");
+    async fn launch_refresh_url() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!(
+            "{}/test_launch_refresh_url.txt?signature=test_expired",
+            test_server.url(),
+        );
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_launch_refresh_url.txt");
+        let test_part_path = part_path(&test_transfer.file_path);
+        test_server
+            .mock("HEAD", "/test_launch_refresh_url.txt?signature=test_fresh")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .create_async()
+            .await;
+        let mock_expired = test_server
+            .mock("GET", "/test_launch_refresh_url.txt?signature=test_expired")
+            .with_status(403)
+            .expect_at_least(1)
+            .create_async()
+            .await;
+        let mock_fresh = test_server
+            .mock("GET", "/test_launch_refresh_url.txt?signature=test_fresh")
+            .match_header("range", "bytes=5-")
+            .with_status(206)
+            .with_header("etag", "\"test_etag\"")
+            .with_header("content-range", "bytes 5-8/9")
+            .with_body(b"body")
+            .expect(1)
+            .create_async()
+            .await;
+        tokio::fs::write(&test_part_path, b"test_").await?;
+        HttpCacheEntry {
+            etag: Some(String::from("\"test_etag\"")),
+            ..HttpCacheEntry::default()
+        }
+        .save(&test_part_path)
+        .await?;
+        let test_refreshes = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let test_counter = test_refreshes.to_owned();
+        test_transfer.refresh_url = Some(Arc::new(move |test_expired: Uri| {
+            test_counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async move {
+                let test_fresh = format!(
+                    "http://{}{}?signature=test_fresh",
+                    test_expired
+                        .authority()
+                        .map(|a| a.as_str())
+                        .unwrap_or_default(),
+                    test_expired.path(),
+                );
+
+                Ok::<_, Box<dyn std::error::Error + Send + Sync>>(test_fresh.parse()?)
+            }
+        }));
+        test_transfer.launch().await?;
+        mock_expired.assert_async().await;
+        mock_fresh.assert_async().await;
+        assert_eq!(test_refreshes.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn init_temp_dir() -> Result<(), std::io::Error> {
-        Transfer::init_temp_dir().await?;
-        let test_temp_dir_metadata = tokio::fs::metadata("/tmp/archeon").await?;
-        assert!(test_temp_dir_metadata.is_dir());
+    async fn launch_refresh_url_exhausted() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!(
+            "{}/test_launch_refresh_url_exhausted.txt",
+            test_server.url()
+        );
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path =
+            std::env::temp_dir().join("archeon_test_launch_refresh_url_exhausted.txt");
+        test_server
+            .mock("GET", "/test_launch_refresh_url_exhausted.txt")
+            .with_status(403)
+            .create_async()
+            .await;
+        test_transfer.refresh_url = Some(Arc::new(|test_expired: Uri| async move {
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(test_expired)
+        }));
+        let test_error = test_transfer.launch().await.unwrap_err();
+        assert_eq!(test_error.status(), Some(StatusCode::FORBIDDEN));
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn init_file_path() -> Result<(), Box<dyn std::error::Error>> {
-        let test_uri = "http://some_test_authority/with/path/and/query.extension";
-        let test_transfer = Transfer::init(test_uri).await?;
-        let test_init_file_path =
-            Transfer::init_file_path(&test_transfer.temp_dir, &test_transfer.filename).await;
+    async fn launch_resume_changed() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_resume_changed.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path =
+            std::env::temp_dir().join("archeon_test_launch_resume_changed.txt");
+        let test_part_path = part_path(&test_transfer.file_path);
+        test_server
+            .mock("HEAD", "/test_launch_resume_changed.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .create_async()
+            .await;
+        let mock_full = test_server
+            .mock("GET", "/test_launch_resume_changed.txt")
+            .match_header("if-range", "\"test_stale_etag\"")
+            .with_status(200)
+            .with_header("etag", "\"test_etag\"")
+            .with_body(b"new_bytes")
+            .expect(1)
+            .create_async()
+            .await;
+        tokio::fs::write(&test_part_path, b"old_").await?;
+        HttpCacheEntry {
+            etag: Some(String::from("\"test_stale_etag\"")),
+            ..HttpCacheEntry::default()
+        }
+        .save(&test_part_path)
+        .await?;
+        test_transfer.launch().await?;
+        mock_full.assert_async().await;
         assert_eq!(
-            test_transfer.file_path.to_str().unwrap(),
-            "/tmp/archeon/query.extension",
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"new_bytes"
         );
+        assert!(tokio::fs::metadata(&test_part_path).await.is_err());
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        tokio::fs::remove_file(HttpCacheEntry::path(&test_transfer.file_path)).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resolve_location() {
+        let test_base = Uri::from_static("https://portal.test/files/index.html?page=1");
         assert_eq!(
-            test_init_file_path.to_str().unwrap(),
-            "/tmp/archeon/query.extension",
+            super::resolve_location(&test_base, "https://cdn.test/file.iso"),
+            Some(Uri::from_static("https://cdn.test/file.iso")),
+        );
+        assert_eq!(
+            super::resolve_location(&test_base, "//mirror.test/file.iso"),
+            Some(Uri::from_static("https://mirror.test/file.iso")),
+        );
+        assert_eq!(
+            super::resolve_location(&test_base, "/other/file.iso"),
+            Some(Uri::from_static("https://portal.test/other/file.iso")),
+        );
+        assert_eq!(
+            super::resolve_location(&test_base, "file.iso?token=1"),
+            Some(Uri::from_static(
+                "https://portal.test/files/file.iso?token=1"
+            )),
         );
-        Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn launch() -> Result<(), Box<dyn std::error::Error>> {
-        let mut test_server = mockito::Server::new();
-        let test_mock_url = test_server.url();
-        let test_mock_url_uri = Uri::from_str(&test_mock_url).unwrap();
-        let test_path_and_query = Uri::builder()
-            .scheme(test_mock_url_uri.scheme_str().unwrap())
-            .authority(test_mock_url_uri.authority().unwrap().as_str())
-            .path_and_query("/test_launch_file.txt")
-            .build()
-            .unwrap();
-        let test_transfer = Transfer::init(&test_path_and_query.to_string()).await?;
-        let mock_get_request = test_server
-            .mock("GET", "/test_launch_file.txt")
+    async fn launch_http_cache() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_launch_http_cache.txt", test_server.url());
+        let test_transfer = Transfer::init(&test_uri).await?;
+        let mock_fresh_get_request = test_server
+            .mock("GET", "/test_launch_http_cache.txt")
             .with_status(200)
             .with_header("content-length", "9")
+            .with_header("cache-control", "max-age=600")
+            .with_header("etag", "\"test_etag\"")
             .with_body(b"test_body")
-            .create();
-        let mock_head_request = test_server
-            .mock("HEAD", "/test_launch_file.txt")
-            .with_status(200)
-            .with_header("content-length", "9")
-            .with_body(b"test_body")
-            .create();
+            .expect(1)
+            .create_async()
+            .await;
         test_transfer.launch().await?;
-        mock_get_request.assert();
-        assert!(mock_get_request.matched());
-        mock_head_request.assert();
-        assert!(mock_head_request.matched());
+        test_transfer.launch().await?;
+        mock_fresh_get_request.assert_async().await;
+        let mut test_entry = HttpCacheEntry::load(&test_transfer.file_path)
+            .await
+            .unwrap();
+        test_entry.max_age = Some(0);
+        test_entry.save(&test_transfer.file_path).await?;
+        let mock_stale_get_request = test_server
+            .mock("GET", "/test_launch_http_cache.txt")
+            .match_header("if-none-match", "\"test_etag\"")
+            .with_status(304)
+            .with_header("etag", "\"test_etag\"")
+            .expect(1)
+            .create_async()
+            .await;
+        test_transfer.launch().await?;
+        mock_stale_get_request.assert_async().await;
         assert_eq!(
-            test_transfer.file_path.to_str().unwrap(),
-            "/tmp/archeon/test_launch_file.txt",
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
         );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
+        tokio::fs::remove_file(HttpCacheEntry::path(&test_transfer.file_path)).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_http_cache_url() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let mut test_mocks = Vec::new();
+        for (test_version, test_body) in [("1.0", "test_old"), ("2.0", "test_new")] {
+            test_mocks.push(
+                test_server
+                    .mock(
+                        "GET",
+                        format!("/{}/test_launch_http_cache_url.txt", test_version).as_str(),
+                    )
+                    .with_status(200)
+                    .with_header("cache-control", "immutable, max-age=600")
+                    .with_body(test_body)
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+        let test_old = Transfer::init(&format!(
+            "{}/1.0/test_launch_http_cache_url.txt",
+            test_server.url(),
+        ))
+        .await?;
+        let test_new = Transfer::init(&format!(
+            "{}/2.0/test_launch_http_cache_url.txt",
+            test_server.url(),
+        ))
+        .await?;
+        assert_eq!(test_old.file_path, test_new.file_path);
+        test_old.launch().await?;
+        test_new.launch().await?;
+        for test_mock in test_mocks {
+            test_mock.assert_async().await;
+        }
+        assert_eq!(tokio::fs::read(&test_new.file_path).await?, b"test_new");
+        tokio::fs::remove_file(&test_new.file_path).await?;
+        tokio::fs::remove_file(HttpCacheEntry::path(&test_new.file_path)).await?;
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn lauch_content_length() -> Result<(), Box<dyn std::error::Error>> {
-        let mut test_server = mockito::Server::new();
+    async fn lauch_content_length() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
         let test_mock_url = test_server.url();
         let test_transfer = Transfer::init(&test_mock_url).await?;
         let mock = test_server
@@ -264,60 +3037,316 @@ mod tests {
             .with_status(200)
             .with_header("Content-Length", "100000")
             .with_body("")
-            .create();
-        let test_content_length_value = test_transfer.launch_content_length().await?;
-        mock.assert();
+            .create_async()
+            .await;
+        let test_content_length_value = test_transfer.launch_probe(&test_transfer.uri).await?.size;
+        mock.assert_async().await;
         assert!(mock.matched());
-        assert_eq!(test_content_length_value.to_str().unwrap(), "100000");
+        assert_eq!(test_content_length_value, Some(100000));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_content_length_fallback() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_head_unsupported.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_head_unsupported.txt");
+        test_server
+            .mock("HEAD", "/test_head_unsupported.txt")
+            .with_status(405)
+            .create_async()
+            .await;
+        let mock_ranged = test_server
+            .mock("GET", "/test_head_unsupported.txt")
+            .match_header("range", "bytes=0-0")
+            .with_status(206)
+            .with_header("content-range", "bytes 0-0/9")
+            .with_body(b"t")
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/test_head_unsupported.txt")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        assert_eq!(
+            test_transfer.launch_probe(&test_transfer.uri).await?.size,
+            Some(9)
+        );
+        mock_ranged.assert_async().await;
+        test_transfer.launch().await?;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn launch_body_to_bytes() -> Result<(), hyper::Error> {
-        let test_body = Body::from("test_body");
-        let test_body_to_bytes = Transfer::launch_body_to_bytes(test_body).await?;
-        assert_eq!(test_body_to_bytes.len(), 9);
-        assert_eq!(test_body_to_bytes, Bytes::from("test_body"));
+    async fn launch_content_length_unknown() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_length_unknown.txt", test_server.url());
+        let mut test_transfer = Transfer::init(&test_uri).await?;
+        test_transfer.file_path = std::env::temp_dir().join("archeon_test_length_unknown.txt");
+        test_server
+            .mock("HEAD", "/test_length_unknown.txt")
+            .with_status(403)
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/test_length_unknown.txt")
+            .match_header("range", "bytes=0-0")
+            .with_status(416)
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/test_length_unknown.txt")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_chunked_body(|test_writer| test_writer.write_all(b"test_body"))
+            .create_async()
+            .await;
+        assert!(test_transfer
+            .launch_probe(&test_transfer.uri)
+            .await?
+            .size
+            .is_none());
+        test_transfer.launch().await?;
+        assert_eq!(
+            tokio::fs::read(&test_transfer.file_path).await?,
+            b"test_body"
+        );
+        tokio::fs::remove_file(&test_transfer.file_path).await?;
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn launch_create_file() -> Result<(), Box<dyn std::error::Error>> {
-        let test_bytes = Bytes::from("test_bytes");
-        let test_content_length = HeaderValue::from_static("10");
-        let test_uri = "http://test-create-file/test_create_file.txt";
-        let test_transfer = Transfer::init(test_uri).await?;
-        if let Ok(()) =
-            Transfer::launch_create_file(&test_transfer, test_bytes, test_content_length).await
+    async fn probe() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_probe.iso", test_server.url());
+        let test_transfer = Transfer::init(&test_uri).await?;
+        test_server
+            .mock("HEAD", "/test_probe.iso")
+            .with_status(302)
+            .with_header("location", "/mirror/test_probe.iso")
+            .create_async()
+            .await;
+        test_server
+            .mock("HEAD", "/mirror/test_probe.iso")
+            .with_status(200)
+            .with_header("content-length", "4096")
+            .with_header("accept-ranges", "bytes")
+            .with_header("etag", "\"test_etag\"")
+            .with_header("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+            .with_header("content-type", "application/x-iso9660-image")
+            .create_async()
+            .await;
+        let test_probe = test_transfer.probe().await?;
+        assert!(test_probe.resumable);
+        assert_eq!(test_probe.size, Some(4096));
+        assert_eq!(test_probe.etag.as_deref(), Some("\"test_etag\""));
+        assert_eq!(
+            test_probe.last_modified.as_deref(),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(
+            test_probe.content_type.as_deref(),
+            Some("application/x-iso9660-image"),
+        );
+        assert_eq!(
+            test_probe.final_uri.to_string(),
+            format!("{}/mirror/test_probe.iso", test_server.url()),
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn probe_not_resumable() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_uri = format!("{}/test_probe_plain.txt", test_server.url());
+        let test_transfer = Transfer::init(&test_uri).await?;
+        test_server
+            .mock("HEAD", "/test_probe_plain.txt")
+            .with_status(405)
+            .create_async()
+            .await;
+        test_server
+            .mock("GET", "/test_probe_plain.txt")
+            .with_status(200)
+            .with_header("content-length", "9")
+            .with_header("accept-ranges", "none")
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        let test_probe = test_transfer.probe().await?;
+        assert!(!test_probe.resumable);
+        assert_eq!(test_probe.size, Some(9));
+        assert!(test_probe.etag.is_none());
+        assert_eq!(test_probe.final_uri.to_string(), test_uri);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_body_to_file() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (mut test_sender, test_body) = Body::channel();
+        let test_permit = HostLimiter::default().acquire("test-body-to-file").await;
+        let mut test_transfer =
+            Transfer::init("http://test-body-to-file/test_body_to_file.txt").await?;
+        let test_progress = BatchProgress::default();
+        test_transfer.progress = Some(test_progress.to_owned());
+        test_transfer.buffer = BufferLimit::init(8);
+        let test_part_path = part_path(&test_transfer.file_path);
+        let test_file = File::create(&test_part_path).await?;
+        let test_held = test_transfer.buffer.reserve(8).await;
+        tokio::spawn(async move {
+            for test_chunk in ["test", "_bod", "y"] {
+                test_sender.send_data(Bytes::from(test_chunk)).await?;
+            }
+
+            Ok::<(), hyper::Error>(())
+        });
+        let mut test_hasher = Digest::new(Algorithm::Sha256);
         {
-            let test_file = File::open(&test_transfer.file_path).await?;
-            let test_file_metadata = test_file.metadata().await?;
-            assert!(test_file_metadata.is_file());
-            assert_eq!(test_file_metadata.len(), 10);
-            tokio::fs::remove_file(&test_transfer.file_path).await?;
+            let test_writer = test_transfer.launch_body_to_file(
+                test_body,
+                &test_permit,
+                test_file,
+                Some(&mut test_hasher),
+                None,
+            );
+            tokio::pin!(test_writer);
+            assert!(
+                tokio::time::timeout(Duration::from_millis(100), &mut test_writer)
+                    .await
+                    .is_err()
+            );
+            assert_eq!(test_progress.aggregate().downloaded_bytes, 0);
+            drop(test_held);
+            test_writer.await?;
         }
+        assert_eq!(test_hasher.finalize(), checksum::sha256_bytes(b"test_body"),);
+        assert_eq!(tokio::fs::read(&test_part_path).await?, b"test_body");
+        assert_eq!(test_transfer.buffer.buffered(), 0);
+        assert!(test_transfer.buffer.high_watermark() <= 8);
+        let test_aggregate = test_progress.aggregate();
+        assert_eq!(test_aggregate.downloaded_bytes, 9);
+        assert!((1..=8).contains(&test_aggregate.buffered_high_watermark));
+        tokio::fs::remove_file(&test_part_path).await?;
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn launch_get_file_length() -> Result<(), Box<dyn std::error::Error>> {
-        let test_bytes = Bytes::from("test_bytes");
-        let test_content_length = HeaderValue::from_static("10");
-        let test_uri = "http://get-file-length/test_get_file_length.txt";
-        let test_transfer = Transfer::init(test_uri).await?;
-        if let Ok(()) =
-            Transfer::launch_create_file(&test_transfer, test_bytes, test_content_length).await
+    async fn launch_shutdown() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_permit = HostLimiter::default().acquire("test-shutdown").await;
+        let test_transfer = Transfer::init("http://test-shutdown/test_shutdown.txt").await?;
+        let test_part_path = part_path(&test_transfer.file_path);
+        let (mut test_sender, test_body) = Body::channel();
+        let test_file = File::create(&test_part_path).await?;
+        let mut test_hasher = Digest::new(Algorithm::Sha256);
         {
-            let test_file = test_transfer.launch_get_file_length().await?;
-            assert_eq!(test_file, 10);
-            tokio::fs::remove_file(&test_transfer.file_path).await?;
+            let test_writer = test_transfer.launch_body_to_file(
+                test_body,
+                &test_permit,
+                test_file,
+                Some(&mut test_hasher),
+                None,
+            );
+            tokio::pin!(test_writer);
+            test_sender.send_data(Bytes::from("test_")).await?;
+            assert!(
+                tokio::time::timeout(Duration::from_millis(100), &mut test_writer)
+                    .await
+                    .is_err()
+            );
+            test_transfer.shutdown.trigger();
+            assert!(matches!(test_writer.await, Err(ArcheonError::Cancelled)));
+        }
+        assert_eq!(test_hasher.finalize(), checksum::sha256_bytes(b"test_"),);
+        assert_eq!(tokio::fs::read(&test_part_path).await?, b"test_");
+        let test_error = test_transfer.launch().await.unwrap_err();
+        assert_eq!(test_error.kind(), ErrorKind::Cancelled);
+        assert_eq!(tokio::fs::read(&test_part_path).await?, b"test_");
+        tokio::fs::remove_file(&test_part_path).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_partial() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_transfer = Transfer::init("http://test-partial/test_partial.txt").await?;
+        let test_part_path = part_path(&test_transfer.file_path);
+        let test_quarantine = std::env::temp_dir().join("archeon_test_partial_quarantine");
+        test_transfer.partial.quarantine_dir = test_quarantine.to_owned();
+        test_transfer.shutdown.trigger();
+        for test_policy in [
+            PartialPolicy::Keep,
+            PartialPolicy::Quarantine,
+            PartialPolicy::Delete,
+        ] {
+            test_transfer.partial.policy = test_policy;
+            tokio::fs::write(&test_part_path, b"test_").await?;
+            tokio::fs::write(HttpCacheEntry::path(&test_part_path), b"{}").await?;
+            let test_error = test_transfer.launch().await.unwrap_err();
+            assert_eq!(test_error.kind(), ErrorKind::Cancelled);
+            assert_eq!(test_part_path.exists(), test_policy == PartialPolicy::Keep);
+            assert_eq!(
+                HttpCacheEntry::path(&test_part_path).exists(),
+                test_policy == PartialPolicy::Keep,
+            );
+        }
+        assert_eq!(
+            tokio::fs::read(test_quarantine.join("test_partial.txt.part")).await?,
+            b"test_",
+        );
+        tokio::fs::remove_dir_all(&test_quarantine).await?;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn launch_write_buffer() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_permit = HostLimiter::default().acquire("test-write-buffer").await;
+        let mut test_transfer =
+            Transfer::init("http://test-write-buffer/test_write_buffer.txt").await?;
+        let test_part_path = part_path(&test_transfer.file_path);
+        for (test_write_buffer, test_written) in [(DEFAULT_WRITE_BUFFER, 0), (0, 5)] {
+            test_transfer.write_buffer = test_write_buffer;
+            let (mut test_sender, test_body) = Body::channel();
+            let test_file = File::create(&test_part_path).await?;
+            let mut test_hasher = Digest::new(Algorithm::Sha256);
+            {
+                let test_writer = test_transfer.launch_body_to_file(
+                    test_body,
+                    &test_permit,
+                    test_file,
+                    Some(&mut test_hasher),
+                    None,
+                );
+                tokio::pin!(test_writer);
+                test_sender.send_data(Bytes::from("test_")).await?;
+                assert!(
+                    tokio::time::timeout(Duration::from_millis(100), &mut test_writer)
+                        .await
+                        .is_err()
+                );
+                assert_eq!(metadata(&test_part_path).await?.len(), test_written);
+                test_sender.send_data(Bytes::from("body")).await?;
+                drop(test_sender);
+                test_writer.await?;
+            }
+            assert_eq!(tokio::fs::read(&test_part_path).await?, b"test_body");
         }
+        tokio::fs::remove_file(&test_part_path).await?;
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
-    async fn install_package() -> Result<(), Box<dyn std::error::Error>> {
-        let mut test_server = mockito::Server::new();
+    async fn install_package() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
         let test_mock_url = test_server.url();
         let test_mock_url_uri = Uri::from_str(&test_mock_url).unwrap();
         let test_path_and_query = Uri::builder()
@@ -333,21 +3362,16 @@ mod tests {
             .with_status(200)
             .with_header("content-length", "9")
             .with_body(b"test_body")
-            .create();
-
-        let mock_head_request = test_server
-            .mock("HEAD", "/test_install_package_file.txt")
-            .with_status(200)
-            .with_header("content-length", "9")
-            .with_body(b"test_body")
-            .create();
+            .create_async()
+            .await;
 
         test_transfer.launch().await?;
-        test_transfer.install_package().await?;
-        mock_get_request.assert();
+        let test_error = test_transfer.install_package().await.unwrap_err();
+        assert!(test_error
+            .to_string()
+            .contains("test_install_package_file.txt"));
+        mock_get_request.assert_async().await;
         assert!(mock_get_request.matched());
-        mock_head_request.assert();
-        assert!(mock_head_request.matched());
         Ok(())
     }
 }
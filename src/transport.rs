@@ -0,0 +1,487 @@
+use hyper::client::connect::Connect;
+use hyper::{Body, Client, Request, Response};
+
+use std::future::Future;
+use std::pin::Pin;
+
+pub type TransportFuture<'a> = Pin<
+    Box<
+        dyn Future<Output = Result<Response<Body>, Box<dyn std::error::Error + Send + Sync>>>
+            + Send
+            + 'a,
+    >,
+>;
+
+pub trait Transport: Send + Sync {
+    fn send(&self, request: Request<Body>) -> TransportFuture<'_>;
+}
+
+impl<C> Transport for Client<C, Body>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    fn send(&self, request: Request<Body>) -> TransportFuture<'_> {
+        let response = self.request(request);
+
+        Box::pin(async move { Ok(response.await?) })
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[derive(Clone, Debug)]
+pub struct ReqwestTransport {
+    pub client: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestTransport {
+    pub fn init(
+        options: &crate::transfer::ClientOptions,
+    ) -> Result<ReqwestTransport, reqwest::Error> {
+        let mut builder = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(std::sync::Arc::new(options.resolver.to_owned()));
+
+        if let Some(proxy) = &options.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy.to_string())?);
+        }
+
+        if let Some(connect_timeout) = options.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+
+        Ok(ReqwestTransport {
+            client: builder.build()?,
+        })
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl Transport for ReqwestTransport {
+    fn send(&self, request: Request<Body>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+            let request = reqwest::Request::try_from(Request::from_parts(parts, body))?;
+            let mut response = self.client.execute(request).await.map_err(reqwest_error)?;
+            let mut builder = Response::builder()
+                .status(response.status())
+                .version(response.version());
+
+            if let Some(headers) = builder.headers_mut() {
+                *headers = response.headers().to_owned();
+            }
+
+            let (mut sender, body) = Body::channel();
+
+            tokio::spawn(async move {
+                loop {
+                    match response.chunk().await {
+                        Ok(Some(chunk)) => {
+                            if sender.send_data(chunk).await.is_err() {
+                                break;
+                            }
+                        }
+                        Ok(None) => break,
+                        Err(_) => {
+                            sender.abort();
+
+                            break;
+                        }
+                    }
+                }
+            });
+
+            Ok(builder.body(body)?)
+        })
+    }
+}
+
+#[cfg(feature = "reqwest")]
+fn reqwest_error(error: reqwest::Error) -> crate::error::ArcheonError {
+    match error.is_builder() {
+        true => crate::error::ArcheonError::Other(Box::new(error)),
+        false => crate::error::ArcheonError::Network(Box::new(error)),
+    }
+}
+
+#[cfg(feature = "curl")]
+#[derive(Clone, Debug, Default)]
+pub struct CurlTransport {
+    pub options: crate::transfer::ClientOptions,
+    pool: std::sync::Arc<std::sync::Mutex<Vec<curl::easy::Easy2<CurlHandler>>>>,
+}
+
+#[cfg(feature = "curl")]
+type CurlHead = Result<(hyper::StatusCode, hyper::Version, hyper::HeaderMap), curl::Error>;
+
+#[cfg(feature = "curl")]
+#[derive(Debug)]
+struct CurlHandler {
+    runtime: tokio::runtime::Handle,
+    head: Option<tokio::sync::oneshot::Sender<CurlHead>>,
+    status: hyper::StatusCode,
+    version: hyper::Version,
+    headers: hyper::HeaderMap,
+    body: hyper::body::Sender,
+}
+
+#[cfg(feature = "curl")]
+impl CurlTransport {
+    pub fn init(options: &crate::transfer::ClientOptions) -> CurlTransport {
+        CurlTransport {
+            options: options.to_owned(),
+            pool: std::sync::Arc::default(),
+        }
+    }
+
+    async fn launch_resolve(&self, uri: &hyper::Uri) -> Result<Option<String>, std::io::Error> {
+        use hyper::service::Service;
+
+        let host = match uri.host() {
+            Some(host) if self.options.proxy.is_none() => {
+                host.trim_start_matches('[').trim_end_matches(']')
+            }
+            _ => return Ok(None),
+        };
+
+        if host.parse::<std::net::IpAddr>().is_ok() {
+            return Ok(None);
+        }
+
+        let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+            Some("https") => 443,
+            _ => 80,
+        });
+        let name = host
+            .parse()
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidInput, error))?;
+        let addresses: Vec<String> = self
+            .options
+            .resolver
+            .to_owned()
+            .call(name)
+            .await?
+            .map(|address| match address.ip() {
+                std::net::IpAddr::V4(ip) => ip.to_string(),
+                std::net::IpAddr::V6(ip) => format!("[{}]", ip),
+            })
+            .collect();
+
+        Ok(Some(format!("{}:{}:{}", host, port, addresses.join(","))))
+    }
+
+    fn launch_easy(
+        &self,
+        parts: hyper::http::request::Parts,
+        body: hyper::body::Bytes,
+        resolve: Option<String>,
+        handler: CurlHandler,
+    ) {
+        let pooled = self.lock().pop();
+        let mut easy = match pooled {
+            Some(mut easy) => {
+                easy.reset();
+                *easy.get_mut() = handler;
+                easy
+            }
+            None => curl::easy::Easy2::new(handler),
+        };
+        let result = self.launch_perform(&mut easy, parts, body, resolve);
+        let handler = easy.get_mut();
+
+        match (handler.head.take(), result) {
+            (Some(head), result) => {
+                let _ = head.send(result.map(|_| {
+                    (
+                        handler.status,
+                        handler.version,
+                        std::mem::take(&mut handler.headers),
+                    )
+                }));
+            }
+            (None, Err(_)) => {
+                std::mem::replace(&mut handler.body, Body::channel().0).abort();
+            }
+            (None, Ok(())) => {}
+        }
+
+        handler.body = Body::channel().0;
+
+        let mut pool = self.lock();
+
+        if pool.len() < 8 {
+            pool.push(easy);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<curl::easy::Easy2<CurlHandler>>> {
+        self.pool
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn launch_perform(
+        &self,
+        easy: &mut curl::easy::Easy2<CurlHandler>,
+        parts: hyper::http::request::Parts,
+        body: hyper::body::Bytes,
+        resolve: Option<String>,
+    ) -> Result<(), curl::Error> {
+        let mut headers = curl::easy::List::new();
+
+        for (name, value) in &parts.headers {
+            headers.append(&format!(
+                "{}: {}",
+                name,
+                String::from_utf8_lossy(value.as_bytes())
+            ))?;
+        }
+
+        easy.url(&parts.uri.to_string())?;
+        easy.http_headers(headers)?;
+
+        match parts.method {
+            hyper::Method::GET => easy.get(true)?,
+            hyper::Method::HEAD => easy.nobody(true)?,
+            method => easy.custom_request(method.as_str())?,
+        }
+
+        if !body.is_empty() {
+            easy.post_fields_copy(&body)?;
+        }
+
+        if let Some(resolve) = resolve {
+            let mut list = curl::easy::List::new();
+
+            list.append(&resolve)?;
+            easy.resolve(list)?;
+        }
+
+        if let Some(proxy) = &self.options.proxy {
+            easy.proxy(&proxy.to_string())?;
+        }
+
+        if let Some(connect_timeout) = self.options.connect_timeout {
+            easy.connect_timeout(connect_timeout)?;
+        }
+
+        easy.perform()
+    }
+}
+
+#[cfg(feature = "curl")]
+fn curl_error(error: curl::Error) -> crate::error::ArcheonError {
+    let network = error.is_couldnt_resolve_proxy()
+        || error.is_couldnt_resolve_host()
+        || error.is_couldnt_connect()
+        || error.is_operation_timedout()
+        || error.is_partial_file()
+        || error.is_ssl_connect_error()
+        || error.is_got_nothing()
+        || error.is_send_error()
+        || error.is_recv_error()
+        || error.is_again()
+        || error.is_http2_error()
+        || error.is_http2_stream_error();
+
+    match network {
+        true => crate::error::ArcheonError::Network(Box::new(error)),
+        false => crate::error::ArcheonError::Other(Box::new(error)),
+    }
+}
+
+#[cfg(feature = "curl")]
+impl CurlHandler {
+    fn launch_head(&mut self) {
+        if let Some(head) = self.head.take() {
+            let headers = std::mem::take(&mut self.headers);
+            let _ = head.send(Ok((self.status, self.version, headers)));
+        }
+    }
+}
+
+#[cfg(feature = "curl")]
+impl curl::easy::Handler for CurlHandler {
+    fn header(&mut self, data: &[u8]) -> bool {
+        let line = String::from_utf8_lossy(data);
+        let line = line.trim_end();
+
+        if line.starts_with("HTTP/") {
+            let mut fields = line.split_whitespace();
+
+            self.version = match fields.next() {
+                Some("HTTP/1.0") => hyper::Version::HTTP_10,
+                Some("HTTP/2") => hyper::Version::HTTP_2,
+                _ => hyper::Version::HTTP_11,
+            };
+            self.status = fields
+                .next()
+                .and_then(|status| status.parse().ok())
+                .unwrap_or_default();
+            self.headers.clear();
+        } else if let Some((name, value)) = line.split_once(':') {
+            let name = hyper::header::HeaderName::from_bytes(name.trim().as_bytes());
+            let value = hyper::header::HeaderValue::from_str(value.trim());
+
+            if let (Ok(name), Ok(value)) = (name, value) {
+                self.headers.append(name, value);
+            }
+        }
+
+        true
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, curl::easy::WriteError> {
+        self.launch_head();
+
+        let chunk = hyper::body::Bytes::copy_from_slice(data);
+
+        match self.runtime.block_on(self.body.send_data(chunk)) {
+            Ok(()) => Ok(data.len()),
+            Err(_) => Ok(0),
+        }
+    }
+}
+
+#[cfg(feature = "curl")]
+impl Transport for CurlTransport {
+    fn send(&self, request: Request<Body>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+            let resolve = self
+                .launch_resolve(&parts.uri)
+                .await
+                .map_err(|error| crate::error::ArcheonError::Network(Box::new(error)))?;
+            let (head, head_receiver) = tokio::sync::oneshot::channel();
+            let (sender, response_body) = Body::channel();
+            let handler = CurlHandler {
+                runtime: tokio::runtime::Handle::current(),
+                head: Some(head),
+                status: hyper::StatusCode::OK,
+                version: hyper::Version::HTTP_11,
+                headers: hyper::HeaderMap::new(),
+                body: sender,
+            };
+            let transport = self.to_owned();
+
+            tokio::task::spawn_blocking(move || {
+                transport.launch_easy(parts, body, resolve, handler)
+            });
+
+            let (status, version, headers) = head_receiver.await?.map_err(curl_error)?;
+            let mut builder = Response::builder().status(status).version(version);
+
+            if let Some(response_headers) = builder.headers_mut() {
+                *response_headers = headers;
+            }
+
+            Ok(builder.body(response_body)?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_send(
+        test_transport: &dyn Transport,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_mock = test_server
+            .mock("GET", "/test_transport.txt")
+            .match_header("user-agent", "test-transport/1.0")
+            .with_status(200)
+            .with_header("x-test-header", "test_value")
+            .with_body(b"test_body")
+            .create_async()
+            .await;
+        let test_request = Request::builder()
+            .uri(format!("{}/test_transport.txt", test_server.url()))
+            .header("user-agent", "test-transport/1.0")
+            .body(Body::empty())?;
+        let test_response = test_transport.send(test_request).await?;
+        assert_eq!(test_response.status(), 200);
+        assert_eq!(test_response.headers()["x-test-header"], "test_value");
+        let test_body = hyper::body::to_bytes(test_response.into_body()).await?;
+        assert_eq!(&test_body[..], b"test_body");
+        test_mock.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn hyper_transport() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        test_send(&Client::new()).await
+    }
+
+    #[cfg(feature = "curl")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn curl_transport() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_transport = CurlTransport::init(&crate::transfer::ClientOptions::default());
+        test_send(&test_transport).await?;
+        test_send(&test_transport).await?;
+        let mut test_server = mockito::Server::new_async().await;
+        let test_body = vec![7; 4 * 1024 * 1024];
+        test_server
+            .mock("GET", "/test_curl_large.bin")
+            .with_status(200)
+            .with_body(&test_body)
+            .create_async()
+            .await;
+        let test_request = Request::builder()
+            .uri(format!("{}/test_curl_large.bin", test_server.url()))
+            .body(Body::empty())?;
+        let test_response = test_transport.send(test_request).await?;
+        let test_received = hyper::body::to_bytes(test_response.into_body()).await?;
+        assert_eq!(test_received.len(), test_body.len());
+        let test_listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let test_address = test_listener.local_addr()?;
+        drop(test_listener);
+        let test_request = Request::builder()
+            .uri(format!("http://{}/test_refused.txt", test_address))
+            .body(Body::empty())?;
+        let test_error =
+            crate::error::ArcheonError::from(test_transport.send(test_request).await.unwrap_err());
+        assert!(test_error.is_retryable());
+        assert_eq!(test_error.kind(), crate::error::ErrorKind::Network);
+        Ok(())
+    }
+
+    #[cfg(feature = "curl")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn curl_resolve() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_transport = CurlTransport::init(&crate::transfer::ClientOptions::default());
+        for test_uri in ["http://[::1]:8080/test.deb", "http://127.0.0.1/test.deb"] {
+            assert!(test_transport
+                .launch_resolve(&test_uri.parse()?)
+                .await?
+                .is_none());
+        }
+        let test_resolve = test_transport
+            .launch_resolve(&"https://localhost/test.deb".parse()?)
+            .await?
+            .unwrap();
+        assert!(test_resolve.starts_with("localhost:443:"));
+        Ok(())
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reqwest_transport() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_transport = ReqwestTransport::init(&crate::transfer::ClientOptions::default())?;
+        test_send(&test_transport).await?;
+        let test_listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let test_address = test_listener.local_addr()?;
+        drop(test_listener);
+        let test_request = Request::builder()
+            .uri(format!("http://{}/test_refused.txt", test_address))
+            .body(Body::empty())?;
+        let test_error =
+            crate::error::ArcheonError::from(test_transport.send(test_request).await.unwrap_err());
+        assert!(test_error.is_retryable());
+        assert_eq!(test_error.kind(), crate::error::ErrorKind::Network);
+        Ok(())
+    }
+}
@@ -0,0 +1,324 @@
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+use std::time::Duration;
+
+use crate::batch::Priority;
+use crate::control::{ControlClient, ControlRequest, ControlResponse};
+use crate::daemon::{Job, JobStatus};
+use crate::progress::AggregateProgress;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dashboard {
+    pub jobs: Vec<Job>,
+    pub selected: usize,
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Request(Box<ControlRequest>),
+    None,
+}
+
+impl Dashboard {
+    pub fn update(&mut self, mut jobs: Vec<Job>) {
+        let selected = self.selected_job().map(|job| job.id);
+
+        jobs.sort_by_key(|job| {
+            let group = match job.status {
+                JobStatus::Running => 0,
+                JobStatus::Queued | JobStatus::Paused => 1,
+                _ => 2,
+            };
+
+            (group, std::cmp::Reverse(job.artifact.priority), job.id)
+        });
+
+        self.selected = selected
+            .and_then(|id| jobs.iter().position(|job| job.id == id))
+            .unwrap_or(0)
+            .min(jobs.len().saturating_sub(1));
+        self.jobs = jobs;
+    }
+
+    pub fn selected_job(&self) -> Option<&Job> {
+        self.jobs.get(self.selected)
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> Action {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.jobs.len().saturating_sub(1))
+            }
+            _ => {}
+        }
+
+        let job = match self.selected_job() {
+            Some(job) => job,
+            None => return Action::None,
+        };
+        let id = job.id;
+        let priority = job.artifact.priority;
+
+        let request = match code {
+            KeyCode::Char('c') => ControlRequest::Cancel { id },
+            KeyCode::Char('p') if job.status == JobStatus::Paused => ControlRequest::Resume { id },
+            KeyCode::Char('p') => ControlRequest::Pause { id },
+            KeyCode::Char('+') => ControlRequest::Prioritize {
+                id,
+                priority: match priority {
+                    Priority::Low => Priority::Normal,
+                    _ => Priority::High,
+                },
+            },
+            KeyCode::Char('-') => ControlRequest::Prioritize {
+                id,
+                priority: match priority {
+                    Priority::High => Priority::Normal,
+                    _ => Priority::Low,
+                },
+            },
+            _ => return Action::None,
+        };
+
+        Action::Request(Box::new(request))
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [jobs_area, help_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let header = Row::new([
+            "id", "status", "priority", "progress", "speed", "eta", "url",
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+        let rows = self.jobs.iter().map(|job| {
+            let progress = job.progress.unwrap_or_default();
+
+            Row::new([
+                Cell::from(job.id.to_string()),
+                Cell::from(job.status.to_string()),
+                Cell::from(job.artifact.priority.to_string()),
+                Cell::from(format_progress(&progress)),
+                Cell::from(format!("{}/s", format_bytes(progress.bytes_per_second))),
+                Cell::from(eta(&progress).map(format_eta).unwrap_or_default()),
+                Cell::from(job.artifact.url.to_owned()),
+            ])
+        });
+        let widths = [
+            Constraint::Length(5),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(20),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Min(20),
+        ];
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title("archeon"))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        let help = self.message.to_owned().unwrap_or_else(|| {
+            String::from("q quit  j/k select  p pause/resume  c cancel  +/- priority")
+        });
+
+        frame.render_stateful_widget(table, jobs_area, &mut state);
+        frame.render_widget(Paragraph::new(help), help_area);
+    }
+}
+
+pub fn eta(progress: &AggregateProgress) -> Option<Duration> {
+    if progress.bytes_per_second == 0 || progress.total_bytes == 0 {
+        return None;
+    }
+
+    let remaining = progress
+        .total_bytes
+        .saturating_sub(progress.downloaded_bytes);
+
+    Some(Duration::from_secs(remaining / progress.bytes_per_second))
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} {}", bytes, units[unit]),
+        _ => format!("{:.1} {}", value, units[unit]),
+    }
+}
+
+fn format_progress(progress: &AggregateProgress) -> String {
+    match progress.total_bytes {
+        0 => format_bytes(progress.downloaded_bytes),
+        total => format!(
+            "{}/{}",
+            format_bytes(progress.downloaded_bytes),
+            format_bytes(total),
+        ),
+    }
+}
+
+fn format_eta(eta: Duration) -> String {
+    let seconds = eta.as_secs();
+
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+pub async fn run(client: &ControlClient) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut terminal = ratatui::init();
+    let result = launch_dashboard(client, &mut terminal).await;
+
+    ratatui::restore();
+
+    result
+}
+
+async fn launch_dashboard(
+    client: &ControlClient,
+    terminal: &mut DefaultTerminal,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut dashboard = Dashboard::default();
+
+    loop {
+        match client.status(None).await? {
+            ControlResponse::Jobs { jobs } => dashboard.update(jobs),
+            ControlResponse::Error { error } => dashboard.message = Some(error),
+            ControlResponse::Job { .. } | ControlResponse::Queue { .. } => {}
+        }
+
+        terminal.draw(|frame| dashboard.draw(frame))?;
+
+        let key = tokio::task::block_in_place(|| -> Result<_, std::io::Error> {
+            match event::poll(Duration::from_millis(500))? {
+                true => Ok(Some(event::read()?)),
+                false => Ok(None),
+            }
+        })?;
+        let code = match key {
+            Some(Event::Key(key)) if key.kind == KeyEventKind::Press => key.code,
+            _ => continue,
+        };
+
+        match dashboard.handle_key(code) {
+            Action::Quit => return Ok(()),
+            Action::Request(request) => {
+                dashboard.message = match client.send(&request).await? {
+                    ControlResponse::Error { error } => Some(error),
+                    _ => None,
+                };
+            }
+            Action::None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch::BatchArtifact;
+
+    fn test_job(id: u64, status: JobStatus, priority: Priority) -> Job {
+        Job {
+            id,
+            status,
+            artifact: BatchArtifact {
+                url: format!("http://test-tui/{}.iso", id),
+                priority,
+                ..BatchArtifact::default()
+            },
+            result: None,
+            progress: None,
+            coalesced: None,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn update() {
+        let mut test_dashboard = Dashboard::default();
+        test_dashboard.update(vec![
+            test_job(0, JobStatus::Completed, Priority::Normal),
+            test_job(1, JobStatus::Queued, Priority::Low),
+            test_job(2, JobStatus::Running, Priority::Normal),
+            test_job(3, JobStatus::Paused, Priority::High),
+        ]);
+        let test_ids: Vec<u64> = test_dashboard.jobs.iter().map(|job| job.id).collect();
+        assert_eq!(test_ids, [2, 3, 1, 0]);
+        test_dashboard.selected = 2;
+        test_dashboard.update(vec![
+            test_job(1, JobStatus::Running, Priority::Low),
+            test_job(3, JobStatus::Paused, Priority::High),
+        ]);
+        assert_eq!(test_dashboard.selected_job().map(|job| job.id), Some(1));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn handle_key() {
+        let mut test_dashboard = Dashboard::default();
+        assert_eq!(test_dashboard.handle_key(KeyCode::Char('c')), Action::None);
+        test_dashboard.update(vec![
+            test_job(0, JobStatus::Running, Priority::Normal),
+            test_job(1, JobStatus::Paused, Priority::Low),
+        ]);
+        assert_eq!(
+            test_dashboard.handle_key(KeyCode::Char('p')),
+            Action::Request(Box::new(ControlRequest::Pause { id: 0 })),
+        );
+        assert_eq!(test_dashboard.handle_key(KeyCode::Down), Action::None,);
+        assert_eq!(
+            test_dashboard.handle_key(KeyCode::Char('p')),
+            Action::Request(Box::new(ControlRequest::Resume { id: 1 })),
+        );
+        assert_eq!(
+            test_dashboard.handle_key(KeyCode::Char('+')),
+            Action::Request(Box::new(ControlRequest::Prioritize {
+                id: 1,
+                priority: Priority::Normal,
+            })),
+        );
+        assert_eq!(
+            test_dashboard.handle_key(KeyCode::Char('c')),
+            Action::Request(Box::new(ControlRequest::Cancel { id: 1 })),
+        );
+        assert_eq!(test_dashboard.handle_key(KeyCode::Char('q')), Action::Quit);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn eta() {
+        let test_progress = AggregateProgress {
+            total_bytes: 1000,
+            downloaded_bytes: 400,
+            bytes_per_second: 100,
+            ..AggregateProgress::default()
+        };
+        assert_eq!(super::eta(&test_progress), Some(Duration::from_secs(6)));
+        assert!(super::eta(&AggregateProgress::default()).is_none());
+        assert_eq!(format_eta(Duration::from_secs(3725)), "1:02:05");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn format_bytes() {
+        assert_eq!(super::format_bytes(512), "512 B");
+        assert_eq!(super::format_bytes(1536), "1.5 KiB");
+        assert_eq!(super::format_bytes(5 * 1024 * 1024 * 1024), "5.0 GiB");
+    }
+}
@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use tokio::fs::read;
+
+use crate::checksum::{self, Algorithm};
+use crate::manifest::Manifest;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedDigest {
+    pub path: PathBuf,
+    pub checksum: String,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerifyStatus {
+    Ok,
+    Mismatch,
+    Missing,
+    Unreadable,
+}
+
+impl std::fmt::Display for VerifyStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            VerifyStatus::Ok => "ok",
+            VerifyStatus::Mismatch => "mismatch",
+            VerifyStatus::Missing => "missing",
+            VerifyStatus::Unreadable => "unreadable",
+        };
+
+        f.write_str(status)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Verification {
+    pub path: PathBuf,
+    pub expected: String,
+    pub actual: Option<String>,
+    pub status: VerifyStatus,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub weak: bool,
+}
+
+impl Verification {
+    pub fn is_drift(&self) -> bool {
+        self.status != VerifyStatus::Ok
+    }
+}
+
+pub fn parse(contents: &str, base: &Path) -> Result<Vec<ExpectedDigest>, Error> {
+    let mut expected = Vec::with_capacity(10);
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (checksum, path) = match line.split_once(char::is_whitespace) {
+            Some((checksum, path)) => (checksum, path.trim_start().trim_start_matches('*')),
+            None => (line, ""),
+        };
+
+        if !checksum::is_valid(checksum) || path.is_empty() {
+            let error = format!("Invalid checksum line '{}'!", line);
+
+            return Err(Error::new(ErrorKind::InvalidData, error));
+        }
+
+        expected.push(ExpectedDigest {
+            path: base.join(path),
+            checksum: checksum.to_ascii_lowercase(),
+            url: None,
+        });
+    }
+
+    Ok(expected)
+}
+
+pub async fn load(path: &Path) -> Result<Vec<ExpectedDigest>, Error> {
+    let contents = read(path).await?;
+
+    if contents.trim_ascii_start().starts_with(b"{") {
+        let manifest = serde_json::from_slice::<Manifest>(&contents)?;
+
+        return Ok(manifest
+            .installations
+            .into_iter()
+            .map(|installation| ExpectedDigest {
+                path: installation.artifact,
+                checksum: installation.checksum.to_ascii_lowercase(),
+                url: Some(installation.source),
+            })
+            .collect());
+    }
+
+    let base = path.parent().unwrap_or(Path::new(""));
+
+    parse(&String::from_utf8_lossy(&contents), base)
+}
+
+pub fn resolve_urls(verifications: &mut [Verification], base: &Path, base_url: &str) {
+    for verification in verifications.iter_mut() {
+        if verification.url.is_some() {
+            continue;
+        }
+
+        if let Ok(relative) = verification.path.strip_prefix(base) {
+            let relative: Vec<_> = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect();
+
+            verification.url = Some(format!(
+                "{}/{}",
+                base_url.trim_end_matches('/'),
+                relative.join("/")
+            ));
+        }
+    }
+}
+
+pub async fn verify(expected: &[ExpectedDigest]) -> Vec<Verification> {
+    let mut verifications = Vec::with_capacity(expected.len());
+
+    for digest in expected {
+        let algorithm = Algorithm::resolve(&digest.checksum, None);
+        let mut verification = Verification {
+            path: digest.path.to_owned(),
+            expected: digest.checksum.to_owned(),
+            actual: None,
+            status: VerifyStatus::Ok,
+            error: None,
+            url: digest.url.to_owned(),
+            weak: algorithm.is_weak(),
+        };
+
+        match checksum::digest(&digest.path, algorithm).await {
+            Ok(actual) => {
+                if !checksum::matches(&digest.checksum, &actual) {
+                    verification.status = VerifyStatus::Mismatch;
+                }
+
+                verification.actual = Some(actual);
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                verification.status = VerifyStatus::Missing;
+            }
+            Err(error) => {
+                verification.status = VerifyStatus::Unreadable;
+                verification.error = Some(error.to_string());
+            }
+        }
+
+        verifications.push(verification);
+    }
+
+    verifications
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::installer::{Installation, Installer};
+
+    const TEST_CHECKSUM: &str = "4443c6a8412e6c11f324c870a8366d6ede75e7f9ed12f00c36b88d479df371d6";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_contents = format!(
+            "# SHA256SUMS\n\n{}  test.iso\n{} *nested/test.deb\n{}  /srv/test.bin\n",
+            TEST_CHECKSUM.to_ascii_uppercase(),
+            TEST_CHECKSUM,
+            TEST_CHECKSUM,
+        );
+        let test_expected = super::parse(&test_contents, Path::new("/tmp/archeon"))?;
+        assert_eq!(test_expected.len(), 3);
+        assert_eq!(
+            test_expected[0].path,
+            PathBuf::from("/tmp/archeon/test.iso")
+        );
+        assert_eq!(test_expected[0].checksum, TEST_CHECKSUM);
+        assert_eq!(
+            test_expected[1].path,
+            PathBuf::from("/tmp/archeon/nested/test.deb"),
+        );
+        assert_eq!(test_expected[2].path, PathBuf::from("/srv/test.bin"));
+        assert!(super::parse("abc123  test.iso", Path::new("")).is_err());
+        assert!(super::parse(TEST_CHECKSUM, Path::new("")).is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let test_dir = std::env::temp_dir().join("archeon_test_verify");
+        tokio::fs::create_dir_all(&test_dir).await?;
+        tokio::fs::write(test_dir.join("intact.txt"), b"test_body").await?;
+        tokio::fs::write(test_dir.join("drifted.txt"), b"test_tags").await?;
+        let test_sums = test_dir.join("SHA256SUMS");
+        tokio::fs::write(
+            &test_sums,
+            format!(
+                "{0}  intact.txt\n{0}  drifted.txt\n{0}  missing.txt\n",
+                TEST_CHECKSUM
+            ),
+        )
+        .await?;
+        let test_results = super::verify(&load(&test_sums).await?).await;
+        assert_eq!(test_results.len(), 3);
+        let test_blake3 = super::verify(&super::parse(
+            &format!(
+                "blake3:{}  intact.txt\n{}  intact.txt\n",
+                checksum::digest_bytes(b"test_body", Algorithm::Blake3),
+                checksum::digest_bytes(b"test_body", Algorithm::Sha512),
+            ),
+            &test_dir,
+        )?)
+        .await;
+        assert!(test_blake3.iter().all(|result| !result.is_drift()));
+        assert!(!test_blake3[0].weak);
+        let test_md5 = super::verify(&super::parse(
+            &format!(
+                "{}  intact.txt\ncrc32:{}  intact.txt\n",
+                checksum::digest_bytes(b"test_body", Algorithm::Md5),
+                checksum::digest_bytes(b"test_body", Algorithm::Crc32),
+            ),
+            &test_dir,
+        )?)
+        .await;
+        assert!(test_md5
+            .iter()
+            .all(|result| !result.is_drift() && result.weak));
+        assert_eq!(test_results[0].status, VerifyStatus::Ok);
+        assert!(!test_results[0].is_drift());
+        assert_eq!(test_results[1].status, VerifyStatus::Mismatch);
+        assert_eq!(
+            test_results[1].actual.as_deref(),
+            Some(checksum::sha256_bytes(b"test_tags").as_str()),
+        );
+        assert_eq!(test_results[2].status, VerifyStatus::Missing);
+        assert!(test_results[2].actual.is_none());
+        let mut test_results = test_results;
+        resolve_urls(
+            &mut test_results,
+            &test_dir,
+            "http://test-verify.invalid/sums/",
+        );
+        assert_eq!(
+            test_results[1].url.as_deref(),
+            Some("http://test-verify.invalid/sums/drifted.txt"),
+        );
+        let test_manifest = test_dir.join("manifest.json");
+        let test_installation = Installation {
+            id: 0,
+            package: String::from("archeon-test-package"),
+            version: String::from("1.0.0"),
+            source: String::from("http://test-verify.invalid/intact.txt"),
+            checksum: TEST_CHECKSUM.to_ascii_uppercase(),
+            installed: 1_686_000_000,
+            artifact: test_dir.join("intact.txt"),
+            installer: Installer::default(),
+        };
+        tokio::fs::write(
+            &test_manifest,
+            serde_json::to_vec(&Manifest {
+                installations: vec![test_installation],
+                ..Manifest::default()
+            })?,
+        )
+        .await?;
+        let test_results = super::verify(&load(&test_manifest).await?).await;
+        assert_eq!(test_results.len(), 1);
+        assert_eq!(test_results[0].status, VerifyStatus::Ok);
+        assert_eq!(
+            test_results[0].url.as_deref(),
+            Some("http://test-verify.invalid/intact.txt"),
+        );
+        tokio::fs::remove_dir_all(&test_dir).await?;
+        Ok(())
+    }
+}